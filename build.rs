@@ -0,0 +1,7 @@
+// koala-diff/build.rs
+// Link flags of the Node.js addon (`node` feature); nothing to do otherwise
+
+fn main() {
+    #[cfg(feature = "node")]
+    napi_build::setup();
+}
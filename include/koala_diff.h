@@ -0,0 +1,53 @@
+/* koala-diff/include/koala_diff.h
+ * C ABI of libkoala_diff (src/ffi.rs): diffs two Arrow C streams of record batches */
+
+#ifndef KOALA_DIFF_H
+#define KOALA_DIFF_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The Arrow C stream interface, as defined by Apache Arrow. */
+#ifndef ARROW_C_STREAM_INTERFACE
+#define ARROW_C_STREAM_INTERFACE
+
+struct ArrowSchema;
+struct ArrowArray;
+
+struct ArrowArrayStream {
+    int (*get_schema)(struct ArrowArrayStream *, struct ArrowSchema *out);
+    int (*get_next)(struct ArrowArrayStream *, struct ArrowArray *out);
+    const char *(*get_last_error)(struct ArrowArrayStream *);
+    void (*release)(struct ArrowArrayStream *);
+    void *private_data;
+};
+
+#endif /* ARROW_C_STREAM_INTERFACE */
+
+/* Diffs stream_a against stream_b, rows matched on key_cols (a JSON array of
+ * column names). options is a JSON object of diff_files options, or NULL.
+ * Both streams are consumed (released) by the call, even when it fails.
+ *
+ * Returns the result as JSON, to be freed with koala_diff_free, or NULL on
+ * error. */
+char *koala_diff_arrow(struct ArrowArrayStream *stream_a,
+                       struct ArrowArrayStream *stream_b,
+                       const char *key_cols,
+                       const char *options);
+
+/* The error of the last failed call on this thread, or NULL. Owned by the
+ * library; valid until the next failed call on the same thread. */
+const char *koala_diff_last_error(void);
+
+/* Frees a result of koala_diff_arrow. NULL is a no-op. */
+void koala_diff_free(char *json);
+
+/* Library version, e.g. "0.3.2". */
+const char *koala_diff_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* KOALA_DIFF_H */
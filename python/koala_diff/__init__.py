@@ -2,7 +2,47 @@
 
 __version__ = "0.3.2"
 
-from .core import DataDiff
+from .core import (
+    DataDiff,
+    DiffResult,
+    column_stats_frame,
+    compare_profiles,
+    compare_results,
+    diff_dataframes,
+    estimate,
+    explore,
+    generate_mutations,
+    publishable_summary,
+    self_check,
+    verify_report,
+    write_signed_report,
+)
+from .dbt import dbt_diff, load_dbt_model
+from .orchestration import DiffCheckFailed, KoalaDiffOperator, run_diff_check
 from .reporter import HtmlReporter
+from .render import available_formats, register_renderer, render
 
-__all__ = ["DataDiff", "HtmlReporter"]
+__all__ = [
+    "DataDiff",
+    "DiffCheckFailed",
+    "DiffResult",
+    "HtmlReporter",
+    "KoalaDiffOperator",
+    "available_formats",
+    "column_stats_frame",
+    "compare_profiles",
+    "compare_results",
+    "dbt_diff",
+    "diff_dataframes",
+    "estimate",
+    "explore",
+    "generate_mutations",
+    "load_dbt_model",
+    "publishable_summary",
+    "register_renderer",
+    "render",
+    "run_diff_check",
+    "self_check",
+    "verify_report",
+    "write_signed_report",
+]
@@ -0,0 +1,81 @@
+# koala_diff/cli.py
+#
+# Command line entry point: `koala-diff A B --key id [--format markdown] [--output out.md]`
+# or `koala-diff self-check [--runs N] [--rows N] [--seed N]`
+# or `koala-diff explore result.kdiff`
+
+import argparse
+import sys
+
+from .core import DataDiff, explore, self_check
+from .render import available_formats, render
+
+
+def _self_check(argv) -> int:
+    parser = argparse.ArgumentParser(
+        prog="koala-diff self-check",
+        description="Diff random datasets against mutated copies and verify the reported counts.",
+    )
+    parser.add_argument("--runs", type=int, default=20, help="Dataset/mutation pairs to check")
+    parser.add_argument("--rows", type=int, default=1000, help="Rows per generated dataset")
+    parser.add_argument("--seed", type=int, help="Seed, to reproduce an earlier check")
+    args = parser.parse_args(argv)
+
+    report = self_check(runs=args.runs, rows=args.rows, seed=args.seed)
+    for failure in report["failures"]:
+        print(
+            f"run {failure['run']} (seed {failure['seed']}): {failure['check']} "
+            f"expected {failure['expected']}, got {failure['actual']}"
+        )
+    print(f"{report['passed']}/{report['runs']} runs passed (seed {report['seed']})")
+    return 0 if report["failed"] == 0 else 1
+
+
+def _explore(argv) -> int:
+    parser = argparse.ArgumentParser(
+        prog="koala-diff explore",
+        description="Browse a saved diff result (written with --save) in the terminal.",
+    )
+    parser.add_argument("result", help="Saved result (.kdiff) or signed report")
+    args = parser.parse_args(argv)
+
+    explore(args.result)
+    return 0
+
+
+def main(argv=None) -> int:
+    argv = sys.argv[1:] if argv is None else list(argv)
+    if argv[:1] == ["self-check"]:
+        return _self_check(argv[1:])
+    if argv[:1] == ["explore"]:
+        return _explore(argv[1:])
+
+    parser = argparse.ArgumentParser(prog="koala-diff", description="Compare two data files.")
+    parser.add_argument("file_a", help="Source (old) file")
+    parser.add_argument("file_b", help="Target (new) file")
+    parser.add_argument("--key", action="append", required=True, help="Key column (repeat for composite keys)")
+    parser.add_argument("--format", default="text", help=f"Output format ({', '.join(available_formats())})")
+    parser.add_argument("--output", help="Write the report to this path instead of stdout")
+    parser.add_argument("--title", default="Koala Diff Report", help="Report title")
+    parser.add_argument("--save", help="Also save the result to this path, for `koala-diff explore`")
+    args = parser.parse_args(argv)
+
+    differ = DataDiff(key_columns=args.key)
+    result = differ.compare(args.file_a, args.file_b)
+    if args.save:
+        differ.save(args.save)
+    output = render(result, args.format, title=args.title)
+
+    if args.output:
+        mode = "wb" if isinstance(output, bytes) else "w"
+        with open(args.output, mode) as f:
+            f.write(output)
+    elif isinstance(output, bytes):
+        sys.stdout.buffer.write(output)
+    else:
+        print(output)
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
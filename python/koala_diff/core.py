@@ -2,6 +2,8 @@
 #
 # The Python wrapper that exposes the Rust binary.
 
+import json
+import warnings
 import polars as pl
 from typing import List, Dict, Any, Optional
 from pathlib import Path
@@ -9,17 +11,244 @@ from pathlib import Path
 # This import assumes the package was built and installed
 try:
     from ._internal import diff_files as _rust_diff_files
+    from ._internal import diff_frames as _rust_diff_frames
+    from ._internal import drill_down as _rust_drill_down
+    from ._internal import export_details as _rust_export_details
+    from ._internal import export_cell_diffs as _rust_export_cell_diffs
+    from ._internal import diff_rows as _rust_diff_rows
+    from ._internal import compare_results as _rust_compare_results
+    from ._internal import write_signed_report as _rust_write_signed_report
+    from ._internal import verify_report as _rust_verify_report
+    from ._internal import publishable_summary as _rust_publishable_summary
+    from ._internal import gate as _rust_gate
+    from ._internal import compare_profiles as _rust_compare_profiles
+    from ._internal import diff_row_sets as _rust_diff_row_sets
+    from ._internal import diff_sequences as _rust_diff_sequences
+    from ._internal import diff_intervals as _rust_diff_intervals
+    from ._internal import merge_files as _rust_merge_files
+    from ._internal import diff_three_way as _rust_diff_three_way
+    from ._internal import generate_mutations as _rust_generate_mutations
+    from ._internal import self_check as _rust_self_check
+    from ._internal import estimate as _rust_estimate
+    from ._internal import explore as _rust_explore
 except ImportError:
     # Fallback for development/IDE linting without binary
-    def _rust_diff_files(a, b, k):
+    def _rust_diff_files(a, b, k, **options):
         return {"error": "Rust extension not compiled"}
 
+    def _rust_diff_frames(a, b, k, **options):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_drill_down(a, b, k, keys, **options):
+        return []
+
+    def _rust_export_details(a, b, k, output_dir, **options):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_export_cell_diffs(a, b, k, path, **options):
+        return 0
+
+    def _rust_diff_rows(a, b, k, **options):
+        raise RuntimeError("Rust extension not compiled")
+
+    def _rust_compare_results(previous, current):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_write_signed_report(result, path, key, algorithm="hmac-sha256"):
+        raise RuntimeError("Rust extension not compiled")
+
+    def _rust_verify_report(path, key):
+        raise RuntimeError("Rust extension not compiled")
+
+    def _rust_publishable_summary(result, **policy):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_gate(result, **thresholds):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_compare_profiles(a, b, **options):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_diff_row_sets(a, b, k, **options):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_diff_sequences(a, b, k, order_by, **options):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_diff_intervals(a, b, k, valid_from, valid_to, **options):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_merge_files(a, b, k, output_path, **options):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_diff_three_way(base, a, b, k, output_path, **options):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_generate_mutations(file, spec, output_path):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_self_check(runs=20, rows=1000, seed=None):
+        raise RuntimeError("Rust extension not compiled")
+
+    def _rust_estimate(a, b):
+        return {"error": "Rust extension not compiled"}
+
+    def _rust_explore(path):
+        raise RuntimeError("Rust extension not compiled")
+
+
+def compare_results(previous: Dict[str, Any], current: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Compares two diff results (e.g. yesterday's vs today's run) and reports
+    which columns improved or regressed and which sampled keys newly regressed.
+    """
+    return _rust_compare_results(previous, current)
+
+
+def write_signed_report(result: Dict[str, Any], path: str, key, algorithm: str = "hmac-sha256") -> None:
+    """
+    Writes ``result`` as a JSON report signed with an HMAC secret
+    (``algorithm="hmac-sha256"``) or an ed25519 private key (``"ed25519"``).
+    """
+    _rust_write_signed_report(result, str(path), key, algorithm)
+
+
+def verify_report(path: str, key) -> bool:
+    """
+    Returns True if the signed report at ``path`` is unmodified. ``key`` is
+    the HMAC secret or the ed25519 public key.
+    """
+    return _rust_verify_report(str(path), key)
+
+
+def compare_profiles(file_a: str, file_b: str, **options: Any) -> Dict[str, Any]:
+    """
+    Quick sanity comparison of two files with no common key: each file's
+    columns are profiled on their own (counts, nulls, min/max, approximate
+    distinct, mean) and only the profiles are compared. Files of very
+    different sizes can be sampled to a common size first with
+    ``normalize_sizes=True`` or ``sample_size``, optionally ``stratify_by`` a
+    column and with a ``sample_seed``.
+    """
+    return _rust_compare_profiles(str(file_a), str(file_b), **options)
+
+def generate_mutations(file: str, spec: Dict[str, Any], out: str) -> Dict[str, Any]:
+    """
+    Writes a copy of ``file`` to ``out`` with controlled mutations, e.g.
+    ``{"drop_rows": 10, "perturb": {"price": 0.01}, "null_out": {"email": 5},
+    "seed": 42}``, and returns what was injected, so a diff configuration
+    can be checked to report exactly that.
+    """
+    return _rust_generate_mutations(str(file), spec, str(out))
+
+def estimate(file_a: str, file_b: str) -> Dict[str, Any]:
+    """
+    Estimates both files' row counts (exact from Parquet footers, sampled
+    for CSV / NDJSON) and the diff's peak memory and ``cost_class``, without
+    reading the data, so schedulers can route large jobs to bigger workers.
+    """
+    return _rust_estimate(str(file_a), str(file_b))
+
+def self_check(runs: int = 20, rows: int = 1000, seed: Optional[int] = None) -> Dict[str, Any]:
+    """
+    Diffs ``runs`` random datasets against randomly mutated copies and checks
+    the reported counts against what was injected. Any failure is listed with
+    the seed reproducing its run.
+    """
+    return _rust_self_check(runs=runs, rows=rows, seed=seed)
+
+def explore(path: str) -> None:
+    """
+    Opens a result saved with ``DataDiff.save`` (or a signed report) in an
+    interactive terminal explorer: page through the columns, filter them by
+    mismatch rate and inspect the samples of each. Press ``q`` to leave.
+    """
+    _rust_explore(str(path))
+
+def publishable_summary(result: Dict[str, Any], epsilon: float = 1.0, noise_threshold: int = 1000,
+                        min_cell_size: int = 10, seed: Optional[int] = None) -> Dict[str, Any]:
+    """
+    Returns an aggregate-only summary for external publishing: counts below
+    ``noise_threshold`` get Laplace noise (privacy budget ``epsilon``), small
+    non-zero cells are suppressed, and samples/keys are dropped.
+    """
+    return _rust_publishable_summary(
+        result, epsilon=epsilon, noise_threshold=noise_threshold,
+        min_cell_size=min_cell_size, seed=seed,
+    )
+
+def column_stats_frame(result: Dict[str, Any]) -> pl.DataFrame:
+    """
+    Returns a result's ``column_stats`` as one tidy DataFrame: a row per
+    column with every scalar metric (types, counts, match rate, value and
+    null diffs, distinct/entropy figures, ...) as a column, to sort, filter
+    or plot. Tags become ``tag_<name>`` columns; samples and change patterns
+    are left out.
+    """
+    rows = []
+    for stats in result.get("column_stats", {}).values():
+        row = {k: v for k, v in stats.items() if not isinstance(v, (dict, list))}
+        for tag, value in (stats.get("tags") or {}).items():
+            row[f"tag_{tag}"] = value
+        rows.append(row)
+    if not rows:
+        return pl.DataFrame({"column_name": []}, schema={"column_name": pl.String})
+    return pl.from_dicts(rows, infer_schema_length=None)
+
+def diff_dataframes(df_a: Any, df_b: Any, key_cols: List[str], **options: Any) -> "DiffResult":
+    """
+    Diffs two in-memory frames, without writing them to files. Polars
+    DataFrames and LazyFrames are handed over as they are (no copy; a
+    LazyFrame's query runs as part of the diff). pandas DataFrames go to the
+    engine as Arrow tables (``pyarrow.Table.from_pandas``; the index is
+    dropped, ``reset_index()`` first to compare on it). Anything else
+    exporting Arrow data (``pyarrow.Table``, ``RecordBatchReader``,
+    ``RecordBatch``, DuckDB relations, ADBC readers, ...) is read zero-copy
+    over the Arrow C Data Interface. Takes the ``DataDiff`` options except
+    the read options.
+    """
+    return _warn(DiffResult(_rust_diff_frames(_to_arrow(df_a), _to_arrow(df_b), list(key_cols), **options)))
+
+def _warn(result: "DiffResult") -> "DiffResult":
+    """Re-issues the run's warnings (``result["warnings"]``) as Python warnings."""
+    for warning in result.get("warnings", []):
+        warnings.warn(warning["message"], RuntimeWarning, stacklevel=3)
+    return result
+
+def _to_arrow(frame: Any) -> Any:
+    """pandas DataFrames as pyarrow Tables; other frames must export ``__arrow_c_stream__`` or ``__arrow_c_array__``."""
+    try:
+        import pandas as pd
+    except ImportError:
+        return frame
+    if isinstance(frame, pd.DataFrame):
+        import pyarrow as pa
+        return pa.Table.from_pandas(frame, preserve_index=False)
+    return frame
+
+class DiffResult(dict):
+    """
+    The result of ``DataDiff.compare``: the same plain dict as before (it
+    still serializes with ``json.dump``), with helper methods.
+    """
+    def column_stats_frame(self) -> pl.DataFrame:
+        """One row per column with all its metrics; see ``column_stats_frame``."""
+        return column_stats_frame(self)
+
 class DataDiff:
     """
     Main entry point for comparing datasets.
     """
-    def __init__(self, key_columns: List[str]):
+    def __init__(self, key_columns: List[str], **options: Any):
+        """
+        Args:
+            key_columns: Columns to join on.
+            **options: Comparison options forwarded to the Rust engine
+                (e.g. ``inf_equal=False``, ``inf_match_threshold=1e300``,
+                ``flush_subnormals=True``).
+        """
         self.key_columns = key_columns
+        self.options = options
         self.last_result = None
         self.file_a = None
         self.file_b = None
@@ -31,61 +260,186 @@ class DataDiff:
         self.file_a = str(file_a)
         self.file_b = str(file_b)
         
-        # Validate files exist
-        if not Path(self.file_a).exists():
-            raise FileNotFoundError(f"File not found: {self.file_a}")
-        if not Path(self.file_b).exists():
-            raise FileNotFoundError(f"File not found: {self.file_b}")
+        # Validate files exist (object store URIs are checked when read)
+        for path in (self.file_a, self.file_b):
+            if "://" not in path and not Path(path).exists():
+                raise FileNotFoundError(f"File not found: {path}")
 
         # Call Rust!
         print(f"🐨 Comparing {self.file_a} vs {self.file_b} using Rust engine...")
-        result = _rust_diff_files(self.file_a, self.file_b, self.key_columns)
+        result = _warn(DiffResult(_rust_diff_files(self.file_a, self.file_b, self.key_columns, **self.options)))
         self.last_result = result
         
         return result
 
+    def compare_row_sets(self, file_a: str, file_b: str) -> Dict[str, Any]:
+        """
+        Compares the rows under each key as unordered multisets, for keys
+        that map to several rows per side (e.g. order -> line items).
+        Reports per-key added, removed and modified rows. Rows match
+        exactly, so ``abs_tol``, ``rel_tol`` and ``tolerances`` are refused.
+        """
+        return _rust_diff_row_sets(str(file_a), str(file_b), self.key_columns, **self.options)
+
+    def compare_sequences(self, file_a: str, file_b: str, order_by: str) -> Dict[str, Any]:
+        """
+        Compares the rows under each key as sequences ordered by ``order_by``
+        (e.g. events per user), reporting insertions, deletions and
+        substitutions per key from an LCS alignment. Rows match exactly, so
+        ``abs_tol``, ``rel_tol`` and ``tolerances`` are refused.
+        """
+        return _rust_diff_sequences(
+            str(file_a), str(file_b), self.key_columns, order_by, **self.options,
+        )
+
+    def compare_intervals(
+        self, file_a: str, file_b: str, valid_from: str, valid_to: str
+    ) -> Dict[str, Any]:
+        """
+        Compares effective-dated records: rows of a key are matched when
+        their ``[valid_from, valid_to)`` intervals overlap (null ``valid_to``
+        is open-ended) and compared over the overlap. Also reports unmatched
+        intervals and the gaps/overlaps in each file's history.
+        """
+        return _rust_diff_intervals(
+            str(file_a), str(file_b), self.key_columns, valid_from, valid_to, **self.options,
+        )
+
+    def drill_down(self, keys: List[Any]) -> List[Dict[str, Any]]:
+        """
+        Re-reads only the given keys from both files and returns the full
+        old/new row pair for each, plus the columns that changed.
+
+        Use tuples for composite keys, in ``key_columns`` order.
+        """
+        if not self.file_a or not self.file_b:
+            raise ValueError("No comparison has been run yet.")
+
+        return _rust_drill_down(self.file_a, self.file_b, self.key_columns, list(keys), **self.options)
+
+    def export_details(self, output_dir: str, **export_options: Any) -> Dict[str, Any]:
+        """
+        Writes the added, removed and modified rows of the last comparison
+        under ``output_dir`` (one sub-folder each).
+
+        ``export_options``: ``format`` ("parquet" or "csv"), ``compression``
+        ("zstd", "snappy", "gzip", "lz4", "uncompressed"), ``partition_by``
+        (column) and ``max_rows_per_file``. Pass ``encrypt_to`` (an age
+        recipient or list of them) to encrypt every file (written as ``*.age``).
+        """
+        if not self.file_a or not self.file_b:
+            raise ValueError("No comparison has been run yet.")
+
+        return _rust_export_details(
+            self.file_a, self.file_b, self.key_columns, str(output_dir),
+            **self.options, **export_options,
+        )
+
+    def get_diff_frames(self) -> Dict[str, pl.DataFrame]:
+        """
+        Returns the rows behind the last comparison as Polars DataFrames under
+        ``"added"``, ``"removed"`` and ``"modified"``. Modified rows carry
+        file A's columns plus file B's values suffixed ``_b``.
+        """
+        if not self.file_a or not self.file_b:
+            raise ValueError("No comparison has been run yet.")
+
+        return dict(_rust_diff_rows(self.file_a, self.file_b, self.key_columns, **self.options))
+
+    def export_cell_diffs(self, path: str, **csv_options: Any) -> int:
+        """
+        Writes one CSV row per changed cell (key columns, ``column``,
+        ``value_a``, ``value_b``) for the last comparison, with proper quoting
+        of embedded delimiters, quotes and newlines.
+
+        ``csv_options``: ``bom=True`` for Excel, ``delimiter``,
+        ``quote_style`` ("necessary", "always", "non_numeric") and
+        ``line_terminator``. ``encrypt_to`` (age recipients) writes the file
+        encrypted as ``<path>.age``. Returns the number of cells written.
+        """
+        if not self.file_a or not self.file_b:
+            raise ValueError("No comparison has been run yet.")
+
+        return _rust_export_cell_diffs(
+            self.file_a, self.file_b, self.key_columns, str(path),
+            **self.options, **csv_options,
+        )
+
+    def merge(self, output_path: str, strategy: str = "prefer_b", **merge_options: Any) -> Dict[str, Any]:
+        """
+        Writes a reconciled Parquet file of the last comparison: one row per
+        key, shared columns resolved by ``strategy`` ("prefer_a", "prefer_b",
+        "prefer_non_null" or "prefer_newer" with ``timestamp_column``).
+        ``column_rules={"col": "prefer_a"}`` overrides it per column.
+        """
+        if not self.file_a or not self.file_b:
+            raise ValueError("No comparison has been run yet.")
+
+        return _rust_merge_files(
+            self.file_a, self.file_b, self.key_columns, str(output_path),
+            **self.options, strategy=strategy, **merge_options,
+        )
+
+    def find_conflicts(self, base_file: str, file_a: str, file_b: str, output_path: str) -> Dict[str, Any]:
+        """
+        Three-way comparison of two files modified from a common
+        ``base_file``: writes the conflicts table (key columns, ``column``,
+        ``kind``, ``base``, ``a``, ``b``) to ``output_path`` (CSV for a
+        ``.csv`` path, Parquet otherwise) and returns the counts, for a sync
+        tool to ask for resolution.
+        """
+        return _rust_diff_three_way(
+            str(base_file), str(file_a), str(file_b), self.key_columns, str(output_path),
+            **self.options,
+        )
+
+    def write_manifest(self, path: str) -> None:
+        """
+        Writes the run manifest of the last comparison as JSON: resolved
+        options, input fingerprints (size, mtime, SHA-256), library versions
+        and phase timings. Requires ``manifest=True`` in the options.
+        """
+        if not self.last_result or "manifest" not in self.last_result:
+            raise ValueError("No manifest recorded; run compare() with manifest=True.")
+
+        with open(path, "w") as f:
+            json.dump(self.last_result["manifest"], f, indent=2)
+
+    def save(self, path: str) -> None:
+        """
+        Writes the last comparison's result as JSON (conventionally
+        ``.kdiff``), to reopen with ``koala-diff explore`` or ``json.load``.
+        """
+        if not self.last_result:
+            raise ValueError("No comparison has been run yet.")
+
+        with open(path, "w") as f:
+            json.dump(self.last_result, f, indent=2)
+
+    def gate(self, **thresholds: Any) -> Dict[str, Any]:
+        """
+        Returns a compact pass/fail verdict of the last comparison, e.g. for
+        an Airflow XCom or Dagster metadata: ``status``, ``failed_checks``,
+        ``worst_column``, ``mismatch_rate`` and a one-line ``summary``.
+
+        Defaults only pass identical files; loosen them with
+        ``max_mismatch_rate``, ``max_added``, ``max_removed``,
+        ``min_column_match_rate`` and ``fail_on_breaking_schema``; require a
+        share of cells actually compared with ``min_cell_coverage``.
+        """
+        if not self.last_result:
+            raise ValueError("No comparison has been run yet.")
+
+        return _rust_gate(self.last_result, **thresholds)
+
     def get_mismatch_df(self) -> pl.DataFrame:
         """
         Returns a Polars DataFrame containing rows that exist in both files
-        but have differing values in at least one column.
+        but have differing values in at least one column: file A's columns
+        plus file B's values suffixed ``_b``, as read and compared by the
+        engine (the ``"modified"`` frame of ``get_diff_frames``).
         """
         if not self.file_a or not self.file_b:
             raise ValueError("No comparison has been run yet.")
-            
-        # Scan files lazily
-        def scan_df(path):
-            if path.endswith(".parquet") or path.endswith(".pq"):
-                return pl.scan_parquet(path)
-            if path.endswith(".json"):
-                # Standard JSON doesn't support lazy scanning in Polars yet
-                return pl.read_json(path).lazy()
-            if path.endswith(".jsonl") or path.endswith(".ndjson"):
-                return pl.scan_ndjson(path)
-            return pl.scan_csv(path)
-
-        lf_a = scan_df(self.file_a)
-        lf_b = scan_df(self.file_b)
-
-        # Join on keys
-        inner = lf_a.join(lf_b, on=self.key_columns, suffix="_right")
-        
-        # Build a filter mask for any column difference
-        mask = None
-        for col_name in lf_a.collect_schema().names():
-            if col_name in self.key_columns:
-                continue
-            
-            # Compare values and handle nulls
-            # We use neq_missing equivalent: (a != b) | (a.is_null() != b.is_null())
-            # In modern Polars, we can also use pl.col(col_name).eq_missing(pl.col(f"{col_name}_right")).not_()
-            col_diff = pl.col(col_name).eq_missing(pl.col(f"{col_name}_right")).not_()
-            
-            if mask is None:
-                mask = col_diff
-            else:
-                mask = mask | col_diff
-        
-        if mask is not None:
-            # We collect using streaming to keep memory usage low
-            return inner.filter(mask).collect(streaming=True)
-        return pl.DataFrame(schema=inner.collect_schema())
+
+        return _rust_diff_rows(self.file_a, self.file_b, self.key_columns, **self.options)["modified"]
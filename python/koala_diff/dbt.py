@@ -0,0 +1,151 @@
+# koala_diff/dbt.py
+#
+# Resolves a dbt model from `target/manifest.json` into export paths and
+# default diff keys, so prod vs CI exports can be diffed without restating
+# what the dbt project already declares.
+
+import json
+from dataclasses import dataclass, field
+from typing import Any, Dict, List, Optional, Tuple
+
+from .core import DataDiff
+
+
+@dataclass
+class DbtModel:
+    unique_id: str
+    name: str
+    database: Optional[str]
+    schema: Optional[str]
+    alias: str
+    key_columns: List[str]
+    columns: List[str] = field(default_factory=list)
+    column_tags: Dict[str, Dict[str, str]] = field(default_factory=dict)
+
+    def path(self, template: str, **overrides: Any) -> str:
+        """
+        Formats an export path, e.g. ``"exports/{schema}/{alias}.parquet"``.
+        ``overrides`` replace fields, typically ``schema="ci_1234"``.
+        """
+        fields = {
+            "database": self.database,
+            "schema": self.schema,
+            "alias": self.alias,
+            "name": self.name,
+        }
+        fields.update(overrides)
+        return template.format(**fields)
+
+
+def _find_node(manifest: Dict[str, Any], model: str) -> Dict[str, Any]:
+    nodes = manifest.get("nodes", {})
+    if model in nodes:
+        return nodes[model]
+    matches = [
+        node for node in nodes.values()
+        if node.get("resource_type") in ("model", "snapshot", "seed")
+        and model in (node.get("name"), node.get("alias"))
+    ]
+    if not matches:
+        raise ValueError(f"dbt model '{model}' not found in manifest")
+    if len(matches) > 1:
+        ids = ", ".join(sorted(node["unique_id"] for node in matches))
+        raise ValueError(f"dbt model '{model}' is ambiguous: {ids}")
+    return matches[0]
+
+
+def _declared_keys(manifest: Dict[str, Any], node: Dict[str, Any]) -> List[str]:
+    """
+    Primary key in order of preference: a model-level primary_key constraint,
+    column-level primary_key constraints, a unique_combination_of_columns
+    test, then a single-column unique test.
+    """
+    for constraint in node.get("constraints", []):
+        if constraint.get("type") == "primary_key" and constraint.get("columns"):
+            return list(constraint["columns"])
+
+    pk_columns = [
+        name for name, column in node.get("columns", {}).items()
+        if any(c.get("type") == "primary_key" for c in column.get("constraints", []))
+    ]
+    if pk_columns:
+        return pk_columns
+
+    combination: Optional[List[str]] = None
+    unique: Optional[str] = None
+    for test in manifest.get("nodes", {}).values():
+        if test.get("resource_type") != "test" or test.get("attached_node") != node["unique_id"]:
+            continue
+        metadata = test.get("test_metadata") or {}
+        kwargs = metadata.get("kwargs", {})
+        if metadata.get("name") == "unique_combination_of_columns" and combination is None:
+            combination = list(kwargs.get("combination_of_columns", []))
+        elif metadata.get("name") == "unique" and unique is None:
+            unique = test.get("column_name") or kwargs.get("column_name")
+    if combination:
+        return combination
+    if unique:
+        return [unique]
+    return []
+
+
+def load_dbt_model(manifest_path: str, model: str) -> DbtModel:
+    """
+    Reads ``model`` (name, alias or unique_id) from a dbt ``manifest.json``.
+    Column ``meta`` entries with string values become diff column tags.
+    """
+    with open(manifest_path) as f:
+        manifest = json.load(f)
+    node = _find_node(manifest, model)
+
+    column_tags = {}
+    for name, column in node.get("columns", {}).items():
+        tags = {k: str(v) for k, v in (column.get("meta") or {}).items() if isinstance(v, (str, int, float, bool))}
+        if tags:
+            column_tags[name] = tags
+
+    return DbtModel(
+        unique_id=node["unique_id"],
+        name=node["name"],
+        database=node.get("database"),
+        schema=node.get("schema"),
+        alias=node.get("alias") or node["name"],
+        key_columns=_declared_keys(manifest, node),
+        columns=list(node.get("columns", {})),
+        column_tags=column_tags,
+    )
+
+
+def dbt_diff(
+    manifest_path: str,
+    model: str,
+    path_template: str,
+    schema_a: Optional[str] = None,
+    schema_b: Optional[str] = None,
+    key_columns: Optional[List[str]] = None,
+    **options: Any,
+) -> Tuple[DataDiff, str, str]:
+    """
+    Builds a ``DataDiff`` for a dbt model and the two export paths to compare,
+    e.g. prod (``schema_a``) vs the CI schema (``schema_b``)::
+
+        differ, a, b = dbt_diff("target/manifest.json", "orders",
+                                "exports/{schema}/{alias}.parquet", schema_b="ci_42")
+        differ.compare(a, b)
+
+    Keys default to the model's declared primary key or unique tests.
+    """
+    dbt_model = load_dbt_model(manifest_path, model)
+    keys = key_columns or dbt_model.key_columns
+    if not keys:
+        raise ValueError(
+            f"dbt model '{dbt_model.name}' declares no primary key or unique test; pass key_columns"
+        )
+
+    tags = {**dbt_model.column_tags, **options.pop("column_tags", {})}
+    if tags:
+        options["column_tags"] = tags
+
+    path_a = dbt_model.path(path_template, **({"schema": schema_a} if schema_a else {}))
+    path_b = dbt_model.path(path_template, **({"schema": schema_b} if schema_b else {}))
+    return DataDiff(key_columns=keys, **options), path_a, path_b
@@ -0,0 +1,118 @@
+# koala_diff/orchestration.py
+#
+# Thin helpers for Airflow/Dagster tasks: run a diff, log the summary, push
+# metrics and fail the task when the gate does not pass. No orchestrator is
+# imported here; the callables work in a PythonOperator, @task or @op alike.
+
+import logging
+from typing import Any, Callable, Dict, List, Optional
+
+from .core import DataDiff
+from .render import render
+
+logger = logging.getLogger(__name__)
+
+MetricsSink = Callable[[Dict[str, float]], None]
+
+
+class DiffCheckFailed(RuntimeError):
+    """
+    Raised when a diff does not pass its gate. ``gate`` holds the verdict.
+    """
+
+    def __init__(self, gate: Dict[str, Any]):
+        super().__init__(gate.get("summary", "koala-diff check failed"))
+        self.gate = gate
+
+
+def diff_metrics(result: Dict[str, Any], gate: Dict[str, Any]) -> Dict[str, float]:
+    """
+    Flat numeric metrics of a diff, suitable for StatsD/Prometheus or
+    Dagster output metadata.
+    """
+    metrics = {
+        "koala_diff.rows_a": result.get("total_rows_a", 0),
+        "koala_diff.rows_b": result.get("total_rows_b", 0),
+        "koala_diff.matched_rows": result.get("joined_count", 0),
+        "koala_diff.modified_rows": result.get("modified_rows_count", 0),
+        "koala_diff.added_rows": result.get("added", 0),
+        "koala_diff.removed_rows": result.get("removed", 0),
+        "koala_diff.mismatch_rate": gate.get("mismatch_rate", 0.0),
+        "koala_diff.failed_checks": len(gate.get("failed_checks", [])),
+    }
+    return {name: float(value) for name, value in metrics.items()}
+
+
+def run_diff_check(
+    file_a: str,
+    file_b: str,
+    key_columns: List[str],
+    thresholds: Optional[Dict[str, Any]] = None,
+    metrics: Optional[MetricsSink] = None,
+    log: Optional[logging.Logger] = None,
+    raise_on_failure: bool = True,
+    **options: Any,
+) -> Dict[str, Any]:
+    """
+    Runs the diff, logs its markdown summary, pushes ``diff_metrics`` to
+    ``metrics`` (if given) and returns the gate verdict, which is small
+    enough for an XCom. Raises ``DiffCheckFailed`` if the gate fails and
+    ``raise_on_failure`` is set.
+
+    ``thresholds`` are passed to ``DataDiff.gate``; ``options`` to the diff.
+    """
+    log = log or logger
+    differ = DataDiff(key_columns=key_columns, **options)
+    result = differ.compare(file_a, file_b)
+    gate = differ.gate(**(thresholds or {}))
+
+    log.info("koala-diff %s vs %s\n%s", file_a, file_b, render(result, "markdown"))
+    if metrics is not None:
+        metrics(diff_metrics(result, gate))
+
+    if gate.get("status") != "pass":
+        log.error(gate.get("summary", "koala-diff check failed"))
+        if raise_on_failure:
+            raise DiffCheckFailed(gate)
+    else:
+        log.info(gate.get("summary", "koala-diff check passed"))
+    return gate
+
+
+class KoalaDiffOperator:
+    """
+    Reusable diff check configured once, called per run::
+
+        check = KoalaDiffOperator(key_columns=["id"], thresholds={"max_mismatch_rate": 0.5})
+        PythonOperator(task_id="diff", python_callable=check,
+                       op_kwargs={"file_a": "...", "file_b": "..."})
+
+    Calling it returns the gate dict (pushed as the task's XCom) or raises
+    ``DiffCheckFailed``.
+    """
+
+    def __init__(
+        self,
+        key_columns: List[str],
+        thresholds: Optional[Dict[str, Any]] = None,
+        metrics: Optional[MetricsSink] = None,
+        raise_on_failure: bool = True,
+        **options: Any,
+    ):
+        self.key_columns = key_columns
+        self.thresholds = thresholds or {}
+        self.metrics = metrics
+        self.raise_on_failure = raise_on_failure
+        self.options = options
+
+    def __call__(self, file_a: str, file_b: str, **context: Any) -> Dict[str, Any]:
+        # Orchestrator context (ti, ds, ...) is accepted and ignored
+        return run_diff_check(
+            file_a,
+            file_b,
+            self.key_columns,
+            thresholds=self.thresholds,
+            metrics=self.metrics,
+            raise_on_failure=self.raise_on_failure,
+            **self.options,
+        )
@@ -0,0 +1,47 @@
+# koala_diff/render.py
+#
+# Uniform `render(result, format, **options)` entry point. Formats come from the
+# Rust renderer registry; Python-side renderers can be registered on top
+# without touching the engine.
+
+from typing import Any, Callable, Dict, List, Union
+
+try:
+    from ._internal import render as _rust_render
+    from ._internal import render_formats as _rust_render_formats
+except ImportError:
+    # Fallback for development/IDE linting without binary
+    def _rust_render(result, format, **options):
+        raise RuntimeError("Rust extension not compiled")
+
+    def _rust_render_formats():
+        return []
+
+Renderer = Callable[..., Union[str, bytes]]
+
+_RENDERERS: Dict[str, Renderer] = {}
+
+
+def register_renderer(name: str, renderer: Renderer) -> None:
+    """
+    Registers a Python renderer: ``renderer(result, **options) -> str | bytes``.
+    Takes precedence over a built-in format of the same name.
+    """
+    _RENDERERS[name] = renderer
+
+
+def available_formats() -> List[str]:
+    """
+    Lists every format `render` accepts.
+    """
+    return sorted(set(_rust_render_formats()) | set(_RENDERERS))
+
+
+def render(result: Dict[str, Any], format: str = "text", **options: Any) -> Union[str, bytes]:
+    """
+    Renders a diff result as text, json, markdown, html, xlsx, ...
+    """
+    if format in _RENDERERS:
+        return _RENDERERS[format](result, **options)
+    return _rust_render(result, format, **options)
+
@@ -0,0 +1,6 @@
+// Registers the routines exported by src/r.rs (extendr_module! koaladiff)
+void R_init_koaladiff_extendr(void *dll);
+
+void R_init_koaladiff(void *dll) {
+    R_init_koaladiff_extendr(dll);
+}
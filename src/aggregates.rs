@@ -0,0 +1,121 @@
+// koala-diff/src/aggregates.rs
+// Per-column aggregate assertions (`aggregate_checks`), evaluated on each whole file
+
+use std::collections::BTreeMap;
+
+use polars::prelude::*;
+
+use crate::error::{Context, DiffError, Result};
+use crate::result::AggregateCheck;
+
+/// The aggregates `aggregate_checks` accepts.
+pub const AGGREGATES: [&str; 7] = [
+    "sum",
+    "mean",
+    "min",
+    "max",
+    "count",
+    "null_count",
+    "count_distinct",
+];
+
+/// Aggregates only defined on numeric columns.
+pub fn is_numeric(aggregate: &str) -> bool {
+    matches!(aggregate, "sum" | "mean" | "min" | "max")
+}
+
+fn expr(column: &str, aggregate: &str) -> Expr {
+    let values = col(column);
+    let agg = match aggregate {
+        "sum" => values.cast(DataType::Float64).sum(),
+        "mean" => values.cast(DataType::Float64).mean(),
+        "min" => values.cast(DataType::Float64).min(),
+        "max" => values.cast(DataType::Float64).max(),
+        "count" => values.count(),
+        "null_count" => values.null_count(),
+        _ => values.n_unique(),
+    };
+    agg.cast(DataType::Float64)
+}
+
+/// Evaluates every check (`column -> {aggregate: tolerance}`) on both files,
+/// in one pass over each. A column missing from a file has no value there,
+/// which fails the check.
+pub fn evaluate(
+    checks: &BTreeMap<String, BTreeMap<String, f64>>,
+    (lf_a, schema_a, file_a): (LazyFrame, &Schema, &str),
+    (lf_b, schema_b, file_b): (LazyFrame, &Schema, &str),
+) -> Result<Vec<AggregateCheck>> {
+    let checks: Vec<(&str, &str, f64)> = checks
+        .iter()
+        .flat_map(|(column, aggregates)| {
+            aggregates
+                .iter()
+                .map(move |(aggregate, &tol)| (column.as_str(), aggregate.as_str(), tol))
+        })
+        .collect();
+    let values_a = values(lf_a, schema_a, file_a, &checks)?;
+    let values_b = values(lf_b, schema_b, file_b, &checks)?;
+
+    Ok(checks
+        .iter()
+        .zip(values_a.into_iter().zip(values_b))
+        .map(|(&(column, aggregate, tolerance), (value_a, value_b))| {
+            let passed = match (value_a, value_b) {
+                (Some(a), Some(b)) => (b - a).abs() <= tolerance,
+                (None, None) => schema_a.contains(column) && schema_b.contains(column),
+                _ => false,
+            };
+            AggregateCheck {
+                column: column.to_string(),
+                aggregate: aggregate.to_string(),
+                value_a,
+                value_b,
+                tolerance,
+                passed,
+            }
+        })
+        .collect())
+}
+
+/// The value of each check on one file, `None` where the column is missing
+/// or the aggregate is undefined (the mean of no values).
+fn values(
+    lf: LazyFrame,
+    schema: &Schema,
+    path: &str,
+    checks: &[(&str, &str, f64)],
+) -> Result<Vec<Option<f64>>> {
+    let mut aggs = Vec::new();
+    for (i, &(column, aggregate, _)) in checks.iter().enumerate() {
+        let Some(dtype) = schema.get(column) else {
+            continue;
+        };
+        if is_numeric(aggregate) && !dtype.is_numeric() {
+            return Err(DiffError::invalid(format!(
+                "aggregate check '{}' needs a numeric column, got {:?}",
+                aggregate, dtype
+            ))
+            .in_column(column)
+            .in_file(path));
+        }
+        aggs.push(expr(column, aggregate).alias(format!("_agg{}", i)));
+    }
+    if aggs.is_empty() {
+        return Ok(vec![None; checks.len()]);
+    }
+
+    let df = lf
+        .select(aggs)
+        .collect()
+        .stage("evaluating aggregate checks")
+        .file(path)?;
+    Ok((0..checks.len())
+        .map(|i| {
+            df.column(&format!("_agg{}", i))
+                .ok()
+                .and_then(|c| c.get(0).ok())
+                .and_then(|v| v.try_extract::<f64>().ok())
+        })
+        .collect())
+}
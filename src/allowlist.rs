@@ -0,0 +1,70 @@
+// koala-diff/src/allowlist.rs
+// Row-level scoping: only rows whose key appears in an allowlist file are read
+
+use polars::prelude::*;
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::{DiffOptions, ReadOptions};
+use crate::reader;
+
+/// Restricts `lf` to the keys listed in `opts.key_allowlist`, if set.
+///
+/// A single key becomes an `is_in` predicate that Polars pushes into the scan
+/// (row-group skipping for Parquet); composite keys use a semi join. The
+/// allowlist must name the key columns, except that a one-column allowlist
+/// for a single key is used whatever its header.
+pub fn apply(mut lf: LazyFrame, keys: &[String], opts: &DiffOptions) -> Result<LazyFrame> {
+    let Some(path) = &opts.key_allowlist else {
+        return Ok(lf);
+    };
+    let schema = lf.collect_schema().stage("applying the key allowlist")?;
+    let mut allowed = reader::scan(path, &ReadOptions::default())?;
+    let allowed_schema = allowed
+        .collect_schema()
+        .stage("reading the key allowlist")
+        .file(path)?;
+
+    // Bring the allowlist columns to the key names and dtypes of the data
+    let mut columns = Vec::with_capacity(keys.len());
+    for key in keys {
+        let source = if allowed_schema.contains(key) {
+            key.as_str()
+        } else if keys.len() == 1 && allowed_schema.len() == 1 {
+            allowed_schema.iter_names().next().unwrap().as_str()
+        } else {
+            return Err(DiffError::invalid(format!(
+                "Key allowlist {} has no column '{}'",
+                path, key
+            )));
+        };
+        let dtype = schema
+            .get(key)
+            .cloned()
+            .ok_or_else(|| DiffError::invalid(format!("Unknown key column '{}'", key)))?;
+        columns.push(col(source).cast(dtype).alias(key.as_str()));
+    }
+    let allowed = allowed
+        .select(columns)
+        .unique(None, UniqueKeepStrategy::Any);
+
+    if let [key] = keys {
+        let values = allowed
+            .collect()
+            .stage("reading the key allowlist")
+            .file(path)?
+            .column(key)
+            .stage("reading the key allowlist")
+            .file(path)?
+            .as_materialized_series()
+            .clone();
+        return Ok(lf.filter(col(key.as_str()).is_in(lit(values).implode(), false)));
+    }
+
+    let key_exprs: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+    Ok(lf.join(
+        allowed,
+        key_exprs.clone(),
+        key_exprs,
+        JoinArgs::new(JoinType::Semi),
+    ))
+}
@@ -0,0 +1,150 @@
+// koala-diff/src/append.rs
+// Windowed diffing for append-only logs keyed by a monotonically increasing column
+
+use polars::prelude::*;
+
+use crate::error::{Context, DiffError, Result};
+use crate::result::AppendLog;
+
+/// The overlapping range of the append column shared by both files.
+#[derive(Debug, Clone, Copy)]
+pub struct AppendWindow {
+    pub start: i64,
+    pub end: i64,
+    /// Rows in B past the end of A (new history, not compared).
+    pub appended_rows: usize,
+    /// Rows in A before the start of B (history B no longer carries).
+    pub truncated_rows: usize,
+}
+
+impl AppendWindow {
+    /// Finds the overlap of `key` between the old (A) and new (B) file.
+    ///
+    /// The column must be integer or temporal; it is compared on its `Int64`
+    /// representation.
+    pub fn resolve(lf_a: &LazyFrame, lf_b: &LazyFrame, key: &str) -> Result<Self> {
+        let bounds = |lf: &LazyFrame, name: &str| -> Result<(i64, i64)> {
+            let res = lf
+                .clone()
+                .select([
+                    position(key).min().alias("min"),
+                    position(key).max().alias("max"),
+                ])
+                .collect()
+                .stage("reading the append column")
+                .file(name)
+                .column(key)?;
+            let get = |c: &str| {
+                res.column(c)
+                    .ok()
+                    .and_then(|c| c.get(0).ok())
+                    .and_then(|v| v.try_extract::<i64>().ok())
+            };
+            // An empty file (or an all-null column) has no position to
+            // window on
+            match (get("min"), get("max")) {
+                (Some(min), Some(max)) => Ok((min, max)),
+                _ => Err(DiffError::invalid(
+                    "The append column has no values, so there is no window to compare",
+                )
+                .in_file(name)
+                .in_column(key)),
+            }
+        };
+
+        let (min_a, max_a) = bounds(lf_a, "File A")?;
+        let (min_b, max_b) = bounds(lf_b, "File B")?;
+        let start = min_a.max(min_b);
+        let end = max_a.min(max_b);
+
+        let count = |lf: &LazyFrame, predicate: Expr| -> Result<usize> {
+            let res = lf
+                .clone()
+                .filter(predicate)
+                .select([len().alias("n")])
+                .collect()
+                .stage("counting appended rows")
+                .column(key)?;
+            let n = res
+                .column("n")
+                .and_then(|c| c.get(0))
+                .and_then(|v| v.try_extract::<u64>())
+                .stage("counting appended rows")?;
+            Ok(n as usize)
+        };
+
+        Ok(Self {
+            start,
+            end,
+            appended_rows: count(lf_b, position(key).gt(lit(end)))?,
+            truncated_rows: count(lf_a, position(key).lt(lit(start)))?,
+        })
+    }
+
+    /// Restricts `lf` to the rows inside the window.
+    pub fn restrict(&self, lf: LazyFrame, key: &str) -> LazyFrame {
+        lf.filter(
+            position(key)
+                .gt_eq(lit(self.start))
+                .and(position(key).lt_eq(lit(self.end))),
+        )
+    }
+
+    /// Summarizes the window next to the diff counts computed inside it.
+    pub fn summary(&self, key: &str, removed: usize, modified: usize) -> AppendLog {
+        AppendLog {
+            append_key: key.to_string(),
+            window_start: self.start,
+            window_end: self.end,
+            appended_rows: self.appended_rows,
+            truncated_rows: self.truncated_rows,
+            missing_history_rows: removed,
+            rewritten_rows: modified,
+            // B is a superset of A when nothing in the overlap was dropped or
+            // rewritten, and none of A's history falls before the window
+            is_superset: removed == 0 && modified == 0 && self.truncated_rows == 0,
+        }
+    }
+}
+
+fn position(key: &str) -> Expr {
+    col(key).cast(DataType::Int64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(from: i64, to: i64) -> LazyFrame {
+        df!("seq" => (from..=to).collect::<Vec<i64>>())
+            .unwrap()
+            .lazy()
+    }
+
+    #[test]
+    fn window_counts_appended_and_truncated_rows() {
+        let window = AppendWindow::resolve(&log(1, 10), &log(4, 15), "seq").unwrap();
+        assert_eq!((window.start, window.end), (4, 10));
+        assert_eq!(window.appended_rows, 5);
+        assert_eq!(window.truncated_rows, 3);
+
+        let restricted = window.restrict(log(1, 10), "seq").collect().unwrap();
+        assert_eq!(restricted.height(), 7);
+        // A's first rows are gone from B: not a superset, even unchanged
+        assert!(!window.summary("seq", 0, 0).is_superset);
+    }
+
+    #[test]
+    fn pure_append_is_a_superset() {
+        let window = AppendWindow::resolve(&log(1, 10), &log(1, 12), "seq").unwrap();
+        assert_eq!((window.appended_rows, window.truncated_rows), (2, 0));
+        assert!(window.summary("seq", 0, 0).is_superset);
+        assert!(!window.summary("seq", 0, 1).is_superset);
+    }
+
+    #[test]
+    fn empty_file_has_no_window() {
+        let empty = log(1, 10).filter(lit(false));
+        assert!(AppendWindow::resolve(&empty, &log(1, 10), "seq").is_err());
+    }
+}
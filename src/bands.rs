@@ -0,0 +1,58 @@
+// koala-diff/src/bands.rs
+// Tolerance bands (`bands`): each matched row's difference graded green / amber / red
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::result::BandCounts;
+
+/// The thresholds of one column: absolute differences below `green` are
+/// green, below `amber` amber, the rest (and one-sided nulls) red.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Thresholds {
+    pub green: f64,
+    pub amber: f64,
+}
+
+/// Counts of the matched rows per band, aliased `{column}_band_{band}`.
+/// Equal values (both null, or the same infinity) are green.
+pub fn aggs(column: &str, right: &str, thresholds: &Thresholds) -> Vec<Expr> {
+    let diff = (col(column).cast(DataType::Float64) - col(right).cast(DataType::Float64)).abs();
+    let green = col(column)
+        .eq_missing(col(right))
+        .or(diff.clone().lt(lit(thresholds.green)))
+        .fill_null(false);
+    let amber = green
+        .clone()
+        .not()
+        .and(diff.lt(lit(thresholds.amber)))
+        .fill_null(false);
+    let red = green.clone().or(amber.clone()).not();
+
+    [("green", green), ("amber", amber), ("red", red)]
+        .into_iter()
+        .map(|(band, rows)| {
+            rows.cast(DataType::Float64)
+                .sum()
+                .alias(format!("{}_band_{}", column, band))
+        })
+        .collect()
+}
+
+/// Reads the counts `aggs` produced for `column`, if it was banded.
+pub fn counts(stats: &DataFrame, column: &str) -> Option<BandCounts> {
+    let count = |band: &str| -> Option<usize> {
+        let value = stats
+            .column(&format!("{}_band_{}", column, band))
+            .ok()?
+            .get(0)
+            .ok()?;
+        Some(value.try_extract::<f64>().unwrap_or(0.0) as usize)
+    };
+    Some(BandCounts {
+        green: count("green")?,
+        amber: count("amber")?,
+        red: count("red")?,
+    })
+}
@@ -0,0 +1,85 @@
+// koala-diff/src/budget.rs
+// The `timeout_seconds` time budget of one diff run
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::DiffError;
+use crate::warnings;
+
+/// Tracks how much of the run's time budget is left and where the run
+/// stopped once it ran out.
+pub struct Budget {
+    deadline: Option<Instant>,
+    /// Phase that was running, or about to start, when the budget ran out.
+    stopped_at: Option<&'static str>,
+}
+
+impl Budget {
+    /// Starts the clock; no limit without `timeout_seconds`.
+    pub fn start(timeout_seconds: Option<f64>) -> Self {
+        Self {
+            deadline: timeout_seconds.map(|s| Instant::now() + Duration::from_secs_f64(s)),
+            stopped_at: None,
+        }
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether `phase` may still start. Once one is refused, every later
+    /// phase is too, so the result never mixes in work done past the limit.
+    pub fn allows(&mut self, phase: &'static str) -> bool {
+        if self.stopped_at.is_none() && self.remaining().is_some_and(|left| left.is_zero()) {
+            self.stopped_at = Some(phase);
+        }
+        self.stopped_at.is_none()
+    }
+
+    /// Runs `pass` (typically a Polars collect) within what is left of the
+    /// budget; `None` when the budget runs out first.
+    ///
+    /// Polars offers no way to interrupt a running query, so a pass that
+    /// overruns is left detached to finish on its own thread, and its result
+    /// and warnings dropped: the run returns on time, the CPU is freed when
+    /// the pass ends. Warnings raised by a pass that finishes in time join
+    /// the caller's.
+    pub fn run<T: Send + 'static>(
+        &mut self,
+        phase: &'static str,
+        pass: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<Option<T>, DiffError> {
+        if !self.allows(phase) {
+            return Ok(None);
+        }
+        let Some(left) = self.remaining() else {
+            return Ok(Some(pass()));
+        };
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let value = pass();
+            // The receiver is gone when the pass overran
+            let _ = tx.send((value, warnings::take()));
+        });
+        match rx.recv_timeout(left) {
+            Ok((value, raised)) => {
+                raised.into_iter().for_each(warnings::raise);
+                Ok(Some(value))
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                self.stopped_at = Some(phase);
+                Ok(None)
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(DiffError::internal(format!("The {} pass panicked", phase)).in_stage(phase))
+            }
+        }
+    }
+
+    pub fn stopped_at(&self) -> Option<&'static str> {
+        self.stopped_at
+    }
+}
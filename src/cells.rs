@@ -0,0 +1,125 @@
+// koala-diff/src/cells.rs
+// Long-format cell diff: one row per (key, column) whose value changed
+
+use polars::prelude::*;
+
+use crate::compare;
+use crate::error::{Context, Result};
+use crate::options::DiffOptions;
+use crate::schema;
+
+/// Builds the cell diff of two frames joined on `keys`.
+///
+/// Columns: the key columns, `column`, `value_a`, `value_b` (values are cast
+/// to strings so every compared column fits the same two value columns).
+pub fn cell_diff(
+    mut lf_a: LazyFrame,
+    mut lf_b: LazyFrame,
+    keys: &[String],
+    opts: &DiffOptions,
+) -> Result<LazyFrame> {
+    let schema_a = lf_a.collect_schema().stage("building the cell diff")?;
+    let schema_b = lf_b.collect_schema().stage("building the cell diff")?;
+    let key_exprs: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+
+    let joined = lf_a.join(
+        lf_b,
+        key_exprs.clone(),
+        key_exprs.clone(),
+        JoinArgs::new(JoinType::Inner).with_suffix(Some("_right".into())),
+    );
+
+    let mut parts = Vec::new();
+    for (name, dtype_a) in schema_a.iter() {
+        if keys.iter().any(|k| k == name.as_str()) {
+            continue;
+        }
+        let Some(dtype_b) = schema_b
+            .get(name.as_str())
+            .filter(|dtype_b| schema::comparable(dtype_a, dtype_b))
+        else {
+            continue;
+        };
+        let right_name = format!("{}_right", name);
+        let mut select = key_exprs.clone();
+        select.extend([
+            lit(name.as_str()).alias("column"),
+            col(name.as_str()).cast(DataType::String).alias("value_a"),
+            col(&right_name).cast(DataType::String).alias("value_b"),
+        ]);
+        parts.push(
+            joined
+                .clone()
+                .filter(compare::diff_expr(
+                    name,
+                    &right_name,
+                    dtype_a,
+                    dtype_b,
+                    opts,
+                ))
+                .select(select),
+        );
+    }
+
+    if parts.is_empty() {
+        let mut select = key_exprs;
+        select.extend([
+            lit(NULL).cast(DataType::String).alias("column"),
+            lit(NULL).cast(DataType::String).alias("value_a"),
+            lit(NULL).cast(DataType::String).alias("value_b"),
+        ]);
+        return Ok(joined.select(select).limit(0));
+    }
+    concat(parts, UnionArgs::default()).stage("building the cell diff")
+}
+
+/// The complete row-level diff of two frames joined on `keys`.
+///
+/// Columns: the key columns, `status` (`added`, `removed` or `modified`),
+/// `column`, `old_value`, `new_value`. Modified rows get one row per changed
+/// cell; added and removed rows one row each, with null column and values.
+pub fn row_level_diff(
+    lf_a: LazyFrame,
+    lf_b: LazyFrame,
+    keys: &[String],
+    opts: &DiffOptions,
+) -> Result<LazyFrame> {
+    let key_exprs: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+    let one_sided = |left: &LazyFrame, right: &LazyFrame, status: &str| {
+        let mut select = key_exprs.clone();
+        select.extend([
+            lit(status).alias("status"),
+            lit(NULL).cast(DataType::String).alias("column"),
+            lit(NULL).cast(DataType::String).alias("old_value"),
+            lit(NULL).cast(DataType::String).alias("new_value"),
+        ]);
+        left.clone()
+            .join(
+                right.clone(),
+                key_exprs.clone(),
+                key_exprs.clone(),
+                JoinArgs::new(JoinType::Anti),
+            )
+            .select(select)
+    };
+    let removed = one_sided(&lf_a, &lf_b, "removed");
+    let added = one_sided(&lf_b, &lf_a, "added");
+
+    let mut select = key_exprs.clone();
+    select.extend([
+        lit("modified").alias("status"),
+        col("column"),
+        col("value_a").alias("old_value"),
+        col("value_b").alias("new_value"),
+    ]);
+    let modified = cell_diff(lf_a, lf_b, keys, opts)?.select(select);
+
+    concat(
+        [removed, added, modified],
+        UnionArgs {
+            to_supertypes: true,
+            ..Default::default()
+        },
+    )
+    .stage("building the row-level diff")
+}
@@ -0,0 +1,250 @@
+// koala-diff/src/cloud.rs
+// Object store URIs (`s3://bucket/key.parquet`, `gs://...`, `az://...`) as inputs, scanned in place
+
+#[cfg(feature = "cloud")]
+use std::collections::HashMap;
+#[cfg(feature = "cloud")]
+use std::env;
+#[cfg(feature = "cloud")]
+use std::path::PathBuf;
+
+#[cfg(feature = "cloud")]
+use polars::io::cloud::{AmazonS3ConfigKey, AzureConfigKey, CloudOptions};
+use polars::prelude::*;
+
+#[cfg(feature = "cloud")]
+use crate::csv;
+use crate::database;
+#[cfg(feature = "cloud")]
+use crate::error::Context;
+use crate::error::{DiffError, Result};
+use crate::http;
+use crate::options::ReadOptions;
+
+const SCHEMES: [&str; 8] = [
+    "s3://", "s3a://", "gs://", "gcs://", "az://", "azure://", "abfs://", "abfss://",
+];
+
+/// Object store URIs, HTTP(S) URLs and database connection strings: no
+/// local file to stat, hash or memory-map.
+pub fn is_remote(path: &str) -> bool {
+    SCHEMES.iter().any(|scheme| path.starts_with(scheme))
+        || http::is_url(path)
+        || database::is_uri(path)
+}
+
+/// S3 URIs: the only store taking SSE-C keys.
+pub fn is_s3(path: &str) -> bool {
+    path.starts_with("s3://") || path.starts_with("s3a://")
+}
+
+/// Scans the object at `path` without downloading it first: Parquet
+/// (`.parquet` / `.pq`), NDJSON (`.jsonl` / `.ndjson`) or CSV.
+///
+/// Only ranged reads are made, so Parquet column and row-group pruning
+/// apply as for local files.
+#[cfg(feature = "cloud")]
+pub fn scan(path: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    let local_only = [
+        ("mmap", read.mmap.is_some()),
+        ("record_schema", read.record_schema.is_some()),
+        ("unload_manifest", read.unload_manifest),
+        ("debezium", read.debezium),
+        ("encoding", read.encoding.is_some()),
+        ("fixed_width", read.fixed_width.is_some()),
+        ("parquet_footer_key", read.parquet_footer_key.is_some()),
+    ];
+    if let Some((option, _)) = local_only.iter().find(|(_, set)| *set) {
+        return Err(
+            DiffError::unsupported(format!("'{}' only applies to local files", option))
+                .in_file(path),
+        );
+    }
+
+    let lower = path.to_lowercase();
+    let local_format = [".json", ".avro", ".orc", ".xlsx", ".xlsm", ".xls"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext));
+    if let Some(ext) = local_format {
+        return Err(DiffError::unsupported(format!(
+            "{} objects can't be read in place; download them first",
+            ext
+        ))
+        .in_file(path));
+    }
+
+    let options = Some(cloud_options(path, read));
+    let lf = if lower.ends_with(".parquet") || lower.ends_with(".pq") {
+        let args = ScanArgsParquet {
+            cloud_options: options,
+            ..Default::default()
+        };
+        LazyFrame::scan_parquet(path.into(), args)
+    } else if lower.ends_with(".jsonl") || lower.ends_with(".ndjson") {
+        LazyJsonLineReader::new(path.into())
+            .with_cloud_options(options)
+            .finish()
+    } else {
+        csv::finish(
+            csv::reader(path, read).with_cloud_options(options),
+            read.csv_options.as_ref(),
+        )
+    };
+    lf.stage("reading").file(path)
+}
+
+/// Credentials for the store `path` is in.
+///
+/// GCS uses Application Default Credentials, which the object store
+/// resolves itself: `GOOGLE_APPLICATION_CREDENTIALS`, else the
+/// `gcloud auth application-default login` file, else the metadata server
+/// of the GCE / GKE instance.
+#[cfg(feature = "cloud")]
+fn cloud_options(path: &str, read: &ReadOptions) -> CloudOptions {
+    match path.split_once("://").map(|(scheme, _)| scheme) {
+        Some("gs" | "gcs") => CloudOptions::default(),
+        Some("az" | "azure" | "abfs" | "abfss") => azure_options(),
+        _ => s3_options(read.sse_c_key.as_deref()),
+    }
+}
+
+/// Azure account and credentials.
+///
+/// The object store reads the `AZURE_*` environment itself: the account
+/// (`AZURE_STORAGE_ACCOUNT_NAME`, needed for `az://container/...`; abfss
+/// URIs name it), an account key, SAS token, service principal or workload
+/// identity. Without any of those, an `az login` session is used when there
+/// is one, else the managed identity of the VM / AKS pod.
+#[cfg(feature = "cloud")]
+fn azure_options() -> CloudOptions {
+    let explicit = [
+        "AZURE_STORAGE_ACCOUNT_KEY",
+        "AZURE_STORAGE_ACCESS_KEY",
+        "AZURE_STORAGE_SAS_KEY",
+        "AZURE_STORAGE_SAS_TOKEN",
+        "AZURE_STORAGE_TOKEN",
+        "AZURE_CLIENT_SECRET",
+        "AZURE_FEDERATED_TOKEN_FILE",
+        "AZURE_USE_AZURE_CLI",
+    ]
+    .iter()
+    .any(|var| env::var_os(var).is_some());
+    let cli_dir = env::var_os("AZURE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| home().map(|home| home.join(".azure")));
+    let logged_in = cli_dir.is_some_and(|dir| dir.join("azureProfile.json").is_file());
+    if explicit || !logged_in {
+        return CloudOptions::default();
+    }
+    CloudOptions::default().with_azure([(AzureConfigKey::UseAzureCli, "true")])
+}
+
+/// S3 credentials, region and endpoint.
+///
+/// The object store reads the environment itself (`AWS_ACCESS_KEY_ID`,
+/// `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN`, `AWS_REGION`,
+/// `AWS_ENDPOINT_URL`, ...). Whatever it leaves unset comes from the
+/// `AWS_PROFILE` profile (else `default`) of `~/.aws/credentials` and
+/// `~/.aws/config`, as the AWS CLI does.
+///
+/// Objects stored with SSE-C need the customer key (base64) they were
+/// written with; it is sent with every request.
+#[cfg(feature = "cloud")]
+fn s3_options(sse_c_key: Option<&str>) -> CloudOptions {
+    let profile = env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string());
+    let credentials = ini_section(
+        aws_file("AWS_SHARED_CREDENTIALS_FILE", "credentials"),
+        &profile,
+    );
+    // ~/.aws/config names all but the default profile `[profile <name>]`
+    let config_section = match profile.as_str() {
+        "default" => profile.clone(),
+        name => format!("profile {}", name),
+    };
+    let config = ini_section(aws_file("AWS_CONFIG_FILE", "config"), &config_section);
+
+    let mut keys = Vec::new();
+    // Never mix a key from the environment with a secret from a profile
+    if env::var_os("AWS_ACCESS_KEY_ID").is_none() {
+        for (key, name) in [
+            (AmazonS3ConfigKey::AccessKeyId, "aws_access_key_id"),
+            (AmazonS3ConfigKey::SecretAccessKey, "aws_secret_access_key"),
+            (AmazonS3ConfigKey::Token, "aws_session_token"),
+        ] {
+            if let Some(value) = credentials.get(name) {
+                keys.push((key, value.clone()));
+            }
+        }
+    }
+    for (key, vars, name) in [
+        (
+            AmazonS3ConfigKey::Region,
+            &["AWS_REGION", "AWS_DEFAULT_REGION"][..],
+            "region",
+        ),
+        (
+            AmazonS3ConfigKey::Endpoint,
+            &["AWS_ENDPOINT_URL"][..],
+            "endpoint_url",
+        ),
+    ] {
+        if vars.iter().all(|var| env::var_os(var).is_none()) {
+            if let Some(value) = config.get(name) {
+                keys.push((key, value.clone()));
+            }
+        }
+    }
+    if let Some(key) = sse_c_key {
+        let sse_c = "aws_sse_customer_key_base64"
+            .parse()
+            .expect("the object store takes SSE-C keys");
+        keys.push((sse_c, key.to_string()));
+    }
+    CloudOptions::default().with_aws(keys)
+}
+
+/// An AWS shared file: `$<var>`, else `~/.aws/<name>`.
+#[cfg(feature = "cloud")]
+fn aws_file(var: &str, name: &str) -> Option<PathBuf> {
+    if let Some(path) = env::var_os(var) {
+        return Some(PathBuf::from(path));
+    }
+    home().map(|home| home.join(".aws").join(name))
+}
+
+#[cfg(feature = "cloud")]
+fn home() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// `key = value` pairs of one `[section]` of an INI file; empty when the
+/// file or section doesn't exist.
+#[cfg(feature = "cloud")]
+fn ini_section(path: Option<PathBuf>, section: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let Some(text) = path.and_then(|path| std::fs::read_to_string(path).ok()) else {
+        return values;
+    };
+    let mut inside = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            inside = name.trim() == section;
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| inside) {
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    values
+}
+
+#[cfg(not(feature = "cloud"))]
+pub fn scan(path: &str, _read: &ReadOptions) -> Result<LazyFrame> {
+    Err(DiffError::unsupported(
+        "Object stores can't be read: koala-diff was built without the 'cloud' feature",
+    )
+    .in_file(path))
+}
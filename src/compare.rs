@@ -0,0 +1,127 @@
+// koala-diff/src/compare.rs
+// Per-column equality expressions used by the diff passes
+
+use polars::prelude::*;
+
+use crate::options::DiffOptions;
+
+/// Builds the expression that is `true` when `name` differs from `right_name`
+/// in a joined row. Nulls on both sides count as equal.
+pub fn diff_expr(
+    name: &str,
+    right_name: &str,
+    dtype_a: &DataType,
+    dtype_b: &DataType,
+    opts: &DiffOptions,
+) -> Expr {
+    // Sensitive columns hold hashes by now: exact match only
+    if opts.sensitive_columns.iter().any(|c| c == name) {
+        return col(name).eq_missing(col(right_name)).not();
+    }
+    if !compares_as_float(name, dtype_a, dtype_b, opts) {
+        return col(name).eq_missing(col(right_name)).not();
+    }
+    let abs_tol = opts.abs_tol_for(name);
+    let tolerant = abs_tol.is_some() || opts.rel_tol.is_some();
+
+    let a = normalize_float(col(name).cast(DataType::Float64), opts);
+    let b = normalize_float(col(right_name).cast(DataType::Float64), opts);
+    let mut is_equal = a.clone().eq_missing(b.clone());
+    if tolerant {
+        is_equal = is_equal.or(
+            within_tolerance(a.clone(), b.clone(), abs_tol, opts.rel_tol).fill_null(lit(false)),
+        );
+    }
+
+    // eq_missing treats inf == inf, so only the opt-out needs handling
+    if !opts.inf_equal {
+        is_equal = is_equal.and(a.clone().is_infinite().fill_null(lit(false)).not());
+    }
+
+    if let Some(threshold) = opts.inf_match_threshold {
+        let near_inf = |x: Expr, y: Expr| {
+            x.clone()
+                .eq(lit(f64::INFINITY))
+                .and(y.clone().gt_eq(lit(threshold)))
+                .or(x.eq(lit(f64::NEG_INFINITY)).and(y.lt_eq(lit(-threshold))))
+        };
+        is_equal = is_equal.or(near_inf(a.clone(), b.clone())
+            .or(near_inf(b, a))
+            .fill_null(lit(false)));
+    }
+
+    is_equal.not()
+}
+
+/// Whether `diff_expr` compares the column as Float64. Tolerances extend
+/// the float path to any pair of numeric columns.
+pub fn compares_as_float(
+    name: &str,
+    dtype_a: &DataType,
+    dtype_b: &DataType,
+    opts: &DiffOptions,
+) -> bool {
+    if opts.sensitive_columns.iter().any(|c| c == name) {
+        return false;
+    }
+    let tolerant = opts.abs_tol_for(name).is_some() || opts.rel_tol.is_some();
+    dtype_a.is_float()
+        || dtype_b.is_float()
+        || (tolerant && dtype_a.is_numeric() && dtype_b.is_numeric())
+}
+
+/// `true` where the value of `name` doesn't survive the cast to Float64
+/// exactly: 64-bit integers past 2^53, decimals with more than 15 digits.
+/// `None` for types every value of which does.
+pub fn lossy_cast_expr(name: &str, dtype: &DataType) -> Option<Expr> {
+    let lossy = match dtype {
+        DataType::Int64 | DataType::UInt64 => true,
+        DataType::Decimal(precision, _) => *precision > 15,
+        _ => false,
+    };
+    // Out-of-range round trips come back null, so they count as lossy too
+    lossy.then(|| {
+        col(name)
+            .cast(DataType::Float64)
+            .cast(dtype.clone())
+            .eq_missing(col(name))
+            .not()
+    })
+}
+
+/// Counts infinite values in a float column.
+pub fn inf_count_expr(name: &str) -> Expr {
+    col(name).is_infinite().cast(DataType::Int32).sum()
+}
+
+/// `|a - b| <= max(abs_tol, rel_tol * max(|a|, |b|))`, as in `math.isclose`.
+fn within_tolerance(a: Expr, b: Expr, abs_tol: Option<f64>, rel_tol: Option<f64>) -> Expr {
+    let abs = |x: Expr| {
+        when(x.clone().lt(lit(0.0)))
+            .then(x.clone() * lit(-1.0))
+            .otherwise(x)
+    };
+    let larger = |x: Expr, y: Expr| when(x.clone().gt(y.clone())).then(x).otherwise(y);
+
+    let mut allowed = lit(abs_tol.unwrap_or(0.0));
+    if let Some(rel_tol) = rel_tol {
+        allowed = larger(
+            allowed,
+            larger(abs(a.clone()), abs(b.clone())) * lit(rel_tol),
+        );
+    }
+    abs(a - b).lt_eq(allowed)
+}
+
+fn normalize_float(x: Expr, opts: &DiffOptions) -> Expr {
+    if !opts.flush_subnormals {
+        return x;
+    }
+    when(
+        x.clone()
+            .gt(lit(-f64::MIN_POSITIVE))
+            .and(x.clone().lt(lit(f64::MIN_POSITIVE))),
+    )
+    .then(lit(0.0))
+    .otherwise(x)
+}
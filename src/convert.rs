@@ -0,0 +1,65 @@
+// koala-diff/src/convert.rs
+// Conversions between Polars values and Python objects
+
+use polars::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict};
+use pyo3::IntoPyObjectExt;
+
+use crate::error::DiffError;
+
+/// Converts a single cell to the closest native Python value.
+pub fn any_to_py<'py>(py: Python<'py>, value: &AnyValue) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        AnyValue::Null => Ok(py.None().into_bound(py)),
+        AnyValue::Boolean(v) => v.into_bound_py_any(py),
+        AnyValue::Int8(v) => v.into_bound_py_any(py),
+        AnyValue::Int16(v) => v.into_bound_py_any(py),
+        AnyValue::Int32(v) => v.into_bound_py_any(py),
+        AnyValue::Int64(v) => v.into_bound_py_any(py),
+        AnyValue::UInt8(v) => v.into_bound_py_any(py),
+        AnyValue::UInt16(v) => v.into_bound_py_any(py),
+        AnyValue::UInt32(v) => v.into_bound_py_any(py),
+        AnyValue::UInt64(v) => v.into_bound_py_any(py),
+        AnyValue::Float32(v) => v.into_bound_py_any(py),
+        AnyValue::Float64(v) => v.into_bound_py_any(py),
+        AnyValue::String(v) => v.into_bound_py_any(py),
+        AnyValue::StringOwned(v) => v.as_str().into_bound_py_any(py),
+        // Temporal, nested and decimal values fall back to their display form
+        other => other.to_string().into_bound_py_any(py),
+    }
+}
+
+/// Converts row `idx` of `df` to a dict. `columns` pairs each output name
+/// with the column of `df` it is read from.
+pub fn row_to_dict<'py>(
+    py: Python<'py>,
+    df: &DataFrame,
+    idx: usize,
+    columns: &[(&str, &str)],
+) -> PyResult<Bound<'py, PyDict>> {
+    let row = PyDict::new(py);
+    for (name, source) in columns {
+        let value = df.column(source).unwrap().get(idx).unwrap();
+        row.set_item(*name, any_to_py(py, &value)?)?;
+    }
+    Ok(row)
+}
+
+/// Converts a Python scalar to a literal expression.
+pub fn py_to_lit(value: &Bound<'_, PyAny>) -> PyResult<Expr> {
+    // bool subclasses int in Python, so check it first
+    if value.is_instance_of::<PyBool>() {
+        return Ok(lit(value.extract::<bool>()?));
+    }
+    if let Ok(v) = value.extract::<i64>() {
+        return Ok(lit(v));
+    }
+    if let Ok(v) = value.extract::<f64>() {
+        return Ok(lit(v));
+    }
+    if let Ok(v) = value.extract::<String>() {
+        return Ok(lit(v));
+    }
+    Err(DiffError::unsupported(format!("Unsupported key value: {}", value)).into())
+}
@@ -0,0 +1,116 @@
+// koala-diff/src/coverage.rs
+// How much of the data a comparison actually compared, cell by cell
+
+use polars::prelude::*;
+
+use crate::error::{Context, Result};
+use crate::options::{DiffOptions, ReadOptions};
+use crate::reader::Input;
+use crate::result::{Coverage, DiffResult};
+use crate::schema::Compatibility;
+
+/// Rows of one file left out of the comparison by the key allowlist, the
+/// read window (`offset` / `limit`) or the append window.
+///
+/// `compared_rows` is the file's row count after all of them; the file is
+/// only counted again when the allowlist or read window is in use.
+pub fn suppressed_rows(
+    input: &Input,
+    read: &ReadOptions,
+    keys: &[String],
+    opts: &DiffOptions,
+    compared_rows: usize,
+    append_suppressed: usize,
+) -> Result<usize> {
+    let windowed = read.offset > 0 || read.limit.is_some();
+    if opts.key_allowlist.is_none() && !windowed {
+        return Ok(append_suppressed);
+    }
+    let unwindowed = ReadOptions {
+        offset: 0,
+        limit: None,
+        ..read.clone()
+    };
+    let df = input
+        .scan(&unwindowed, keys)?
+        .select([len().alias("n")])
+        .collect()
+        .stage("counting suppressed rows")
+        .file(&input.label())?;
+    let all = df
+        .column("n")
+        .ok()
+        .and_then(|c| c.get(0).ok())
+        .and_then(|v| v.try_extract::<u64>().ok())
+        .unwrap_or(0) as usize;
+    Ok(all.saturating_sub(compared_rows))
+}
+
+/// Builds the coverage section of `result`.
+///
+/// The cell universe is every row of either file (suppressed ones included)
+/// times every non-key column of either file. Compared cells are the matched
+/// rows times the shared columns, minus columns whose type change is
+/// breaking: their "matches" don't say much.
+pub fn measure(
+    result: &DiffResult,
+    schema_a: &Schema,
+    schema_b: &Schema,
+    key_cols: &[String],
+    suppressed_rows_a: usize,
+    suppressed_rows_b: usize,
+) -> Coverage {
+    let is_key = |name: &str| key_cols.iter().any(|k| k == name);
+    let incompatible_columns: Vec<String> = result
+        .schema_compatibility
+        .changes
+        .iter()
+        .filter(|c| c.change == "retyped" && c.compatibility == Compatibility::Breaking)
+        .filter(|c| !is_key(&c.column))
+        .map(|c| c.column.clone())
+        .collect();
+
+    let mut one_sided_columns = Vec::new();
+    let mut value_columns = 0;
+    let mut compared_columns = 0;
+    for name in schema_a.iter_names().map(|n| n.as_str()) {
+        if is_key(name) {
+            continue;
+        }
+        value_columns += 1;
+        if !schema_b.contains(name) {
+            one_sided_columns.push(name.to_string());
+        } else if !incompatible_columns.iter().any(|c| c == name) {
+            compared_columns += 1;
+        }
+    }
+    for name in schema_b.iter_names().map(|n| n.as_str()) {
+        if !is_key(name) && !schema_a.contains(name) {
+            value_columns += 1;
+            one_sided_columns.push(name.to_string());
+        }
+    }
+
+    let unmatched_rows = result.added + result.removed;
+    let rows = result.joined_count + unmatched_rows + suppressed_rows_a + suppressed_rows_b;
+    let total_cells = rows * value_columns;
+    let compared_cells = result.joined_count * compared_columns;
+    let cell_coverage = if total_cells > 0 {
+        compared_cells as f64 / total_cells as f64 * 100.0
+    } else {
+        100.0
+    };
+
+    Coverage {
+        cell_coverage,
+        compared_cells,
+        total_cells,
+        compared_columns,
+        value_columns,
+        one_sided_columns,
+        incompatible_columns,
+        unmatched_rows,
+        suppressed_rows_a,
+        suppressed_rows_b,
+    }
+}
@@ -0,0 +1,213 @@
+// koala-diff/src/csv.rs
+// CSV scanning with the per-file parse options (`csv_options`) and encoding
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+use polars::prelude::*;
+
+use crate::dtypes;
+use crate::error::{Context, Result};
+use crate::options::{CsvOptions, ReadOptions};
+use crate::result::Warning;
+use crate::warnings;
+
+/// A lazy CSV reader for `path`, parsed as its `csv_options` say (validated
+/// with the other options: single ASCII characters), the columns of its
+/// `dtypes` parsed as those types.
+pub fn reader(path: &str, read: &ReadOptions) -> LazyCsvReader {
+    configure(LazyCsvReader::new(path.into()), read)
+}
+
+fn configure(reader: LazyCsvReader, read: &ReadOptions) -> LazyCsvReader {
+    let reader = reader.with_dtype_overwrite(overwrite(read));
+    let Some(options) = &read.csv_options else {
+        return reader;
+    };
+    let byte = |c: &Option<String>| c.as_deref().map(|c| c.bytes().next());
+
+    let mut reader = reader
+        .with_has_header(options.has_header.unwrap_or(true))
+        .with_skip_rows(options.skip_rows);
+    if let Some(Some(separator)) = byte(&options.separator) {
+        reader = reader.with_separator(separator);
+    }
+    if let Some(quote_char) = byte(&options.quote_char) {
+        // "" disables quoting
+        reader = reader.with_quote_char(quote_char);
+    }
+    if let Some(comment) = &options.comment_char {
+        reader = reader.with_comment_prefix(Some(comment.as_str().into()));
+    }
+    reader
+}
+
+/// The `dtypes` by the names the parser gives the columns: headerless
+/// columns named by `column_names` are column_1, ... until renamed.
+fn overwrite(read: &ReadOptions) -> Option<SchemaRef> {
+    if read.dtypes.is_empty() {
+        return None;
+    }
+    let names = read
+        .csv_options
+        .as_ref()
+        .filter(|options| options.has_header == Some(false))
+        .and_then(|options| options.column_names.as_ref());
+    let schema = Schema::from_iter(read.dtypes.iter().filter_map(|(name, dtype)| {
+        let parsed = match names.and_then(|names| names.iter().position(|n| n == name)) {
+            Some(i) => format!("column_{}", i + 1),
+            None => name.clone(),
+        };
+        Some(Field::new(parsed.into(), dtypes::parse(dtype)?))
+    }));
+    Some(Arc::new(schema))
+}
+
+/// Finishes a `reader` of `options`, naming the columns by `column_names`
+/// (checked against the columns found first).
+pub fn finish(reader: LazyCsvReader, options: Option<&CsvOptions>) -> PolarsResult<LazyFrame> {
+    let mut lf = reader.finish()?;
+    let Some(names) = options.and_then(|o| o.column_names.as_ref()) else {
+        return Ok(lf);
+    };
+    let found: Vec<PlSmallStr> = lf.collect_schema()?.iter_names().cloned().collect();
+    if found.len() != names.len() {
+        return Err(PolarsError::ComputeError(
+            format!(
+                "csv_options 'column_names' names {} columns, the file has {}",
+                names.len(),
+                found.len()
+            )
+            .into(),
+        ));
+    }
+    Ok(lf.rename(found, names, true))
+}
+
+/// The encoding a label names: WHATWG labels ("utf-8", "windows-1252",
+/// "shift_jis", ...), also as Python spells them ("latin-1", "utf_16").
+pub fn encoding_for(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.as_bytes())
+        .or_else(|| Encoding::for_label(label.replace(['-', '_'], "").as_bytes()))
+}
+
+/// Guesses the encoding of a file that isn't UTF-8 from its bytes, with a
+/// warning naming the guess.
+pub fn detect(path: &str, bytes: &[u8]) -> &'static Encoding {
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let guess = detector.guess(None, true);
+    warnings::raise(
+        Warning::new(
+            "encoding_detected",
+            format!("{} is not UTF-8: read as {} (detected)", path, guess.name()),
+        )
+        .in_file(path),
+    );
+    guess
+}
+
+/// The decoded copy of a file, removed once read.
+struct Decoded(PathBuf);
+
+impl Drop for Decoded {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Reads a CSV file in the `encoding` read option. UTF-8 files are scanned
+/// lazily as usual; Polars only parses UTF-8, so other encodings are decoded
+/// to a temporary UTF-8 copy, read in full, then removed. "auto" reads the
+/// file once to check it is UTF-8 and otherwise guesses the encoding from
+/// its bytes, with a warning naming the guess. Bytes the encoding has no
+/// character for are replaced (U+FFFD), also with a warning.
+pub fn read_encoded(path: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    let options = read.csv_options.as_ref();
+    let scan = |path: &str| finish(reader(path, read), options);
+    let label = read.encoding.as_deref().unwrap_or("utf-8");
+    let bytes = match label {
+        "auto" => Some(fs::read(path).stage("reading").file(path)?),
+        _ => None,
+    };
+    let encoding = match &bytes {
+        Some(bytes) if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        Some(bytes) => detect(path, bytes),
+        None => encoding_for(label).unwrap_or(UTF_8),
+    };
+    if encoding == UTF_8 {
+        return scan(path).stage("reading").file(path);
+    }
+
+    let bytes = match bytes {
+        Some(bytes) => bytes,
+        None => fs::read(path).stage("reading").file(path)?,
+    };
+    let text = decode(path, encoding, &bytes);
+    let decoded = Decoded(temp_path());
+    fs::write(&decoded.0, text.as_bytes())
+        .stage("decoding")
+        .file(path)?;
+    let df = scan(&decoded.0.to_string_lossy())
+        .and_then(LazyFrame::collect)
+        .stage("reading")
+        .file(path)?;
+    Ok(df.lazy())
+}
+
+/// Reads CSV text held in memory (uploads, in the browser build) as
+/// `read` says, `label` naming it in warnings and errors. Text in another
+/// encoding than UTF-8 is decoded first, as `read_encoded` does for files.
+#[cfg(feature = "wasm")]
+pub fn read_buffer(label: &str, bytes: Vec<u8>, read: &ReadOptions) -> Result<LazyFrame> {
+    let encoding = match read.encoding.as_deref() {
+        None => UTF_8,
+        Some("auto") if std::str::from_utf8(&bytes).is_ok() => UTF_8,
+        Some("auto") => detect(label, &bytes),
+        Some(other) => encoding_for(other).unwrap_or(UTF_8),
+    };
+    let bytes = match encoding == UTF_8 {
+        true => bytes,
+        false => decode(label, encoding, &bytes).into_owned().into_bytes(),
+    };
+    let reader = LazyCsvReader::new_with_sources(ScanSources::Buffers([bytes.into()].into()));
+    finish(configure(reader, read), read.csv_options.as_ref())
+        .stage("reading")
+        .file(label)
+}
+
+/// `bytes` decoded from `encoding`, with a warning counting the byte
+/// sequences replaced (U+FFFD) for having no character there.
+fn decode<'a>(path: &str, encoding: &'static Encoding, bytes: &'a [u8]) -> Cow<'a, str> {
+    let (text, _, replaced) = encoding.decode(bytes);
+    if replaced {
+        let count = text.matches('\u{FFFD}').count();
+        warnings::raise(
+            Warning::new(
+                "encoding_replacements",
+                format!(
+                    "{} byte sequences of {} aren't valid {} and were replaced with U+FFFD",
+                    count,
+                    path,
+                    encoding.name()
+                ),
+            )
+            .in_file(path)
+            .count(count),
+        );
+    }
+    text
+}
+
+fn temp_path() -> PathBuf {
+    static COPIES: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "koala-diff-{}-{}-utf8.csv",
+        std::process::id(),
+        COPIES.fetch_add(1, Ordering::Relaxed)
+    ))
+}
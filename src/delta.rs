@@ -0,0 +1,416 @@
+// koala-diff/src/delta.rs
+// Delta Lake tables as inputs, at their latest or a past version
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use polars::prelude::*;
+use serde_json::Value;
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::ReadOptions;
+
+/// A directory with a `_delta_log` is read as a Delta table.
+pub fn is_table(path: &str) -> bool {
+    Path::new(path).join("_delta_log").is_dir()
+}
+
+/// The table state replayed from the log: live data files and the schema.
+#[derive(Default)]
+struct Snapshot {
+    /// Data file (relative to the table) -> its partition values.
+    files: BTreeMap<String, BTreeMap<String, Option<String>>>,
+    metadata: Option<Value>,
+}
+
+impl Snapshot {
+    fn apply(&mut self, action: &Value, table: &str) -> Result<()> {
+        if let Some(add) = action.get("add").filter(|a| !a.is_null()) {
+            if add.get("deletionVector").is_some_and(|dv| !dv.is_null()) {
+                return Err(DiffError::unsupported(
+                    "Delta tables with deletion vectors aren't supported; run REORG TABLE ... APPLY (PURGE) first",
+                )
+                .in_file(table));
+            }
+            let path = str_field(add, "path", table)?;
+            self.files
+                .insert(decode_path(&path), string_map(add.get("partitionValues")));
+        } else if let Some(remove) = action.get("remove").filter(|r| !r.is_null()) {
+            let path = str_field(remove, "path", table)?;
+            self.files.remove(&decode_path(&path));
+        } else if let Some(metadata) = action.get("metaData").filter(|m| !m.is_null()) {
+            self.metadata = Some(metadata.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Commits (`N.json`) and checkpoints (`N.checkpoint[.part.parts].parquet`)
+/// of a table log, by version.
+#[derive(Default)]
+struct Log {
+    commits: BTreeMap<u64, PathBuf>,
+    checkpoints: BTreeMap<u64, Vec<PathBuf>>,
+}
+
+fn list_log(table: &str) -> Result<Log> {
+    let dir = Path::new(table).join("_delta_log");
+    let mut log = Log::default();
+    let entries = std::fs::read_dir(&dir)
+        .stage("reading the Delta log")
+        .file(table)?;
+    for entry in entries {
+        let path = entry.stage("reading the Delta log").file(table)?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some((version, rest)) = name.split_once('.') else {
+            continue;
+        };
+        let Ok(version) = version.parse::<u64>() else {
+            continue;
+        };
+        if rest == "json" {
+            log.commits.insert(version, path);
+        } else if rest.starts_with("checkpoint") && rest.ends_with(".parquet") {
+            log.checkpoints.entry(version).or_default().push(path);
+        }
+    }
+    Ok(log)
+}
+
+/// Commit time of a version: its in-commit timestamp, else the commit
+/// info's, else the commit file's modification time (epoch ms).
+fn commit_timestamp(path: &Path, actions: &[Value]) -> Option<i64> {
+    let info = actions.iter().find_map(|a| a.get("commitInfo"));
+    info.and_then(|i| i.get("inCommitTimestamp").or_else(|| i.get("timestamp")))
+        .and_then(Value::as_i64)
+        .or_else(|| {
+            let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+            let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+            Some(since_epoch.as_millis() as i64)
+        })
+}
+
+fn read_commit(path: &Path, table: &str) -> Result<Vec<Value>> {
+    let text = std::fs::read_to_string(path)
+        .stage("reading the Delta log")
+        .file(table)?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| {
+                DiffError::invalid(format!("Invalid Delta commit {}: {}", path.display(), e))
+                    .in_file(table)
+            })
+        })
+        .collect()
+}
+
+/// Checkpoint rows as JSON actions (one action per row, the others null).
+#[cfg(feature = "parquet")]
+fn read_checkpoint(parts: &[PathBuf], table: &str) -> Result<Vec<Value>> {
+    let mut actions = Vec::new();
+    for part in parts {
+        let file = std::fs::File::open(part)
+            .stage("reading a Delta checkpoint")
+            .file(table)?;
+        let mut df = ParquetReader::new(file)
+            .finish()
+            .stage("reading a Delta checkpoint")
+            .file(table)?;
+        let mut buf = Vec::new();
+        JsonWriter::new(&mut buf)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish(&mut df)
+            .stage("reading a Delta checkpoint")
+            .file(table)?;
+        for line in buf.split(|b| *b == b'\n') {
+            if !line.is_empty() {
+                actions.push(serde_json::from_slice(line).map_err(|e| {
+                    DiffError::invalid(format!("Invalid Delta checkpoint: {}", e)).in_file(table)
+                })?);
+            }
+        }
+    }
+    Ok(actions)
+}
+
+/// The version to read: `delta_version`, the last one committed at or
+/// before `delta_timestamp`, or the latest.
+fn target_version(log: &Log, read: &ReadOptions, table: &str) -> Result<u64> {
+    let latest = log
+        .commits
+        .keys()
+        .chain(log.checkpoints.keys())
+        .max()
+        .copied()
+        .ok_or_else(|| DiffError::invalid("The Delta log has no commits").in_file(table))?;
+    match (read.delta_version, &read.delta_timestamp) {
+        (Some(_), Some(_)) => Err(DiffError::invalid(
+            "Pass either 'delta_version' or 'delta_timestamp', not both",
+        )
+        .in_file(table)),
+        (Some(version), None) if version > latest => Err(DiffError::invalid(format!(
+            "Delta version {} doesn't exist (latest is {})",
+            version, latest
+        ))
+        .in_file(table)),
+        (Some(version), None) => Ok(version),
+        (None, Some(timestamp)) => {
+            let at = parse_timestamp(timestamp).map_err(|e| e.in_file(table))?;
+            let mut found = None;
+            for (&version, path) in &log.commits {
+                match commit_timestamp(path, &read_commit(path, table)?) {
+                    Some(committed) if committed <= at => found = Some(version),
+                    Some(_) => break,
+                    None => {}
+                }
+            }
+            found.ok_or_else(|| {
+                DiffError::invalid(format!("No Delta version was committed by {}", timestamp))
+                    .in_file(table)
+            })
+        }
+        (None, None) => Ok(latest),
+    }
+}
+
+/// RFC 3339, "YYYY-MM-DD HH:MM:SS" (UTC) or "YYYY-MM-DD" (midnight UTC),
+/// as epoch ms.
+fn parse_timestamp(text: &str) -> Result<i64, DiffError> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Ok(at.timestamp_millis());
+    }
+    if let Ok(at) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S") {
+        return Ok(at.and_utc().timestamp_millis());
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(day
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp_millis());
+    }
+    Err(DiffError::invalid(format!(
+        "delta_timestamp must be RFC 3339 or 'YYYY-MM-DD[ HH:MM:SS]', got '{}'",
+        text
+    )))
+}
+
+/// Replays the log up to the requested version and scans its data files.
+///
+/// Starts from the newest checkpoint at or before that version, then applies
+/// the JSON commits after it. Partition columns, which the data files don't
+/// hold, are added from each file's partition values; columns added to the
+/// table after a file was written are null in that file's rows.
+#[cfg(feature = "parquet")]
+pub fn scan(table: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    let log = list_log(table)?;
+    let version = target_version(&log, read, table)?;
+
+    let mut snapshot = Snapshot::default();
+    let mut next = 0;
+    if let Some((&checkpoint, parts)) = log.checkpoints.range(..=version).next_back() {
+        for action in read_checkpoint(parts, table)? {
+            snapshot.apply(&action, table)?;
+        }
+        next = checkpoint + 1;
+    }
+    for v in next..=version {
+        let path = log.commits.get(&v).ok_or_else(|| {
+            DiffError::invalid(format!(
+                "Delta commit {} is missing from the log (vacuumed?)",
+                v
+            ))
+            .in_file(table)
+        })?;
+        for action in read_commit(path, table)? {
+            snapshot.apply(&action, table)?;
+        }
+    }
+
+    let metadata = snapshot
+        .metadata
+        .ok_or_else(|| DiffError::invalid("The Delta log has no table metadata").in_file(table))?;
+    let configuration = string_map(metadata.get("configuration"));
+    let mapping = configuration
+        .get("delta.columnMapping.mode")
+        .cloned()
+        .flatten()
+        .unwrap_or_else(|| "none".to_string());
+    if mapping != "none" {
+        return Err(DiffError::unsupported(format!(
+            "Delta column mapping ('{}') isn't supported",
+            mapping
+        ))
+        .in_file(table));
+    }
+    let schema = table_schema(&metadata, table)?;
+
+    if snapshot.files.is_empty() {
+        return Ok(DataFrame::empty_with_schema(&schema).lazy());
+    }
+    let partition_columns: HashSet<&str> = metadata
+        .get("partitionColumns")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let columns: Vec<Expr> = schema.iter_names().map(|name| col(name.clone())).collect();
+
+    let mut frames = Vec::with_capacity(snapshot.files.len());
+    for (file, values) in &snapshot.files {
+        let path = Path::new(table).join(file);
+        let mut lf =
+            LazyFrame::scan_parquet(path.to_string_lossy().as_ref().into(), Default::default())
+                .stage("reading a Delta data file")
+                .file(table)?;
+        let file_schema = lf
+            .collect_schema()
+            .stage("reading a Delta data file")
+            .file(table)?;
+        // Partition values, and nulls for columns added to the table after
+        // this file was written
+        let filled: Vec<Expr> = schema
+            .iter()
+            .filter(|(name, _)| !file_schema.contains(name))
+            .map(|(name, dtype)| {
+                let value = values
+                    .get(name.as_str())
+                    .cloned()
+                    .flatten()
+                    .filter(|_| partition_columns.contains(name.as_str()));
+                match value {
+                    Some(value) => lit(value),
+                    None => lit(NULL),
+                }
+                .cast(dtype.clone())
+                .alias(name.clone())
+            })
+            .collect();
+        frames.push(lf.with_columns(filled).select(columns.clone()));
+    }
+    let args = UnionArgs {
+        to_supertypes: true,
+        ..Default::default()
+    };
+    concat(frames, args).stage("reading").file(table)
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn scan(table: &str, _read: &ReadOptions) -> Result<LazyFrame> {
+    Err(DiffError::unsupported(
+        "Delta tables can't be read: koala-diff was built without the 'parquet' feature",
+    )
+    .in_file(table))
+}
+
+/// Polars schema of the table's `schemaString` (top-level primitive types;
+/// nested ones are left to the data files).
+fn table_schema(metadata: &Value, table: &str) -> Result<Schema> {
+    let text = metadata
+        .get("schemaString")
+        .and_then(Value::as_str)
+        .ok_or_else(|| DiffError::invalid("Delta metadata has no schemaString").in_file(table))?;
+    let parsed: Value = serde_json::from_str(text)
+        .map_err(|e| DiffError::invalid(format!("Invalid Delta schema: {}", e)).in_file(table))?;
+    let mut schema = Schema::default();
+    for field in parsed
+        .get("fields")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(name) = field.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let dtype = match field.get("type").and_then(Value::as_str) {
+            Some(primitive) => delta_dtype(primitive),
+            None => DataType::Null,
+        };
+        schema.with_column(name.into(), dtype);
+    }
+    Ok(schema)
+}
+
+fn delta_dtype(primitive: &str) -> DataType {
+    match primitive {
+        "string" => DataType::String,
+        "long" => DataType::Int64,
+        "integer" => DataType::Int32,
+        "short" => DataType::Int16,
+        "byte" => DataType::Int8,
+        "float" => DataType::Float32,
+        "double" => DataType::Float64,
+        "boolean" => DataType::Boolean,
+        "binary" => DataType::Binary,
+        "date" => DataType::Date,
+        "timestamp" => DataType::Datetime(TimeUnit::Microseconds, Some(TimeZone::UTC)),
+        "timestamp_ntz" => DataType::Datetime(TimeUnit::Microseconds, None),
+        other => match other
+            .strip_prefix("decimal(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .and_then(|args| args.split_once(','))
+        {
+            Some((precision, scale)) => DataType::Decimal(
+                precision.trim().parse().unwrap_or(38),
+                scale.trim().parse().unwrap_or(0),
+            ),
+            None => DataType::String,
+        },
+    }
+}
+
+fn str_field(action: &Value, name: &str, table: &str) -> Result<String> {
+    action
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| {
+            DiffError::invalid(format!("Delta action without '{}'", name)).in_file(table)
+        })
+}
+
+/// A string map (`partitionValues`, `configuration`) as written in commits
+/// (an object) or checkpoints (a list of key / value pairs).
+fn string_map(value: Option<&Value>) -> BTreeMap<String, Option<String>> {
+    let text = |v: &Value| v.as_str().map(str::to_string);
+    match value {
+        Some(Value::Object(map)) => map.iter().map(|(k, v)| (k.clone(), text(v))).collect(),
+        Some(Value::Array(pairs)) => pairs
+            .iter()
+            .filter_map(|pair| {
+                let key = pair.get("key")?.as_str()?.to_string();
+                Some((key, pair.get("value").and_then(text)))
+            })
+            .collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Data file paths in the log are URL-encoded.
+fn decode_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
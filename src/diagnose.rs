@@ -0,0 +1,494 @@
+// koala-diff/src/diagnose.rs
+// Root-cause heuristics run over the mismatched rows of the joined frame
+
+use polars::prelude::*;
+
+use crate::error::{Context, Result};
+use crate::result::Finding;
+
+/// Share of a column's mismatches a swap must explain to be reported.
+const SWAP_MIN_RATE: f64 = 0.5;
+/// Pairwise swap checks are quadratic; skip them beyond this many columns.
+const SWAP_MAX_COLUMNS: usize = 64;
+/// Share of a column's (non-null) mismatches a pattern must explain.
+const PATTERN_MIN_RATE: f64 = 0.9;
+/// Relative tolerance when checking that an offset or factor is constant.
+const SHIFT_TOLERANCE: f64 = 1e-6;
+/// Most decimal places considered when explaining differences by rounding.
+const ROUNDING_MAX_DECIMALS: u32 = 12;
+/// Mismatched pairs per column inspected for double-encoding artifacts.
+const MOJIBAKE_SAMPLE_ROWS: u32 = 1000;
+/// Encoding damage usually hits only the non-ASCII rows, so a lower bar.
+const MOJIBAKE_MIN_RATE: f64 = 0.5;
+
+/// A compared column with at least one mismatch.
+pub struct Mismatched {
+    pub name: String,
+    pub dtype_a: DataType,
+    pub dtype_b: DataType,
+    pub diff_count: usize,
+}
+
+impl Mismatched {
+    fn right(&self) -> String {
+        format!("{}_right", self.name)
+    }
+}
+
+/// Runs every detector. `joined` is the inner join of A and B with B's
+/// columns suffixed `_right`.
+pub fn run(joined: &LazyFrame, mismatched: &[Mismatched]) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    findings.extend(column_swaps(joined, mismatched)?);
+    findings.extend(rounding(joined, mismatched)?);
+    findings.extend(constant_shifts(joined, mismatched)?);
+    findings.extend(truncations(joined, mismatched)?);
+    findings.extend(mojibake(joined, mismatched)?);
+    Ok(findings)
+}
+
+fn extract_count(df: &DataFrame, name: &str) -> usize {
+    df.column(name)
+        .ok()
+        .and_then(|c| c.get(0).ok())
+        .and_then(|v| v.try_extract::<u64>().ok())
+        .unwrap_or(0) as usize
+}
+
+fn extract_f64(df: &DataFrame, name: &str) -> Option<f64> {
+    df.column(name)
+        .ok()
+        .and_then(|c| c.get(0).ok())
+        .and_then(|v| v.try_extract::<f64>().ok())
+        .filter(|v| v.is_finite())
+}
+
+fn same_kind(a: &DataType, b: &DataType) -> bool {
+    a == b || (a.is_numeric() && b.is_numeric()) || (a.is_string() && b.is_string())
+}
+
+/// Column pairs whose values appear exchanged: A.x == B.y and A.y == B.x.
+fn column_swaps(joined: &LazyFrame, mismatched: &[Mismatched]) -> Result<Vec<Finding>> {
+    if mismatched.len() < 2 || mismatched.len() > SWAP_MAX_COLUMNS {
+        return Ok(Vec::new());
+    }
+
+    let mut pairs = Vec::new();
+    let mut aggs = Vec::new();
+    for (i, x) in mismatched.iter().enumerate() {
+        for y in &mismatched[i + 1..] {
+            if !same_kind(&x.dtype_a, &y.dtype_b) || !same_kind(&y.dtype_a, &x.dtype_b) {
+                continue;
+            }
+            let swapped = col(x.name.as_str())
+                .eq(col(y.right().as_str()))
+                .and(col(y.name.as_str()).eq(col(x.right().as_str())))
+                .and(col(x.name.as_str()).neq(col(x.right().as_str())))
+                .fill_null(lit(false));
+            aggs.push(
+                swapped
+                    .cast(DataType::UInt64)
+                    .sum()
+                    .alias(format!("_swap_{}", pairs.len())),
+            );
+            pairs.push((x, y));
+        }
+    }
+    if aggs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let df = joined
+        .clone()
+        .select(aggs)
+        .collect()
+        .stage("diagnosing mismatches")?;
+    let mut findings = Vec::new();
+    for (i, (x, y)) in pairs.into_iter().enumerate() {
+        let swapped_rows = extract_count(&df, &format!("_swap_{}", i));
+        let swap_rate = swapped_rows as f64 / x.diff_count.max(y.diff_count) as f64;
+        if swapped_rows > 0 && swap_rate >= SWAP_MIN_RATE {
+            findings.push(Finding::ColumnSwap {
+                columns: [x.name.clone(), y.name.clone()],
+                swapped_rows,
+                swap_rate,
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// Float columns whose mismatched pairs all round to the same value at N
+/// decimal places; the most decimals that holds for is reported (coarser
+/// rounding explains even more, but says less).
+fn rounding(joined: &LazyFrame, mismatched: &[Mismatched]) -> Result<Vec<Finding>> {
+    let floats: Vec<&Mismatched> = mismatched
+        .iter()
+        .filter(|m| m.dtype_a.is_float() || m.dtype_b.is_float())
+        .collect();
+    if floats.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut aggs = Vec::new();
+    for (i, m) in floats.iter().enumerate() {
+        let a = col(m.name.as_str()).cast(DataType::Float64);
+        let b = col(m.right().as_str()).cast(DataType::Float64);
+        // Null on one side is a mismatch rounding can't explain
+        let differs = a.clone().neq_missing(b.clone());
+        aggs.push(
+            (a.clone() - b.clone())
+                .abs()
+                .max()
+                .alias(format!("_max_diff_{}", i)),
+        );
+        for decimals in 0..=ROUNDING_MAX_DECIMALS {
+            let agrees = a
+                .clone()
+                .round(decimals, RoundMode::HalfToEven)
+                .eq_missing(b.clone().round(decimals, RoundMode::HalfToEven));
+            aggs.push(
+                differs
+                    .clone()
+                    .and(agrees.not())
+                    .cast(DataType::UInt64)
+                    .sum()
+                    .alias(format!("_unexplained_{}_{}", i, decimals)),
+            );
+        }
+    }
+    let df = joined
+        .clone()
+        .select(aggs)
+        .collect()
+        .stage("diagnosing mismatches")?;
+
+    let mut findings = Vec::new();
+    for (i, m) in floats.into_iter().enumerate() {
+        let Some(max_abs_diff) = extract_f64(&df, &format!("_max_diff_{}", i)) else {
+            continue;
+        };
+        if max_abs_diff <= 0.0 {
+            continue;
+        }
+        let explained =
+            |decimals: &u32| extract_count(&df, &format!("_unexplained_{}_{}", i, decimals)) == 0;
+        if let Some(decimals) = (0..=ROUNDING_MAX_DECIMALS).rev().find(explained) {
+            findings.push(Finding::Rounding {
+                column: m.name.clone(),
+                decimals,
+                max_abs_diff,
+                // Equal once rounded to N places: less than a unit of the N-th decimal apart
+                suggested_tolerance: 10f64.powi(-(decimals as i32)),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// Numeric columns where B = A + c or B = A × k on (nearly) every mismatched row.
+fn constant_shifts(joined: &LazyFrame, mismatched: &[Mismatched]) -> Result<Vec<Finding>> {
+    let numeric: Vec<&Mismatched> = mismatched
+        .iter()
+        .filter(|m| m.dtype_a.is_numeric() && m.dtype_b.is_numeric())
+        .collect();
+    if numeric.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let a = |m: &Mismatched| col(m.name.as_str()).cast(DataType::Float64);
+    let b = |m: &Mismatched| col(m.right().as_str()).cast(DataType::Float64);
+    // Mismatched rows with a value on both sides
+    let differs = |m: &Mismatched| {
+        a(m).neq(b(m))
+            .and(a(m).is_not_null())
+            .and(b(m).is_not_null())
+    };
+    let offset = |m: &Mismatched| b(m) - a(m);
+    let factor = |m: &Mismatched| {
+        when(a(m).neq(lit(0.0)))
+            .then(b(m) / a(m))
+            .otherwise(lit(NULL))
+    };
+
+    // Pass 1: candidate offset and factor (medians over the mismatched rows)
+    let mut medians = Vec::new();
+    for (i, m) in numeric.iter().enumerate() {
+        medians.push(
+            offset(m)
+                .filter(differs(m))
+                .median()
+                .alias(format!("_offset_{}", i)),
+        );
+        medians.push(
+            factor(m)
+                .filter(differs(m))
+                .median()
+                .alias(format!("_factor_{}", i)),
+        );
+    }
+    let candidates = joined
+        .clone()
+        .select(medians)
+        .collect()
+        .stage("diagnosing mismatches")?;
+
+    // Pass 2: how many mismatched rows each candidate explains
+    let within = |x: Expr, target: f64| {
+        (x - lit(target))
+            .abs()
+            .lt_eq(lit(SHIFT_TOLERANCE * target.abs().max(1.0)))
+            .fill_null(lit(false))
+    };
+    let mut aggs = Vec::new();
+    let mut checks = Vec::new();
+    for (i, m) in numeric.iter().enumerate() {
+        let candidate_offset = extract_f64(&candidates, &format!("_offset_{}", i));
+        let candidate_factor = extract_f64(&candidates, &format!("_factor_{}", i))
+            .filter(|k| (k - 1.0).abs() > SHIFT_TOLERANCE);
+        aggs.push(
+            differs(m)
+                .cast(DataType::UInt64)
+                .sum()
+                .alias(format!("_rows_{}", i)),
+        );
+        if let Some(c) = candidate_offset {
+            aggs.push(
+                within(offset(m), c)
+                    .and(differs(m))
+                    .cast(DataType::UInt64)
+                    .sum()
+                    .alias(format!("_offset_hits_{}", i)),
+            );
+        }
+        if let Some(k) = candidate_factor {
+            aggs.push(
+                within(factor(m), k)
+                    .and(differs(m))
+                    .cast(DataType::UInt64)
+                    .sum()
+                    .alias(format!("_factor_hits_{}", i)),
+            );
+        }
+        checks.push((m, candidate_offset, candidate_factor));
+    }
+    let hits = joined
+        .clone()
+        .select(aggs)
+        .collect()
+        .stage("diagnosing mismatches")?;
+
+    let mut findings = Vec::new();
+    for (i, (m, candidate_offset, candidate_factor)) in checks.into_iter().enumerate() {
+        let rows = extract_count(&hits, &format!("_rows_{}", i));
+        if rows == 0 {
+            continue;
+        }
+        let rate = |name: &str| extract_count(&hits, name) as f64 / rows as f64;
+        let offset_rate = rate(&format!("_offset_hits_{}", i));
+        let factor_rate = rate(&format!("_factor_hits_{}", i));
+
+        // Prefer the simpler explanation when both fit
+        if let Some(offset) = candidate_offset.filter(|_| offset_rate >= PATTERN_MIN_RATE) {
+            findings.push(Finding::ConstantOffset {
+                column: m.name.clone(),
+                offset,
+                explained_rate: offset_rate,
+            });
+        } else if let Some(factor) = candidate_factor.filter(|_| factor_rate >= PATTERN_MIN_RATE) {
+            findings.push(Finding::ConstantFactor {
+                column: m.name.clone(),
+                factor,
+                explained_rate: factor_rate,
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// String columns where B holds A cut at a fixed length (e.g. `VARCHAR(255)`).
+fn truncations(joined: &LazyFrame, mismatched: &[Mismatched]) -> Result<Vec<Finding>> {
+    let strings: Vec<&Mismatched> = mismatched
+        .iter()
+        .filter(|m| m.dtype_a.is_string() && m.dtype_b.is_string())
+        .collect();
+    if strings.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut aggs = Vec::new();
+    for (i, m) in strings.iter().enumerate() {
+        let a = col(m.name.as_str());
+        let b = col(m.right().as_str());
+        let differs = a
+            .clone()
+            .neq(b.clone())
+            .and(a.clone().is_not_null())
+            .and(b.clone().is_not_null());
+        let b_len = b.clone().str().len_chars();
+        let truncated = differs
+            .clone()
+            .and(a.clone().str().starts_with(b.clone()))
+            .and(b_len.clone().lt(a.str().len_chars()))
+            .fill_null(lit(false));
+        aggs.extend([
+            differs
+                .cast(DataType::UInt64)
+                .sum()
+                .alias(format!("_rows_{}", i)),
+            truncated
+                .clone()
+                .cast(DataType::UInt64)
+                .sum()
+                .alias(format!("_truncated_{}", i)),
+            b_len
+                .clone()
+                .filter(truncated.clone())
+                .min()
+                .cast(DataType::UInt64)
+                .alias(format!("_min_len_{}", i)),
+            b_len
+                .filter(truncated)
+                .max()
+                .cast(DataType::UInt64)
+                .alias(format!("_max_len_{}", i)),
+        ]);
+    }
+    let df = joined
+        .clone()
+        .select(aggs)
+        .collect()
+        .stage("diagnosing mismatches")?;
+
+    let mut findings = Vec::new();
+    for (i, m) in strings.into_iter().enumerate() {
+        let rows = extract_count(&df, &format!("_rows_{}", i));
+        let truncated = extract_count(&df, &format!("_truncated_{}", i));
+        let min_len = extract_count(&df, &format!("_min_len_{}", i));
+        let max_len = extract_count(&df, &format!("_max_len_{}", i));
+        if rows == 0 || truncated == 0 || min_len != max_len {
+            continue;
+        }
+        let explained_rate = truncated as f64 / rows as f64;
+        if explained_rate >= PATTERN_MIN_RATE {
+            findings.push(Finding::Truncation {
+                column: m.name.clone(),
+                limit: max_len,
+                explained_rate,
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// String columns whose mismatches are UTF-8 text decoded as Latin-1 /
+/// Windows-1252 on one side (`é` -> `Ã©`, `’` -> `â€™`).
+fn mojibake(joined: &LazyFrame, mismatched: &[Mismatched]) -> Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+    for m in mismatched
+        .iter()
+        .filter(|m| m.dtype_a.is_string() && m.dtype_b.is_string())
+    {
+        let (a, b) = (col(m.name.as_str()), col(m.right().as_str()));
+        let sample = joined
+            .clone()
+            .filter(a.clone().neq(b.clone()))
+            .select([a.alias("a"), b.alias("b")])
+            .limit(MOJIBAKE_SAMPLE_ROWS)
+            .collect()
+            .stage("diagnosing mismatches")?;
+        let (Ok(col_a), Ok(col_b)) = (sample.column("a"), sample.column("b")) else {
+            continue;
+        };
+        let (Ok(col_a), Ok(col_b)) = (col_a.str(), col_b.str()) else {
+            continue;
+        };
+
+        let mut corrupted_a = 0;
+        let mut corrupted_b = 0;
+        let mut examples = Vec::new();
+        for (va, vb) in col_a.into_iter().zip(col_b) {
+            let (Some(va), Some(vb)) = (va, vb) else {
+                continue;
+            };
+            let hit = if redecode(vb).as_deref() == Some(va) {
+                corrupted_b += 1;
+                true
+            } else if redecode(va).as_deref() == Some(vb) {
+                corrupted_a += 1;
+                true
+            } else {
+                false
+            };
+            if hit && examples.len() < 3 {
+                examples.push([va.to_string(), vb.to_string()]);
+            }
+        }
+
+        let sampled_rows = sample.height();
+        let corrupted_rows = corrupted_a + corrupted_b;
+        if sampled_rows > 0 && corrupted_rows as f64 / sampled_rows as f64 >= MOJIBAKE_MIN_RATE {
+            findings.push(Finding::EncodingCorruption {
+                column: m.name.clone(),
+                corrupted_side: if corrupted_b >= corrupted_a { "b" } else { "a" }.to_string(),
+                sampled_rows,
+                corrupted_rows,
+                examples,
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// Undoes one round of UTF-8-read-as-Windows-1252: maps each char back to its
+/// single byte and decodes the bytes as UTF-8. `None` if `text` isn't
+/// mojibake (pure ASCII, a char outside the code page, or invalid UTF-8).
+fn redecode(text: &str) -> Option<String> {
+    if text.is_ascii() {
+        return None;
+    }
+    let bytes = text
+        .chars()
+        .map(|c| match c {
+            '\u{0}'..='\u{7F}' | '\u{A0}'..='\u{FF}' => Some(c as u8),
+            _ => cp1252_byte(c),
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok().filter(|fixed| fixed != text)
+}
+
+/// The Windows-1252 byte (0x80..=0x9F) for a char, including the five
+/// undefined positions that Latin-1 decoders pass through as C1 controls.
+fn cp1252_byte(c: char) -> Option<u8> {
+    Some(match c {
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8A,
+        '‹' => 0x8B,
+        'Œ' => 0x8C,
+        'Ž' => 0x8E,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9A,
+        '›' => 0x9B,
+        'œ' => 0x9C,
+        'ž' => 0x9E,
+        'Ÿ' => 0x9F,
+        '\u{81}' | '\u{8D}' | '\u{8F}' | '\u{90}' | '\u{9D}' => c as u8,
+        _ => return None,
+    })
+}
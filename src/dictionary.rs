@@ -0,0 +1,272 @@
+// koala-diff/src/dictionary.rs
+// Data dictionary: per-column semantics (timezone, unit, locale, sensitivity) applied to every input
+
+use polars::prelude::*;
+use serde::Serialize;
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::{DiffOptions, ReadOptions};
+use crate::reader;
+
+/// Sensitivity labels whose columns are compared by salted hash.
+const SENSITIVE: [&str; 5] = ["confidential", "restricted", "secret", "sensitive", "pii"];
+
+/// One row of the dictionary: what the values of `column` mean, in every
+/// input or only in `dataset`'s.
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub column: String,
+    /// The dataset the entry is for (a `dataset` read option, else a path);
+    /// it replaces the general entry of the column there.
+    pub dataset: Option<String>,
+    /// IANA timezone of naive timestamps, e.g. "Europe/Berlin".
+    pub timezone: Option<String>,
+    /// Unit of numbers, e.g. "cents", "bps", "ms".
+    pub unit: Option<String>,
+    /// Locale numbers stored as text are written in, e.g. "de_DE".
+    pub locale: Option<String>,
+    /// Classification, e.g. "public", "confidential", "pii".
+    pub sensitivity: Option<String>,
+}
+
+/// Reads the `data_dictionary` file (CSV, Parquet, ...: a `column` column
+/// and any of `dataset`, `timezone`, `unit`, `locale`, `sensitivity`) into
+/// `opts.dictionary`. Columns it classifies confidential, restricted,
+/// secret, sensitive or PII join `sensitive_columns`.
+pub fn load(opts: &mut DiffOptions) -> Result<()> {
+    let Some(path) = opts.data_dictionary.clone() else {
+        return Ok(());
+    };
+    let mut lf = reader::scan(&path, &ReadOptions::default())?;
+    let schema = lf
+        .collect_schema()
+        .stage("reading the data dictionary")
+        .file(&path)?;
+    if !schema.contains("column") {
+        return Err(DiffError::invalid(format!(
+            "Data dictionary {} has no 'column' column",
+            path
+        )));
+    }
+    let fields = [
+        "column",
+        "dataset",
+        "timezone",
+        "unit",
+        "locale",
+        "sensitivity",
+    ];
+    let df = lf
+        .select(
+            fields
+                .iter()
+                .filter(|field| schema.contains(field))
+                .map(|field| col(*field).cast(DataType::String))
+                .collect::<Vec<_>>(),
+        )
+        .collect()
+        .stage("reading the data dictionary")
+        .file(&path)?;
+    let text = |name: &str, row: usize| -> Option<String> {
+        let value = df.column(name).ok()?.str().ok()?.get(row)?.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    };
+
+    for row in 0..df.height() {
+        let Some(column) = text("column", row) else {
+            continue;
+        };
+        let entry = Entry {
+            column,
+            dataset: text("dataset", row),
+            timezone: text("timezone", row),
+            unit: text("unit", row),
+            locale: text("locale", row),
+            sensitivity: text("sensitivity", row),
+        };
+        entry.validate().map_err(|e| e.in_file(&path))?;
+        if opts
+            .dictionary
+            .iter()
+            .any(|e| e.column == entry.column && e.dataset == entry.dataset)
+        {
+            return Err(
+                DiffError::invalid("Column listed more than once for a dataset")
+                    .in_file(&path)
+                    .in_column(&entry.column),
+            );
+        }
+        let sensitive = entry
+            .sensitivity
+            .as_deref()
+            .is_some_and(|s| SENSITIVE.contains(&s.to_lowercase().as_str()));
+        if sensitive && !opts.sensitive_columns.contains(&entry.column) {
+            opts.sensitive_columns.push(entry.column.clone());
+        }
+        opts.dictionary.push(entry);
+    }
+    Ok(())
+}
+
+impl Entry {
+    fn validate(&self) -> Result<(), DiffError> {
+        let unknown = |what: &str, value: &str| {
+            Err(DiffError::invalid(format!("Unknown {} '{}'", what, value)).in_column(&self.column))
+        };
+        if let Some(timezone) = self
+            .timezone
+            .as_deref()
+            .filter(|tz| time_zone(tz).is_none())
+        {
+            return unknown("timezone", timezone);
+        }
+        if let Some(unit) = self.unit.as_deref().filter(|unit| scale(unit).is_none()) {
+            return unknown("unit", unit);
+        }
+        if let Some(locale) = self.locale.as_deref().filter(|l| separators(l).is_none()) {
+            return unknown("locale", locale);
+        }
+        Ok(())
+    }
+}
+
+/// Normalizes the columns of `lf` the dictionary describes for `dataset`:
+///
+/// - `timezone`: naive timestamps are local times there and are converted
+///   to UTC, as are aware ones. Ambiguous local times (the hour repeated
+///   when DST ends) take the earlier instant; skipped ones become null.
+/// - `locale`: numbers stored as text the locale's way ("1.234,56") are
+///   parsed; text that isn't a number becomes null.
+/// - `unit`: numbers are brought to the base unit of their dimension
+///   (cents to units, basis points to fractions, milliseconds to seconds).
+///
+/// So inputs disagreeing only in those conventions compare equal.
+pub fn normalize(mut lf: LazyFrame, opts: &DiffOptions, dataset: &str) -> Result<LazyFrame> {
+    if opts.dictionary.is_empty() {
+        return Ok(lf);
+    }
+    let schema = lf.collect_schema().stage("applying the data dictionary")?;
+    let columns: Vec<Expr> = schema
+        .iter()
+        .filter_map(|(name, dtype)| {
+            let entry = entry_for(&opts.dictionary, name, dataset)?;
+            Some(normalized(entry, dtype)?.alias(name.clone()))
+        })
+        .collect();
+    if columns.is_empty() {
+        return Ok(lf);
+    }
+    Ok(lf.with_columns(columns))
+}
+
+/// The entry of `column` for `dataset`, else its general one.
+fn entry_for<'a>(entries: &'a [Entry], column: &str, dataset: &str) -> Option<&'a Entry> {
+    let of_column = || entries.iter().filter(|e| e.column == column);
+    of_column()
+        .find(|e| e.dataset.as_deref() == Some(dataset))
+        .or_else(|| of_column().find(|e| e.dataset.is_none()))
+}
+
+/// The normalized column `entry` describes, if it changes anything.
+fn normalized(entry: &Entry, dtype: &DataType) -> Option<Expr> {
+    let mut expr = col(entry.column.as_str());
+    let mut numeric = dtype.is_primitive_numeric() || dtype.is_decimal();
+    let mut changed = false;
+
+    if let (DataType::String, Some((decimal, groups))) =
+        (dtype, entry.locale.as_deref().and_then(separators))
+    {
+        expr = expr.str().strip_chars(lit(NULL));
+        for group in groups {
+            expr = expr
+                .str()
+                .replace_all(lit(group.to_string()), lit(""), true);
+        }
+        if decimal != '.' {
+            expr = expr.str().replace(lit(decimal.to_string()), lit("."), true);
+        }
+        expr = expr.cast(DataType::Float64);
+        (numeric, changed) = (true, true);
+    }
+    if let Some((times, per)) = entry.unit.as_deref().and_then(scale) {
+        // Dividing keeps 12345 cents exactly 123.45, as parsed from text
+        if numeric && (times, per) != (1.0, 1.0) {
+            expr = expr.cast(DataType::Float64) * lit(times) / lit(per);
+            changed = true;
+        }
+    }
+    if let (DataType::Datetime(_, zone), Some(timezone)) =
+        (dtype, entry.timezone.as_deref().and_then(time_zone))
+    {
+        if zone.is_none() {
+            expr = expr
+                .dt()
+                .replace_time_zone(Some(timezone), lit("earliest"), NonExistent::Null);
+        }
+        expr = expr.dt().convert_time_zone(TimeZone::UTC);
+        changed = true;
+    }
+    changed.then_some(expr)
+}
+
+fn time_zone(name: &str) -> Option<TimeZone> {
+    TimeZone::opt_try_new(Some(name)).ok().flatten()
+}
+
+/// `unit` relative to the base unit of its dimension, as (times, per):
+/// plain quantities and money in units, ratios as fractions, durations in
+/// seconds, mass in kilograms, length in metres.
+fn scale(unit: &str) -> Option<(f64, f64)> {
+    Some(match unit.to_lowercase().as_str() {
+        "units" | "unit" => (1.0, 1.0),
+        "cents" | "cent" | "minor_units" => (1.0, 100.0),
+        "thousands" => (1e3, 1.0),
+        "millions" => (1e6, 1.0),
+        "billions" => (1e9, 1.0),
+        "fraction" | "ratio" => (1.0, 1.0),
+        "percent" | "%" => (1.0, 100.0),
+        "permille" | "‰" => (1.0, 1e3),
+        "bps" | "basis_points" => (1.0, 1e4),
+        "s" | "seconds" => (1.0, 1.0),
+        "ms" | "milliseconds" => (1.0, 1e3),
+        "us" | "microseconds" => (1.0, 1e6),
+        "ns" | "nanoseconds" => (1.0, 1e9),
+        "min" | "minutes" => (60.0, 1.0),
+        "h" | "hours" => (3600.0, 1.0),
+        "d" | "days" => (86400.0, 1.0),
+        "kg" => (1.0, 1.0),
+        "g" => (1.0, 1e3),
+        "mg" => (1.0, 1e6),
+        "t" | "tonnes" => (1e3, 1.0),
+        "m" | "metres" | "meters" => (1.0, 1.0),
+        "cm" => (1.0, 100.0),
+        "mm" => (1.0, 1e3),
+        "km" => (1e3, 1.0),
+        _ => return None,
+    })
+}
+
+/// The decimal separator and digit group separators of a locale ("de_DE",
+/// "fr-CH", "en"), by language, and region where it differs.
+fn separators(locale: &str) -> Option<(char, &'static [char])> {
+    const SPACES: &[char] = &[' ', '\u{a0}', '\u{202f}'];
+    let locale = locale.replace('-', "_").to_lowercase();
+    let (language, region) = locale.split_once('_').unwrap_or((&locale, ""));
+    let separators: (char, &'static [char]) = match (language, region) {
+        ("de" | "it" | "rm", "ch" | "li") => ('.', &['\'', '\u{2019}']),
+        ("es", "mx" | "us") => ('.', &[',']),
+        ("en" | "ja" | "zh" | "ko" | "he" | "th" | "hi" | "ms" | "ga", _) => ('.', &[',']),
+        (
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl"
+            | "sr" | "vi",
+            _,
+        ) => (',', &['.']),
+        (
+            "fr" | "ru" | "pl" | "sv" | "nb" | "nn" | "no" | "fi" | "cs" | "sk" | "uk" | "hu"
+            | "bg" | "lt" | "lv" | "et",
+            _,
+        ) => (',', SPACES),
+        _ => return None,
+    };
+    Some(separators)
+}
@@ -0,0 +1,168 @@
+// koala-diff/src/drill.rs
+// Targeted re-read of a handful of keys, returning full old/new row pairs
+
+use polars::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::compare;
+use crate::convert;
+use crate::error::{Context, DiffError};
+use crate::options::DiffOptions;
+use crate::reader;
+
+/// Re-reads only the rows matching `keys` from both files (predicate pushdown)
+/// and returns the complete old/new row pair for each key.
+///
+/// Args:
+///     file_a (str): Path to first file
+///     file_b (str): Path to second file
+///     key_cols (list[str]): Columns to join on
+///     keys (list): Key values to inspect; tuples for composite keys
+///     **options: Same options as `diff_files`
+///
+/// Returns:
+///     list[dict]: [{
+///         "key": dict,
+///         "a": dict | None,           // None when the key is missing in A
+///         "b": dict | None,
+///         "changed_columns": list[str],
+///     }]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, key_cols, keys, **options))]
+pub fn drill_down<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    keys: Vec<Bound<'py, PyAny>>,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyList>> {
+    let opts = DiffOptions::from_kwargs(options)?;
+    let keys_strs: Vec<&str> = key_cols.iter().map(|s| s.as_str()).collect();
+
+    // Any of the requested keys, all key columns equal
+    let mut predicate: Option<Expr> = None;
+    for key in &keys {
+        let values: Vec<Bound<'py, PyAny>> = if keys_strs.len() == 1 {
+            vec![key.clone()]
+        } else {
+            key.extract()?
+        };
+        if values.len() != keys_strs.len() {
+            return Err(DiffError::invalid(format!(
+                "Key {} has {} values but {} key columns were given",
+                key,
+                values.len(),
+                keys_strs.len()
+            ))
+            .into());
+        }
+        let mut matches: Option<Expr> = None;
+        for (k, v) in keys_strs.iter().zip(&values) {
+            let eq = col(*k).eq(convert::py_to_lit(v)?);
+            matches = Some(match matches {
+                Some(m) => m.and(eq),
+                None => eq,
+            });
+        }
+        if let Some(m) = matches {
+            predicate = Some(match predicate {
+                Some(p) => p.or(m),
+                None => m,
+            });
+        }
+    }
+    let Some(predicate) = predicate else {
+        return Ok(PyList::empty(py));
+    };
+
+    let mut lf_a = reader::open(&file_a, &opts.read_a, &key_cols, &opts)?.filter(predicate.clone());
+    let mut lf_b = reader::open(&file_b, &opts.read_b, &key_cols, &opts)?.filter(predicate);
+    let schema_a = lf_a.collect_schema().stage("reading").file(&file_a)?;
+    let schema_b = lf_b.collect_schema().stage("reading").file(&file_b)?;
+
+    let key_exprs: Vec<Expr> = keys_strs.iter().map(|k| col(*k)).collect();
+    let joined = lf_a.with_column(lit(true).alias("_in_a")).join(
+        lf_b.with_column(lit(true).alias("_in_b")),
+        key_exprs.clone(),
+        key_exprs,
+        JoinArgs::new(JoinType::Full)
+            .with_coalesce(JoinCoalesce::CoalesceColumns)
+            .with_suffix(Some("_right".into())),
+    );
+
+    // Output name -> joined column, per side
+    let mut columns_a: Vec<(&str, String)> = Vec::new();
+    let mut columns_b: Vec<(&str, String)> = Vec::new();
+    let mut flags = Vec::new();
+    for (name, dtype_a) in schema_a.iter() {
+        columns_a.push((name.as_str(), name.to_string()));
+        if keys_strs.contains(&name.as_str()) {
+            continue;
+        }
+        if let Some(dtype_b) = schema_b.get(name.as_str()) {
+            let right_name = format!("{}_right", name);
+            flags.push(
+                compare::diff_expr(name, &right_name, dtype_a, dtype_b, &opts)
+                    .alias(format!("{}_is_diff", name)),
+            );
+        }
+    }
+    for (name, _) in schema_b.iter() {
+        let shared = schema_a.contains(name.as_str()) && !keys_strs.contains(&name.as_str());
+        let source = if shared {
+            format!("{}_right", name)
+        } else {
+            name.to_string()
+        };
+        columns_b.push((name.as_str(), source));
+    }
+
+    let df = joined
+        .with_columns(flags)
+        .collect()
+        .stage("drilling down")?;
+    let columns_a: Vec<(&str, &str)> = columns_a.iter().map(|(n, s)| (*n, s.as_str())).collect();
+    let columns_b: Vec<(&str, &str)> = columns_b.iter().map(|(n, s)| (*n, s.as_str())).collect();
+    let key_columns: Vec<(&str, &str)> = keys_strs.iter().map(|k| (*k, *k)).collect();
+    let present = |flag: &str, i: usize| {
+        matches!(
+            df.column(flag).unwrap().get(i).unwrap(),
+            AnyValue::Boolean(true)
+        )
+    };
+
+    let pairs = PyList::empty(py);
+    for i in 0..df.height() {
+        let in_a = present("_in_a", i);
+        let in_b = present("_in_b", i);
+
+        let changed = PyList::empty(py);
+        if in_a && in_b {
+            for (name, _) in schema_a.iter() {
+                let flag = format!("{}_is_diff", name);
+                if df.column(&flag).is_ok() && present(&flag, i) {
+                    changed.append(name.as_str())?;
+                }
+            }
+        }
+
+        let pair = PyDict::new(py);
+        pair.set_item("key", convert::row_to_dict(py, &df, i, &key_columns)?)?;
+        if in_a {
+            pair.set_item("a", convert::row_to_dict(py, &df, i, &columns_a)?)?;
+        } else {
+            pair.set_item("a", py.None())?;
+        }
+        if in_b {
+            pair.set_item("b", convert::row_to_dict(py, &df, i, &columns_b)?)?;
+        } else {
+            pair.set_item("b", py.None())?;
+        }
+        pair.set_item("changed_columns", changed)?;
+        pairs.append(pair)?;
+    }
+
+    Ok(pairs)
+}
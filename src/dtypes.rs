@@ -0,0 +1,61 @@
+// koala-diff/src/dtypes.rs
+// Column types set at read time (`dtypes`), overriding what the reader infers
+
+use polars::prelude::*;
+
+use crate::error::{Context, Result};
+use crate::options::ReadOptions;
+
+/// The type a `dtypes` name stands for, spelled as in Python / Polars:
+/// "str", "int64", "float64", "bool", "date", "datetime", "decimal(18, 2)", ...
+pub fn parse(name: &str) -> Option<DataType> {
+    let name = name.trim().to_lowercase();
+    Some(match name.as_str() {
+        "str" | "string" | "utf8" => DataType::String,
+        "int" | "int64" | "i64" => DataType::Int64,
+        "int32" | "i32" => DataType::Int32,
+        "int16" | "i16" => DataType::Int16,
+        "int8" | "i8" => DataType::Int8,
+        "uint64" | "u64" => DataType::UInt64,
+        "uint32" | "u32" => DataType::UInt32,
+        "uint16" | "u16" => DataType::UInt16,
+        "uint8" | "u8" => DataType::UInt8,
+        "float" | "float64" | "f64" => DataType::Float64,
+        "float32" | "f32" => DataType::Float32,
+        "bool" | "boolean" => DataType::Boolean,
+        "date" => DataType::Date,
+        "datetime" => DataType::Datetime(TimeUnit::Microseconds, None),
+        "time" => DataType::Time,
+        other => {
+            let (precision, scale) = other
+                .strip_prefix("decimal(")?
+                .strip_suffix(')')?
+                .split_once(',')?;
+            DataType::Decimal(precision.trim().parse().ok()?, scale.trim().parse().ok()?)
+        }
+    })
+}
+
+/// Casts the columns of `lf` named in the `dtypes` read option that the
+/// reader typed otherwise. CSV files are already parsed that way (so
+/// "007" stays "007"); other formats are cast, values that don't fit
+/// becoming null. Columns the file doesn't have are skipped.
+pub fn cast(mut lf: LazyFrame, read: &ReadOptions) -> Result<LazyFrame> {
+    if read.dtypes.is_empty() {
+        return Ok(lf);
+    }
+    let schema = lf.collect_schema().stage("applying dtypes")?;
+    let casts: Vec<Expr> = read
+        .dtypes
+        .iter()
+        .filter_map(|(name, dtype)| {
+            let dtype = parse(dtype)?;
+            let current = schema.get(name)?;
+            (current != &dtype).then(|| col(name.as_str()).cast(dtype))
+        })
+        .collect();
+    if casts.is_empty() {
+        return Ok(lf);
+    }
+    Ok(lf.with_columns(casts))
+}
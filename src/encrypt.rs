@@ -0,0 +1,160 @@
+// koala-diff/src/encrypt.rs
+// Optional age encryption of exported files
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "encryption")]
+use std::str::FromStr;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use crate::error::DiffError;
+
+/// Reads the `encrypt_to` option: one age recipient (`age1...`) or a list.
+/// Keys are validated here so a typo fails before anything is written.
+#[cfg(feature = "python")]
+pub fn recipients(value: &Bound<'_, PyAny>) -> PyResult<Vec<String>> {
+    let keys: Vec<String> = match value.extract::<String>() {
+        Ok(key) => vec![key],
+        Err(_) => value.extract()?,
+    };
+    for key in &keys {
+        parse(key).map_err(|e| DiffError::invalid(e.to_string()))?;
+    }
+    Ok(keys)
+}
+
+#[cfg(feature = "encryption")]
+fn parse(key: &str) -> io::Result<age::x25519::Recipient> {
+    age::x25519::Recipient::from_str(key.trim()).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid age recipient '{}': {}", key, e),
+        )
+    })
+}
+
+#[cfg(not(feature = "encryption"))]
+fn parse(key: &str) -> io::Result<std::convert::Infallible> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "Can't encrypt to '{}': koala-diff was built without the 'encryption' feature",
+            key.trim()
+        ),
+    ))
+}
+
+/// Path of the file actually written: `.age` is appended when encrypting.
+pub fn output_path(path: &Path, recipients: &[String]) -> PathBuf {
+    if recipients.is_empty() {
+        path.to_path_buf()
+    } else {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".age");
+        PathBuf::from(name)
+    }
+}
+
+/// A file that is either written as-is or encrypted to age recipients.
+pub enum Sink {
+    Plain(BufWriter<File>),
+    #[cfg(feature = "encryption")]
+    Age(age::stream::StreamWriter<BufWriter<File>>),
+}
+
+impl Sink {
+    pub fn create(path: &Path, recipients: &[String]) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        if recipients.is_empty() {
+            return Ok(Sink::Plain(file));
+        }
+        Self::age(file, recipients)
+    }
+
+    #[cfg(feature = "encryption")]
+    fn age(file: BufWriter<File>, recipients: &[String]) -> io::Result<Self> {
+        let recipients = recipients
+            .iter()
+            .map(|key| parse(key))
+            .collect::<io::Result<Vec<_>>>()?;
+        let encryptor =
+            age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        Ok(Sink::Age(encryptor.wrap_output(file)?))
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn age(_: BufWriter<File>, recipients: &[String]) -> io::Result<Self> {
+        match parse(&recipients[0])? {}
+    }
+
+    /// Flushes the file; for age this also writes the final authenticated chunk.
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Sink::Plain(mut w) => w.flush(),
+            #[cfg(feature = "encryption")]
+            Sink::Age(w) => w.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Plain(w) => w.write(buf),
+            #[cfg(feature = "encryption")]
+            Sink::Age(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Plain(w) => w.flush(),
+            #[cfg(feature = "encryption")]
+            Sink::Age(w) => w.flush(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    #[test]
+    fn encrypted_output_decrypts_with_the_identity() {
+        let identity = age::x25519::Identity::generate();
+        let recipients = vec![identity.to_public().to_string()];
+        let path = output_path(
+            &std::env::temp_dir().join(format!("koala-diff-encrypt-{}.csv", std::process::id())),
+            &recipients,
+        );
+        assert!(path.to_string_lossy().ends_with(".csv.age"));
+
+        let mut sink = Sink::create(&path, &recipients).unwrap();
+        sink.write_all(b"id,value\n1,a\n").unwrap();
+        sink.finish().unwrap();
+
+        let decryptor = age::Decryptor::new(File::open(&path).unwrap()).unwrap();
+        let mut reader = decryptor
+            .decrypt(std::iter::once(&identity as &dyn age::Identity))
+            .unwrap();
+        let mut plain = Vec::new();
+        reader.read_to_end(&mut plain).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(plain, b"id,value\n1,a\n");
+    }
+
+    #[test]
+    fn rejects_malformed_recipients() {
+        assert!(parse("age1notakey").is_err());
+        assert_eq!(
+            output_path(Path::new("details.csv"), &[]),
+            Path::new("details.csv")
+        );
+    }
+}
@@ -0,0 +1,212 @@
+// koala-diff/src/error.rs
+// Crate-wide error: what failed, plus where (stage, file, column)
+
+use std::fmt;
+use std::io;
+
+use polars::prelude::PolarsError;
+#[cfg(feature = "python")]
+use pyo3::exceptions::{
+    PyFileNotFoundError, PyIOError, PyPermissionError, PyRuntimeError, PyTimeoutError, PyTypeError,
+    PyValueError,
+};
+#[cfg(feature = "python")]
+use pyo3::PyErr;
+
+/// Result of the engine; `DiffError` becomes a Python exception at the
+/// `python` boundary only.
+pub type Result<T, E = DiffError> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ErrorKind {
+    #[error(transparent)]
+    Polars(#[from] PolarsError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Bad input or option value (Python `ValueError`).
+    #[error("{0}")]
+    Invalid(String),
+    /// Unexpected argument or input that can't be handled (Python `TypeError`).
+    #[error("{0}")]
+    Unsupported(String),
+    /// An external source didn't answer in time (Python `TimeoutError`).
+    #[error("{0}")]
+    Timeout(String),
+    /// A bug or a crashed worker, not something the input caused (Python
+    /// `RuntimeError`).
+    #[error("{0}")]
+    Internal(String),
+}
+
+/// An error with the context it happened in.
+///
+/// Built from any `PolarsError` / `io::Error` with `?` or the `Context`
+/// methods, and converted to the matching Python exception at the boundary.
+#[derive(Debug)]
+pub struct DiffError {
+    pub kind: ErrorKind,
+    /// What was being done, e.g. "reading", "merging".
+    pub stage: Option<&'static str>,
+    pub file: Option<String>,
+    pub column: Option<String>,
+}
+
+impl DiffError {
+    pub fn invalid(msg: impl Into<String>) -> Self {
+        ErrorKind::Invalid(msg.into()).into()
+    }
+
+    pub fn unsupported(msg: impl Into<String>) -> Self {
+        ErrorKind::Unsupported(msg.into()).into()
+    }
+
+    pub fn timeout(msg: impl Into<String>) -> Self {
+        ErrorKind::Timeout(msg.into()).into()
+    }
+
+    pub fn internal(msg: impl Into<String>) -> Self {
+        ErrorKind::Internal(msg.into()).into()
+    }
+
+    /// The innermost stage wins: it is the most specific.
+    pub fn in_stage(mut self, stage: &'static str) -> Self {
+        self.stage.get_or_insert(stage);
+        self
+    }
+
+    pub fn in_file(mut self, path: &str) -> Self {
+        self.file.get_or_insert_with(|| path.to_string());
+        self
+    }
+
+    pub fn in_column(mut self, column: &str) -> Self {
+        self.column.get_or_insert_with(|| column.to_string());
+        self
+    }
+
+    /// A next step for the errors users can fix themselves.
+    fn hint(&self) -> Option<&'static str> {
+        fn io_hint(e: &io::Error) -> Option<&'static str> {
+            match e.kind() {
+                io::ErrorKind::NotFound => {
+                    Some("check the path exists and is reachable from this process")
+                }
+                io::ErrorKind::PermissionDenied => {
+                    Some("check the file permissions of the current user")
+                }
+                _ => None,
+            }
+        }
+        fn polars_hint(e: &PolarsError) -> Option<&'static str> {
+            match e {
+                PolarsError::ColumnNotFound(_) | PolarsError::SchemaFieldNotFound(_) => Some(
+                    "check the column names of both files against `key_cols` and the column options (names are case-sensitive)",
+                ),
+                PolarsError::SchemaMismatch(_) => {
+                    Some("the files disagree on a column type; cast it on one side or exclude it")
+                }
+                PolarsError::NoData(_) => Some("the input is empty; check the file was fully written"),
+                PolarsError::IO { error, .. } => io_hint(error),
+                PolarsError::Context { error, .. } => polars_hint(error),
+                _ => None,
+            }
+        }
+        match &self.kind {
+            ErrorKind::Polars(e) => polars_hint(e),
+            ErrorKind::Io(e) => io_hint(e),
+            ErrorKind::Invalid(_)
+            | ErrorKind::Unsupported(_)
+            | ErrorKind::Timeout(_)
+            | ErrorKind::Internal(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut context = Vec::new();
+        if let Some(file) = &self.file {
+            context.push(format!("file '{}'", file));
+        }
+        if let Some(column) = &self.column {
+            context.push(format!("column '{}'", column));
+        }
+        match (self.stage, context.is_empty()) {
+            (Some(stage), true) => write!(f, "Error {}: ", stage)?,
+            (Some(stage), false) => write!(f, "Error {} ({}): ", stage, context.join(", "))?,
+            (None, false) => write!(f, "{}: ", context.join(", "))?,
+            (None, true) => {}
+        }
+        write!(f, "{}", self.kind)?;
+        if let Some(hint) = self.hint() {
+            write!(f, "\nHint: {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DiffError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+impl<E: Into<ErrorKind>> From<E> for DiffError {
+    fn from(e: E) -> Self {
+        Self {
+            kind: e.into(),
+            stage: None,
+            file: None,
+            column: None,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<DiffError> for PyErr {
+    fn from(e: DiffError) -> Self {
+        fn io_exception(e: &io::Error, msg: String) -> PyErr {
+            match e.kind() {
+                io::ErrorKind::NotFound => PyFileNotFoundError::new_err(msg),
+                io::ErrorKind::PermissionDenied => PyPermissionError::new_err(msg),
+                _ => PyIOError::new_err(msg),
+            }
+        }
+        let msg = e.to_string();
+        match &e.kind {
+            ErrorKind::Io(io) => io_exception(io, msg),
+            ErrorKind::Polars(PolarsError::IO { error, .. }) => io_exception(error, msg),
+            ErrorKind::Polars(
+                PolarsError::ColumnNotFound(_)
+                | PolarsError::SchemaFieldNotFound(_)
+                | PolarsError::SchemaMismatch(_),
+            ) => PyValueError::new_err(msg),
+            ErrorKind::Polars(_) => PyRuntimeError::new_err(msg),
+            ErrorKind::Invalid(_) => PyValueError::new_err(msg),
+            ErrorKind::Unsupported(_) => PyTypeError::new_err(msg),
+            ErrorKind::Timeout(_) => PyTimeoutError::new_err(msg),
+            ErrorKind::Internal(_) => PyRuntimeError::new_err(msg),
+        }
+    }
+}
+
+/// Attaches context to the error of a `Result`.
+pub trait Context<T> {
+    fn stage(self, stage: &'static str) -> Result<T, DiffError>;
+    fn file(self, path: &str) -> Result<T, DiffError>;
+    fn column(self, column: &str) -> Result<T, DiffError>;
+}
+
+impl<T, E: Into<DiffError>> Context<T> for Result<T, E> {
+    fn stage(self, stage: &'static str) -> Result<T, DiffError> {
+        self.map_err(|e| e.into().in_stage(stage))
+    }
+
+    fn file(self, path: &str) -> Result<T, DiffError> {
+        self.map_err(|e| e.into().in_file(path))
+    }
+
+    fn column(self, column: &str) -> Result<T, DiffError> {
+        self.map_err(|e| e.into().in_column(column))
+    }
+}
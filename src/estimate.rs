@@ -0,0 +1,157 @@
+// koala-diff/src/estimate.rs
+// Row-count and cost estimates from file metadata, before any full read
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+
+use parquet::file::reader::{FileReader, SerializedFileReader};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+use crate::error::{Context, Result};
+use crate::result::{DiffEstimate, FileEstimate};
+
+/// Bytes read from the start of a line-oriented file to size its rows.
+const SAMPLE_BYTES: u64 = 1 << 20;
+/// Peak memory above which a diff is "medium" / "large".
+pub const MEDIUM_BYTES: u64 = 1 << 30;
+const LARGE_BYTES: u64 = 16 << 30;
+
+fn is_parquet(path: &str) -> bool {
+    path.ends_with(".parquet") || path.ends_with(".pq")
+}
+
+fn is_line_oriented(path: &str) -> bool {
+    [".csv", ".tsv", ".txt", ".jsonl", ".ndjson"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+}
+
+/// Exact row count and uncompressed size from the Parquet footer.
+fn from_footer(path: &str, estimate: &mut FileEstimate) -> Result<()> {
+    let file = File::open(path).stage("estimating").file(path)?;
+    let reader = SerializedFileReader::new(file)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        .stage("estimating")
+        .file(path)?;
+    let metadata = reader.metadata();
+    let uncompressed: i64 = metadata
+        .row_groups()
+        .iter()
+        .map(|rg| rg.total_byte_size())
+        .sum();
+    estimate.method = "parquet_footer".to_string();
+    estimate.exact = true;
+    estimate.estimated_rows = Some(metadata.file_metadata().num_rows().max(0) as u64);
+    estimate.columns = Some(
+        metadata
+            .file_metadata()
+            .schema_descr()
+            .root_schema()
+            .get_fields()
+            .len(),
+    );
+    estimate.estimated_memory_bytes = Some(uncompressed.max(0) as u64);
+    Ok(())
+}
+
+/// Rows extrapolated from the average line length of the file's first MiB.
+fn from_line_sample(path: &str, estimate: &mut FileEstimate) -> Result<()> {
+    let mut sample = Vec::new();
+    File::open(path)
+        .and_then(|f| f.take(SAMPLE_BYTES).read_to_end(&mut sample))
+        .stage("estimating")
+        .file(path)?;
+    let lines = sample.iter().filter(|&&b| b == b'\n').count() as u64;
+    let sampled_all = sample.len() as u64 >= estimate.file_bytes;
+    let has_header = !path.ends_with(".jsonl") && !path.ends_with(".ndjson");
+
+    let rows = if sampled_all {
+        // Whole file read: count, allowing for a missing final newline
+        let trailing = sample.last().is_some_and(|&b| b != b'\n') as u64;
+        Some(lines + trailing)
+    } else if lines > 0 {
+        Some(estimate.file_bytes * lines / sample.len() as u64)
+    } else {
+        None
+    };
+    estimate.method = "line_sample".to_string();
+    estimate.exact = sampled_all;
+    estimate.estimated_rows = rows.map(|r| r.saturating_sub(has_header as u64));
+    // Text and columnar in-memory sizes are of the same order
+    estimate.estimated_memory_bytes = Some(estimate.file_bytes);
+    Ok(())
+}
+
+fn estimate_file(path: &str) -> Result<FileEstimate> {
+    let mut estimate = FileEstimate {
+        path: path.to_string(),
+        method: "unknown".to_string(),
+        ..Default::default()
+    };
+    if path.contains("://") {
+        return Ok(estimate);
+    }
+    estimate.file_bytes = fs::metadata(path).stage("estimating").file(path)?.len();
+    if is_parquet(path) {
+        from_footer(path, &mut estimate)?;
+    } else if is_line_oriented(path) {
+        from_line_sample(path, &mut estimate)?;
+    }
+    Ok(estimate)
+}
+
+/// Compares the estimates of both files and sizes the diff.
+pub fn estimate_pair(path_a: &str, path_b: &str) -> Result<DiffEstimate> {
+    let a = estimate_file(path_a)?;
+    let b = estimate_file(path_b)?;
+
+    let row_count_delta = a
+        .estimated_rows
+        .zip(b.estimated_rows)
+        .map(|(a, b)| b as i64 - a as i64);
+    let peak = a
+        .estimated_memory_bytes
+        .zip(b.estimated_memory_bytes)
+        .map(|(a, b)| a + b);
+    let cost_class = match peak {
+        None => "unknown",
+        Some(bytes) if bytes < MEDIUM_BYTES => "small",
+        Some(bytes) if bytes < LARGE_BYTES => "medium",
+        Some(_) => "large",
+    };
+    Ok(DiffEstimate {
+        row_count_delta,
+        estimated_peak_memory_bytes: peak,
+        cost_class: cost_class.to_string(),
+        a,
+        b,
+    })
+}
+
+/// Estimates row counts and diff cost from metadata, without reading the data
+///
+/// Parquet row counts come from the footer (exact); CSV / NDJSON rows are
+/// extrapolated from the first MiB. Other formats and remote inputs are
+/// reported with method "unknown". Meant for routing large jobs to bigger
+/// workers before running `diff_files`.
+///
+/// Args:
+///     file_a (str): Path to the source (old) file
+///     file_b (str): Path to the target (new) file
+///
+/// Returns:
+///     dict: {"a" / "b": {"path", "method", "exact", "file_bytes",
+///            "estimated_rows", "columns", "estimated_memory_bytes"},
+///            "row_count_delta": int | None,
+///            "estimated_peak_memory_bytes": int | None,
+///            "cost_class": "small" | "medium" | "large" | "unknown"}
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn estimate<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+) -> PyResult<Bound<'py, PyAny>> {
+    Ok(pythonize::pythonize(py, &estimate_pair(&file_a, &file_b)?)?)
+}
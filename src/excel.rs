@@ -0,0 +1,196 @@
+// koala-diff/src/excel.rs
+// Excel workbooks as inputs: one sheet (or a cell range of it) as a table
+
+#[cfg(feature = "excel")]
+use calamine::{open_workbook_auto, Data, DataType as _, Range, Reader};
+use polars::prelude::*;
+
+#[cfg(feature = "excel")]
+use crate::error::Context;
+use crate::error::{DiffError, Result};
+use crate::options::ReadOptions;
+
+pub fn is_workbook(path: &str) -> bool {
+    [".xlsx", ".xlsm", ".xls"]
+        .iter()
+        .any(|ext| path.to_lowercase().ends_with(ext))
+}
+
+/// Reads one sheet of the workbook at `path`.
+///
+/// The sheet is `sheet_name`, `sheet_index` or the first one. The table is
+/// the sheet's used range, or `cell_range` (e.g. "B3:H200"); its column
+/// names are on `header_row` (1-based sheet row, default the table's first
+/// row) and the rows below it are the data. Each column is typed from its
+/// non-empty cells: boolean, integer, float or datetime when they all agree,
+/// text otherwise.
+#[cfg(feature = "excel")]
+pub fn read(path: &str, read: &ReadOptions) -> Result<DataFrame> {
+    let mut workbook = open_workbook_auto(path).map_err(|e| excel_err(e, path))?;
+    let sheet = match (&read.sheet_name, read.sheet_index) {
+        (Some(_), Some(_)) => {
+            return Err(
+                DiffError::invalid("Pass either 'sheet_name' or 'sheet_index', not both")
+                    .in_file(path),
+            )
+        }
+        (Some(name), None) => name.clone(),
+        (None, index) => {
+            let index = index.unwrap_or(0);
+            workbook.sheet_names().get(index).cloned().ok_or_else(|| {
+                DiffError::invalid(format!("The workbook has no sheet at index {}", index))
+                    .in_file(path)
+            })?
+        }
+    };
+    let range = workbook
+        .worksheet_range(&sheet)
+        .map_err(|e| excel_err(e, path))?;
+
+    let ((mut top, left), (bottom, right)) = match &read.cell_range {
+        Some(cells) => parse_range(cells).map_err(|e| e.in_file(path))?,
+        None => match (range.start(), range.end()) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Ok(DataFrame::empty()),
+        },
+    };
+    if let Some(header_row) = read.header_row {
+        if header_row == 0 || header_row as u32 - 1 < top || header_row as u32 - 1 > bottom {
+            return Err(DiffError::invalid(format!(
+                "header_row {} is outside the table (rows {} to {})",
+                header_row,
+                top + 1,
+                bottom + 1
+            ))
+            .in_file(path));
+        }
+        top = header_row as u32 - 1;
+    }
+    table(&range.range((top, left), (bottom, right)))
+        .stage("reading the workbook")
+        .file(path)
+}
+
+/// Top-left and bottom-right (row, column) of a table, 0-based.
+#[cfg(feature = "excel")]
+type Corners = ((u32, u32), (u32, u32));
+
+/// Parses an A1-style range ("B3:H200") into 0-based (row, column) corners.
+#[cfg(feature = "excel")]
+fn parse_range(cells: &str) -> Result<Corners, DiffError> {
+    let invalid = || {
+        DiffError::invalid(format!(
+            "cell_range must look like 'A1:D100', got '{}'",
+            cells
+        ))
+    };
+    let cell = |a1: &str| {
+        let split = a1.find(|c: char| c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (letters, digits) = a1.split_at(split);
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(invalid());
+        }
+        let column = letters
+            .to_ascii_uppercase()
+            .bytes()
+            .fold(0u32, |acc, b| acc * 26 + (b - b'A' + 1) as u32);
+        let row: u32 = digits.parse().map_err(|_| invalid())?;
+        if row == 0 {
+            return Err(invalid());
+        }
+        Ok((row - 1, column - 1))
+    };
+    let (start, end) = cells.trim().split_once(':').ok_or_else(invalid)?;
+    let (start, end) = (cell(start)?, cell(end)?);
+    if start.0 > end.0 || start.1 > end.1 {
+        return Err(invalid());
+    }
+    Ok((start, end))
+}
+
+/// First row as column names, the rest as data.
+#[cfg(feature = "excel")]
+fn table(range: &Range<Data>) -> PolarsResult<DataFrame> {
+    let mut rows = range.rows();
+    let Some(header) = rows.next() else {
+        return Ok(DataFrame::empty());
+    };
+    let data: Vec<&[Data]> = rows.collect();
+    let columns = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let name = match name {
+                Data::Empty => format!("column_{}", i + 1),
+                other => other.to_string(),
+            };
+            let cells: Vec<&Data> = data.iter().map(|row| &row[i]).collect();
+            column(&name, &cells).map(Series::into_column)
+        })
+        .collect::<PolarsResult<Vec<_>>>()?;
+    DataFrame::new(data.len(), columns)
+}
+
+/// One typed column; empty and error cells are nulls.
+#[cfg(feature = "excel")]
+fn column(name: &str, cells: &[&Data]) -> PolarsResult<Series> {
+    let name = PlSmallStr::from(name);
+    let values: Vec<&Data> = cells
+        .iter()
+        .copied()
+        .filter(|c| !matches!(c, Data::Empty | Data::Error(_)))
+        .collect();
+    let all = |f: fn(&Data) -> bool| !values.is_empty() && values.iter().all(|c| f(c));
+
+    if all(|c| matches!(c, Data::Bool(_))) {
+        let bools: BooleanChunked = cells
+            .iter()
+            .map(|c| match c {
+                Data::Bool(b) => Some(*b),
+                _ => None,
+            })
+            .collect();
+        return Ok(bools.with_name(name).into_series());
+    }
+    if all(|c| matches!(c, Data::Int(_)) || matches!(c, Data::Float(f) if f.fract() == 0.0)) {
+        let ints: Int64Chunked = cells.iter().map(|c| c.as_i64()).collect();
+        return Ok(ints.with_name(name).into_series());
+    }
+    if all(|c| matches!(c, Data::Int(_) | Data::Float(_))) {
+        let floats: Float64Chunked = cells.iter().map(|c| c.as_f64()).collect();
+        return Ok(floats.with_name(name).into_series());
+    }
+    if all(|c| matches!(c, Data::DateTime(_) | Data::DateTimeIso(_))) {
+        let millis: Int64Chunked = cells
+            .iter()
+            .map(|c| c.as_datetime().map(|dt| dt.and_utc().timestamp_millis()))
+            .collect();
+        return millis
+            .with_name(name)
+            .into_series()
+            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None));
+    }
+    let text: StringChunked = cells
+        .iter()
+        .map(|c| match c {
+            Data::Empty | Data::Error(_) => None,
+            other => Some(other.to_string()),
+        })
+        .collect();
+    Ok(text.with_name(name).into_series())
+}
+
+#[cfg(feature = "excel")]
+fn excel_err(e: impl std::fmt::Display, path: &str) -> DiffError {
+    DiffError::invalid(e.to_string())
+        .in_stage("reading the workbook")
+        .in_file(path)
+}
+
+#[cfg(not(feature = "excel"))]
+pub fn read(path: &str, _read: &ReadOptions) -> Result<DataFrame> {
+    Err(DiffError::unsupported(
+        "Workbooks can't be read: koala-diff was built without the 'excel' feature",
+    )
+    .in_file(path))
+}
@@ -0,0 +1,325 @@
+// koala-diff/src/explore.rs
+// Terminal explorer for a saved diff: page through columns, filter, inspect samples
+
+use std::io;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::error::{Context, DiffError, Result};
+use crate::result::{ColumnStats, DiffResult};
+
+/// Mismatch-rate filters cycled with `f` / `F`: a column is listed when its
+/// mismatch rate (%) is above the threshold. Columns that weren't compared
+/// only show under "all".
+const FILTERS: [(&str, f64); 5] = [
+    ("all", -1.0),
+    ("> 0%", 0.0),
+    ("> 1%", 1.0),
+    ("> 10%", 10.0),
+    ("> 50%", 50.0),
+];
+
+const HELP: &str =
+    " ↑↓ move  PgUp/PgDn page  g/G first/last  f/F raise/lower filter  s sort  q quit";
+
+/// Mismatch rate in percent.
+fn mismatch_rate(stats: &ColumnStats) -> Option<f64> {
+    stats.match_rate.map(|rate| 100.0 - rate)
+}
+
+/// Reads a result saved as JSON (`DataDiff.save`), or the report inside a
+/// signed report file.
+fn load(path: &str) -> Result<DiffResult> {
+    let text = std::fs::read_to_string(path)
+        .stage("loading the result")
+        .file(path)?;
+    let invalid = |e: serde_json::Error| {
+        DiffError::invalid(format!("Not a saved diff result: {}", e)).in_file(path)
+    };
+    let mut value: serde_json::Value = serde_json::from_str(&text).map_err(invalid)?;
+    if value.get("signature").is_some() {
+        value = value["report"].take();
+    }
+    serde_json::from_value(value).map_err(invalid)
+}
+
+struct Explorer {
+    result: DiffResult,
+    /// Positions in `column_stats` of the listed columns, in display order.
+    listed: Vec<usize>,
+    table: TableState,
+    filter: usize,
+    by_rate: bool,
+    /// Table rows visible at once, for paging; updated on every draw.
+    page: usize,
+}
+
+impl Explorer {
+    fn new(result: DiffResult) -> Self {
+        let mut explorer = Explorer {
+            result,
+            listed: Vec::new(),
+            table: TableState::default(),
+            filter: 0,
+            by_rate: false,
+            page: 1,
+        };
+        explorer.refresh();
+        explorer
+    }
+
+    fn stats(&self, position: usize) -> &ColumnStats {
+        &self.result.column_stats[position]
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.table
+            .selected()
+            .and_then(|row| self.listed.get(row))
+            .copied()
+    }
+
+    /// Re-applies the filter and sort, keeping the selected column when it
+    /// is still listed.
+    fn refresh(&mut self) {
+        let selected = self.selected();
+        let threshold = FILTERS[self.filter].1;
+        self.listed = (0..self.result.column_stats.len())
+            .filter(|&i| {
+                threshold < 0.0 || mismatch_rate(self.stats(i)).is_some_and(|r| r > threshold)
+            })
+            .collect();
+        if self.by_rate {
+            let rates: Vec<f64> = (0..self.result.column_stats.len())
+                .map(|i| mismatch_rate(self.stats(i)).unwrap_or(-1.0))
+                .collect();
+            // Stable: ties keep the file's column order
+            self.listed.sort_by(|&a, &b| rates[b].total_cmp(&rates[a]));
+        }
+        let row = selected
+            .and_then(|s| self.listed.iter().position(|&i| i == s))
+            .unwrap_or(0);
+        self.table.select((!self.listed.is_empty()).then_some(row));
+    }
+
+    fn move_by(&mut self, rows: isize) {
+        if self.listed.is_empty() {
+            return;
+        }
+        let row = self.table.selected().unwrap_or(0) as isize;
+        let last = self.listed.len() as isize - 1;
+        self.table
+            .select(Some(row.saturating_add(rows).clamp(0, last) as usize));
+    }
+
+    /// Handles one key press; returns `false` to quit.
+    fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let page = self.page as isize;
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.move_by(page),
+            KeyCode::PageUp => self.move_by(-page),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX),
+            KeyCode::Char('f') => {
+                self.filter = (self.filter + 1).min(FILTERS.len() - 1);
+                self.refresh();
+            }
+            KeyCode::Char('F') => {
+                self.filter = self.filter.saturating_sub(1);
+                self.refresh();
+            }
+            KeyCode::Char('s') => {
+                self.by_rate = !self.by_rate;
+                self.refresh();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list, detail] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(body);
+
+        // Borders and the header row aren't data rows
+        self.page = list.height.saturating_sub(3).max(1) as usize;
+        frame.render_widget(Paragraph::new(self.summary()), header);
+        frame.render_stateful_widget(self.column_table(), list, &mut self.table);
+        frame.render_widget(self.details(), detail);
+        frame.render_widget(Paragraph::new(HELP).dim(), footer);
+    }
+
+    fn summary(&self) -> Line<'static> {
+        let r = &self.result;
+        Line::from(format!(
+            " {} vs {} rows | {} matched, {} modified, {} added, {} removed | {} of {} columns, mismatch {}{}",
+            r.total_rows_a,
+            r.total_rows_b,
+            r.joined_count,
+            r.modified_rows_count,
+            r.added,
+            r.removed,
+            self.listed.len(),
+            r.column_stats.len(),
+            FILTERS[self.filter].0,
+            if self.by_rate { ", by rate" } else { "" },
+        ))
+        .bold()
+    }
+
+    fn column_table(&self) -> Table<'static> {
+        let rows = self.listed.iter().map(|&i| {
+            let stats = self.stats(i);
+            let dtype = if stats.source_dtype == stats.target_dtype {
+                stats.source_dtype.clone()
+            } else {
+                format!("{} -> {}", stats.source_dtype, stats.target_dtype)
+            };
+            let (mismatches, rate) = match (&stats.skip_reason, stats.is_key) {
+                (Some(reason), _) => (String::new(), reason.clone()),
+                (None, true) => (String::new(), "key".to_string()),
+                (None, false) => (
+                    stats
+                        .non_match_count
+                        .map(|n| n.to_string())
+                        .unwrap_or_default(),
+                    mismatch_rate(stats)
+                        .map(|r| format!("{:.2}%", r))
+                        .unwrap_or_default(),
+                ),
+            };
+            Row::new([stats.column_name.clone(), dtype, mismatches, rate])
+        });
+        Table::new(
+            rows,
+            [
+                Constraint::Fill(2),
+                Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Length(18),
+            ],
+        )
+        .header(Row::new(["Column", "Type", "Mismatches", "Rate"]).bold())
+        .block(Block::bordered().title(" Columns "))
+        .row_highlight_style(Style::new().reversed())
+    }
+
+    fn details(&self) -> Paragraph<'static> {
+        let block = Block::bordered().title(" Details ");
+        let Some(stats) = self.selected().map(|i| self.stats(i)) else {
+            return Paragraph::new("No column matches the filter").block(block);
+        };
+
+        let mut lines = vec![Line::from(stats.column_name.clone()).bold()];
+        if stats.source_dtype == stats.target_dtype {
+            lines.push(Line::from(format!("Type: {}", stats.source_dtype)));
+        } else {
+            lines.push(Line::from(format!(
+                "Type: {} -> {}",
+                stats.source_dtype, stats.target_dtype
+            )));
+        }
+        if let Some(reason) = &stats.skip_reason {
+            lines.push(Line::from(format!("Not compared: {}", reason)));
+        }
+        if let (Some(mismatches), Some(total)) = (stats.non_match_count, stats.total_count) {
+            lines.push(Line::from(format!(
+                "Mismatches: {} of {} ({:.4}%)",
+                mismatches,
+                total,
+                mismatch_rate(stats).unwrap_or(0.0)
+            )));
+        }
+        if let Some(lossy) = stats.coercion_lossy_count.filter(|n| *n > 0) {
+            lines.push(Line::from(format!(
+                "Inexact as Float64: {} rows, {} of them matched",
+                lossy,
+                stats.coercion_lossy_matches.unwrap_or(0)
+            )));
+        }
+        if let Some(diff) = stats.max_value_diff {
+            lines.push(Line::from(format!("Max value diff: {}", diff)));
+        }
+        if let Some(diff) = stats.null_count_diff {
+            lines.push(Line::from(format!("Null count diff: {:+}", diff)));
+        }
+        if let Some(patterns) = stats.change_patterns.as_ref().filter(|p| !p.is_empty()) {
+            lines.push(Line::default());
+            lines.push(Line::from("Change patterns").bold());
+            for (pattern, rows) in patterns {
+                lines.push(Line::from(format!("  {}  ({} rows)", pattern, rows)));
+            }
+        }
+        if let Some(keys) = stats
+            .mismatched_sample_keys
+            .as_ref()
+            .filter(|k| !k.is_empty())
+        {
+            lines.push(Line::default());
+            lines.push(Line::from("Samples").bold());
+            // Values are withheld for sensitive columns; keys still show
+            let values = stats
+                .mismatched_value_samples
+                .as_deref()
+                .unwrap_or_default();
+            for (i, key) in keys.iter().enumerate() {
+                lines.push(Line::from(format!("  {}", key)));
+                if let Some(value) = values.get(i) {
+                    lines.push(Line::from(format!("    {}", value)).dim());
+                }
+            }
+        }
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, mut explorer: Explorer) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| explorer.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !explorer.key(key.code, key.modifiers) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Opens a saved diff result in an interactive terminal explorer
+///
+/// Pages through the columns (with their types, mismatch counts and rates),
+/// filters them by mismatch rate and shows the change patterns and sample
+/// keys / values of the selected one: reviewing a diff of hundreds of
+/// columns without printing dicts. Also `koala-diff explore result.kdiff`.
+///
+/// Args:
+///     path (str): Result saved with `DataDiff.save` (JSON), or a signed
+///         report written by `write_signed_report`
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn explore(py: Python<'_>, path: String) -> PyResult<()> {
+    let result = load(&path)?;
+    let mut terminal = ratatui::try_init().stage("starting the explorer")?;
+    let outcome = py.detach(|| run(&mut terminal, Explorer::new(result)));
+    ratatui::restore();
+    Ok(outcome.stage("exploring the result")?)
+}
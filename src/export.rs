@@ -0,0 +1,591 @@
+// koala-diff/src/export.rs
+// Writes the added / removed / modified rows as detail files for the lake
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+#[cfg(feature = "python")]
+use pyo3_polars::PyDataFrame;
+use serde::Serialize;
+
+use crate::cells;
+use crate::compare;
+use crate::encrypt::{self, Sink};
+use crate::error::{Context, DiffError, Result};
+use crate::options::DiffOptions;
+use crate::reader;
+use crate::schema;
+
+/// File format of the exported detail files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    Csv,
+}
+
+/// Options controlling how detail files are laid out on disk.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Codec name: `zstd`, `snappy`, `gzip`, `lz4` or `uncompressed` for
+    /// Parquet; `gzip` or `uncompressed` for CSV.
+    pub compression: String,
+    /// Hive-style partitioning (`column=value/`, values percent-encoded) by
+    /// this column.
+    pub partition_by: Option<String>,
+    /// Split each partition into files of at most this many rows.
+    pub max_rows_per_file: Option<usize>,
+    /// age recipients; when set every file is encrypted and gets `.age` appended.
+    pub encrypt_to: Vec<String>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Parquet,
+            compression: "zstd".to_string(),
+            partition_by: None,
+            max_rows_per_file: None,
+            encrypt_to: Vec::new(),
+        }
+    }
+}
+
+impl ExportOptions {
+    /// Splits `**options` into export options and the comparison options
+    /// forwarded to the diff.
+    #[cfg(feature = "python")]
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<(Self, DiffOptions)> {
+        let mut opts = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok((opts, DiffOptions::default()));
+        };
+        let mut compression = None;
+        let rest = kwargs.copy()?;
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "format" => {
+                    opts.format = match value.extract::<String>()?.as_str() {
+                        "parquet" => ExportFormat::Parquet,
+                        "csv" => ExportFormat::Csv,
+                        other => {
+                            return Err(DiffError::invalid(format!(
+                                "Unknown export format '{}' (expected 'parquet' or 'csv')",
+                                other
+                            ))
+                            .into())
+                        }
+                    }
+                }
+                "compression" => compression = value.extract::<Option<String>>()?,
+                "partition_by" => opts.partition_by = value.extract()?,
+                "max_rows_per_file" => opts.max_rows_per_file = value.extract()?,
+                "encrypt_to" => opts.encrypt_to = encrypt::recipients(&value)?,
+                _ => continue,
+            }
+            rest.del_item(&key)?;
+        }
+        opts.compression = match (compression, opts.format) {
+            (Some(c), _) => c,
+            (None, ExportFormat::Parquet) => "zstd".to_string(),
+            (None, ExportFormat::Csv) => "gzip".to_string(),
+        };
+        if opts.max_rows_per_file == Some(0) {
+            return Err(DiffError::invalid("max_rows_per_file must be at least 1").into());
+        }
+        Ok((opts, DiffOptions::from_kwargs(Some(&rest))?))
+    }
+
+    fn extension(&self) -> &'static str {
+        match (self.format, self.compression.as_str()) {
+            (ExportFormat::Parquet, _) => "parquet",
+            (ExportFormat::Csv, "gzip") => "csv.gz",
+            (ExportFormat::Csv, _) => "csv",
+        }
+    }
+}
+
+/// Writes `df` as Parquet compressed with `codec` (a codec name as in
+/// `ExportOptions::compression`).
+#[cfg(feature = "parquet")]
+fn write_parquet(df: &mut DataFrame, out: impl Write, codec: &str) -> Result<()> {
+    let compression = match codec {
+        "zstd" => ParquetCompression::Zstd(None),
+        "snappy" => ParquetCompression::Snappy,
+        "gzip" => ParquetCompression::Gzip(None),
+        "lz4" => ParquetCompression::Lz4Raw,
+        "uncompressed" | "none" => ParquetCompression::Uncompressed,
+        other => return Err(unknown_codec(other, "parquet")),
+    };
+    ParquetWriter::new(out)
+        .with_compression(compression)
+        .finish(df)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_: &mut DataFrame, _: impl Write, _: &str) -> Result<()> {
+    Err(DiffError::unsupported(
+        "Parquet files can't be written: koala-diff was built without the 'parquet' feature",
+    ))
+}
+
+fn unknown_codec(codec: &str, format: &str) -> DiffError {
+    DiffError::invalid(format!(
+        "Unsupported compression '{}' for {} export",
+        codec, format
+    ))
+}
+
+/// Files written for one kind of detail (added, removed or modified).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DetailFiles {
+    pub rows: usize,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExportSummary {
+    pub added: DetailFiles,
+    pub removed: DetailFiles,
+    pub modified: DetailFiles,
+}
+
+/// Writes `df` under `dir`, partitioned and split per the options.
+fn write_details(df: DataFrame, dir: &Path, opts: &ExportOptions) -> Result<DetailFiles> {
+    let mut written = DetailFiles {
+        rows: df.height(),
+        files: Vec::new(),
+    };
+
+    let partitions: Vec<(PathBuf, DataFrame)> = match &opts.partition_by {
+        Some(column) => {
+            let mut parts = Vec::new();
+            for part in df
+                .partition_by_stable([column.as_str()], true)
+                .stage("partitioning details")
+                .column(column)?
+            {
+                let value = part
+                    .column(column)
+                    .and_then(|c| c.get(0))
+                    .stage("partitioning details")
+                    .column(column)?;
+                let value = match value {
+                    AnyValue::Null => HIVE_DEFAULT_PARTITION.to_string(),
+                    v => v.get_str().map_or_else(|| v.to_string(), str::to_string),
+                };
+                let name = format!("{}={}", hive_escape(column), hive_escape(&value));
+                let part_dir = dir.join(&name);
+                // One directory level below `dir`, whatever the value holds
+                if part_dir.parent() != Some(dir) || part_dir.file_name() != Some(name.as_ref()) {
+                    return Err(DiffError::invalid(format!(
+                        "Partition value {:?} doesn't make a directory name",
+                        value
+                    ))
+                    .in_column(column));
+                }
+                parts.push((part_dir, part));
+            }
+            parts
+        }
+        None => vec![(dir.to_path_buf(), df)],
+    };
+
+    for (part_dir, part) in partitions {
+        fs::create_dir_all(&part_dir)
+            .stage("creating the export directory")
+            .file(&part_dir.to_string_lossy())?;
+        let chunk = opts.max_rows_per_file.unwrap_or(part.height().max(1));
+        let mut offset = 0;
+        let mut index = 0;
+        // Always write at least one (possibly empty) file so consumers find the schema
+        while offset < part.height() || index == 0 {
+            let mut slice = part.slice(offset as i64, chunk);
+            let path = encrypt::output_path(
+                &part_dir.join(format!("part-{:05}.{}", index, opts.extension())),
+                &opts.encrypt_to,
+            );
+            write_file(&mut slice, &path, opts)?;
+            written.files.push(path.to_string_lossy().into_owned());
+            offset += chunk;
+            index += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// Directory name Hive gives the partition of null (and empty) values.
+const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Percent-encodes a partition column or value the way Hive does, so
+/// separators, "..", NUL and control characters can't leave `dir` or break
+/// the `column=value` layout.
+fn hive_escape(text: &str) -> String {
+    if text.is_empty() {
+        return HIVE_DEFAULT_PARTITION.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{0}'..='\u{1f}'
+            | '\u{7f}'
+            | '"'
+            | '#'
+            | '%'
+            | '\''
+            | '*'
+            | '/'
+            | ':'
+            | '='
+            | '?'
+            | '\\'
+            | '['
+            | ']'
+            | '^'
+            | '{' => out.push_str(&format!("%{:02X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    // Never "." or "..", whatever the text is joined to
+    if out.chars().all(|c| c == '.') {
+        return out.replace('.', "%2E");
+    }
+    out
+}
+
+fn write_file(df: &mut DataFrame, path: &Path, opts: &ExportOptions) -> Result<()> {
+    let path_str = path.to_string_lossy();
+    let mut sink = Sink::create(path, &opts.encrypt_to)
+        .stage("writing details")
+        .file(&path_str)?;
+
+    match opts.format {
+        ExportFormat::Parquet => {
+            write_parquet(df, &mut sink, &opts.compression)
+                .stage("writing details")
+                .file(&path_str)?;
+        }
+        ExportFormat::Csv => match opts.compression.as_str() {
+            "gzip" => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(&mut sink, flate2::Compression::default());
+                CsvWriter::new(&mut encoder)
+                    .finish(df)
+                    .stage("writing details")
+                    .file(&path_str)?;
+                encoder
+                    .finish()
+                    .and_then(|sink| sink.flush())
+                    .stage("writing details")
+                    .file(&path_str)?;
+            }
+            "uncompressed" | "none" => {
+                CsvWriter::new(&mut sink)
+                    .finish(df)
+                    .stage("writing details")
+                    .file(&path_str)?;
+            }
+            other => return Err(unknown_codec(other, "csv")),
+        },
+    }
+    sink.finish().stage("writing details").file(&path_str)
+}
+
+/// Writes `df` to `path`: CSV for a `.csv` path, zstd Parquet otherwise.
+pub fn write_table(df: &mut DataFrame, path: &str, stage: &'static str) -> Result<()> {
+    let file = fs::File::create(path).stage(stage).file(path)?;
+    if path.to_lowercase().ends_with(".csv") {
+        CsvWriter::new(file).finish(df).stage(stage).file(path)
+    } else {
+        write_parquet(df, file, "zstd").stage(stage).file(path)
+    }
+}
+
+/// Collects the added, removed and modified rows of a diff.
+///
+/// Added and removed rows keep their file's columns; modified rows carry A's
+/// columns plus B's values suffixed `_b`.
+fn detail_frames(
+    file_a: &str,
+    file_b: &str,
+    key_cols: &[String],
+    opts: &DiffOptions,
+) -> Result<(DataFrame, DataFrame, DataFrame)> {
+    let mut lf_a = reader::open(file_a, &opts.read_a, key_cols, opts)?;
+    let mut lf_b = reader::open(file_b, &opts.read_b, key_cols, opts)?;
+    let schema_a = lf_a.collect_schema().stage("reading").file(file_a)?;
+    let schema_b = lf_b.collect_schema().stage("reading").file(file_b)?;
+    let keys: Vec<Expr> = key_cols.iter().map(|k| col(k.as_str())).collect();
+
+    let anti = |left: &LazyFrame, right: &LazyFrame| {
+        left.clone().join(
+            right.clone(),
+            keys.clone(),
+            keys.clone(),
+            JoinArgs::new(JoinType::Anti),
+        )
+    };
+    let removed = anti(&lf_a, &lf_b)
+        .collect()
+        .stage("collecting removed rows")?;
+    let added = anti(&lf_b, &lf_a)
+        .collect()
+        .stage("collecting added rows")?;
+
+    let mut mask: Option<Expr> = None;
+    for (name, dtype_a) in schema_a.iter() {
+        if key_cols.iter().any(|k| k == name.as_str()) {
+            continue;
+        }
+        // Same columns as `diff_files` compares: types that can't be compared
+        // value by value would flag every row
+        if let Some(dtype_b) = schema_b
+            .get(name.as_str())
+            .filter(|dtype_b| schema::comparable(dtype_a, dtype_b))
+        {
+            let right_name = format!("{}_b", name);
+            let is_diff = compare::diff_expr(name, &right_name, dtype_a, dtype_b, opts);
+            mask = Some(match mask {
+                Some(m) => m.or(is_diff),
+                None => is_diff,
+            });
+        }
+    }
+    let joined = lf_a.join(
+        lf_b,
+        keys.clone(),
+        keys,
+        JoinArgs::new(JoinType::Inner).with_suffix(Some("_b".into())),
+    );
+    let modified = match mask {
+        Some(mask) => joined.filter(mask),
+        None => joined.limit(0),
+    }
+    .with_new_streaming(true)
+    .collect()
+    .stage("collecting modified rows")?;
+
+    Ok((added, removed, modified))
+}
+
+/// Exports the rows behind a diff as detail files
+///
+/// Writes `added/`, `removed/` and `modified/` under `output_dir`. Added and
+/// removed rows keep their file's columns; modified rows carry A's columns
+/// plus B's values suffixed `_b`.
+///
+/// Args:
+///     file_a (str): Path to first file
+///     file_b (str): Path to second file
+///     key_cols (list[str]): Columns to join on
+///     output_dir (str): Directory to write into
+///     **options: Export options, plus any `diff_files` option
+///         format (str): "parquet" (default) or "csv"
+///         compression (str): "zstd" (default for parquet), "snappy", "gzip"
+///             (default for csv), "lz4" or "uncompressed"
+///         partition_by (str): Column to partition by (`column=value/` folders)
+///         max_rows_per_file (int): Split files larger than this
+///         encrypt_to (str | list[str]): age recipient(s) (`age1...`); files
+///             are encrypted and written with an extra `.age` extension
+///
+/// Returns:
+///     dict: {"added" | "removed" | "modified": {"rows": int, "files": list[str]}}
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, key_cols, output_dir, **options))]
+pub fn export_details<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    output_dir: String,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let (export, opts) = ExportOptions::from_kwargs(options)?;
+
+    let (added, removed, modified) = detail_frames(&file_a, &file_b, &key_cols, &opts)?;
+
+    let root = Path::new(&output_dir);
+    let summary = ExportSummary {
+        added: write_details(added, &root.join("added"), &export)?,
+        removed: write_details(removed, &root.join("removed"), &export)?,
+        modified: write_details(modified, &root.join("modified"), &export)?,
+    };
+    Ok(pythonize::pythonize(py, &summary)?)
+}
+
+/// CSV dialect of the cell diff export.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Start the file with a UTF-8 byte order mark so Excel detects the encoding.
+    pub bom: bool,
+    pub delimiter: u8,
+    pub quote_style: QuoteStyle,
+    pub line_terminator: String,
+    /// age recipients; when set the file is encrypted and gets `.age` appended.
+    pub encrypt_to: Vec<String>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            bom: false,
+            delimiter: b',',
+            quote_style: QuoteStyle::Necessary,
+            line_terminator: "\n".to_string(),
+            encrypt_to: Vec::new(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Splits `**options` into CSV options and the comparison options
+    /// forwarded to the diff.
+    #[cfg(feature = "python")]
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<(Self, DiffOptions)> {
+        let mut opts = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok((opts, DiffOptions::default()));
+        };
+        let invalid = |msg: String| -> PyErr { DiffError::invalid(msg).into() };
+        let rest = kwargs.copy()?;
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "bom" => opts.bom = value.extract()?,
+                "delimiter" => {
+                    let delimiter: String = value.extract()?;
+                    opts.delimiter = match delimiter.as_bytes() {
+                        [b] => *b,
+                        _ => {
+                            return Err(invalid(format!(
+                                "delimiter must be a single ASCII character, got '{}'",
+                                delimiter
+                            )))
+                        }
+                    };
+                }
+                "quote_style" => {
+                    opts.quote_style = match value.extract::<String>()?.as_str() {
+                        "necessary" => QuoteStyle::Necessary,
+                        "always" => QuoteStyle::Always,
+                        "non_numeric" => QuoteStyle::NonNumeric,
+                        other => {
+                            return Err(invalid(format!(
+                                "Unknown quote_style '{}' (expected 'necessary', 'always' or 'non_numeric')",
+                                other
+                            )))
+                        }
+                    }
+                }
+                "line_terminator" => opts.line_terminator = value.extract()?,
+                "encrypt_to" => opts.encrypt_to = encrypt::recipients(&value)?,
+                _ => continue,
+            }
+            rest.del_item(&key)?;
+        }
+        Ok((opts, DiffOptions::from_kwargs(Some(&rest))?))
+    }
+}
+
+/// Writes the long-format cell diff (one row per changed cell) as CSV
+///
+/// Values containing the delimiter, quotes or newlines are quoted and
+/// escaped per RFC 4180.
+///
+/// Args:
+///     file_a (str): Path to first file
+///     file_b (str): Path to second file
+///     key_cols (list[str]): Columns to join on
+///     path (str): CSV file to write
+///     **options: CSV options, plus any `diff_files` option
+///         bom (bool): Prefix a UTF-8 BOM for Excel (default False)
+///         delimiter (str): Field separator (default ",")
+///         quote_style (str): "necessary" (default), "always" or "non_numeric"
+///         line_terminator (str): Default "\n"; use "\r\n" for Excel on Windows
+///         encrypt_to (str | list[str]): age recipient(s); the file is written
+///             encrypted as `<path>.age`
+///
+/// Returns:
+///     int: Number of changed cells written
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, key_cols, path, **options))]
+pub fn export_cell_diffs(
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    path: String,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<usize> {
+    let (csv, opts) = CsvOptions::from_kwargs(options)?;
+
+    let lf_a = reader::open(&file_a, &opts.read_a, &key_cols, &opts)?;
+    let lf_b = reader::open(&file_b, &opts.read_b, &key_cols, &opts)?;
+    let mut df = cells::cell_diff(lf_a, lf_b, &key_cols, &opts)?
+        .with_new_streaming(true)
+        .collect()
+        .stage("building the cell diff")?;
+
+    let path = encrypt::output_path(Path::new(&path), &csv.encrypt_to);
+    let path_str = path.to_string_lossy();
+    let mut sink = Sink::create(&path, &csv.encrypt_to)
+        .stage("writing cell diffs")
+        .file(&path_str)?;
+    CsvWriter::new(&mut sink)
+        .include_bom(csv.bom)
+        .with_separator(csv.delimiter)
+        .with_quote_style(csv.quote_style)
+        .with_line_terminator(csv.line_terminator.into())
+        .finish(&mut df)
+        .stage("writing cell diffs")
+        .file(&path_str)?;
+    sink.finish().stage("writing cell diffs").file(&path_str)?;
+    Ok(df.height())
+}
+
+/// Returns the rows behind a diff as Polars DataFrames
+///
+/// Frames are handed over through the Arrow C data interface, without
+/// copying. Added and removed rows keep their file's columns; modified rows
+/// carry A's columns plus B's values suffixed `_b`.
+///
+/// Args:
+///     file_a (str): Path to first file
+///     file_b (str): Path to second file
+///     key_cols (list[str]): Columns to join on
+///     **options: Any `diff_files` option
+///
+/// Returns:
+///     dict: {"added" | "removed" | "modified": polars.DataFrame}
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, key_cols, **options))]
+pub fn diff_rows<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let opts = DiffOptions::from_kwargs(options)?;
+    let (added, removed, modified) = detail_frames(&file_a, &file_b, &key_cols, &opts)?;
+
+    let frames = PyDict::new(py);
+    for (name, df) in [
+        ("added", added),
+        ("removed", removed),
+        ("modified", modified),
+    ] {
+        frames.set_item(name, PyDataFrame(df))?;
+    }
+    Ok(frames)
+}
@@ -0,0 +1,156 @@
+// koala-diff/src/ffi.rs
+// C ABI over Arrow C stream pointers, for callers embedding the engine without Python
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use polars::prelude::*;
+use polars_arrow::array::new_empty_array;
+use polars_arrow::ffi::{ArrowArrayStream, ArrowArrayStreamReader};
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::DiffOptions;
+use crate::reader::Input;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Diffs two Arrow C streams of record batches (e.g. from
+/// `RecordBatchReader` / `ArrowArrayStream` exporters in .NET, Java, Go or
+/// C++) keyed by `key_cols`.
+///
+/// `key_cols` is a JSON array of column names; `options` a JSON object with
+/// the `diff_files` options (`abs_tol`, `comparison_groups`, ...) or NULL.
+/// Both streams are consumed: they are released when the call returns.
+///
+/// Returns the result as JSON (the `diff_files` dict), to be freed with
+/// `koala_diff_free`, or NULL on error (see `koala_diff_last_error`).
+///
+/// # Safety
+/// The streams must be valid Arrow C stream structs and the strings valid
+/// NUL-terminated UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn koala_diff_arrow(
+    stream_a: *mut ArrowArrayStream,
+    stream_b: *mut ArrowArrayStream,
+    key_cols: *const c_char,
+    options: *const c_char,
+) -> *mut c_char {
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        // Taken over even if the call fails early, as the C stream
+        // interface expects of a consumer
+        let streams = [stream_a, stream_b].map(|stream| {
+            (!stream.is_null()).then(|| Box::new(ptr::replace(stream, ArrowArrayStream::empty())))
+        });
+        let key_cols: Vec<String> = serde_json::from_str(text(key_cols, "key_cols")?)
+            .map_err(|e| DiffError::invalid(format!("key_cols must be a JSON array: {}", e)))?;
+        let opts = if options.is_null() {
+            DiffOptions::default()
+        } else {
+            let options = serde_json::from_str(text(options, "options")?)
+                .map_err(|e| DiffError::invalid(format!("options must be JSON: {}", e)))?;
+            DiffOptions::from_json(options)?
+        };
+        let [a, b] = streams;
+        let inputs = [import(a, "arrow stream a")?, import(b, "arrow stream b")?];
+        let result = crate::run_diff(inputs, key_cols, opts, None)?;
+        let json = serde_json::to_string(&result)
+            .map_err(|e| DiffError::invalid(format!("Can't serialize the result: {}", e)))?;
+        Ok::<_, DiffError>(CString::new(json).expect("JSON has no NUL bytes"))
+    }));
+    let message = match outcome {
+        Ok(Ok(json)) => return json.into_raw(),
+        Ok(Err(e)) => e.to_string(),
+        Err(panic) => panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "the diff panicked".to_string()),
+    };
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with_borrow_mut(|last| *last = Some(message));
+    ptr::null_mut()
+}
+
+/// The error of the last failed call on this thread, or NULL. Valid until
+/// the next failed call on the same thread; not to be freed.
+#[no_mangle]
+pub extern "C" fn koala_diff_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|last| last.as_ref().map_or(ptr::null(), |msg| msg.as_ptr()))
+}
+
+/// Frees a result returned by `koala_diff_arrow`. NULL is a no-op.
+///
+/// # Safety
+/// `json` must come from `koala_diff_arrow` and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn koala_diff_free(json: *mut c_char) {
+    if !json.is_null() {
+        drop(CString::from_raw(json));
+    }
+}
+
+/// Version of the library, as a static string.
+#[no_mangle]
+pub extern "C" fn koala_diff_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+unsafe fn text<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    if ptr.is_null() {
+        return Err(DiffError::invalid(format!("{} is NULL", name)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| DiffError::invalid(format!("{} isn't valid UTF-8", name)))
+}
+
+/// Reads a stream of record batches into one frame.
+unsafe fn import(stream: Option<Box<ArrowArrayStream>>, label: &str) -> Result<Input> {
+    let Some(stream) = stream else {
+        return Err(DiffError::invalid(format!("{} is NULL", label)));
+    };
+    Ok(Input::Frame {
+        label: label.to_string(),
+        lf: read_stream(stream, label)?.lazy(),
+    })
+}
+
+/// Collects an Arrow C stream of record batches (struct arrays) into a
+/// frame, releasing the stream.
+///
+/// # Safety
+/// `stream` must be a valid Arrow C stream.
+pub unsafe fn read_stream(stream: Box<ArrowArrayStream>, label: &str) -> Result<DataFrame> {
+    let mut reader = ArrowArrayStreamReader::try_new(stream)
+        .stage("importing")
+        .file(label)?;
+    let field = reader.field().clone();
+    let mut chunks = Vec::new();
+    while let Some(batch) = reader.next() {
+        chunks.push(batch.stage("importing").file(label)?);
+    }
+    record_batches(&field, chunks, label)
+}
+
+/// One frame of record batches (struct arrays of `field`'s type).
+pub fn record_batches(
+    field: &ArrowField,
+    mut chunks: Vec<ArrayRef>,
+    label: &str,
+) -> Result<DataFrame> {
+    if chunks.is_empty() {
+        chunks.push(new_empty_array(field.dtype.clone()));
+    }
+    let series = Series::try_from((field, chunks))
+        .stage("importing")
+        .file(label)?;
+    Ok(series
+        .struct_()
+        .map_err(|_| DiffError::invalid("expected record batches (struct arrays)").in_file(label))?
+        .clone()
+        .unnest())
+}
@@ -0,0 +1,103 @@
+// koala-diff/src/fixed_width.rs
+// Fixed-width flat files (mainframe / legacy extracts) as inputs
+
+use std::fs;
+
+use encoding_rs::UTF_8;
+use polars::prelude::*;
+
+use crate::csv;
+use crate::error::{Context, DiffError, Result};
+use crate::options::ReadOptions;
+use crate::result::Warning;
+use crate::warnings;
+
+/// Reads a fixed-width file laid out by the `fixed_width` read option: one
+/// `(name, start, width)` per column, `start` 0-based in characters. Fields
+/// are trimmed; empty fields and lines too short to reach a field are null.
+/// Blank lines are skipped. The file is decoded as the `encoding` option
+/// says, bytes it has no character for replaced (with a warning).
+///
+/// Columns whose every value is an integer (without leading zeros, which
+/// identifiers keep) are read as Int64, those of numbers as Float64, the
+/// rest as strings, so they compare against typed replacements.
+pub fn read(path: &str, read: &ReadOptions) -> Result<DataFrame> {
+    let Some(layout) = &read.fixed_width else {
+        return Ok(DataFrame::empty());
+    };
+    let bytes = fs::read(path).stage("reading").file(path)?;
+    let encoding = match read.encoding.as_deref() {
+        Some("auto") if std::str::from_utf8(&bytes).is_err() => csv::detect(path, &bytes),
+        Some(label) => csv::encoding_for(label).unwrap_or(UTF_8),
+        None => UTF_8,
+    };
+    let (text, _, replaced) = encoding.decode(&bytes);
+    if replaced && encoding == UTF_8 {
+        return Err(DiffError::invalid(
+            "The file isn't valid UTF-8; set its 'encoding' (e.g. 'latin-1', 'windows-1252')",
+        )
+        .in_file(path));
+    }
+    if replaced {
+        let count = text.matches('\u{FFFD}').count();
+        warnings::raise(
+            Warning::new(
+                "encoding_replacements",
+                format!(
+                    "{} byte sequences of {} aren't valid {} and were replaced with U+FFFD",
+                    count,
+                    path,
+                    encoding.name()
+                ),
+            )
+            .in_file(path)
+            .count(count),
+        );
+    }
+
+    let lines: Vec<Vec<char>> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.chars().collect())
+        .collect();
+    let columns: Vec<Column> = layout
+        .iter()
+        .map(|(name, start, width)| {
+            let values: Vec<Option<String>> = lines
+                .iter()
+                .map(|line| {
+                    let field: String = line.iter().skip(*start).take(*width).collect();
+                    Some(field.trim().to_string()).filter(|v| !v.is_empty())
+                })
+                .collect();
+            typed(name, values)
+        })
+        .collect();
+    DataFrame::new(lines.len(), columns)
+        .stage("reading")
+        .file(path)
+}
+
+/// The column of `values` in the narrowest type they all parse as.
+fn typed(name: &str, values: Vec<Option<String>>) -> Column {
+    let present = || values.iter().flatten();
+    let leading_zero = |v: &str| {
+        let digits = v.trim_start_matches(['-', '+']);
+        digits.len() > 1 && digits.starts_with('0')
+    };
+    if present().all(|v| v.parse::<i64>().is_ok() && !leading_zero(v)) {
+        let ints: Vec<Option<i64>> = values
+            .iter()
+            .map(|v| v.as_deref().and_then(|v| v.parse().ok()))
+            .collect();
+        return Column::new(name.into(), ints);
+    }
+    if present().all(|v| v.parse::<f64>().is_ok()) {
+        let floats: Vec<Option<f64>> = values
+            .iter()
+            .map(|v| v.as_deref().and_then(|v| v.parse().ok()))
+            .collect();
+        return Column::new(name.into(), floats);
+    }
+    Column::new(name.into(), values)
+}
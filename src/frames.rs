@@ -0,0 +1,126 @@
+// koala-diff/src/frames.rs
+// In-memory inputs from Python: Polars frames, or objects exporting Arrow data through the PyCapsule interface
+
+use std::ffi::{c_void, CStr};
+use std::ptr::{self, NonNull};
+
+use polars::prelude::*;
+use polars_arrow::ffi::{
+    import_array_from_c, import_field_from_c, ArrowArray, ArrowArrayStream, ArrowSchema,
+};
+use pyo3::prelude::*;
+use pyo3::types::{PyCapsule, PyDict};
+use pyo3_polars::{PyDataFrame, PyLazyFrame};
+
+use crate::error::{Context, DiffError};
+use crate::ffi;
+use crate::options::DiffOptions;
+use crate::reader::Input;
+
+/// `diff_files` over two in-memory tables instead of paths: Polars
+/// DataFrames / LazyFrames, or any object exporting Arrow data through the
+/// PyCapsule interface (pyarrow Tables, RecordBatchReaders and RecordBatches,
+/// DuckDB results, pandas DataFrames converted by the Python wrapper, ...).
+/// Read options don't apply.
+#[pyfunction]
+#[pyo3(signature = (frame_a, frame_b, _key_cols, **options))]
+pub fn diff_frames<'py>(
+    py: Python<'py>,
+    frame_a: &Bound<'py, PyAny>,
+    frame_b: &Bound<'py, PyAny>,
+    _key_cols: Vec<String>,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let opts = DiffOptions::from_kwargs(options)?;
+    let inputs = [import(frame_a, "frame a")?, import(frame_b, "frame b")?];
+    let result = crate::run_diff(inputs, _key_cols, opts, Some(py.version().to_string()))?;
+    Ok(pythonize::pythonize(py, &result)?)
+}
+
+/// Takes one side over from Python. Polars frames come through pyo3-polars
+/// without copying or serializing the data: a DataFrame's buffers are
+/// shared, a LazyFrame's query plan joins the diff's own (filters and
+/// projections pushed down). Anything else is read from the Arrow data it
+/// exports, without copying the buffers.
+fn import(obj: &Bound<'_, PyAny>, label: &str) -> PyResult<Input> {
+    let frame = |lf: LazyFrame| Input::Frame {
+        label: label.to_string(),
+        lf,
+    };
+    if let Ok(PyLazyFrame(lf)) = obj.extract() {
+        return Ok(frame(lf));
+    }
+    // A query plan only carries over between the same Polars versions;
+    // otherwise it runs on the Python side
+    let collected;
+    let obj = if obj.get_type().name()? == "LazyFrame" && obj.hasattr("collect")? {
+        collected = obj.call_method0("collect")?;
+        &collected
+    } else {
+        obj
+    };
+    if let Ok(PyDataFrame(df)) = obj.extract() {
+        return Ok(frame(df.lazy()));
+    }
+
+    let df = if obj.hasattr("__arrow_c_stream__")? {
+        read_stream(obj, label)?
+    } else if obj.hasattr("__arrow_c_array__")? {
+        read_array(obj, label)?
+    } else {
+        return Err(DiffError::unsupported(format!(
+            "{} ({}) doesn't export Arrow data (__arrow_c_stream__ / __arrow_c_array__)",
+            label,
+            obj.get_type().name()?
+        ))
+        .into());
+    };
+    Ok(frame(df.lazy()))
+}
+
+/// Reads the Arrow C stream an object exports (pyarrow Tables and
+/// RecordBatchReaders, DuckDB results, ADBC readers, ...) to its end.
+fn read_stream(obj: &Bound<'_, PyAny>, label: &str) -> PyResult<DataFrame> {
+    let stream = obj.call_method0("__arrow_c_stream__")?;
+    let pointer = capsule(&stream, c"arrow_array_stream", label)?;
+    // Moved out of the capsule, whose destructor then finds it released
+    let stream = unsafe {
+        Box::new(ptr::replace(
+            pointer.cast::<ArrowArrayStream>().as_ptr(),
+            ArrowArrayStream::empty(),
+        ))
+    };
+    unsafe { Ok(ffi::read_stream(stream, label)?) }
+}
+
+/// Reads the single Arrow C array an object exports (a pyarrow
+/// RecordBatch, ...) over the C Data Interface: a struct array and its
+/// schema.
+fn read_array(obj: &Bound<'_, PyAny>, label: &str) -> PyResult<DataFrame> {
+    let (schema, array): (Bound<'_, PyAny>, Bound<'_, PyAny>) =
+        obj.call_method0("__arrow_c_array__")?.extract()?;
+    let schema = capsule(&schema, c"arrow_schema", label)?;
+    let array = capsule(&array, c"arrow_array", label)?;
+    // The schema is only read, its capsule releases it; the array is moved
+    // out like a stream
+    let field = unsafe { import_field_from_c(schema.cast::<ArrowSchema>().as_ref()) }
+        .stage("importing")
+        .file(label)?;
+    let array = unsafe {
+        import_array_from_c(
+            ptr::replace(array.cast::<ArrowArray>().as_ptr(), ArrowArray::empty()),
+            field.dtype.clone(),
+        )
+    }
+    .stage("importing")
+    .file(label)?;
+    Ok(ffi::record_batches(&field, vec![array], label)?)
+}
+
+/// The pointer held by an Arrow PyCapsule named `name`.
+fn capsule(obj: &Bound<'_, PyAny>, name: &CStr, label: &str) -> PyResult<NonNull<c_void>> {
+    let capsule = obj
+        .cast::<PyCapsule>()
+        .map_err(|_| DiffError::invalid(format!("{}: expected an Arrow PyCapsule", label)))?;
+    capsule.pointer_checked(Some(name))
+}
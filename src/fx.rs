@@ -0,0 +1,94 @@
+// koala-diff/src/fx.rs
+// Monetary columns converted to a common currency with an FX rate table
+
+use polars::prelude::*;
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::{DiffOptions, ReadOptions};
+use crate::reader;
+
+/// Reads the `fx_rates` table: one `rate` per `currency`, the value of one
+/// unit of that currency in the common currency.
+fn rates(path: &str) -> Result<DataFrame> {
+    let mut lf = reader::scan(path, &ReadOptions::default())?;
+    let schema = lf.collect_schema().stage("reading FX rates").file(path)?;
+    for name in ["currency", "rate"] {
+        if !schema.contains(name) {
+            return Err(DiffError::invalid(format!(
+                "FX rate table {} has no '{}' column",
+                path, name
+            )));
+        }
+    }
+    let df = lf
+        .select([
+            col("currency").cast(DataType::String).alias("_fx_currency"),
+            col("rate").cast(DataType::Float64).alias("_fx_rate"),
+        ])
+        .collect()
+        .stage("reading FX rates")
+        .file(path)?;
+    let currencies = df.column("_fx_currency").stage("reading FX rates")?;
+    if currencies.n_unique().stage("reading FX rates")? != df.height() {
+        return Err(DiffError::invalid(format!(
+            "FX rate table {} lists a currency more than once",
+            path
+        )));
+    }
+    Ok(df)
+}
+
+/// Converts every `currency_columns` column of `lf` to the common currency:
+/// value times the rate of the currency in the row's currency column.
+///
+/// Values in a currency missing from the rate table (or with a null
+/// currency) become null, so they show up as mismatches.
+pub fn convert(mut lf: LazyFrame, opts: &DiffOptions) -> Result<LazyFrame> {
+    let Some(path) = &opts.fx_rates else {
+        return Ok(lf);
+    };
+    if opts.currency_columns.is_empty() {
+        return Ok(lf);
+    }
+    let rates = rates(path)?;
+    let schema = lf.collect_schema().stage("converting currencies")?;
+    let columns: Vec<Expr> = schema.iter_names().map(|name| col(name.clone())).collect();
+
+    // One join per distinct currency column
+    let mut currency_columns: Vec<&String> = opts.currency_columns.values().collect();
+    currency_columns.sort();
+    currency_columns.dedup();
+    for (i, currency) in currency_columns.into_iter().enumerate() {
+        if !schema.contains(currency) {
+            return Err(
+                DiffError::invalid("currency column missing from an input").in_column(currency)
+            );
+        }
+        let rate = format!("_fx_rate{}", i);
+        let converted: Vec<Expr> = opts
+            .currency_columns
+            .iter()
+            .filter(|(value, c)| *c == currency && schema.contains(value.as_str()))
+            .map(|(value, _)| {
+                (col(value.as_str()).cast(DataType::Float64) * col(rate.as_str()))
+                    .alias(value.as_str())
+            })
+            .collect();
+        lf = lf
+            .join(
+                rates
+                    .clone()
+                    .lazy()
+                    .select([col("_fx_currency"), col("_fx_rate").alias(rate.as_str())]),
+                [col(currency.as_str()).cast(DataType::String)],
+                [col("_fx_currency")],
+                JoinArgs {
+                    maintain_order: MaintainOrderJoin::Left,
+                    ..JoinArgs::new(JoinType::Left)
+                },
+            )
+            .with_columns(converted)
+            .select(columns.clone());
+    }
+    Ok(lf)
+}
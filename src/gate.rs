@@ -0,0 +1,204 @@
+// koala-diff/src/gate.rs
+// Pass/fail verdict of a diff result for orchestrators (Airflow XCom, Dagster metadata)
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use serde::Serialize;
+
+use crate::error::DiffError;
+use crate::result::DiffResult;
+use crate::schema::Compatibility;
+
+/// Limits a result must stay within. The defaults only pass identical files.
+#[derive(Debug, Clone)]
+pub struct GateThresholds {
+    /// Percentage of matched rows allowed to differ.
+    pub max_mismatch_rate: f64,
+    pub max_added: Option<usize>,
+    pub max_removed: Option<usize>,
+    /// Every compared column must match at least this percentage of rows.
+    pub min_column_match_rate: Option<f64>,
+    /// Minimum percentage of cells actually compared.
+    pub min_cell_coverage: Option<f64>,
+    pub fail_on_breaking_schema: bool,
+}
+
+impl Default for GateThresholds {
+    fn default() -> Self {
+        Self {
+            max_mismatch_rate: 0.0,
+            max_added: Some(0),
+            max_removed: Some(0),
+            min_column_match_rate: None,
+            min_cell_coverage: None,
+            fail_on_breaking_schema: true,
+        }
+    }
+}
+
+impl GateThresholds {
+    #[cfg(feature = "python")]
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut thresholds = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok(thresholds);
+        };
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "max_mismatch_rate" => thresholds.max_mismatch_rate = value.extract()?,
+                "max_added" => thresholds.max_added = value.extract()?,
+                "max_removed" => thresholds.max_removed = value.extract()?,
+                "min_column_match_rate" => thresholds.min_column_match_rate = value.extract()?,
+                "min_cell_coverage" => thresholds.min_cell_coverage = value.extract()?,
+                "fail_on_breaking_schema" => {
+                    thresholds.fail_on_breaking_schema = value.extract()?
+                }
+                other => {
+                    return Err(DiffError::unsupported(format!(
+                        "gate() got an unexpected threshold '{}'",
+                        other
+                    ))
+                    .into())
+                }
+            }
+        }
+        Ok(thresholds)
+    }
+}
+
+/// Compact verdict, small enough for XCom or task metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct Gate {
+    /// `"pass"` or `"fail"`.
+    pub status: &'static str,
+    pub failed_checks: Vec<String>,
+    /// Column with the lowest match rate, if any column mismatches.
+    pub worst_column: Option<String>,
+    /// Percentage of matched rows with at least one differing column.
+    pub mismatch_rate: f64,
+    /// One-line form for logs and Airflow's return value.
+    pub summary: String,
+}
+
+pub fn evaluate(result: &DiffResult, thresholds: &GateThresholds) -> Gate {
+    let mismatch_rate = if result.joined_count > 0 {
+        result.modified_rows_count as f64 / result.joined_count as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    let worst = result
+        .column_stats
+        .values()
+        .filter(|s| !s.is_key)
+        .filter_map(|s| s.match_rate.map(|rate| (s, rate)))
+        .filter(|(_, rate)| *rate < 100.0)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    let mut failed_checks = Vec::new();
+    if mismatch_rate > thresholds.max_mismatch_rate {
+        failed_checks.push(format!(
+            "mismatch_rate {:.2}% > {}%",
+            mismatch_rate, thresholds.max_mismatch_rate
+        ));
+    }
+    if let Some(max) = thresholds.max_added.filter(|max| result.added > *max) {
+        failed_checks.push(format!("added {} > {}", result.added, max));
+    }
+    if let Some(max) = thresholds.max_removed.filter(|max| result.removed > *max) {
+        failed_checks.push(format!("removed {} > {}", result.removed, max));
+    }
+    if let Some(min) = thresholds.min_column_match_rate {
+        for stats in result.column_stats.values().filter(|s| !s.is_key) {
+            if let Some(rate) = stats.match_rate.filter(|rate| *rate < min) {
+                failed_checks.push(format!(
+                    "column {} match_rate {:.2}% < {}%",
+                    stats.column_name, rate, min
+                ));
+            }
+        }
+    }
+    if let Some(min) = thresholds
+        .min_cell_coverage
+        .filter(|min| result.coverage.cell_coverage < *min)
+    {
+        failed_checks.push(format!(
+            "cell_coverage {:.2}% < {}%",
+            result.coverage.cell_coverage, min
+        ));
+    }
+    if thresholds.fail_on_breaking_schema
+        && result.schema_compatibility.verdict == Compatibility::Breaking
+    {
+        failed_checks.push("schema change is breaking".to_string());
+    }
+    // A partial result can't vouch for the data
+    if result.timed_out {
+        failed_checks.push(format!(
+            "timed out during {}",
+            result.timed_out_phase.as_deref().unwrap_or("the run")
+        ));
+    }
+
+    let status = if failed_checks.is_empty() {
+        "pass"
+    } else {
+        "fail"
+    };
+    let mut summary = format!(
+        "{}: {:.2}% rows mismatched, +{} / -{} rows",
+        status.to_uppercase(),
+        mismatch_rate,
+        result.added,
+        result.removed
+    );
+    if let Some((stats, rate)) = worst {
+        summary.push_str(&format!(
+            ", worst column {} ({:.2}% match)",
+            stats.column_name, rate
+        ));
+    }
+    if !failed_checks.is_empty() {
+        summary.push_str(&format!(" [{}]", failed_checks.join("; ")));
+    }
+
+    Gate {
+        status,
+        failed_checks,
+        worst_column: worst.map(|(stats, _)| stats.column_name.clone()),
+        mismatch_rate,
+        summary,
+    }
+}
+
+/// Pass/fail verdict of a diff result for pipeline orchestrators
+///
+/// Args:
+///     result (dict): Result returned by `diff_files`
+///     **thresholds: Limits (defaults only pass identical files)
+///         max_mismatch_rate (float): % of matched rows allowed to differ (0)
+///         max_added (int | None): Rows only in B allowed (0; None = any)
+///         max_removed (int | None): Rows only in A allowed (0; None = any)
+///         min_column_match_rate (float): Minimum % match per column
+///         min_cell_coverage (float): Minimum % of cells actually compared
+///         fail_on_breaking_schema (bool): Fail on a breaking schema change (True)
+///
+/// Returns:
+///     dict: {"status": "pass" | "fail", "failed_checks": list[str],
+///            "worst_column": str | None, "mismatch_rate": float,
+///            "summary": str}
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (result, **thresholds))]
+pub fn gate<'py>(
+    py: Python<'py>,
+    result: &Bound<'py, PyAny>,
+    thresholds: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let thresholds = GateThresholds::from_kwargs(thresholds)?;
+    let result: DiffResult = pythonize::depythonize(result)?;
+    Ok(pythonize::pythonize(py, &evaluate(&result, &thresholds))?)
+}
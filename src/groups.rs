@@ -0,0 +1,96 @@
+// koala-diff/src/groups.rs
+// Mismatch rates per column, broken down by a grouping column
+
+use indexmap::IndexMap;
+use polars::prelude::*;
+
+use crate::error::{Context, Result};
+use crate::result::{GroupMismatch, GroupedMismatch};
+
+/// Most groups reported; the rest (fewest modified rows) are dropped.
+const MAX_GROUPS: usize = 500;
+
+/// Pivot of per-group mismatch rates over the matched rows.
+///
+/// `joined` must carry the `{column}_is_diff` flag of every name in `columns`.
+/// A non-key `group_column` is taken from file A.
+pub fn mismatch_by_group(
+    joined: LazyFrame,
+    group_column: &str,
+    columns: &[String],
+) -> Result<GroupedMismatch> {
+    let flag = |c: &str| col(format!("{}_is_diff", c).as_str());
+
+    let mut aggs = vec![len().alias("_matched")];
+    let mut any_diff: Option<Expr> = None;
+    for c in columns {
+        aggs.push(
+            flag(c)
+                .cast(DataType::UInt64)
+                .sum()
+                .alias(format!("{}_diff_count", c)),
+        );
+        any_diff = Some(match any_diff {
+            Some(m) => m.or(flag(c)),
+            None => flag(c),
+        });
+    }
+    if let Some(mask) = any_diff {
+        aggs.push(mask.cast(DataType::UInt64).sum().alias("_modified"));
+    } else {
+        aggs.push(lit(0u64).alias("_modified"));
+    }
+
+    let df = joined
+        .group_by([col(group_column).alias("_group")])
+        .agg(aggs)
+        .sort(
+            ["_modified"],
+            SortMultipleOptions::default().with_order_descending(true),
+        )
+        .collect()
+        .stage("grouping mismatches")
+        .column(group_column)?;
+
+    let count = |name: &str, i: usize| -> usize {
+        df.column(name)
+            .unwrap()
+            .get(i)
+            .unwrap()
+            .try_extract::<u64>()
+            .unwrap_or(0) as usize
+    };
+
+    let mut groups = Vec::new();
+    for i in 0..df.height().min(MAX_GROUPS) {
+        let group = match df.column("_group").unwrap().get(i).unwrap() {
+            AnyValue::Null => "null".to_string(),
+            v => v.get_str().map_or_else(|| v.to_string(), str::to_string),
+        };
+        let matched_rows = count("_matched", i);
+        let mismatch_rates: IndexMap<String, f64> = columns
+            .iter()
+            .map(|c| {
+                let diff = count(&format!("{}_diff_count", c), i);
+                let rate = if matched_rows > 0 {
+                    diff as f64 / matched_rows as f64 * 100.0
+                } else {
+                    0.0
+                };
+                (c.clone(), rate)
+            })
+            .collect();
+        groups.push(GroupMismatch {
+            group,
+            matched_rows,
+            modified_rows: count("_modified", i),
+            mismatch_rates,
+        });
+    }
+
+    Ok(GroupedMismatch {
+        group_column: group_column.to_string(),
+        truncated: df.height() > MAX_GROUPS,
+        groups,
+    })
+}
@@ -0,0 +1,160 @@
+// koala-diff/src/hierarchy.rs
+// Header-level rollup of a line-level diff (order -> line items)
+
+use polars::prelude::*;
+
+use crate::error::{Context, Result};
+use crate::result::{HeaderDiff, HierarchySummary};
+
+/// Most modified headers listed in the summary.
+const MAX_SAMPLE_HEADERS: usize = 20;
+
+/// Rolls the line-level diff up to the header keys.
+///
+/// `joined` holds the lines matched on header + line keys and must carry a
+/// boolean `_line_modified` column. A header counts as modified when any of
+/// its lines was added, removed or changed.
+pub fn summarize(
+    lf_a: LazyFrame,
+    lf_b: LazyFrame,
+    joined: LazyFrame,
+    header_keys: &[String],
+    line_keys: &[String],
+) -> Result<HierarchySummary> {
+    let headers: Vec<Expr> = header_keys.iter().map(|k| col(k.as_str())).collect();
+    let count_lines = |lf: LazyFrame, alias: &str| {
+        lf.group_by(headers.clone())
+            .agg([len().cast(DataType::Int64).alias(alias)])
+    };
+
+    let per_header = count_lines(lf_a, "_lines_a")
+        .join(
+            count_lines(lf_b, "_lines_b"),
+            headers.clone(),
+            headers.clone(),
+            JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .join(
+            joined.group_by(headers.clone()).agg([
+                len().cast(DataType::Int64).alias("_matched"),
+                col("_line_modified")
+                    .cast(DataType::Int64)
+                    .sum()
+                    .alias("_modified"),
+            ]),
+            headers.clone(),
+            headers.clone(),
+            JoinArgs::new(JoinType::Left),
+        )
+        .with_columns([
+            col("_matched").fill_null(lit(0i64)),
+            col("_modified").fill_null(lit(0i64)),
+        ])
+        .with_columns([
+            (col("_lines_a").fill_null(lit(0i64)) - col("_matched")).alias("_removed"),
+            (col("_lines_b").fill_null(lit(0i64)) - col("_matched")).alias("_added"),
+        ])
+        .with_column(
+            (col("_removed")
+                .gt(0)
+                .or(col("_added").gt(0))
+                .or(col("_modified").gt(0)))
+            .alias("_changed"),
+        );
+
+    let in_both = col("_lines_a")
+        .is_not_null()
+        .and(col("_lines_b").is_not_null());
+    let counts = per_header
+        .clone()
+        .select([
+            col("_lines_a").is_not_null().sum().alias("headers_a"),
+            col("_lines_b").is_not_null().sum().alias("headers_b"),
+            in_both.clone().sum().alias("matched_headers"),
+            col("_lines_a").is_null().sum().alias("added_headers"),
+            col("_lines_b").is_null().sum().alias("removed_headers"),
+            in_both
+                .clone()
+                .and(col("_changed"))
+                .sum()
+                .alias("modified_headers"),
+            in_both
+                .clone()
+                .and(col("_added").gt(0))
+                .sum()
+                .alias("headers_with_added_lines"),
+            in_both
+                .clone()
+                .and(col("_removed").gt(0))
+                .sum()
+                .alias("headers_with_removed_lines"),
+            in_both
+                .clone()
+                .and(col("_modified").gt(0))
+                .sum()
+                .alias("headers_with_modified_lines"),
+        ])
+        .collect()
+        .stage("rolling up line diffs to headers")?;
+    let count = |name: &str| -> usize {
+        counts
+            .column(name)
+            .ok()
+            .and_then(|c| c.get(0).ok())
+            .and_then(|v| v.try_extract::<u64>().ok())
+            .unwrap_or(0) as usize
+    };
+
+    let samples = per_header
+        .filter(in_both.and(col("_changed")))
+        .sort(
+            ["_modified", "_added", "_removed"],
+            SortMultipleOptions::default().with_order_descending(true),
+        )
+        .limit(MAX_SAMPLE_HEADERS as IdxSize)
+        .collect()
+        .stage("rolling up line diffs to headers")?;
+    let value = |name: &str, i: usize| -> usize {
+        samples
+            .column(name)
+            .ok()
+            .and_then(|c| c.get(i).ok())
+            .and_then(|v| v.try_extract::<u64>().ok())
+            .unwrap_or(0) as usize
+    };
+    let mut sample_headers = Vec::with_capacity(samples.height());
+    for i in 0..samples.height() {
+        let key = header_keys
+            .iter()
+            .map(|k| match samples.column(k).and_then(|c| c.get(i)) {
+                Ok(AnyValue::Null) | Err(_) => "null".to_string(),
+                Ok(v) => v.get_str().map_or_else(|| v.to_string(), str::to_string),
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+        sample_headers.push(HeaderDiff {
+            key,
+            added_lines: value("_added", i),
+            removed_lines: value("_removed", i),
+            modified_lines: value("_modified", i),
+        });
+    }
+
+    let matched_headers = count("matched_headers");
+    let modified_headers = count("modified_headers");
+    Ok(HierarchySummary {
+        header_keys: header_keys.to_vec(),
+        line_keys: line_keys.to_vec(),
+        headers_a: count("headers_a"),
+        headers_b: count("headers_b"),
+        matched_headers,
+        identical_headers: matched_headers.saturating_sub(modified_headers),
+        modified_headers,
+        added_headers: count("added_headers"),
+        removed_headers: count("removed_headers"),
+        headers_with_added_lines: count("headers_with_added_lines"),
+        headers_with_removed_lines: count("headers_with_removed_lines"),
+        headers_with_modified_lines: count("headers_with_modified_lines"),
+        sample_headers,
+    })
+}
@@ -0,0 +1,166 @@
+// koala-diff/src/history.rs
+// Schema history store: each dataset's last seen schema, to flag drift across runs
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Context, DiffError, Result};
+use crate::result::{SchemaDrift, SchemaDriftChange, Warning};
+use crate::schema;
+use crate::warnings;
+
+/// The store: a JSON file holding the last schema seen per dataset name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct History {
+    datasets: BTreeMap<String, Seen>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Seen {
+    fingerprint: String,
+    columns: Vec<(String, String)>,
+    run_id: String,
+    run_at: String,
+}
+
+/// Compares each `(dataset, schema)` against the last schema the store at
+/// `path` recorded for that dataset, then records them (a new store is
+/// created). A drifted schema also raises a warning.
+///
+/// Both sides are checked against the store as it was before the run, so a
+/// dataset named on both sides isn't compared with itself.
+pub fn check(
+    path: &str,
+    sides: [(&str, &Schema); 2],
+    run_id: &str,
+    run_at: &str,
+) -> Result<Vec<SchemaDrift>> {
+    let mut history = if Path::new(path).exists() {
+        let text = fs::read_to_string(path)
+            .stage("reading the schema history")
+            .file(path)?;
+        serde_json::from_str::<History>(&text).map_err(|e| {
+            DiffError::invalid(format!("Not a schema history store: {}", e)).in_file(path)
+        })?
+    } else {
+        History::default()
+    };
+
+    let mut drift = Vec::with_capacity(sides.len());
+    let mut seen = Vec::with_capacity(sides.len());
+    for (dataset, schema) in sides {
+        let current = Seen {
+            fingerprint: schema::fingerprint(schema),
+            columns: schema::columns(schema),
+            run_id: run_id.to_string(),
+            run_at: run_at.to_string(),
+        };
+        let previous = history.datasets.get(dataset);
+        let drifted = previous.is_some_and(|p| p.fingerprint != current.fingerprint);
+        let changes = match previous {
+            Some(previous) if drifted => changes(&previous.columns, &current.columns),
+            _ => Vec::new(),
+        };
+        if let Some(previous) = previous.filter(|_| drifted) {
+            warnings::raise(
+                Warning::new(
+                    "schema_drift",
+                    format!(
+                        "The schema of {} changed since run {} at {} ({} column changes)",
+                        dataset,
+                        previous.run_id,
+                        previous.run_at,
+                        changes.len()
+                    ),
+                )
+                .count(changes.len()),
+            );
+        }
+        drift.push(SchemaDrift {
+            dataset: dataset.to_string(),
+            fingerprint: current.fingerprint.clone(),
+            previous_fingerprint: previous.map(|p| p.fingerprint.clone()),
+            previous_run_id: previous.map(|p| p.run_id.clone()),
+            previous_run_at: previous.map(|p| p.run_at.clone()),
+            drifted,
+            changes,
+        });
+        seen.push((dataset.to_string(), current));
+    }
+
+    history.datasets.extend(seen);
+    // Written aside and renamed over, so a crash can't leave half a store
+    let json =
+        serde_json::to_string_pretty(&history).map_err(|e| DiffError::invalid(e.to_string()))?;
+    let staged = format!("{}.tmp", path);
+    fs::write(&staged, json)
+        .and_then(|_| fs::rename(&staged, path))
+        .stage("writing the schema history")
+        .file(path)?;
+    Ok(drift)
+}
+
+/// Column changes from `previous` to `current`, ordered like `schema_diff`:
+/// removed and retyped columns, added columns, then reordered shared ones.
+fn changes(previous: &[(String, String)], current: &[(String, String)]) -> Vec<SchemaDriftChange> {
+    let dtype = |columns: &[(String, String)], name: &str| {
+        columns
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, dtype)| dtype.clone())
+    };
+    let change =
+        |column: &str, change: &str, from: Option<String>, to: Option<String>| SchemaDriftChange {
+            column: column.to_string(),
+            change: change.to_string(),
+            from,
+            to,
+        };
+
+    let mut changes = Vec::new();
+    for (name, from) in previous {
+        match dtype(current, name) {
+            None => changes.push(change(name, "removed", Some(from.clone()), None)),
+            Some(to) if &to != from => {
+                changes.push(change(name, "retyped", Some(from.clone()), Some(to)))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, to) in current {
+        if dtype(previous, name).is_none() {
+            changes.push(change(name, "added", None, Some(to.clone())));
+        }
+    }
+
+    // Positions among the shared columns, as in `schema::schema_diff`
+    let shared = |columns: &[(String, String)], other: &[(String, String)]| -> Vec<String> {
+        columns
+            .iter()
+            .filter(|(n, _)| dtype(other, n).is_some())
+            .map(|(n, _)| n.clone())
+            .collect()
+    };
+    let shared_previous = shared(previous, current);
+    let shared_current = shared(current, previous);
+    for (from, name) in shared_previous.iter().enumerate() {
+        let to = shared_current
+            .iter()
+            .position(|n| n == name)
+            .unwrap_or(from);
+        if from != to {
+            changes.push(change(
+                name,
+                "reordered",
+                Some(from.to_string()),
+                Some(to.to_string()),
+            ));
+        }
+    }
+    changes
+}
@@ -0,0 +1,125 @@
+// koala-diff/src/http.rs
+// HTTP(S) URLs as inputs (published open-data snapshots), downloaded before reading
+
+#[cfg(feature = "http")]
+use std::fs::{self, File};
+#[cfg(feature = "http")]
+use std::io;
+#[cfg(feature = "http")]
+use std::path::PathBuf;
+#[cfg(feature = "http")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "http")]
+use std::time::Duration;
+
+use polars::prelude::*;
+
+#[cfg(feature = "http")]
+use crate::error::Context;
+use crate::error::{DiffError, Result};
+use crate::options::ReadOptions;
+#[cfg(feature = "http")]
+use crate::reader;
+
+pub fn is_url(path: &str) -> bool {
+    path.starts_with("https://") || path.starts_with("http://")
+}
+
+/// The download, removed once read.
+#[cfg(feature = "http")]
+struct Download(PathBuf);
+
+#[cfg(feature = "http")]
+impl Drop for Download {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Downloads `url` and reads it like a local file with the same extension
+/// (`.../snapshot.parquet?version=3` reads as Parquet), sending the
+/// `http_headers` read option with the request.
+///
+/// The body is streamed to a temporary file rather than held in memory,
+/// then read in full before the file is removed: the frame doesn't depend
+/// on the server staying up, and a URL serving a new snapshot mid-run can't
+/// mix two versions.
+#[cfg(feature = "http")]
+pub fn read(url: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    let local_only = [
+        ("unload_manifest", read.unload_manifest),
+        ("debezium", read.debezium),
+    ];
+    if let Some((option, _)) = local_only.iter().find(|(_, set)| *set) {
+        return Err(
+            DiffError::unsupported(format!("'{}' only applies to local files", option))
+                .in_file(url),
+        );
+    }
+
+    let download = Download(temp_path(url));
+    // No overall timeout: snapshots can take long to come down; a stalled
+    // connection still fails on the socket
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .timeout(None)
+        .build()
+        .map_err(io::Error::other)
+        .stage("downloading")?;
+    let mut request = client.get(url);
+    for (name, value) in &read.http_headers {
+        request = request.header(name, value);
+    }
+    let mut response = request
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(io::Error::other)
+        .stage("downloading")
+        .file(url)?;
+    let mut file = File::create(&download.0).stage("downloading").file(url)?;
+    response
+        .copy_to(&mut file)
+        .map_err(io::Error::other)
+        .stage("downloading")
+        .file(url)?;
+    drop(file);
+
+    let path = download.0.to_string_lossy();
+    let df = reader::scan_format(&path, read)?
+        .collect()
+        .stage("reading")
+        .file(url)?;
+    Ok(df.lazy())
+}
+
+/// A unique temporary path ending in the URL's file name, which the
+/// readers pick the format from.
+#[cfg(feature = "http")]
+fn temp_path(url: &str) -> PathBuf {
+    static DOWNLOADS: AtomicUsize = AtomicUsize::new(0);
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .split_once("://")
+        .map_or("", |(_, rest)| rest);
+    let name = path
+        .split_once('/')
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("download");
+    std::env::temp_dir().join(format!(
+        "koala-diff-{}-{}-{}",
+        std::process::id(),
+        DOWNLOADS.fetch_add(1, Ordering::Relaxed),
+        name
+    ))
+}
+
+#[cfg(not(feature = "http"))]
+pub fn read(url: &str, _read: &ReadOptions) -> Result<LazyFrame> {
+    Err(DiffError::unsupported(
+        "URLs can't be read: koala-diff was built without the 'http' feature",
+    )
+    .in_file(url))
+}
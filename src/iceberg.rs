@@ -0,0 +1,307 @@
+// koala-diff/src/iceberg.rs
+// Apache Iceberg tables as inputs, at their current or a chosen snapshot
+
+use std::path::{Path, PathBuf};
+
+use polars::prelude::*;
+use serde_json::Value;
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::ReadOptions;
+use crate::records;
+
+const METADATA_SUFFIX: &str = ".metadata.json";
+
+/// A table metadata file, or a table directory holding `metadata/*.metadata.json`.
+pub fn is_table(path: &str) -> bool {
+    if path.ends_with(METADATA_SUFFIX) {
+        return true;
+    }
+    std::fs::read_dir(Path::new(path).join("metadata")).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|e| e.file_name().to_string_lossy().ends_with(METADATA_SUFFIX))
+    })
+}
+
+/// Version of a metadata file name: `v3.metadata.json` or
+/// `00003-<uuid>.metadata.json`.
+fn metadata_version(name: &str) -> Option<u64> {
+    let name = name.strip_prefix('v').unwrap_or(name);
+    let digits = name
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(name.len());
+    name[..digits].parse().ok()
+}
+
+/// The metadata file to read and the table directory: the path itself when
+/// it is a metadata file, else the one named by `version-hint.text` or the
+/// newest one.
+fn locate(path: &str) -> Result<(PathBuf, PathBuf)> {
+    let path = Path::new(path);
+    if path.to_string_lossy().ends_with(METADATA_SUFFIX) {
+        let table = path
+            .parent()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        return Ok((path.to_path_buf(), table));
+    }
+    let dir = path.join("metadata");
+    if let Ok(hint) = std::fs::read_to_string(dir.join("version-hint.text")) {
+        let hinted = dir.join(format!("v{}{}", hint.trim(), METADATA_SUFFIX));
+        if hinted.is_file() {
+            return Ok((hinted, path.to_path_buf()));
+        }
+    }
+    let newest = std::fs::read_dir(&dir)
+        .stage("reading the Iceberg metadata")
+        .file(&path.to_string_lossy())?
+        .flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            let version = metadata_version(&name).filter(|_| name.ends_with(METADATA_SUFFIX))?;
+            Some((version, e.path()))
+        })
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, file)| file)
+        .ok_or_else(|| {
+            DiffError::invalid("No Iceberg metadata file found").in_file(&path.to_string_lossy())
+        })?;
+    Ok((newest, path.to_path_buf()))
+}
+
+/// Local path of a file referenced by the metadata. `file:` URIs are
+/// stripped, and paths under the table's recorded `location` are resolved
+/// against the directory actually given, so copied or re-mounted tables
+/// still read.
+fn local_path(uri: &str, location: &str, table: &Path) -> PathBuf {
+    let strip = |uri: &'_ str| -> String {
+        uri.strip_prefix("file://")
+            .or_else(|| uri.strip_prefix("file:"))
+            .unwrap_or(uri)
+            .to_string()
+    };
+    let (uri, location) = (strip(uri), strip(location));
+    match uri.strip_prefix(location.trim_end_matches('/')) {
+        Some(rest) if !location.is_empty() => table.join(rest.trim_start_matches('/')),
+        _ => PathBuf::from(uri),
+    }
+}
+
+/// Reads the table at `path` (a table directory or a `*.metadata.json`) at
+/// `iceberg_snapshot_id`, or its current snapshot.
+///
+/// The snapshot's manifest list and manifests (Avro) give its live data
+/// files, scanned as one frame in the snapshot's schema. Columns are matched
+/// by name; those added after a file was written are null in its rows.
+#[cfg(feature = "parquet")]
+pub fn scan(path: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    let (metadata_file, table) = locate(path)?;
+    let text = std::fs::read_to_string(&metadata_file)
+        .stage("reading the Iceberg metadata")
+        .file(path)?;
+    let metadata: Value = serde_json::from_str(&text).map_err(|e| {
+        DiffError::invalid(format!("Invalid Iceberg metadata: {}", e)).in_file(path)
+    })?;
+    let location = metadata
+        .get("location")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    let snapshot_id = read
+        .iceberg_snapshot_id
+        .or_else(|| metadata.get("current-snapshot-id").and_then(Value::as_i64))
+        .filter(|&id| id != -1);
+    let snapshot = match snapshot_id {
+        Some(id) => Some(
+            metadata
+                .get("snapshots")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .find(|s| s.get("snapshot-id").and_then(Value::as_i64) == Some(id))
+                .ok_or_else(|| {
+                    DiffError::invalid(format!("Iceberg snapshot {} doesn't exist", id))
+                        .in_file(path)
+                })?,
+        ),
+        None => None,
+    };
+    let schema = table_schema(&metadata, snapshot, path)?;
+    let Some(snapshot) = snapshot else {
+        // No snapshot yet: an empty table
+        return Ok(DataFrame::empty_with_schema(&schema).lazy());
+    };
+
+    let mut files = Vec::new();
+    for manifest in manifests(snapshot, location, &table, path)? {
+        for entry in records::read_avro_values(&manifest.to_string_lossy())? {
+            // 2 = deleted in this snapshot
+            if entry.get("status").and_then(Value::as_i64) == Some(2) {
+                continue;
+            }
+            let Some(data_file) = entry.get("data_file") else {
+                continue;
+            };
+            if data_file
+                .get("content")
+                .and_then(Value::as_i64)
+                .unwrap_or(0)
+                != 0
+            {
+                return Err(DiffError::unsupported(
+                    "Iceberg delete files (merge-on-read) aren't supported; compact the table first",
+                )
+                .in_file(path));
+            }
+            let format = data_file
+                .get("file_format")
+                .and_then(Value::as_str)
+                .unwrap_or("PARQUET");
+            if !format.eq_ignore_ascii_case("parquet") {
+                return Err(DiffError::unsupported(format!(
+                    "Iceberg data files in {} format aren't supported",
+                    format
+                ))
+                .in_file(path));
+            }
+            let file = data_file
+                .get("file_path")
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    DiffError::invalid("Iceberg manifest entry without a file_path").in_file(path)
+                })?;
+            files.push(local_path(file, location, &table));
+        }
+    }
+    if files.is_empty() {
+        return Ok(DataFrame::empty_with_schema(&schema).lazy());
+    }
+
+    let columns: Vec<Expr> = schema.iter_names().map(|name| col(name.clone())).collect();
+    let mut frames = Vec::with_capacity(files.len());
+    for file in &files {
+        let mut lf =
+            LazyFrame::scan_parquet(file.to_string_lossy().as_ref().into(), Default::default())
+                .stage("reading an Iceberg data file")
+                .file(path)?;
+        let file_schema = lf
+            .collect_schema()
+            .stage("reading an Iceberg data file")
+            .file(path)?;
+        let missing: Vec<Expr> = schema
+            .iter()
+            .filter(|(name, _)| !file_schema.contains(name))
+            .map(|(name, dtype)| lit(NULL).cast(dtype.clone()).alias(name.clone()))
+            .collect();
+        frames.push(lf.with_columns(missing).select(columns.clone()));
+    }
+    let args = UnionArgs {
+        to_supertypes: true,
+        ..Default::default()
+    };
+    concat(frames, args).stage("reading").file(path)
+}
+
+#[cfg(not(feature = "parquet"))]
+pub fn scan(path: &str, _read: &ReadOptions) -> Result<LazyFrame> {
+    Err(DiffError::unsupported(
+        "Iceberg tables can't be read: koala-diff was built without the 'parquet' feature",
+    )
+    .in_file(path))
+}
+
+/// Manifest files of a snapshot: from its manifest list (format v2, and
+/// most v1 tables) or its inline `manifests` (early v1).
+fn manifests(snapshot: &Value, location: &str, table: &Path, path: &str) -> Result<Vec<PathBuf>> {
+    if let Some(list) = snapshot.get("manifest-list").and_then(Value::as_str) {
+        let list = local_path(list, location, table);
+        return records::read_avro_values(&list.to_string_lossy())?
+            .iter()
+            .map(|m| {
+                m.get("manifest_path")
+                    .and_then(Value::as_str)
+                    .map(|p| local_path(p, location, table))
+                    .ok_or_else(|| {
+                        DiffError::invalid("Iceberg manifest list entry without a manifest_path")
+                            .in_file(path)
+                    })
+            })
+            .collect();
+    }
+    Ok(snapshot
+        .get("manifests")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|p| local_path(p, location, table))
+        .collect())
+}
+
+/// Polars schema of the snapshot's schema (else the current one; `schema`
+/// in v1 metadata). Top-level primitive types only; nested ones are left to
+/// the data files.
+fn table_schema(metadata: &Value, snapshot: Option<&Value>, path: &str) -> Result<Schema> {
+    let schema_id = snapshot
+        .and_then(|s| s.get("schema-id"))
+        .or_else(|| metadata.get("current-schema-id"))
+        .and_then(Value::as_i64);
+    let found = metadata
+        .get("schemas")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .find(|s| s.get("schema-id").and_then(Value::as_i64) == schema_id)
+        .or_else(|| metadata.get("schema"))
+        .ok_or_else(|| DiffError::invalid("Iceberg metadata has no schema").in_file(path))?;
+
+    let mut schema = Schema::default();
+    for field in found
+        .get("fields")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let Some(name) = field.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let dtype = match field.get("type").and_then(Value::as_str) {
+            Some(primitive) => iceberg_dtype(primitive),
+            None => DataType::Null,
+        };
+        schema.with_column(name.into(), dtype);
+    }
+    Ok(schema)
+}
+
+fn iceberg_dtype(primitive: &str) -> DataType {
+    match primitive {
+        "boolean" => DataType::Boolean,
+        "int" => DataType::Int32,
+        "long" => DataType::Int64,
+        "float" => DataType::Float32,
+        "double" => DataType::Float64,
+        "date" => DataType::Date,
+        "time" => DataType::Time,
+        "timestamp" => DataType::Datetime(TimeUnit::Microseconds, None),
+        "timestamptz" => DataType::Datetime(TimeUnit::Microseconds, Some(TimeZone::UTC)),
+        "timestamp_ns" => DataType::Datetime(TimeUnit::Nanoseconds, None),
+        "timestamptz_ns" => DataType::Datetime(TimeUnit::Nanoseconds, Some(TimeZone::UTC)),
+        "string" => DataType::String,
+        "uuid" | "binary" => DataType::Binary,
+        other if other.starts_with("fixed[") => DataType::Binary,
+        other => match other
+            .strip_prefix("decimal(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .and_then(|args| args.split_once(','))
+        {
+            Some((precision, scale)) => DataType::Decimal(
+                precision.trim().parse().unwrap_or(38),
+                scale.trim().parse().unwrap_or(0),
+            ),
+            None => DataType::String,
+        },
+    }
+}
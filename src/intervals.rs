@@ -0,0 +1,426 @@
+// koala-diff/src/intervals.rs
+// Effective-dated comparison: records aligned on key and overlapping validity
+
+use indexmap::IndexMap;
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+
+use crate::compare;
+use crate::error::{Context, DiffError, Result};
+use crate::multiset::key_string;
+use crate::options::DiffOptions;
+use crate::reader;
+use crate::result::{IntervalDiff, IntervalIntegrity, IntervalSegmentDiff};
+use crate::schema;
+
+/// Most modified segments / broken keys listed in the result.
+const MAX_SAMPLES: usize = 20;
+
+/// The validity columns, shared by both files (B's cast to A's types).
+struct Validity<'a> {
+    from: &'a str,
+    to: &'a str,
+}
+
+impl Validity<'_> {
+    /// `[from, to)` of the left row overlaps that of the right row; a null
+    /// `to` is open-ended.
+    fn overlaps(&self) -> Expr {
+        let (from_r, to_r) = (self.right(self.from), self.right(self.to));
+        let starts_before_end = |from: Expr, to: Expr| to.clone().is_null().or(from.lt(to));
+        starts_before_end(col(from_r.as_str()), col(self.to))
+            .and(starts_before_end(col(self.from), col(to_r.as_str())))
+    }
+
+    fn right(&self, name: &str) -> String {
+        format!("{}_right", name)
+    }
+}
+
+/// Gaps and overlaps between consecutive validity intervals of each key.
+fn integrity(
+    lf: LazyFrame,
+    keys: &[Expr],
+    key_cols: &[String],
+    validity: &Validity,
+) -> Result<IntervalIntegrity> {
+    let (from, to) = (col(validity.from), col(validity.to));
+    let prev_to = to.clone().shift(lit(1)).over(keys);
+    let is_first = from.clone().shift(lit(1)).over(keys).is_null();
+    let checked = lf
+        .sort_by_exprs(
+            keys.iter()
+                .cloned()
+                .chain([from.clone()])
+                .collect::<Vec<_>>(),
+            SortMultipleOptions::default(),
+        )
+        .with_columns([
+            is_first
+                .clone()
+                .not()
+                .and(prev_to.clone().is_not_null())
+                .and(from.clone().gt(prev_to.clone()))
+                .alias("_gap"),
+            is_first
+                .not()
+                .and(prev_to.clone().is_null().or(from.lt(prev_to)))
+                .alias("_overlap"),
+        ]);
+
+    let per_key = checked
+        .group_by(keys)
+        .agg([
+            col("_gap").sum().alias("_gaps"),
+            col("_overlap").sum().alias("_overlaps"),
+        ])
+        .collect()
+        .stage("checking validity intervals")?;
+    let count = |name: &str, row: usize| {
+        per_key
+            .column(name)
+            .ok()
+            .and_then(|c| c.get(row).ok())
+            .and_then(|v| v.try_extract::<u64>().ok())
+            .unwrap_or(0) as usize
+    };
+
+    let mut report = IntervalIntegrity::default();
+    for row in 0..per_key.height() {
+        let (gaps, overlaps) = (count("_gaps", row), count("_overlaps", row));
+        report.gaps += gaps;
+        report.overlaps += overlaps;
+        if gaps + overlaps > 0 && report.sample_keys.len() < MAX_SAMPLES {
+            report.sample_keys.push(key_string(&per_key, key_cols, row));
+        }
+    }
+    Ok(report)
+}
+
+/// Compares two effective-dated datasets.
+///
+/// Rows of a key are matched when their validity intervals (`[valid_from,
+/// valid_to)`, a null `valid_to` being open-ended) overlap; the attributes
+/// of each overlapping pair are compared over that shared segment. Intervals
+/// with no overlapping counterpart are reported as unmatched, and each side
+/// is checked for gaps and overlaps between the intervals of a key.
+pub fn compare_intervals(
+    mut lf_a: LazyFrame,
+    mut lf_b: LazyFrame,
+    key_cols: &[String],
+    valid_from: &str,
+    valid_to: &str,
+    opts: &DiffOptions,
+) -> Result<IntervalDiff> {
+    let schema_a = lf_a.collect_schema().stage("comparing intervals")?;
+    let schema_b = lf_b.collect_schema().stage("comparing intervals")?;
+    for name in [valid_from, valid_to] {
+        let (Some(dtype), true) = (schema_a.get(name), schema_b.contains(name)) else {
+            return Err(DiffError::invalid("validity column missing from an input").in_column(name));
+        };
+        lf_b = lf_b.with_column(col(name).cast(dtype.clone()));
+    }
+    let validity = Validity {
+        from: valid_from,
+        to: valid_to,
+    };
+
+    let keys: Vec<Expr> = key_cols.iter().map(|k| col(k.as_str())).collect();
+    let columns: Vec<(&str, &DataType, &DataType)> = schema_a
+        .iter()
+        .filter(|(name, _)| {
+            !key_cols.iter().any(|k| k == name.as_str())
+                && name.as_str() != valid_from
+                && name.as_str() != valid_to
+        })
+        .filter_map(|(name, dtype_a)| {
+            let dtype_b = schema_b.get(name.as_str())?;
+            schema::comparable(dtype_a, dtype_b).then_some((name.as_str(), dtype_a, dtype_b))
+        })
+        .collect();
+
+    lf_a = lf_a.with_row_index("_row", None);
+    lf_b = lf_b.with_row_index("_row", None);
+    let flags: Vec<Expr> = columns
+        .iter()
+        .map(|&(name, dtype_a, dtype_b)| {
+            compare::diff_expr(name, &validity.right(name), dtype_a, dtype_b, opts)
+                .alias(format!("{}_is_diff", name))
+        })
+        .collect();
+    let any_diff = columns
+        .iter()
+        .map(|(name, _, _)| col(format!("{}_is_diff", name).as_str()))
+        .reduce(|a, b| a.or(b))
+        .unwrap_or(lit(false));
+
+    let from_r = validity.right(valid_from);
+    let to_r = validity.right(valid_to);
+    let segments = lf_a
+        .clone()
+        .join(
+            lf_b.clone(),
+            keys.clone(),
+            keys.clone(),
+            JoinArgs::new(JoinType::Inner).with_suffix(Some("_right".into())),
+        )
+        .filter(validity.overlaps())
+        .with_columns(flags)
+        .with_columns([
+            any_diff.alias("_modified"),
+            col(valid_from)
+                .eq(col(from_r.as_str()))
+                .and(col(valid_to).eq_missing(col(to_r.as_str())))
+                .not()
+                .alias("_shifted"),
+            polars::lazy::dsl::max_horizontal([col(valid_from), col(from_r.as_str())])
+                .stage("comparing intervals")?
+                .alias("_segment_from"),
+            polars::lazy::dsl::min_horizontal([col(valid_to), col(to_r.as_str())])
+                .stage("comparing intervals")?
+                .alias("_segment_to"),
+        ]);
+
+    let mut aggs = vec![
+        len().alias("overlapping_segments"),
+        col("_modified").sum().alias("modified_segments"),
+        col("_shifted").sum().alias("shifted_segments"),
+        col("_row").n_unique().alias("matched_intervals_a"),
+        col("_row_right").n_unique().alias("matched_intervals_b"),
+    ];
+    for (name, _, _) in &columns {
+        aggs.push(
+            col(format!("{}_is_diff", name).as_str())
+                .sum()
+                .alias(format!("{}_diff_count", name)),
+        );
+    }
+    let totals = segments
+        .clone()
+        .select(aggs)
+        .collect()
+        .stage("comparing intervals")?;
+    let sides = |lf: &LazyFrame| {
+        lf.clone()
+            .select([
+                len().alias("intervals"),
+                as_struct(keys.clone()).n_unique().alias("keys"),
+            ])
+            .collect()
+            .stage("comparing intervals")
+    };
+    let (sides_a, sides_b) = (sides(&lf_a)?, sides(&lf_b)?);
+    let matched_keys = lf_a
+        .clone()
+        .select(keys.clone())
+        .unique(None, UniqueKeepStrategy::Any)
+        .join(
+            lf_b.clone().select(keys.clone()),
+            keys.clone(),
+            keys.clone(),
+            JoinArgs::new(JoinType::Semi),
+        )
+        .select([len().alias("keys")])
+        .collect()
+        .stage("comparing intervals")?;
+    let count = |df: &DataFrame, name: &str| {
+        df.column(name)
+            .ok()
+            .and_then(|c| c.get(0).ok())
+            .and_then(|v| v.try_extract::<u64>().ok())
+            .unwrap_or(0) as usize
+    };
+
+    let samples = segments
+        .filter(col("_modified"))
+        .limit(MAX_SAMPLES as IdxSize)
+        .collect()
+        .stage("comparing intervals")?;
+    let flag = |name: &str, row: usize| {
+        matches!(
+            samples
+                .column(&format!("{}_is_diff", name))
+                .and_then(|c| c.get(row)),
+            Ok(AnyValue::Boolean(true))
+        )
+    };
+    let bound = |name: &str, row: usize| match samples.column(name).and_then(|c| c.get(row)) {
+        Ok(AnyValue::Null) | Err(_) => None,
+        Ok(v) => Some(v.to_string()),
+    };
+    let sample_segments = (0..samples.height())
+        .map(|row| IntervalSegmentDiff {
+            key: key_string(&samples, key_cols, row),
+            valid_from: bound("_segment_from", row).unwrap_or_default(),
+            valid_to: bound("_segment_to", row),
+            columns: columns
+                .iter()
+                .filter(|(name, _, _)| flag(name, row))
+                .map(|(name, _, _)| name.to_string())
+                .collect(),
+        })
+        .collect();
+
+    let (intervals_a, intervals_b) = (count(&sides_a, "intervals"), count(&sides_b, "intervals"));
+    let (keys_a, keys_b) = (count(&sides_a, "keys"), count(&sides_b, "keys"));
+    let matched = count(&matched_keys, "keys");
+    let overlapping = count(&totals, "overlapping_segments");
+    let modified = count(&totals, "modified_segments");
+    Ok(IntervalDiff {
+        keys_a,
+        keys_b,
+        matched_keys: matched,
+        added_keys: keys_b.saturating_sub(matched),
+        removed_keys: keys_a.saturating_sub(matched),
+        intervals_a,
+        intervals_b,
+        overlapping_segments: overlapping,
+        identical_segments: overlapping.saturating_sub(modified),
+        modified_segments: modified,
+        shifted_segments: count(&totals, "shifted_segments"),
+        unmatched_intervals_a: intervals_a.saturating_sub(count(&totals, "matched_intervals_a")),
+        unmatched_intervals_b: intervals_b.saturating_sub(count(&totals, "matched_intervals_b")),
+        column_mismatches: columns
+            .iter()
+            .map(|(name, _, _)| {
+                (
+                    name.to_string(),
+                    count(&totals, &format!("{}_diff_count", name)),
+                )
+            })
+            .collect::<IndexMap<_, _>>(),
+        integrity_a: integrity(lf_a, &keys, key_cols, &validity)?,
+        integrity_b: integrity(lf_b, &keys, key_cols, &validity)?,
+        sample_segments,
+    })
+}
+
+/// Compares two effective-dated datasets (key + validity interval)
+///
+/// Reference data versioned by `[valid_from, valid_to)` can't be joined on
+/// the key alone: rows of a key are matched when their validity intervals
+/// overlap, and their attributes compared over the shared segment. Each
+/// side is also checked for gaps and overlaps in its own history.
+///
+/// Args:
+///     file_a (str): Path to the source (old) file
+///     file_b (str): Path to the target (new) file
+///     key_cols (list[str]): Business key columns (without the validity columns)
+///     valid_from (str): Start of validity, inclusive
+///     valid_to (str): End of validity, exclusive; null for open-ended
+///     **options: Same options as `diff_files`
+///
+/// Returns:
+///     dict: Key and interval counts, overlapping segments (identical /
+///         modified / "shifted" when the boundaries differ), unmatched
+///         intervals per side, "column_mismatches", "integrity_a" /
+///         "integrity_b" ({"gaps", "overlaps", "sample_keys"}) and
+///         "sample_segments" with the differing columns per segment
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, key_cols, valid_from, valid_to, **options))]
+pub fn diff_intervals<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    valid_from: String,
+    valid_to: String,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let opts = DiffOptions::from_kwargs(options)?;
+    let lf_a = reader::open(&file_a, &opts.read_a, &key_cols, &opts)?;
+    let lf_b = reader::open(&file_b, &opts.read_b, &key_cols, &opts)?;
+    let diff = compare_intervals(lf_a, lf_b, &key_cols, &valid_from, &valid_to, &opts)?;
+    Ok(pythonize::pythonize(py, &diff)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(key: &[&str], from: &[i64], to: &[Option<i64>], v: &[i64]) -> LazyFrame {
+        df!("id" => key, "valid_from" => from, "valid_to" => to, "v" => v)
+            .unwrap()
+            .lazy()
+    }
+
+    fn compare(a: LazyFrame, b: LazyFrame) -> IntervalDiff {
+        let keys = ["id".to_string()];
+        compare_intervals(
+            a,
+            b,
+            &keys,
+            "valid_from",
+            "valid_to",
+            &DiffOptions::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn compares_attributes_over_each_overlap() {
+        // A changes v at 10, B at 5 and again from 5 on; both stay open-ended
+        let a = history(&["k", "k"], &[0, 10], &[Some(10), None], &[1, 2]);
+        let b = history(&["k", "k"], &[0, 5], &[Some(5), None], &[1, 3]);
+        let diff = compare(a, b);
+        assert_eq!(diff.matched_keys, 1);
+        // [0, 5) identical, [5, 10) and [10, open) modified
+        assert_eq!(diff.overlapping_segments, 3);
+        assert_eq!(diff.identical_segments, 1);
+        assert_eq!(diff.modified_segments, 2);
+        assert_eq!(diff.column_mismatches["v"], 2);
+        assert_eq!(
+            (diff.unmatched_intervals_a, diff.unmatched_intervals_b),
+            (0, 0)
+        );
+        let mut bounds: Vec<_> = diff
+            .sample_segments
+            .iter()
+            .map(|s| (s.valid_from.clone(), s.valid_to.clone()))
+            .collect();
+        bounds.sort();
+        assert_eq!(
+            bounds,
+            [
+                ("10".to_string(), None),
+                ("5".to_string(), Some("10".to_string()))
+            ]
+        );
+    }
+
+    #[test]
+    fn intervals_without_overlap_are_unmatched() {
+        let a = history(&["k"], &[0], &[Some(5)], &[1]);
+        let b = history(&["k"], &[5], &[None], &[1]);
+        let diff = compare(a, b);
+        assert_eq!(diff.matched_keys, 1);
+        assert_eq!(diff.overlapping_segments, 0);
+        assert_eq!(
+            (diff.unmatched_intervals_a, diff.unmatched_intervals_b),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn reports_gaps_and_overlaps_within_a_key() {
+        // "gap": nothing valid in [5, 7); "overlap": the open-ended interval
+        // still runs when the next one starts; "clean": contiguous
+        let lf = history(
+            &["gap", "gap", "overlap", "overlap", "clean", "clean"],
+            &[0, 7, 0, 5, 0, 5],
+            &[Some(5), Some(10), None, Some(10), Some(5), None],
+            &[1, 1, 1, 1, 1, 1],
+        );
+        let diff = compare(lf.clone(), lf);
+        let integrity = diff.integrity_a;
+        assert_eq!((integrity.gaps, integrity.overlaps), (1, 1));
+        let mut keys = integrity.sample_keys;
+        keys.sort();
+        assert_eq!(keys, ["gap", "overlap"]);
+        assert_eq!(diff.modified_segments, 0);
+    }
+}
@@ -0,0 +1,179 @@
+// koala-diff/src/kafka.rs
+// Kafka topic snapshots: `kafka://broker1:9092,broker2:9092/topic` as an input
+
+use polars::prelude::*;
+
+use crate::error::{DiffError, Result};
+use crate::options::ReadOptions;
+
+/// Consumes `target` (`brokers/topic`) into its current contents: JSON
+/// payloads, latest message per key, tombstones (null payloads) deleting
+/// the key. Unless `kafka_key_field` names the payload field keying the
+/// rows, the message key is added as a `kafka_key` column. `kafka_from` /
+/// `kafka_to` (epoch milliseconds) bound the messages read; the end
+/// defaults to the high watermark at start-up.
+#[cfg(feature = "kafka")]
+pub fn scan(target: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use indexmap::IndexMap;
+    use rdkafka::consumer::{BaseConsumer, Consumer};
+    use rdkafka::message::Message;
+    use rdkafka::{ClientConfig, Offset, TopicPartitionList};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    const KEY_COLUMN: &str = "kafka_key";
+
+    let (brokers, topic) = target.rsplit_once('/').ok_or_else(|| {
+        DiffError::invalid(format!(
+            "Expected kafka://<brokers>/<topic>, got kafka://{}",
+            target
+        ))
+    })?;
+
+    let consumer: BaseConsumer = ClientConfig::new()
+        .set("bootstrap.servers", brokers)
+        .set("group.id", "koala-diff")
+        .set("enable.auto.commit", "false")
+        .create()
+        .map_err(kafka_err)?;
+    let metadata = consumer
+        .fetch_metadata(Some(topic), TIMEOUT)
+        .map_err(kafka_err)?;
+    let partitions: Vec<i32> = metadata
+        .topics()
+        .first()
+        .map(|t| t.partitions().iter().map(|p| p.id()).collect())
+        .unwrap_or_default();
+    if partitions.is_empty() {
+        return Err(DiffError::invalid(format!(
+            "Kafka topic '{}' not found or has no partitions",
+            topic
+        ))
+        .into());
+    }
+
+    // First offset at or after `at` per partition; `None` if there is none yet
+    let offsets_at = |at: i64| -> Result<HashMap<i32, Option<i64>>> {
+        let mut query = TopicPartitionList::new();
+        for &p in &partitions {
+            query
+                .add_partition_offset(topic, p, Offset::Offset(at))
+                .map_err(kafka_err)?;
+        }
+        let found = consumer
+            .offsets_for_times(query, TIMEOUT)
+            .map_err(kafka_err)?;
+        Ok(found
+            .elements()
+            .iter()
+            .map(|e| (e.partition(), e.offset().to_raw().filter(|o| *o >= 0)))
+            .collect())
+    };
+    let starts = read.kafka_from.map(offsets_at).transpose()?;
+    let ends = read.kafka_to.map(offsets_at).transpose()?;
+
+    let mut assignment = TopicPartitionList::new();
+    let mut end_offsets: HashMap<i32, i64> = HashMap::new();
+    for &p in &partitions {
+        let (low, high) = consumer
+            .fetch_watermarks(topic, p, TIMEOUT)
+            .map_err(kafka_err)?;
+        let start = match &starts {
+            Some(starts) => starts.get(&p).copied().flatten().unwrap_or(high),
+            None => low,
+        };
+        let end = match &ends {
+            Some(ends) => ends.get(&p).copied().flatten().unwrap_or(high),
+            None => high,
+        };
+        if start < end {
+            assignment
+                .add_partition_offset(topic, p, Offset::Offset(start))
+                .map_err(kafka_err)?;
+            end_offsets.insert(p, end);
+        }
+    }
+    consumer.assign(&assignment).map_err(kafka_err)?;
+
+    let mut latest: IndexMap<String, Option<serde_json::Value>> = IndexMap::new();
+    while !end_offsets.is_empty() {
+        let Some(message) = consumer.poll(TIMEOUT) else {
+            return Err(
+                DiffError::timeout(format!("Timed out reading Kafka topic '{}'", topic)).into(),
+            );
+        };
+        let message = message.map_err(kafka_err)?;
+        let partition = message.partition();
+        let Some(&end) = end_offsets.get(&partition) else {
+            continue;
+        };
+        if message.offset() >= end {
+            end_offsets.remove(&partition);
+            continue;
+        }
+        if message.offset() + 1 >= end {
+            end_offsets.remove(&partition);
+        }
+
+        let message_key = message
+            .key()
+            .map(|k| String::from_utf8_lossy(k).into_owned());
+        let value = match message.payload() {
+            Some(payload) => {
+                let mut value: serde_json::Value =
+                    serde_json::from_slice(payload).map_err(|e| {
+                        DiffError::invalid(format!(
+                            "Non-JSON payload at {}/{}@{}: {}",
+                            topic,
+                            partition,
+                            message.offset(),
+                            e
+                        ))
+                    })?;
+                if read.kafka_key_field.is_none() {
+                    if let Some(object) = value.as_object_mut() {
+                        object.insert(
+                            KEY_COLUMN.to_string(),
+                            message_key
+                                .clone()
+                                .map_or(serde_json::Value::Null, Into::into),
+                        );
+                    }
+                }
+                Some(value)
+            }
+            None => None,
+        };
+        let row_key = match (&read.kafka_key_field, &value) {
+            (Some(field), Some(value)) => value.get(field).map(|v| v.to_string()),
+            _ => message_key,
+        };
+        match row_key {
+            Some(row_key) => {
+                latest.insert(row_key, value);
+            }
+            // Unkeyed messages can't be superseded; keep every one of them
+            None => {
+                latest.insert(format!("{}@{}", partition, message.offset()), value);
+            }
+        }
+    }
+
+    let rows: Vec<serde_json::Value> = latest.into_values().flatten().collect();
+    crate::records::rows_to_frame(&rows).map(|df| df.lazy())
+}
+
+#[cfg(feature = "kafka")]
+fn kafka_err(e: rdkafka::error::KafkaError) -> DiffError {
+    DiffError::from(std::io::Error::other(e)).in_stage("reading from Kafka")
+}
+
+#[cfg(not(feature = "kafka"))]
+pub fn scan(target: &str, _read: &ReadOptions) -> Result<LazyFrame> {
+    Err(DiffError::invalid(format!(
+        "Cannot read kafka://{}: koala-diff was built without the 'kafka' feature",
+        target
+    )))
+}
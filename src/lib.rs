@@ -1,124 +1,494 @@
 // koala-diff/src/lib.rs
 // The Rust core for fast data diffing
 
+// Without `python` only the C ABI, Node, R and wasm entry points are built, which
+// reach a part of the engine
+#![cfg_attr(not(feature = "python"), allow(dead_code, unused_imports))]
+
+mod aggregates;
+mod allowlist;
+mod append;
+mod bands;
+mod budget;
+mod cdc;
+mod cells;
+mod cloud;
+mod compare;
+#[cfg(feature = "python")]
+mod convert;
+mod coverage;
+mod csv;
+mod database;
+mod decrypt;
+mod delta;
+mod diagnose;
+mod dictionary;
+#[cfg(feature = "python")]
+mod drill;
+mod dtypes;
+mod encrypt;
+mod error;
+mod estimate;
+mod excel;
+#[cfg(feature = "python")]
+mod explore;
+mod export;
+mod ffi;
+mod fixed_width;
+#[cfg(feature = "python")]
+mod frames;
+mod fx;
+mod gate;
+mod groups;
+mod hierarchy;
+mod history;
+mod http;
+mod iceberg;
+mod intervals;
+mod kafka;
+mod manifest;
+mod merge;
+mod multiset;
+mod mutate;
+#[cfg(feature = "node")]
+mod node;
+mod options;
+mod orc;
+mod order;
+mod parallel;
+mod parquet_stats;
+mod partition;
+mod planner;
+mod privacy;
+mod profile;
+#[cfg(feature = "r")]
+mod r;
+mod reader;
+mod receipt;
+mod records;
+mod regression;
+mod render;
+mod result;
+mod samples;
+mod schema;
+mod selfcheck;
+mod sensitive;
+mod sequence;
+mod shards;
+mod signing;
+mod subsets;
+mod threeway;
+mod warnings;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+use indexmap::IndexMap;
 use polars::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyDict;
+#[cfg(feature = "python")]
 use pyo3::wrap_pyfunction;
-// No longer needed
 
-/// Compares two CSV or Parquet files and returns a difference summary
+use crate::append::AppendWindow;
+use crate::budget::Budget;
+use crate::error::{Context, DiffError, Result};
+use crate::manifest::Timings;
+use crate::options::{DiffOptions, ReadOptions};
+use crate::planner::Plan;
+use crate::reader::Input;
+use crate::result::{ColumnStats, DiffResult, RowLevelOutput, Warning};
+
+/// Compares two CSV, Parquet, ORC, JSON, NDJSON (`.jsonl` / `.ndjson`), Avro
+/// (`.avro`) or Excel (`.xlsx`) files and returns a difference summary
 ///
 /// Args:
-///     file_a (str): Path to first file
-///     file_b (str): Path to second file
+///     file_a (str): Path to first file, or an `s3://bucket/key`,
+///         `gs://bucket/key` or `az://container/key` / `abfss://` URI
+///         (Parquet, CSV or NDJSON; credentials from the AWS environment or
+///         profile, Google Application Default Credentials, or the `AZURE_*`
+///         environment / `az login` / managed identity), or an `https://`
+///         URL (downloaded first, any format; see `http_headers`), or a
+///         `postgresql://` / `mysql://` connection string or SQLite file
+///         (`.sqlite`, `.sqlite3`, `.db`; see `query` / `table`; needs the
+///         `database` build feature)
+///     file_b (str): Path to second file, or an `s3://` / `gs://` / `az://` URI,
+///         `https://` URL, database connection string or SQLite file
 ///     key_cols (list[str]): Columns to join on
+///     **options: Comparison options
+///         inf_equal (bool): Treat `inf == inf` as a match (default True)
+///         inf_match_threshold (float): Finite values at or beyond this
+///             magnitude match an infinity of the same sign
+///         flush_subnormals (bool): Treat subnormal floats as zero
+///         abs_tol (float): Numeric values at most this far apart match
+///         tolerances (dict[str, float]): Absolute tolerance per column (e.g.
+///             {"price": 0.01}), overriding `abs_tol`; differences within it
+///             count neither as mismatches nor towards "max_value_diff"
+///         fx_rates (str): FX rate table (CSV, Parquet, ...) with "currency"
+///             and "rate" columns, the value of one unit in a common currency
+///         currency_columns (dict[str, str]): Monetary column -> column holding
+///             its currency code; with `fx_rates`, values are converted to the
+///             common currency before comparison (use `abs_tol` / `tolerances`
+///             for rounding); unknown currencies give nulls
+///         rel_tol (float): Numeric values within this fraction of the larger
+///             magnitude match (with `abs_tol`: either one is enough, as in
+///             `math.isclose`)
+///         profile (bool): Also profile each file independently (approximate
+///             distinct counts, uniqueness ratio and entropy per column and
+///             side; default: when the inputs are estimated under 1 GiB)
+///         drift_drop_threshold (float): Relative uniqueness/entropy drop from
+///             A to B flagged in profile mode (default 0.5)
+///         check_order (bool): Compare the row order of matched keys and
+///             report it under "row_order"
+///         append_key (str): Treat both files as append-only logs ordered by
+///             this column; only the overlapping window is diffed and the
+///             result gains an "append_log" section
+///         record_schema (str): Avro schema (.avsc) or protobuf descriptor set
+///             used to decode a binary record file (suffix `_a` / `_b` to set
+///             it for one file only)
+///         record_message (str): Protobuf message name for `record_schema`
+///         limit (int): Read at most this many rows per file (`limit_a` /
+///             `limit_b` for one file)
+///         offset (int): Skip this many leading rows per file
+///         unload_manifest (bool): The file is a warehouse unload manifest
+///             (Snowflake/Redshift MANIFEST, Snowflake DETAILED_OUTPUT rows or
+///             a BigQuery extract job); its shards are checked and stitched
+///         kafka_from / kafka_to (int): Epoch-ms bounds for `kafka://brokers/topic`
+///             inputs, read as the latest JSON payload per key
+///         kafka_key_field (str): Payload field keying a Kafka input's rows
+///             (default: the message key, as a `kafka_key` column)
+///         query (str) / table (str): What a database input compares: the
+///             result of a SQL query, or a whole table (`query_a` / `table_b`
+///             etc. for one side, e.g. a Postgres table against its Parquet
+///             export)
+///         debezium (bool): The file holds Debezium change events (NDJSON,
+///             JSON array, Avro or `record_schema` records); it is diffed as
+///             the latest state per key, deletes applied
+///         sheet_name (str) / sheet_index (int): Sheet of an Excel (`.xlsx`)
+///             input (default the first; `sheet_name_a` etc. for one file)
+///         header_row (int): Excel inputs: 1-based row holding the column
+///             names (default the first row of the table)
+///         cell_range (str): Excel inputs: read the table from this A1-style
+///             range only, e.g. "B3:H200"
+///         csv_options (dict): How CSV inputs are parsed: "separator"
+///             (e.g. ";"), "quote_char" ("" for none), "has_header" (False
+///             names the columns column_1, ...), "column_names" (a list
+///             naming every column, for headerless extracts: {"has_header":
+///             False, "column_names": ["acct", "amount", ...]}), "skip_rows"
+///             (preamble lines before the header) and "comment_char" (e.g.
+///             "#"); Polars' defaults for the keys left out (`csv_options_a`
+///             etc. for one file)
+///         dtypes (dict[str, str]): Column types overriding the ones inferred,
+///             e.g. {"account_id": "str"} so zero-padded ids read from CSV
+///             aren't parsed as integers ("str", "int64", "int32", "float64",
+///             "bool", "date", "datetime", "decimal(18, 2)", ...). CSV
+///             columns are parsed as that type; other formats are cast, values
+///             that don't fit becoming null (`dtypes_a` for one file)
+///         fixed_width (list[tuple[str, int, int]]): The file is a
+///             fixed-width flat file: one (name, start, width) per column,
+///             start 0-based, e.g. [("acct", 0, 10), ("amount", 10, 12)].
+///             Fields are trimmed, and typed as integers or numbers when all
+///             their values are (`fixed_width_a` to lay out one file)
+///         encoding (str): Character encoding of CSV and fixed-width inputs
+///             ("latin-1", "windows-1252", "shift_jis", ...; default UTF-8),
+///             or "auto" to detect it. Non-UTF-8 files are decoded before
+///             reading; the detected encoding and any undecodable bytes are
+///             reported in the result's "warnings"
+///         mmap (bool): Local Parquet inputs are memory-mapped (True, the
+///             default): repeat diffs of the same files are served from the
+///             OS page cache. False reads them into memory instead, for
+///             network filesystems where a file changing underneath a mapping
+///             would crash the process
+///         parquet_footer_key (str) / parquet_column_keys (dict[str, str]):
+///             Keys of Parquet files encrypted with Parquet modular
+///             encryption, hex-encoded AES keys (16, 24 or 32 bytes): the
+///             footer key, and the keys of columns encrypted with their own,
+///             by column path. The file is decrypted in memory; keys are
+///             never written to manifests (`parquet_footer_key_a` etc. for
+///             one file)
+///         sse_c_key (str): `s3://` inputs stored with SSE-C: the
+///             base64-encoded 256-bit customer key, sent with each request;
+///             never written to manifests
+///         delta_version (int) / delta_timestamp (str): Delta table inputs (a
+///             directory with a `_delta_log`): read that version, or the last
+///             one committed by that time ("2024-05-01 12:00:00", RFC 3339 or
+///             a date), instead of the latest. Use `delta_version_a=N,
+///             delta_version_b=N + 1` to diff two versions of one table
+///         iceberg_snapshot_id (int): Iceberg table inputs (a table directory
+///             or its `*.metadata.json`): read that snapshot instead of the
+///             current one
+///         http_headers (dict[str, str]): Request headers for `https://`
+///             inputs, e.g. {"Authorization": "Bearer ..."}; never written to
+///             manifests
+///         column_tags (dict[str, dict[str, str]]): Metadata per column (owner,
+///             domain, criticality, ...) echoed into its stats as "tags"
+///         max_sample_value_len (int): Truncate sample values longer than this
+///             (middle ellipsis, original length appended)
+///         change_patterns (int): Group each column's changed cells by their
+///             "old -> new" values and report this many most frequent
+///             patterns with row counts under "change_patterns" in its stats
+///         diagnose (bool): Look for likely root causes of mismatches and
+///             report them under "findings" (default True): swapped columns,
+///             rounding, constant offsets/factors, truncated strings, mojibake
+///         group_by (str): Report per-group mismatch rates per column under
+///             "group_mismatch" (value taken from file A for non-key columns)
+///         sensitive_columns (list[str]): Compare these columns by salted hash;
+///             match rates stay exact but no values appear in samples
+///         hash_salt (str): Salt for `sensitive_columns` (random per run by default)
+///         key_allowlist (str): File (CSV, Parquet, ...) of the keys to compare;
+///             rows with other keys are filtered out while reading
+///         manifest (bool): Attach a "manifest" with the resolved options,
+///             input fingerprints (size, mtime, SHA-256), versions and timings
+///         parquet_stats (bool): For two Parquet files, also compare their footer
+///             statistics (row counts, null counts, min/max per column) and
+///             report them under "parquet_stats"
+///         line_keys (list[str]): Hierarchical keys: rows are matched on
+///             `key_cols` + `line_keys` (e.g. order_id + line_no) and the
+///             result gains a header-level rollup under "hierarchy"
+///         column_parallelism (int): Split the column statistics into this many
+///             passes run concurrently (default 1, a single pass)
+///         critical_columns (list[str]): Columns compared first and listed
+///             first in "column_stats"
+///         output_path (str): Write the complete row-level diff here (CSV for
+///             a `.csv` path, Parquet otherwise): the key columns, "status"
+///             (added / removed / modified), "column", "old_value" and
+///             "new_value", one row per added/removed row and changed cell
+///         max_detail_rows (int | None): Largest row-level diff `output_path`
+///             gets (default 10,000,000). A larger one isn't written: the
+///             compared columns get their top "change_patterns" instead and
+///             "row_level_output" is flagged "summarized". None: no limit
+///         streaming (bool): Run every pass on Polars' streaming engine so
+///             inputs larger than memory don't have to fit in RAM (default:
+///             on when the estimated size of both inputs exceeds half the
+///             available memory). Plain `.json` arrays are still read whole;
+///             use NDJSON or Parquet for large inputs
+///         join_strategy (str): "hash" or "merge" (both sides sorted by the
+///             keys first). Default: merge when streaming Parquet inputs whose
+///             footers show them already sorted by the keys, hash otherwise
+///         partitions (int): Split the key space into this many hash
+///             partitions, each joined and compared on its own so only one
+///             partition's join is in memory at a time (1: a single pass).
+///             Each partition reads both inputs again, so n partitions cost
+///             n scans of each file. Default: chosen by the planner, as few
+///             as fit, when the estimated peak memory exceeds half the
+///             available memory
+///         content_hash (bool): Attach "content_hash_a" / "content_hash_b" even
+///             when the files differ (identical files always get them)
+///         timeout_seconds (float): Time budget of the run. The row-count,
+///             statistics and sample passes are cut off when it runs out, and
+///             later phases (diagnose, profile, coverage, ...) aren't started;
+///             the result computed so far is returned with "timed_out" True
+///             and "timed_out_phase" naming where it stopped
+///         comparison_groups (dict[str, dict]): Named column subsets, e.g.
+///             {"financials": {"columns": [...], "abs_tol": 0.01}}, each
+///             compared with its own `abs_tol` / `rel_tol` / `inf_equal` /
+///             `inf_match_threshold` / `flush_subnormals` in the same pass and
+///             summarized under "comparison_groups"
+///         run_id (str) / run_at (str): The run's id and start time (RFC 3339)
+///             instead of a random id and the current time, e.g. for golden
+///             files: reports and manifests then come out byte-identical
+///             (manifest phase timings aside)
+///         schema_history (str): Path of a JSON store of each dataset's last
+///             seen schema (created if missing): both inputs are checked for
+///             drift against it, independently of each other, and recorded.
+///             Drift is reported in "schema_drift" and the "warnings"
+///         dataset (str): Names the file's dataset in the schema history
+///             and the data dictionary (default its path; `dataset_a` /
+///             `dataset_b` for one file)
+///         data_dictionary (str): Path of a data dictionary (CSV, Parquet,
+///             ...) with a "column" column and any of "timezone" (IANA name:
+///             naive timestamps are local times there; all are compared in
+///             UTC), "unit" ("cents", "bps", "ms", "thousands", ...: numbers
+///             are compared in the base unit), "locale" ("de_DE": numbers
+///             stored as text that way are parsed) and "sensitivity"
+///             ("confidential", "restricted", "secret", "sensitive" and "pii"
+///             columns are compared like `sensitive_columns`). A "dataset"
+///             column scopes a row to one dataset, replacing the column's
+///             general row there, e.g. amounts in cents in one system only
+///         aggregate_checks (dict[str, dict[str, float]]): Aggregate assertions
+///             per column, e.g. {"amount": {"sum": 0.01}, "customer_id":
+///             {"count_distinct": 0}}: each aggregate (sum, mean, min, max,
+///             count, null_count, count_distinct) is taken over each whole
+///             file and must agree within the absolute tolerance. Reported
+///             under "aggregate_checks"
+///         bands (dict[str, dict[str, float]]): Tolerance bands per numeric
+///             column, e.g. {"amount": {"green": 0.01, "amber": 1.0}}: matched
+///             rows differing by less than green are green, by less than
+///             amber amber, the rest red; counted per column under
+///             "band_counts"
 ///
 /// Returns:
 ///     dict: {
 ///         "total_rows_a": int,
 ///         "total_rows_b": int,
-///         "matched": int,
+///         "joined_count": int,
+///         "identical_rows_count": int,
+///         "modified_rows_count": int,
 ///         "added": int,
 ///         "removed": int,
-///         "modified_cols": list[str],
-///         "schema_diff": list[dict],  // New!
-///         "null_counts": dict,        // New! { "col_name": [nulls_in_a, nulls_in_b] }
+///         "identical": bool,
+///         "timed_out": bool,          // `timeout_seconds` ran out; see "timed_out_phase"
+///         "run_id": str,              // random unless set with `run_id`
+///         "run_at": str,              // RFC 3339 start time, or `run_at`
+///         "column_stats": dict,       // per column: counts, match_rate, samples, ...
+///         "schema_diff": list[dict],  // {column, change, from, to, lossy}
+///         "schema_compatibility": dict,
+///         "schema_fingerprint_a": str,  // SHA-256 of column names and dtypes
+///         "schema_fingerprint_b": str,
+///         "coverage": dict,
+///         "plan": dict,               // engine, join, profile and the reasons for each
+///         "warnings": list[dict],     // {kind, message, file, column, count}: lossy
+///                                     // casts, duplicate keys, filtered rows, ...
+///         ...                         // optional sections enabled by the options
 ///     }
+#[cfg(feature = "python")]
 #[pyfunction]
+#[pyo3(signature = (file_a, file_b, _key_cols, **options))]
 fn diff_files<'py>(
     py: Python<'py>,
     file_a: String,
     file_b: String,
     _key_cols: Vec<String>,
-) -> PyResult<Bound<'py, PyDict>> {
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let opts = DiffOptions::from_kwargs(options)?;
+    let result = run_diff(
+        [Input::Path(file_a), Input::Path(file_b)],
+        _key_cols,
+        opts,
+        Some(py.version().to_string()),
+    )?;
+    Ok(pythonize::pythonize(py, &result)?)
+}
+
+/// The diff behind `diff_files` and the C ABI (`ffi`). Needs no Python:
+/// `python_version` only goes into the manifest.
+fn run_diff(
+    [input_a, input_b]: [Input; 2],
+    _key_cols: Vec<String>,
+    opts: DiffOptions,
+    python_version: Option<String>,
+) -> Result<DiffResult> {
+    let mut timings = Timings::start(&opts);
+    let (label_a, label_b) = (input_a.label(), input_b.label());
+    let (file_a, file_b) = (label_a.as_ref(), label_b.as_ref());
+    let mut budget = Budget::start(opts.timeout_seconds);
+    warnings::take();
+
+    // Hierarchical keys: rows are matched on header + line keys, the
+    // allowlist still applies to the header keys
+    let header_keys = _key_cols.clone();
+    let _key_cols: Vec<String> = _key_cols
+        .into_iter()
+        .chain(opts.line_keys.iter().cloned())
+        .collect();
+
     // 1. Read files lazily using Polars
-    let scan_df = |path: &str| -> PyResult<LazyFrame> {
-        if path.ends_with(".parquet") || path.ends_with(".pq") {
-            LazyFrame::scan_parquet(path.into(), Default::default())
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
-        } else if path.ends_with(".jsonl") || path.ends_with(".ndjson") {
-            LazyJsonLineReader::new(path.into())
-                .finish()
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
-        } else if path.ends_with(".json") {
-            // Standard JSON doesn't have a native lazy scanner in Polars
-            let df = JsonReader::new(
-                std::fs::File::open(path)
-                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?,
-            )
-            .finish()
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
-            Ok(df.lazy())
-        } else {
-            LazyCsvReader::new(path.into())
-                .finish()
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))
+    let dataset_a = opts.read_a.dataset.as_deref().unwrap_or(file_a);
+    let dataset_b = opts.read_b.dataset.as_deref().unwrap_or(file_b);
+    let scanned_a = input_a.scan(&opts.read_a, &_key_cols)?;
+    let scanned_b = input_b.scan(&opts.read_b, &_key_cols)?;
+    let scanned_a = dictionary::normalize(scanned_a, &opts, dataset_a)?;
+    let scanned_b = dictionary::normalize(scanned_b, &opts, dataset_b)?;
+    let mut lf_a = allowlist::apply(scanned_a.clone(), &header_keys, &opts)?;
+    let mut lf_b = allowlist::apply(scanned_b.clone(), &header_keys, &opts)?;
+    if opts.key_allowlist.is_some() {
+        warnings::dropped_rows(&scanned_a, &lf_a, file_a, "the key allowlist")?;
+        warnings::dropped_rows(&scanned_b, &lf_b, file_b, "the key allowlist")?;
+    }
+
+    // Inputs larger than memory: the streaming flag carries over to every
+    // frame derived from these, so all passes below run out-of-core
+    let plan = Plan::decide(file_a, file_b, &_key_cols, &opts);
+    if plan.streaming() {
+        lf_a = lf_a.with_new_streaming(true);
+        lf_b = lf_b.with_new_streaming(true);
+    }
+
+    // Append-only logs: only the window both files cover is compared
+    let append_window = match &opts.append_key {
+        Some(key) => {
+            let window = AppendWindow::resolve(&lf_a, &lf_b, key)?;
+            lf_a = window.restrict(lf_a, key);
+            lf_b = window.restrict(lf_b, key);
+            Some(window)
         }
+        None => None,
     };
 
-    let mut lf_a = scan_df(&file_a)?;
-    let mut lf_b = scan_df(&file_b)?;
+    // Content hashes are taken from the real values, not the per-run salted
+    // or currency-converted ones
+    let (raw_a, raw_b) = (lf_a.clone(), lf_b.clone());
+    lf_a = fx::convert(lf_a, &opts)?;
+    lf_b = fx::convert(lf_b, &opts)?;
 
     // Get schemas for analysis
-    let schema_a = lf_a
-        .collect_schema()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
-    let schema_b = lf_b
-        .collect_schema()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+    let schema_a = lf_a.collect_schema().stage("reading").file(file_a)?;
+    let schema_b = lf_b.collect_schema().stage("reading").file(file_b)?;
+    // Hash sensitive columns only now, so the schema report shows their real types
+    lf_a = sensitive::mask(lf_a, &opts)?;
+    lf_b = sensitive::mask(lf_b, &opts)?;
+    timings.lap("scan");
 
     // 2. Core Diffing Logic using Joins
-    let keys: Vec<Expr> = _key_cols.iter().map(|s| col(s.as_str())).collect();
     let keys_strs: Vec<&str> = _key_cols.iter().map(|s| s.as_str()).collect();
 
     // 2.2 Perform the Join (Lazy)
-    let joined_lf = lf_a.clone().join(
-        lf_b.clone(),
-        keys.clone(),
-        keys.clone(),
-        JoinArgs::new(JoinType::Inner).with_suffix(Some("_right".into())),
-    );
+    let (join_a, join_b) = plan.join_inputs(lf_a.clone(), lf_b.clone(), &_key_cols)?;
+    let joined_lf = partition::join(join_a.clone(), join_b.clone(), &_key_cols);
 
     // 2.2 Pre-Calculation: Height and Uniqueness (Small passes)
-    // We don't use streaming here because these are lightweight and streaming adds overhead for small files
-    let get_meta = |lf: LazyFrame, name: &str, key: &str| -> PyResult<(usize, usize)> {
-        let res = lf
-            .select([len().alias("total"), col(key).n_unique().alias("unique")])
-            .collect()
-            .map_err(|e| {
-                PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
-                    "Error reading {}: {}",
-                    name, e
-                ))
-            })?;
-
-        let total = res
-            .column("total")
-            .unwrap()
-            .get(0)
-            .unwrap()
-            .try_extract::<u32>()
-            .unwrap_or(0) as usize;
-        let unique = res
-            .column("unique")
-            .unwrap()
-            .get(0)
-            .unwrap()
-            .try_extract::<u32>()
-            .unwrap_or(0) as usize;
+    // Not forced to stream: these are lightweight and streaming adds overhead
+    // for small files (large inputs stream anyway, see above)
+    let mut get_meta =
+        |lf: LazyFrame, name: &str, path: &str, key: &str| -> Result<Option<(usize, usize)>> {
+            let counts = lf.select([len().alias("total"), col(key).n_unique().alias("unique")]);
+            let Some(res) = budget.run("key_check", move || counts.collect())? else {
+                return Ok(None);
+            };
+            let res = res.stage("reading").file(path).column(key)?;
 
-        if unique < total && total > 0 {
-            println!(
-                "⚠️ WARNING: Join keys are not unique in {} ({} unique / {} total).",
-                name, unique, total
-            );
-        }
-        Ok((total, unique))
-    };
+            let total = res
+                .column("total")
+                .unwrap()
+                .get(0)
+                .unwrap()
+                .try_extract::<u32>()
+                .unwrap_or(0) as usize;
+            let unique = res
+                .column("unique")
+                .unwrap()
+                .get(0)
+                .unwrap()
+                .try_extract::<u32>()
+                .unwrap_or(0) as usize;
 
-    let (height_a, unique_a) = get_meta(lf_a.clone(), "File A", keys_strs[0])?;
-    let (height_b, unique_b) = get_meta(lf_b.clone(), "File B", keys_strs[0])?;
+            if unique < total && total > 0 {
+                warnings::raise(
+                    Warning::new(
+                        "duplicate_keys",
+                        format!(
+                            "Join keys are not unique in {} ({} unique / {} total).",
+                            name, unique, total
+                        ),
+                    )
+                    .in_file(path)
+                    .in_column(key)
+                    .count(total - unique),
+                );
+            }
+            Ok(Some((total, unique)))
+        };
+
+    let meta_a = get_meta(lf_a.clone(), "File A", file_a, keys_strs[0])?;
+    let meta_b = get_meta(lf_b.clone(), "File B", file_b, keys_strs[0])?;
+    let (Some((height_a, unique_a)), Some((height_b, unique_b))) = (meta_a, meta_b) else {
+        let mut result = timed_out_result(&budget, &plan, &schema_a, &schema_b, None);
+        result.warnings = warnings::take();
+        timings.stamp(&mut result);
+        return Ok(result);
+    };
 
     // 2.2.1 Join Safety Guard (Cartesian Product Estimation)
     // If keys are not unique, the worst case join size is (non-unique_a * non-unique_b)
@@ -137,33 +507,70 @@ fn diff_files<'py>(
                 Please refine your 'key_columns' to be more unique.",
                 dups_a, dups_b
             );
-            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(msg));
+            return Err(DiffError::from(PolarsError::ComputeError(msg.into())));
         }
     }
 
+    timings.lap("key_check");
+
     // 2.3 Core Statistics Calculation
 
-    // 2.3.1 Build Statistics Query
-    let mut aggs = Vec::new();
-    aggs.push(len().alias("_total_matched"));
+    // 2.3.1 Build Statistics Query (critical columns first)
+    let column_order = parallel::critical_first(&schema_a, &opts.critical_columns);
+    let mut global_aggs = vec![len().alias("_total_matched")];
+    let mut column_aggs = Vec::new();
     let mut total_modified_mask: Option<Expr> = None;
+    let mut diff_flags = Vec::new();
+    let mut compared_columns = Vec::new();
 
-    for (col_name, dtype_a) in schema_a.iter() {
+    for &(col_name, dtype_a) in &column_order {
         let name_str = col_name.as_str();
         if keys_strs.contains(&name_str) {
             continue;
         }
-        if schema_b.contains(name_str) {
+        if let Some(dtype_b) = schema_b
+            .get(name_str)
+            .filter(|dtype_b| schema::comparable(dtype_a, dtype_b))
+        {
+            let mut aggs = Vec::new();
             let right_name = format!("{}_right", name_str);
-            let dtype_b = schema_b.get(name_str).unwrap();
-            let is_diff_expr = col(name_str).eq_missing(col(&right_name)).not();
+            let is_diff_expr = compare::diff_expr(name_str, &right_name, dtype_a, dtype_b, &opts);
+            diff_flags.push(is_diff_expr.clone().alias(format!("{}_is_diff", name_str)));
+            compared_columns.push(name_str.to_string());
             aggs.push(
                 is_diff_expr
                     .clone()
                     .cast(DataType::Float64)
                     .sum()
-                    .alias(&format!("{}_diff_count", name_str)),
+                    .alias(format!("{}_diff_count", name_str)),
             );
+            // Values the Float64 comparison can't represent exactly, and the
+            // matches among them, which may hide a difference the cast rounded away
+            if compare::compares_as_float(name_str, dtype_a, dtype_b, &opts) {
+                let lossy = [
+                    compare::lossy_cast_expr(name_str, dtype_a),
+                    compare::lossy_cast_expr(&right_name, dtype_b),
+                ]
+                .into_iter()
+                .flatten()
+                .reduce(|a, b| a.or(b));
+                if let Some(lossy) = lossy {
+                    aggs.push(
+                        lossy
+                            .clone()
+                            .cast(DataType::Float64)
+                            .sum()
+                            .alias(format!("{}_lossy", name_str)),
+                    );
+                    aggs.push(
+                        lossy
+                            .and(is_diff_expr.clone().not())
+                            .cast(DataType::Float64)
+                            .sum()
+                            .alias(format!("{}_lossy_match", name_str)),
+                    );
+                }
+            }
             total_modified_mask = match total_modified_mask {
                 Some(m) => Some(m.or(is_diff_expr)),
                 None => Some(is_diff_expr),
@@ -173,28 +580,63 @@ fn diff_files<'py>(
                     .is_null()
                     .cast(DataType::Int32)
                     .sum()
-                    .alias(&format!("{}_null_a", name_str)),
+                    .alias(format!("{}_null_a", name_str)),
             );
             aggs.push(
                 col(&right_name)
                     .is_null()
                     .cast(DataType::Int32)
                     .sum()
-                    .alias(&format!("{}_null_b", name_str)),
+                    .alias(format!("{}_null_b", name_str)),
             );
-            if dtype_a.is_numeric() && dtype_b.is_numeric() {
+            let is_sensitive = opts.sensitive_columns.iter().any(|c| c == name_str);
+            if dtype_a.is_numeric() && dtype_b.is_numeric() && !is_sensitive {
                 let diff_expr = col(name_str).cast(DataType::Float64)
                     - col(&right_name).cast(DataType::Float64);
                 let abs_diff = when(diff_expr.clone().gt(0.0))
                     .then(diff_expr.clone())
                     .otherwise(diff_expr * lit(-1.0));
-                aggs.push(abs_diff.max().alias(&format!("{}_max_diff", name_str)));
+                // Differences within a column's own tolerance don't count
+                let abs_diff = match opts.tolerances.get(name_str) {
+                    Some(&tol) => when(abs_diff.clone().gt(lit(tol)))
+                        .then(abs_diff)
+                        .otherwise(lit(0.0)),
+                    None => abs_diff,
+                };
+                aggs.push(abs_diff.max().alias(format!("{}_max_diff", name_str)));
             }
+            if dtype_a.is_float() && !is_sensitive {
+                aggs.push(compare::inf_count_expr(name_str).alias(format!("{}_inf_a", name_str)));
+            }
+            if dtype_b.is_float() && !is_sensitive {
+                aggs.push(
+                    compare::inf_count_expr(&right_name).alias(format!("{}_inf_b", name_str)),
+                );
+            }
+            if let Some(thresholds) = opts.bands.get(name_str) {
+                if !(dtype_a.is_numeric() && dtype_b.is_numeric()) {
+                    return Err(DiffError::invalid(format!(
+                        "bands need a numeric column, got {:?} / {:?}",
+                        dtype_a, dtype_b
+                    ))
+                    .in_column(name_str));
+                }
+                aggs.extend(bands::aggs(name_str, &right_name, thresholds));
+            }
+            column_aggs.push(aggs);
         }
     }
 
+    global_aggs.extend(subsets::aggs(
+        &opts.comparison_groups,
+        &schema_a,
+        &schema_b,
+        &_key_cols,
+        &opts,
+    ));
+
     if let Some(mask) = &total_modified_mask {
-        aggs.push(
+        global_aggs.push(
             mask.clone()
                 .cast(DataType::Float64)
                 .sum()
@@ -202,13 +644,34 @@ fn diff_files<'py>(
         );
     }
 
-    // Run the main statistics pass (Streaming is only forced here for big data)
-    let stats_res = joined_lf
-        .clone()
-        .select(aggs)
-        .with_new_streaming(true)
-        .collect()
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+    // Run the main statistics pass (Streaming is only forced here for big
+    // data), in key-hash partitions when the join wouldn't fit in memory
+    let (parallelism, partitions) = (opts.column_parallelism, plan.partitions());
+    let stats_keys = _key_cols.clone();
+    let Some(stats_res) = budget.run("statistics", move || {
+        partition::collect_stats(
+            join_a,
+            join_b,
+            &stats_keys,
+            global_aggs,
+            column_aggs,
+            parallelism,
+            partitions,
+        )
+    })?
+    else {
+        let mut result = timed_out_result(
+            &budget,
+            &plan,
+            &schema_a,
+            &schema_b,
+            Some((height_a, height_b)),
+        );
+        result.warnings = warnings::take();
+        timings.stamp(&mut result);
+        return Ok(result);
+    };
+    let stats_res = stats_res?;
 
     let matched = stats_res
         .column("_total_matched")
@@ -229,42 +692,156 @@ fn diff_files<'py>(
         0
     };
 
+    timings.lap("statistics");
+
     let removed = height_a.saturating_sub(matched);
     let added = height_b.saturating_sub(matched);
     let identical_rows_count = matched.saturating_sub(modified_rows_count);
 
+    // 2.3.2 Root-cause heuristics over the columns that have mismatches
+    let findings = if opts.diagnose && budget.allows("diagnose") {
+        // Sensitive columns hold salted hashes by now (not the dtypes in
+        // `schema_a`), and findings would quote their values
+        let mismatched: Vec<diagnose::Mismatched> = schema_a
+            .iter()
+            .filter(|(name, _)| !opts.sensitive_columns.iter().any(|s| s == name.as_str()))
+            .filter_map(|(name, dtype_a)| {
+                let diff_count = stats_res
+                    .column(&format!("{}_diff_count", name))
+                    .ok()?
+                    .get(0)
+                    .ok()?
+                    .try_extract::<f64>()
+                    .ok()? as usize;
+                (diff_count > 0).then(|| diagnose::Mismatched {
+                    name: name.to_string(),
+                    dtype_a: dtype_a.clone(),
+                    dtype_b: schema_b.get(name.as_str()).unwrap().clone(),
+                    diff_count,
+                })
+            })
+            .collect();
+        let findings = diagnose::run(&joined_lf, &mismatched)?;
+        timings.lap("diagnose");
+        findings
+    } else {
+        Vec::new()
+    };
+
+    // 2.3.3 Optional per-group breakdown of the mismatch rates
+    let group_mismatch = match &opts.group_by {
+        Some(group_column) if budget.allows("group_mismatch") => Some(groups::mismatch_by_group(
+            joined_lf.clone().with_columns(diff_flags.clone()),
+            group_column,
+            &compared_columns,
+        )?),
+        _ => None,
+    };
+    if group_mismatch.is_some() {
+        timings.lap("group_mismatch");
+    }
+
+    // 2.3.4 Header-level rollup of the line-level diff
+    let hierarchy = if opts.line_keys.is_empty() || !budget.allows("hierarchy") {
+        None
+    } else {
+        let line_modified = total_modified_mask.clone().unwrap_or(lit(false));
+        let summary = hierarchy::summarize(
+            lf_a.clone(),
+            lf_b.clone(),
+            joined_lf
+                .clone()
+                .with_column(line_modified.alias("_line_modified")),
+            &header_keys,
+            &opts.line_keys,
+        )?;
+        timings.lap("hierarchy");
+        Some(summary)
+    };
+
+    // 2.3.5 Optional top "old -> new" patterns per column (never for sensitive columns)
+    let mut change_patterns = match opts.change_patterns {
+        Some(top) if top > 0 && budget.allows("change_patterns") => {
+            let columns: Vec<String> = compared_columns
+                .iter()
+                .filter(|c| !opts.sensitive_columns.contains(c))
+                .cloned()
+                .collect();
+            let patterns = samples::change_patterns(
+                &joined_lf.clone().with_columns(diff_flags.clone()),
+                &columns,
+                top,
+                opts.max_sample_value_len,
+            )?;
+            timings.lap("change_patterns");
+            patterns
+        }
+        _ => IndexMap::new(),
+    };
+
     // 2.4 Global Sample Pass (Fetch samples for ALL columns in one pass)
     let global_samples = if let Some(mask) = total_modified_mask {
-        joined_lf
+        let modified = joined_lf
             .clone()
             .filter(mask)
-            .limit(100) // Fetch up to 100 modified rows once
-            .collect()
-            .ok()
+            .with_columns(diff_flags.clone())
+            .limit(100); // Fetch up to 100 modified rows once
+        budget
+            .run("samples", move || modified.collect().ok())?
+            .flatten()
     } else {
         None
     };
 
-    // 2.5 Assemble Stats Dictionary
-    let column_stats = PyDict::new(py);
-    for (col_name, dtype_a) in schema_a.iter() {
+    timings.lap("samples");
+
+    // 2.4.1 Optional Profile Pass (each side on its own, full file)
+    let profiles = if plan.profile() && budget.allows("profile") {
+        Some((
+            profile::profile_side(lf_a.clone(), &schema_a, file_a)?,
+            profile::profile_side(lf_b.clone(), &schema_b, file_b)?,
+        ))
+    } else {
+        None
+    };
+
+    if profiles.is_some() {
+        timings.lap("profile");
+    }
+
+    // 2.5 Assemble Column Stats
+    let mut column_stats = IndexMap::new();
+    for &(col_name, dtype_a) in &column_order {
         let name_str = col_name.as_str();
         let is_key = keys_strs.contains(&name_str);
+        let is_sensitive = opts.sensitive_columns.iter().any(|c| c == name_str);
 
-        let stats = PyDict::new(py);
-        stats.set_item("column_name", name_str)?;
-        stats.set_item("is_key", is_key)?;
-        stats.set_item("source_dtype", format!("{:?}", dtype_a))?;
+        let mut stats = ColumnStats {
+            column_name: name_str.to_string(),
+            is_key,
+            source_dtype: format!("{:?}", dtype_a),
+            tags: opts.column_tags.get(name_str).cloned(),
+            ..Default::default()
+        };
+        if let Some((profile_a, profile_b)) = &profiles {
+            profile::fill_stats(&mut stats, name_str, profile_a, profile_b, &opts);
+        }
 
         if let Some(dtype_b) = schema_b.get(name_str) {
-            stats.set_item("target_dtype", format!("{:?}", dtype_b))?;
-            stats.set_item("total_count", matched)?;
+            stats.target_dtype = format!("{:?}", dtype_b);
+            if !is_key && !schema::comparable(dtype_a, dtype_b) {
+                stats.skip_reason = Some("incompatible_dtype".to_string());
+                column_stats.insert(name_str.to_string(), stats);
+                continue;
+            }
+            stats.compared = true;
+            stats.total_count = Some(matched);
 
             if is_key {
-                stats.set_item("match_count", matched)?;
-                stats.set_item("non_match_count", 0)?;
-                stats.set_item("match_rate", 100.0)?;
-                stats.set_item("all_match", true)?;
+                stats.match_count = Some(matched);
+                stats.non_match_count = Some(0);
+                stats.match_rate = Some(100.0);
+                stats.all_match = true;
             } else {
                 let diff_count = stats_res
                     .column(&format!("{}_diff_count", name_str))
@@ -273,22 +850,47 @@ fn diff_files<'py>(
                     .unwrap()
                     .try_extract::<f64>()
                     .unwrap_or(0.0) as usize;
+                let lossy_count = |suffix: &str| {
+                    stats_res
+                        .column(&format!("{}_{}", name_str, suffix))
+                        .ok()
+                        .map(|col| col.get(0).unwrap().try_extract::<f64>().unwrap_or(0.0) as usize)
+                };
+                stats.coercion_lossy_count = lossy_count("lossy");
+                stats.coercion_lossy_matches = lossy_count("lossy_match");
+                if let Some(lossy) = stats.coercion_lossy_count.filter(|&n| n > 0) {
+                    warnings::raise(
+                        Warning::new(
+                            "lossy_cast",
+                            format!(
+                                "{} values of '{}' don't convert exactly to Float64 \
+                                 ({} of them match, possibly only after rounding)",
+                                lossy,
+                                name_str,
+                                stats.coercion_lossy_matches.unwrap_or(0)
+                            ),
+                        )
+                        .in_column(name_str)
+                        .count(lossy),
+                    );
+                }
                 let match_count = matched.saturating_sub(diff_count);
+                let non_match_count = diff_count;
                 let match_rate = if matched > 0 {
                     (match_count as f64 / matched as f64) * 100.0
                 } else {
                     100.0
                 };
 
-                stats.set_item("match_count", match_count)?;
-                stats.set_item("non_match_count", diff_count)?;
-                stats.set_item("match_rate", match_rate)?;
-                stats.set_item("all_match", diff_count == 0)?;
+                stats.match_count = Some(match_count);
+                stats.non_match_count = Some(non_match_count);
+                stats.match_rate = Some(match_rate);
+                stats.all_match = diff_count == 0;
 
                 if dtype_a.is_numeric() && dtype_b.is_numeric() {
                     if let Ok(col) = stats_res.column(&format!("{}_max_diff", name_str)) {
                         let max_v = col.get(0).unwrap().try_extract::<f64>().unwrap_or(0.0);
-                        stats.set_item("max_value_diff", max_v)?;
+                        stats.max_value_diff = Some(max_v);
                     }
                 }
 
@@ -306,64 +908,346 @@ fn diff_files<'py>(
                     .unwrap()
                     .try_extract::<i32>()
                     .unwrap_or(0);
-                stats.set_item("null_count_diff", (n_b - n_a) as i64)?;
+                stats.null_count_diff = Some((n_b - n_a) as i64);
+
+                let inf_count = |side: &str| {
+                    stats_res
+                        .column(&format!("{}_inf_{}", name_str, side))
+                        .ok()
+                        .map(|col| col.get(0).unwrap().try_extract::<i32>().unwrap_or(0) as i64)
+                };
+                stats.inf_count_a = inf_count("a");
+                stats.inf_count_b = inf_count("b");
+                stats.change_patterns = change_patterns
+                    .swap_remove(name_str)
+                    .filter(|patterns| !patterns.is_empty());
+                stats.band_counts = bands::counts(&stats_res, name_str);
 
                 // Extract samples from biological sample buffer in memory
                 if diff_count > 0 {
                     if let Some(samples) = &global_samples {
                         let right_name = format!("{}_right", name_str);
-                        let sample_keys = pyo3::types::PyList::empty(py);
-                        let sample_values = pyo3::types::PyList::empty(py);
+                        let flag_name = format!("{}_is_diff", name_str);
+                        let mut sample_keys = Vec::new();
+                        let mut sample_values = Vec::new();
 
-                        let mut found_samples = 0;
                         for i in 0..samples.height() {
                             let val_a = samples.column(name_str).unwrap().get(i).unwrap();
                             let val_b = samples.column(&right_name).unwrap().get(i).unwrap();
+                            let is_diff = samples.column(&flag_name).unwrap().get(i).unwrap();
 
                             // Only include if THIS specific column differs in this row
-                            if val_a != val_b {
+                            if matches!(is_diff, AnyValue::Boolean(true)) {
                                 let mut key_map = String::new();
                                 for k in &keys_strs {
                                     let val = samples.column(k).unwrap().get(i).unwrap();
                                     key_map.push_str(&format!("{}: {} ", k, val));
                                 }
-                                sample_keys.append(key_map.trim())?;
-                                sample_values.append(format!("{} -> {}", val_a, val_b))?;
-                                found_samples += 1;
-                                if found_samples >= 5 {
+                                sample_keys.push(key_map.trim().to_string());
+                                // Sensitive values hold hashes; never expose them
+                                if !is_sensitive {
+                                    sample_values.push(format!(
+                                        "{} -> {}",
+                                        samples::display_value(&val_a, opts.max_sample_value_len),
+                                        samples::display_value(&val_b, opts.max_sample_value_len)
+                                    ));
+                                }
+                                if sample_keys.len() >= 5 {
                                     break;
                                 }
                             }
                         }
-                        stats.set_item("mismatched_sample_keys", sample_keys)?;
-                        stats.set_item("mismatched_value_samples", sample_values)?;
+                        stats.mismatched_sample_keys = Some(sample_keys);
+                        stats.mismatched_value_samples = (!is_sensitive).then_some(sample_values);
                     }
                 }
             }
         } else {
-            stats.set_item("target_dtype", "MISSING")?;
-            stats.set_item("all_match", false)?;
+            stats.target_dtype = "MISSING".to_string();
+            stats.skip_reason = Some("only_in_a".to_string());
+        }
+        column_stats.insert(name_str.to_string(), stats);
+    }
+    // Columns only in B get a stub entry too, after A's columns
+    for (name, dtype_b) in schema_b.iter() {
+        if schema_a.contains(name.as_str()) {
+            continue;
         }
-        column_stats.set_item(name_str, stats)?;
+        let stats = ColumnStats {
+            column_name: name.to_string(),
+            source_dtype: "MISSING".to_string(),
+            target_dtype: format!("{:?}", dtype_b),
+            tags: opts.column_tags.get(name.as_str()).cloned(),
+            skip_reason: Some("only_in_b".to_string()),
+            ..Default::default()
+        };
+        column_stats.insert(name.to_string(), stats);
     }
 
     // --- Final Assembly ---
-    let dict = pyo3::types::PyDict::new(py);
-    dict.set_item("total_rows_a", height_a)?;
-    dict.set_item("total_rows_b", height_b)?;
-    dict.set_item("joined_count", matched)?;
-    dict.set_item("identical_rows_count", identical_rows_count)?;
-    dict.set_item("modified_rows_count", modified_rows_count)?;
-    dict.set_item("added", added)?;
-    dict.set_item("removed", removed)?;
-    dict.set_item("column_stats", column_stats)?;
-
-    Ok(dict)
+    let mut result = DiffResult {
+        total_rows_a: height_a,
+        total_rows_b: height_b,
+        joined_count: matched,
+        identical_rows_count,
+        modified_rows_count,
+        added,
+        removed,
+        column_stats,
+        schema_diff: schema::schema_diff(&schema_a, &schema_b),
+        schema_compatibility: schema::compatibility_report(&schema_a, &schema_b),
+        schema_fingerprint_a: schema::fingerprint(&schema_a),
+        schema_fingerprint_b: schema::fingerprint(&schema_b),
+        group_mismatch,
+        comparison_groups: subsets::summaries(
+            &opts.comparison_groups,
+            &stats_res,
+            &schema_a,
+            &schema_b,
+            &_key_cols,
+            matched,
+        ),
+        hierarchy,
+        findings,
+        ..Default::default()
+    };
+
+    if let (Some(window), Some(key)) = (&append_window, &opts.append_key) {
+        result.append_log = Some(window.summary(key, removed, modified_rows_count));
+    }
+
+    result.identical = added == 0
+        && removed == 0
+        && modified_rows_count == 0
+        && result.schema_compatibility.changes.is_empty();
+
+    timings.lap("assemble");
+
+    if !opts.aggregate_checks.is_empty() && budget.allows("aggregate_checks") {
+        result.aggregate_checks = aggregates::evaluate(
+            &opts.aggregate_checks,
+            (lf_a.clone(), &schema_a, file_a),
+            (lf_b.clone(), &schema_b, file_b),
+        )?;
+        timings.lap("aggregate_checks");
+    }
+
+    if (result.identical || opts.content_hash) && budget.allows("content_hash") {
+        result.content_hash_a = Some(receipt::content_hash(
+            raw_a,
+            &_key_cols,
+            &compared_columns,
+            file_a,
+        )?);
+        result.content_hash_b = Some(receipt::content_hash(
+            raw_b,
+            &_key_cols,
+            &compared_columns,
+            file_b,
+        )?);
+        timings.lap("content_hash");
+    }
+
+    if budget.allows("coverage") {
+        let (appended, truncated) = append_window
+            .as_ref()
+            .map_or((0, 0), |w| (w.appended_rows, w.truncated_rows));
+        let suppressed_a = coverage::suppressed_rows(
+            &input_a,
+            &opts.read_a,
+            &_key_cols,
+            &opts,
+            height_a,
+            truncated,
+        )?;
+        let suppressed_b = coverage::suppressed_rows(
+            &input_b,
+            &opts.read_b,
+            &_key_cols,
+            &opts,
+            height_b,
+            appended,
+        )?;
+        result.coverage = coverage::measure(
+            &result,
+            &schema_a,
+            &schema_b,
+            &_key_cols,
+            suppressed_a,
+            suppressed_b,
+        );
+        timings.lap("coverage");
+    }
+
+    if opts.check_order && budget.allows("row_order") {
+        result.row_order = Some(order::compare_order(
+            lf_a.clone(),
+            lf_b.clone(),
+            &keys_strs,
+        )?);
+        timings.lap("row_order");
+    }
+
+    let is_parquet = |input: &Input, read: &ReadOptions| {
+        input.path().is_some_and(|path| {
+            (path.ends_with(".parquet") || path.ends_with(".pq"))
+                && read.record_schema.is_none()
+                && !read.unload_manifest
+                && !read.debezium
+                && read.parquet_footer_key.is_none()
+                && !cloud::is_remote(path)
+        })
+    };
+    if opts.parquet_stats
+        && is_parquet(&input_a, &opts.read_a)
+        && is_parquet(&input_b, &opts.read_b)
+        && budget.allows("parquet_stats")
+    {
+        result.parquet_stats = Some(parquet_stats::compare(file_a, file_b)?);
+        timings.lap("parquet_stats");
+    }
+
+    if let Some(path) = opts
+        .output_path
+        .as_ref()
+        .filter(|_| budget.allows("row_level_output"))
+    {
+        // Known from the statistics already: one row per added / removed
+        // row and per changed cell
+        let changed_cells: usize = result
+            .column_stats
+            .values()
+            .filter(|stats| stats.compared && !stats.is_key)
+            .map(|stats| matched - stats.match_count.unwrap_or(matched))
+            .sum();
+        let rows = added + removed + changed_cells;
+        if opts.max_detail_rows.is_some_and(|max| rows > max) {
+            // Too large to be of use as a file: summarize the changes
+            warnings::raise(
+                Warning::new(
+                    "detail_rows_summarized",
+                    format!(
+                        "The row-level diff ({} rows) exceeds max_detail_rows: {} was not \
+                         written, the changes are summarized as change patterns",
+                        rows, path
+                    ),
+                )
+                .count(rows),
+            );
+            let columns: Vec<String> = compared_columns
+                .iter()
+                .filter(|c| !opts.sensitive_columns.contains(c))
+                .filter(|c| {
+                    result.column_stats[c.as_str()].change_patterns.is_none()
+                        && result.column_stats[c.as_str()].non_match_count > Some(0)
+                })
+                .cloned()
+                .collect();
+            let patterns = samples::change_patterns(
+                &joined_lf.clone().with_columns(diff_flags),
+                &columns,
+                opts.change_patterns
+                    .filter(|&top| top > 0)
+                    .unwrap_or(samples::SUMMARY_PATTERNS),
+                opts.max_sample_value_len,
+            )?;
+            for (column, patterns) in patterns {
+                result.column_stats[column.as_str()].change_patterns =
+                    Some(patterns).filter(|p| !p.is_empty());
+            }
+            result.row_level_output = Some(RowLevelOutput {
+                path: path.clone(),
+                rows,
+                summarized: true,
+            });
+        } else {
+            let mut diff = cells::row_level_diff(lf_a.clone(), lf_b.clone(), &_key_cols, &opts)?
+                .with_new_streaming(true)
+                .collect()
+                .stage("building the row-level diff")?;
+            export::write_table(&mut diff, path, "writing the row-level diff")?;
+            result.row_level_output = Some(RowLevelOutput {
+                path: path.clone(),
+                rows: diff.height(),
+                summarized: false,
+            });
+        }
+        timings.lap("row_level_output");
+    }
+
+    timings.stamp(&mut result);
+    if let Some(path) = &opts.schema_history {
+        result.schema_drift = history::check(
+            path,
+            [(dataset_a, &schema_a), (dataset_b, &schema_b)],
+            &result.run_id,
+            &result.run_at,
+        )?;
+    }
+    result.warnings = warnings::take();
+    if opts.manifest && budget.allows("manifest") {
+        result.manifest =
+            Some(timings.into_manifest(python_version, &input_a, &input_b, &_key_cols, &opts)?);
+    }
+
+    result.plan = Some(plan.report);
+    result.timed_out = budget.stopped_at().is_some();
+    result.timed_out_phase = budget.stopped_at().map(str::to_string);
+    Ok(result)
+}
+
+/// The result of a run whose budget ran out before the statistics were in:
+/// the schema comparison and, when they were counted, the row counts.
+fn timed_out_result(
+    budget: &Budget,
+    plan: &Plan,
+    schema_a: &Schema,
+    schema_b: &Schema,
+    heights: Option<(usize, usize)>,
+) -> DiffResult {
+    let (total_rows_a, total_rows_b) = heights.unwrap_or_default();
+    DiffResult {
+        total_rows_a,
+        total_rows_b,
+        schema_diff: schema::schema_diff(schema_a, schema_b),
+        schema_compatibility: schema::compatibility_report(schema_a, schema_b),
+        schema_fingerprint_a: schema::fingerprint(schema_a),
+        schema_fingerprint_b: schema::fingerprint(schema_b),
+        timed_out: true,
+        timed_out_phase: budget.stopped_at().map(str::to_string),
+        plan: Some(plan.report.clone()),
+        ..Default::default()
+    }
 }
 
 /// A Python module implemented in Rust.
+#[cfg(feature = "python")]
 #[pymodule]
 fn _internal(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(diff_files, m)?)?;
+    m.add_function(wrap_pyfunction!(frames::diff_frames, m)?)?;
+    m.add_function(wrap_pyfunction!(drill::drill_down, m)?)?;
+    m.add_function(wrap_pyfunction!(export::export_details, m)?)?;
+    m.add_function(wrap_pyfunction!(export::export_cell_diffs, m)?)?;
+    m.add_function(wrap_pyfunction!(export::diff_rows, m)?)?;
+    m.add_function(wrap_pyfunction!(merge::merge_files, m)?)?;
+    m.add_function(wrap_pyfunction!(multiset::diff_row_sets, m)?)?;
+    m.add_function(wrap_pyfunction!(sequence::diff_sequences, m)?)?;
+    m.add_function(wrap_pyfunction!(intervals::diff_intervals, m)?)?;
+    m.add_function(wrap_pyfunction!(gate::gate, m)?)?;
+    m.add_function(wrap_pyfunction!(privacy::publishable_summary, m)?)?;
+    m.add_function(wrap_pyfunction!(profile::compare_profiles, m)?)?;
+    m.add_function(wrap_pyfunction!(regression::compare_results, m)?)?;
+    m.add_function(wrap_pyfunction!(render::render, m)?)?;
+    m.add_function(wrap_pyfunction!(render::render_formats, m)?)?;
+    m.add_function(wrap_pyfunction!(render::format_number, m)?)?;
+    m.add_function(wrap_pyfunction!(render::report_labels, m)?)?;
+    m.add_function(wrap_pyfunction!(signing::write_signed_report, m)?)?;
+    m.add_function(wrap_pyfunction!(signing::verify_report, m)?)?;
+    m.add_function(wrap_pyfunction!(threeway::diff_three_way, m)?)?;
+    m.add_function(wrap_pyfunction!(mutate::generate_mutations, m)?)?;
+    m.add_function(wrap_pyfunction!(selfcheck::self_check, m)?)?;
+    m.add_function(wrap_pyfunction!(estimate::estimate, m)?)?;
+    m.add_function(wrap_pyfunction!(explore::explore, m)?)?;
     Ok(())
 }
@@ -0,0 +1,181 @@
+// koala-diff/src/manifest.rs
+// Reproducible run manifest: what was compared, with which options and versions
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant; // std's panics in the browser
+
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::cloud;
+use crate::error::{Context, Result};
+use crate::options::DiffOptions;
+use crate::reader::Input;
+use crate::result::DiffResult;
+
+/// Everything needed to reproduce (or audit) one `diff_files` run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub koala_diff_version: String,
+    pub polars_version: String,
+    /// `None` when run through the C ABI.
+    pub python_version: Option<String>,
+    pub run_id: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub key_columns: Vec<String>,
+    /// Every option with its resolved value, defaults included.
+    pub options: serde_json::Value,
+    pub inputs: Vec<InputFingerprint>,
+    /// Wall-clock milliseconds per phase, in execution order.
+    pub timings_ms: IndexMap<String, f64>,
+}
+
+/// Size, modification time and content hash of an input file. Objects in
+/// object storage (`s3://...`) are only recorded by path: hashing them would
+/// mean downloading them. In-memory frames are only recorded by label.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputFingerprint {
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<String>,
+    pub sha256: String,
+}
+
+impl InputFingerprint {
+    pub fn of(input: &Input) -> Result<Self> {
+        let path = match input.path() {
+            Some(path) if !cloud::is_remote(path) => path,
+            _ => {
+                return Ok(Self {
+                    path: input.label().to_string(),
+                    ..Default::default()
+                })
+            }
+        };
+        let metadata = std::fs::metadata(path).stage("fingerprinting").file(path)?;
+        let modified_at = metadata
+            .modified()
+            .ok()
+            .map(|t| DateTime::<Utc>::from(t).to_rfc3339());
+
+        let mut hasher = Sha256::new();
+        let mut file = File::open(Path::new(path))
+            .stage("fingerprinting")
+            .file(path)?;
+        let mut buf = vec![0u8; 1 << 20];
+        loop {
+            let n = file.read(&mut buf).stage("fingerprinting").file(path)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            size_bytes: metadata.len(),
+            modified_at,
+            sha256: format!("{:x}", hasher.finalize()),
+        })
+    }
+}
+
+/// Phase timer for the manifest, and the run's id and start time. Cheap
+/// enough to run unconditionally.
+pub struct Timings {
+    run_id: String,
+    started_at: DateTime<FixedOffset>,
+    /// `run_at` was given: the clock stands still at it, so reports and
+    /// manifests come out the same on every run (phase timings aside).
+    frozen: bool,
+    start: Instant,
+    last: Instant,
+    phases: IndexMap<String, f64>,
+}
+
+impl Timings {
+    /// Starts the clock; `run_id` / `run_at` replace the generated id and
+    /// the current time.
+    pub fn start(opts: &DiffOptions) -> Self {
+        let now = Instant::now();
+        let run_at = opts
+            .run_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok());
+        Self {
+            run_id: opts.run_id.clone().unwrap_or_else(new_run_id),
+            started_at: run_at.unwrap_or_else(|| Local::now().fixed_offset()),
+            frozen: run_at.is_some(),
+            start: now,
+            last: now,
+            phases: IndexMap::new(),
+        }
+    }
+
+    /// Records the run's id and start time in `result`.
+    pub fn stamp(&self, result: &mut DiffResult) {
+        result.run_id = self.run_id.clone();
+        result.run_at = self.started_at.to_rfc3339();
+    }
+
+    /// Records the time since the previous lap under `phase`.
+    pub fn lap(&mut self, phase: &str) {
+        let now = Instant::now();
+        self.phases.insert(
+            phase.to_string(),
+            now.duration_since(self.last).as_secs_f64() * 1000.0,
+        );
+        self.last = now;
+    }
+
+    /// Builds the manifest, fingerprinting both inputs.
+    pub fn into_manifest(
+        mut self,
+        python_version: Option<String>,
+        input_a: &Input,
+        input_b: &Input,
+        key_columns: &[String],
+        opts: &DiffOptions,
+    ) -> Result<RunManifest> {
+        let inputs = vec![
+            InputFingerprint::of(input_a)?,
+            InputFingerprint::of(input_b)?,
+        ];
+        self.lap("fingerprint");
+        self.phases.insert(
+            "total".to_string(),
+            self.start.elapsed().as_secs_f64() * 1000.0,
+        );
+
+        let finished_at = match self.frozen {
+            true => self.started_at,
+            false => Local::now().fixed_offset(),
+        };
+        Ok(RunManifest {
+            koala_diff_version: env!("CARGO_PKG_VERSION").to_string(),
+            polars_version: polars::VERSION.to_string(),
+            python_version,
+            run_id: self.run_id,
+            started_at: self.started_at.to_rfc3339(),
+            finished_at: finished_at.to_rfc3339(),
+            key_columns: key_columns.to_vec(),
+            options: serde_json::to_value(opts).unwrap_or_default(),
+            inputs,
+            timings_ms: self.phases,
+        })
+    }
+}
+
+/// A random run id: 32 hex digits.
+fn new_run_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
@@ -0,0 +1,282 @@
+// koala-diff/src/merge.rs
+// Survivorship: resolves A and B into one reconciled Parquet file
+
+use std::collections::HashMap;
+use std::fs::File;
+
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use serde::Serialize;
+
+use crate::allowlist;
+use crate::error::{Context, DiffError, Result};
+use crate::options::DiffOptions;
+use crate::reader;
+
+/// Which side's value survives for a matched key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    PreferA,
+    PreferB,
+    /// B's value unless it is null.
+    PreferNonNull,
+    /// The value of the side with the later `timestamp_column` (B on ties).
+    PreferNewer,
+}
+
+impl Strategy {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "prefer_a" => Strategy::PreferA,
+            "prefer_b" => Strategy::PreferB,
+            "prefer_non_null" => Strategy::PreferNonNull,
+            "prefer_newer" => Strategy::PreferNewer,
+            other => {
+                return Err(DiffError::invalid(format!(
+                    "Unknown merge strategy '{}' (expected 'prefer_a', 'prefer_b', 'prefer_non_null' or 'prefer_newer')",
+                    other
+                )))
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    pub strategy: Strategy,
+    /// Per-column strategies overriding `strategy`.
+    pub column_rules: HashMap<String, Strategy>,
+    /// Required by `prefer_newer`.
+    pub timestamp_column: Option<String>,
+    /// Keep rows whose key is only in B.
+    pub keep_added: bool,
+    /// Keep rows whose key is only in A.
+    pub keep_removed: bool,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            strategy: Strategy::PreferB,
+            column_rules: HashMap::new(),
+            timestamp_column: None,
+            keep_added: true,
+            keep_removed: true,
+        }
+    }
+}
+
+impl MergeOptions {
+    /// Splits `**options` into merge options and the comparison options
+    /// used to read both files.
+    #[cfg(feature = "python")]
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<(Self, DiffOptions)> {
+        let mut opts = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok((opts, DiffOptions::default()));
+        };
+        let rest = kwargs.copy()?;
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "strategy" => opts.strategy = Strategy::parse(&value.extract::<String>()?)?,
+                "column_rules" => {
+                    let rules: HashMap<String, String> = value.extract()?;
+                    for (column, rule) in rules {
+                        opts.column_rules.insert(column, Strategy::parse(&rule)?);
+                    }
+                }
+                "timestamp_column" => opts.timestamp_column = value.extract()?,
+                "keep_added" => opts.keep_added = value.extract()?,
+                "keep_removed" => opts.keep_removed = value.extract()?,
+                _ => continue,
+            }
+            rest.del_item(&key)?;
+        }
+        let uses_newer = opts.strategy == Strategy::PreferNewer
+            || opts
+                .column_rules
+                .values()
+                .any(|s| *s == Strategy::PreferNewer);
+        if uses_newer && opts.timestamp_column.is_none() {
+            return Err(DiffError::invalid(
+                "The 'prefer_newer' strategy needs a 'timestamp_column'",
+            )
+            .into());
+        }
+        Ok((opts, DiffOptions::from_kwargs(Some(&rest))?))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeSummary {
+    pub path: String,
+    pub rows: usize,
+    pub matched_rows: usize,
+    /// Rows kept from a key only in A / only in B.
+    pub a_only_rows: usize,
+    pub b_only_rows: usize,
+}
+
+/// Builds the reconciled frame: one row per key, each shared column resolved
+/// by its strategy, columns of only one file passed through.
+fn reconcile(
+    mut lf_a: LazyFrame,
+    mut lf_b: LazyFrame,
+    key_cols: &[String],
+    merge: &MergeOptions,
+) -> PolarsResult<LazyFrame> {
+    let schema_a = lf_a.collect_schema()?;
+    let schema_b = lf_b.collect_schema()?;
+    let keys: Vec<Expr> = key_cols.iter().map(|k| col(k.as_str())).collect();
+
+    let joined = lf_a.with_column(lit(true).alias("_in_a")).join(
+        lf_b.with_column(lit(true).alias("_in_b")),
+        keys.clone(),
+        keys.clone(),
+        JoinArgs::new(JoinType::Full)
+            .with_coalesce(JoinCoalesce::CoalesceColumns)
+            .with_suffix(Some("_b".into())),
+    );
+    let in_a = col("_in_a").is_not_null();
+    let in_b = col("_in_b").is_not_null();
+    let b_newer = merge.timestamp_column.as_ref().map(|ts| {
+        let ts_b = if schema_a.contains(ts.as_str()) {
+            format!("{}_b", ts)
+        } else {
+            ts.clone()
+        };
+        in_b.clone().and(
+            in_a.clone()
+                .not()
+                .or(col(ts.as_str()).is_null())
+                .or(col(ts_b.as_str()).gt_eq(col(ts.as_str()))),
+        )
+    });
+
+    let mut columns = keys;
+    for name in schema_a.iter_names() {
+        let a = name.as_str();
+        if key_cols.iter().any(|k| k == a) {
+            continue;
+        }
+        if !schema_b.contains(a) {
+            columns.push(col(a));
+            continue;
+        }
+        let b = format!("{}_b", a);
+        let strategy = merge.column_rules.get(a).copied().unwrap_or(merge.strategy);
+        let take_b = match strategy {
+            Strategy::PreferA => in_a.clone().not(),
+            Strategy::PreferB => in_b.clone(),
+            Strategy::PreferNonNull => col(b.as_str()).is_not_null().or(in_a.clone().not()),
+            Strategy::PreferNewer => b_newer.clone().unwrap_or_else(|| in_b.clone()),
+        };
+        columns.push(
+            when(take_b)
+                .then(col(b.as_str()))
+                .otherwise(col(a))
+                .alias(a),
+        );
+    }
+    for name in schema_b.iter_names() {
+        if !schema_a.contains(name.as_str()) {
+            columns.push(col(name.clone()));
+        }
+    }
+
+    let mut keep = in_a.clone().and(in_b.clone());
+    if merge.keep_removed {
+        keep = keep.or(in_a.clone().and(in_b.clone().not()));
+    }
+    if merge.keep_added {
+        keep = keep.or(in_b.clone().and(in_a.clone().not()));
+    }
+    columns.push(in_a.alias("_in_a"));
+    columns.push(in_b.alias("_in_b"));
+    Ok(joined.filter(keep).select(columns))
+}
+
+/// Writes a reconciled dataset resolving the differences between two files
+///
+/// Each key appears once. For keys in both files every shared column is
+/// resolved by the merge strategy; keys in one file only are kept as they
+/// are (see `keep_added` / `keep_removed`).
+///
+/// Args:
+///     file_a (str): Path to the source (old) file
+///     file_b (str): Path to the target (new) file
+///     key_cols (list[str]): Columns to join on
+///     output_path (str): Parquet file to write
+///     **options: Merge options, plus any `diff_files` read option
+///         strategy (str): "prefer_b" (default), "prefer_a", "prefer_non_null"
+///             (B unless null) or "prefer_newer" (later `timestamp_column`)
+///         column_rules (dict[str, str]): Strategy per column
+///         timestamp_column (str): Row timestamp for "prefer_newer"
+///         keep_added (bool): Keep keys only in B (default True)
+///         keep_removed (bool): Keep keys only in A (default True)
+///
+/// Returns:
+///     dict: {"path", "rows", "matched_rows", "a_only_rows", "b_only_rows"}
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, key_cols, output_path, **options))]
+pub fn merge_files<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    output_path: String,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let (merge, opts) = MergeOptions::from_kwargs(options)?;
+    // Real values are written out, so sensitive columns are not hashed here
+    let lf_a = allowlist::apply(
+        reader::scan_keyed(&file_a, &opts.read_a, &key_cols)?,
+        &key_cols,
+        &opts,
+    )?;
+    let lf_b = allowlist::apply(
+        reader::scan_keyed(&file_b, &opts.read_b, &key_cols)?,
+        &key_cols,
+        &opts,
+    )?;
+    let mut df = reconcile(lf_a, lf_b, &key_cols, &merge)
+        .stage("merging")?
+        .with_new_streaming(true)
+        .collect()
+        .stage("merging")?;
+
+    let flag = |name: &str| -> Result<BooleanChunked> {
+        Ok(df
+            .column(name)
+            .stage("merging")?
+            .bool()
+            .stage("merging")?
+            .clone())
+    };
+    let (in_a, in_b) = (flag("_in_a")?, flag("_in_b")?);
+    let matched_rows = (&in_a & &in_b).sum().unwrap_or(0) as usize;
+    let summary = MergeSummary {
+        path: output_path.clone(),
+        rows: df.height(),
+        matched_rows,
+        a_only_rows: (in_a.sum().unwrap_or(0) as usize).saturating_sub(matched_rows),
+        b_only_rows: (in_b.sum().unwrap_or(0) as usize).saturating_sub(matched_rows),
+    };
+    let _ = df.drop_in_place("_in_a");
+    let _ = df.drop_in_place("_in_b");
+
+    let file = File::create(&output_path)
+        .stage("writing the merged file")
+        .file(&output_path)?;
+    ParquetWriter::new(file)
+        .with_compression(ParquetCompression::Zstd(None))
+        .finish(&mut df)
+        .stage("writing the merged file")
+        .file(&output_path)?;
+    Ok(pythonize::pythonize(py, &summary)?)
+}
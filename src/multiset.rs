@@ -0,0 +1,285 @@
+// koala-diff/src/multiset.rs
+// Order-insensitive comparison of the rows under each (non-unique) key
+
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::DiffOptions;
+use crate::reader;
+use crate::result::{KeyGroupDiff, RowSetDiff};
+use crate::schema;
+
+/// Most changed keys listed in the result.
+const MAX_SAMPLE_KEYS: usize = 20;
+
+/// Compares the multiset of rows under each key.
+///
+/// Rows are identified by a hash of their shared non-key columns; under a
+/// key, rows only in A are removed and rows only in B added, except that an
+/// A row and a B row left over together count as one modified row.
+pub fn compare_row_sets(
+    mut lf_a: LazyFrame,
+    mut lf_b: LazyFrame,
+    key_cols: &[String],
+    opts: &DiffOptions,
+) -> Result<RowSetDiff> {
+    exact_only(opts, "row sets")?;
+    let schema_a = lf_a.collect_schema().stage("comparing row sets")?;
+    let schema_b = lf_b.collect_schema().stage("comparing row sets")?;
+    let [hash_a, hash_b] = row_hashes(&schema_a, &schema_b, key_cols, opts);
+
+    let keys: Vec<Expr> = key_cols.iter().map(|k| col(k.as_str())).collect();
+    let mut by_row = keys.clone();
+    by_row.push(col("_row_hash"));
+    let count_rows = |lf: LazyFrame, row_hash: Expr, alias: &str| {
+        lf.with_column(row_hash.alias("_row_hash"))
+            .group_by(by_row.clone())
+            .agg([len().cast(DataType::Int64).alias(alias)])
+    };
+
+    let surplus = |a: &str, b: &str| {
+        when(col(a).gt(col(b)))
+            .then(col(a) - col(b))
+            .otherwise(lit(0i64))
+    };
+    let per_key = count_rows(lf_a, hash_a, "_n_a")
+        .join(
+            count_rows(lf_b, hash_b, "_n_b"),
+            by_row.clone(),
+            by_row.clone(),
+            JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .with_columns([
+            col("_n_a").fill_null(lit(0i64)),
+            col("_n_b").fill_null(lit(0i64)),
+        ])
+        .group_by(keys.clone())
+        .agg([
+            col("_n_a").sum().alias("_rows_a"),
+            col("_n_b").sum().alias("_rows_b"),
+            surplus("_n_a", "_n_b").sum().alias("_only_a"),
+            surplus("_n_b", "_n_a").sum().alias("_only_b"),
+        ])
+        .with_column(
+            when(col("_only_a").lt(col("_only_b")))
+                .then(col("_only_a"))
+                .otherwise(col("_only_b"))
+                .alias("_modified"),
+        )
+        .with_columns([
+            (col("_only_b") - col("_modified")).alias("_added"),
+            (col("_only_a") - col("_modified")).alias("_removed"),
+            (col("_only_a") + col("_only_b")).gt(0).alias("_changed"),
+        ]);
+
+    let in_both = col("_rows_a").gt(0).and(col("_rows_b").gt(0));
+    // Row counts of keys present on both sides only
+    let sum_matched = |column: &str| {
+        when(in_both.clone())
+            .then(col(column))
+            .otherwise(lit(0i64))
+            .sum()
+    };
+    let totals = per_key
+        .clone()
+        .select([
+            col("_rows_a").gt(0).sum().alias("keys_a"),
+            col("_rows_b").gt(0).sum().alias("keys_b"),
+            in_both.clone().sum().alias("matched_keys"),
+            in_both
+                .clone()
+                .and(col("_changed"))
+                .sum()
+                .alias("modified_keys"),
+            col("_rows_a").eq(0).sum().alias("added_keys"),
+            col("_rows_b").eq(0).sum().alias("removed_keys"),
+            col("_rows_a").sum().alias("rows_a"),
+            col("_rows_b").sum().alias("rows_b"),
+            sum_matched("_added").alias("added_rows"),
+            sum_matched("_removed").alias("removed_rows"),
+            sum_matched("_modified").alias("modified_rows"),
+        ])
+        .collect()
+        .stage("comparing row sets")?;
+    let total = |name: &str| extract(&totals, name, 0);
+
+    let samples = per_key
+        .filter(in_both.and(col("_changed")))
+        .with_column((col("_added") + col("_removed") + col("_modified")).alias("_weight"))
+        .sort(
+            ["_weight"],
+            SortMultipleOptions::default().with_order_descending(true),
+        )
+        .limit(MAX_SAMPLE_KEYS as IdxSize)
+        .collect()
+        .stage("comparing row sets")?;
+    let sample_keys = (0..samples.height())
+        .map(|i| KeyGroupDiff {
+            key: key_string(&samples, key_cols, i),
+            rows_a: extract(&samples, "_rows_a", i),
+            rows_b: extract(&samples, "_rows_b", i),
+            added_rows: extract(&samples, "_added", i),
+            removed_rows: extract(&samples, "_removed", i),
+            modified_rows: extract(&samples, "_modified", i),
+        })
+        .collect();
+
+    let matched_keys = total("matched_keys");
+    let modified_keys = total("modified_keys");
+    Ok(RowSetDiff {
+        keys_a: total("keys_a"),
+        keys_b: total("keys_b"),
+        matched_keys,
+        identical_keys: matched_keys.saturating_sub(modified_keys),
+        modified_keys,
+        added_keys: total("added_keys"),
+        removed_keys: total("removed_keys"),
+        rows_a: total("rows_a"),
+        rows_b: total("rows_b"),
+        added_rows: total("added_rows"),
+        removed_rows: total("removed_rows"),
+        modified_rows: total("modified_rows"),
+        sample_keys,
+    })
+}
+
+/// Refuses the tolerance options: rows under a key match by hash, exactly.
+pub fn exact_only(opts: &DiffOptions, comparison: &str) -> Result<()> {
+    let tolerances = [
+        ("abs_tol", opts.abs_tol.is_some()),
+        ("rel_tol", opts.rel_tol.is_some()),
+        ("tolerances", !opts.tolerances.is_empty()),
+    ];
+    if let Some((option, _)) = tolerances.iter().find(|(_, set)| *set) {
+        return Err(DiffError::unsupported(format!(
+            "'{}' doesn't apply to {}: their rows match exactly, by hash",
+            option, comparison
+        )));
+    }
+    Ok(())
+}
+
+/// Each side's hash of the non-key columns both sides have (less
+/// `skipped`). A column typed differently on the two sides is hashed as one
+/// dtype on both, so equal values hash alike: A's when B's promotes to it,
+/// B's when A's promotes to it, Float64 for other numeric pairs, else text.
+pub fn row_hashes(
+    schema_a: &Schema,
+    schema_b: &Schema,
+    skipped: &[String],
+    opts: &DiffOptions,
+) -> [Expr; 2] {
+    let (mut values_a, mut values_b) = (Vec::new(), Vec::new());
+    for (name, dtype_a) in schema_a.iter() {
+        let Some(dtype_b) = schema_b.get(name.as_str()) else {
+            continue;
+        };
+        if skipped.iter().any(|k| k == name.as_str()) {
+            continue;
+        }
+        let (a, b) = (col(name.clone()), col(name.clone()));
+        if dtype_a == dtype_b || schema::promotes(dtype_b, dtype_a) {
+            values_a.push(a);
+            values_b.push(b.cast(dtype_a.clone()));
+        } else if schema::promotes(dtype_a, dtype_b) {
+            values_a.push(a.cast(dtype_b.clone()));
+            values_b.push(b);
+        } else {
+            let common = match dtype_a.is_numeric() && dtype_b.is_numeric() {
+                true => DataType::Float64,
+                false => DataType::String,
+            };
+            values_a.push(a.cast(common.clone()));
+            values_b.push(b.cast(common));
+        }
+    }
+    let [k0, k1, k2, k3] = opts.hash_seeds;
+    let hash = |values: Vec<Expr>| match values.is_empty() {
+        true => lit(0u64),
+        false => as_struct(values).hash(k0, k1, k2, k3),
+    };
+    [hash(values_a), hash(values_b)]
+}
+
+fn extract(df: &DataFrame, name: &str, row: usize) -> usize {
+    df.column(name)
+        .ok()
+        .and_then(|c| c.get(row).ok())
+        .and_then(|v| v.try_extract::<u64>().ok())
+        .unwrap_or(0) as usize
+}
+
+/// Key values of `row`, joined with `|`.
+pub fn key_string(df: &DataFrame, key_cols: &[String], row: usize) -> String {
+    key_cols
+        .iter()
+        .map(|k| match df.column(k).and_then(|c| c.get(row)) {
+            Ok(AnyValue::Null) | Err(_) => "null".to_string(),
+            Ok(v) => v.get_str().map_or_else(|| v.to_string(), str::to_string),
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Compares the rows under each key as unordered multisets
+///
+/// For keys that map to several rows per side (order -> line items) and
+/// have no line-level key: duplicates count, row order doesn't.
+///
+/// Args:
+///     file_a (str): Path to the source (old) file
+///     file_b (str): Path to the target (new) file
+///     key_cols (list[str]): Group key columns
+///     **options: Read and preprocessing options of `diff_files`; rows match
+///         exactly, so `abs_tol`, `rel_tol` and `tolerances` are refused
+///
+/// Returns:
+///     dict: Key and row counts (matched/identical/modified/added/removed)
+///         and "sample_keys" with the per-key added/removed/modified rows
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, key_cols, **options))]
+pub fn diff_row_sets<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let opts = DiffOptions::from_kwargs(options)?;
+    let lf_a = reader::open(&file_a, &opts.read_a, &key_cols, &opts)?;
+    let lf_b = reader::open(&file_b, &opts.read_b, &key_cols, &opts)?;
+    let diff = compare_row_sets(lf_a, lf_b, &key_cols, &opts)?;
+    Ok(pythonize::pythonize(py, &diff)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_typed_differently_on_each_side_still_match() {
+        let a = df!("order" => [1i64, 1, 2], "qty" => [5i32, 7, 3]).unwrap();
+        let b = df!("order" => [1i64, 1, 2], "qty" => [7i64, 5, 4]).unwrap();
+        let keys = ["order".to_string()];
+        let diff = compare_row_sets(a.lazy(), b.lazy(), &keys, &DiffOptions::default()).unwrap();
+        assert_eq!(diff.identical_keys, 1);
+        assert_eq!(diff.modified_keys, 1);
+        assert_eq!(diff.modified_rows, 1);
+    }
+
+    #[test]
+    fn tolerances_are_refused() {
+        let a = df!("order" => [1i64], "qty" => [5.0]).unwrap();
+        let opts = DiffOptions {
+            abs_tol: Some(0.1),
+            ..Default::default()
+        };
+        let keys = ["order".to_string()];
+        assert!(compare_row_sets(a.clone().lazy(), a.lazy(), &keys, &opts).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+// koala-diff/src/mutate.rs
+// Controlled mutations of a dataset, to check a diff configuration catches them
+
+use std::collections::BTreeMap;
+use std::fs::File;
+
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::ReadOptions;
+use crate::reader;
+use crate::result::MutationReport;
+
+/// What to inject, applied in this order: row drops, perturbations, nulls.
+#[derive(Debug, Clone, Default)]
+pub struct MutationSpec {
+    /// Rows removed at random.
+    pub drop_rows: usize,
+    /// Column -> epsilon added to or subtracted from every non-null value.
+    pub perturb: BTreeMap<String, f64>,
+    /// Column -> percentage of rows set to null.
+    pub null_out: BTreeMap<String, f64>,
+    pub seed: Option<u64>,
+}
+
+impl MutationSpec {
+    #[cfg(feature = "python")]
+    fn from_dict(spec: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let mut parsed = Self::default();
+        for (key, value) in spec.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "drop_rows" => parsed.drop_rows = value.extract()?,
+                "perturb" => parsed.perturb = value.extract()?,
+                "null_out" => parsed.null_out = value.extract()?,
+                "seed" => parsed.seed = value.extract()?,
+                other => {
+                    return Err(DiffError::unsupported(format!(
+                        "generate_mutations() got an unexpected mutation '{}'",
+                        other
+                    ))
+                    .into())
+                }
+            }
+        }
+        for (name, epsilon) in &parsed.perturb {
+            if !epsilon.is_finite() {
+                return Err(DiffError::invalid("perturb epsilon must be finite")
+                    .in_column(name)
+                    .into());
+            }
+        }
+        for (name, pct) in &parsed.null_out {
+            if !(0.0..=100.0).contains(pct) {
+                return Err(DiffError::invalid("null_out takes a percentage (0-100)")
+                    .in_column(name)
+                    .into());
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// `amount` distinct rows out of `n`, as a mask.
+fn pick(rng: &mut StdRng, n: usize, amount: usize) -> BooleanChunked {
+    let mut mask = vec![false; n];
+    for i in index::sample(rng, n, amount.min(n)) {
+        mask[i] = true;
+    }
+    BooleanChunked::from_slice("mask".into(), &mask)
+}
+
+/// Applies `spec` to `df`; the report counts what was actually changed.
+pub fn mutate(
+    mut df: DataFrame,
+    spec: &MutationSpec,
+) -> Result<(DataFrame, MutationReport), DiffError> {
+    let mut rng = match spec.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+    let mut report = MutationReport {
+        rows_in: df.height(),
+        ..Default::default()
+    };
+
+    if spec.drop_rows > 0 {
+        let dropped = pick(&mut rng, df.height(), spec.drop_rows);
+        df = df.filter(&!dropped)?;
+    }
+    report.dropped_rows = report.rows_in - df.height();
+    let n = df.height();
+
+    for (name, epsilon) in &spec.perturb {
+        let column = df.column(name)?.as_materialized_series().clone();
+        if !column.dtype().is_numeric() {
+            return Err(
+                DiffError::invalid(format!("can't perturb a {} column", column.dtype()))
+                    .in_column(name),
+            );
+        }
+        let offsets: Float64Chunked = (0..n)
+            .map(|_| {
+                Some(if rng.random_bool(0.5) {
+                    *epsilon
+                } else {
+                    -*epsilon
+                })
+            })
+            .collect();
+        let values = column.cast(&DataType::Float64)?;
+        let shifted = (values.f64()? + &offsets)
+            .into_series()
+            .with_name(name.as_str().into())
+            .cast(column.dtype())?;
+        report
+            .perturbed
+            .insert(name.clone(), n - column.null_count());
+        df.with_column(shifted.into_column())?;
+    }
+
+    for (name, pct) in &spec.null_out {
+        let column = df.column(name)?.as_materialized_series().clone();
+        let amount = (n as f64 * pct / 100.0).round() as usize;
+        let mask = pick(&mut rng, n, amount);
+        let nulled = (&mask & &column.is_not_null()).sum().unwrap_or(0) as usize;
+        let nulls = Series::full_null(name.as_str().into(), n, column.dtype());
+        report.nulled.insert(name.clone(), nulled);
+        df.with_column(nulls.zip_with(&mask, &column)?.into_column())?;
+    }
+
+    report.rows_out = df.height();
+    Ok((df, report))
+}
+
+/// Writes a mutated copy of a dataset, to validate a diff configuration
+///
+/// Diffing `file` against `output_path` should report exactly the returned
+/// counts: `dropped_rows` removed rows and, per column, at most `perturbed`
+/// + `nulled` mismatches (fewer when a perturbation is below the comparison
+/// tolerance or lost to the column type, e.g. an epsilon under 1 on integers).
+///
+/// Args:
+///     file (str): Dataset to mutate
+///     spec (dict): Mutations, applied in this order
+///         drop_rows (int): Rows removed at random
+///         perturb (dict[str, float]): Column -> epsilon, added to or
+///             subtracted from every non-null value (numeric columns)
+///         null_out (dict[str, float]): Column -> percentage of rows set to null
+///         seed (int): RNG seed for a reproducible output
+///     output_path (str): Mutated copy; CSV for a `.csv` path, Parquet otherwise
+///
+/// Returns:
+///     dict: {"path", "rows_in", "rows_out", "dropped_rows",
+///            "perturbed": {column: values}, "nulled": {column: values}}
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file, spec, output_path))]
+pub fn generate_mutations<'py>(
+    py: Python<'py>,
+    file: String,
+    spec: &Bound<'py, PyDict>,
+    output_path: String,
+) -> PyResult<Bound<'py, PyAny>> {
+    let spec = MutationSpec::from_dict(spec)?;
+    let df = reader::scan(&file, &ReadOptions::default())?
+        .collect()
+        .stage("reading")
+        .file(&file)?;
+    let (mut df, mut report) = mutate(df, &spec).stage("mutating")?;
+
+    let out = File::create(&output_path)
+        .stage("writing mutations")
+        .file(&output_path)?;
+    let written = if output_path.to_lowercase().ends_with(".csv") {
+        CsvWriter::new(out).finish(&mut df)
+    } else {
+        ParquetWriter::new(out)
+            .with_compression(ParquetCompression::Zstd(None))
+            .finish(&mut df)
+            .map(|_| ())
+    };
+    written.stage("writing mutations").file(&output_path)?;
+
+    report.path = output_path;
+    Ok(pythonize::pythonize(py, &report)?)
+}
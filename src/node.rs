@@ -0,0 +1,91 @@
+// koala-diff/src/node.rs
+// Node.js bindings (napi-rs): the same engine and results as the Python module
+
+use std::mem;
+
+use napi::bindgen_prelude::{AsyncTask, Buffer, Either};
+use napi::{Env, Task};
+use napi_derive::napi;
+
+use crate::error::DiffError;
+use crate::options::DiffOptions;
+use crate::reader::Input;
+use crate::render::{Registry, RenderOptions};
+use crate::result::DiffResult;
+
+/// A `diffFiles` call, run on the libuv thread pool.
+pub struct Diff {
+    file_a: String,
+    file_b: String,
+    keys: Vec<String>,
+    opts: DiffOptions,
+}
+
+impl Task for Diff {
+    type Output = DiffResult;
+    type JsValue = serde_json::Value;
+
+    fn compute(&mut self) -> napi::Result<DiffResult> {
+        let inputs = [
+            Input::Path(mem::take(&mut self.file_a)),
+            Input::Path(mem::take(&mut self.file_b)),
+        ];
+        crate::run_diff(
+            inputs,
+            mem::take(&mut self.keys),
+            mem::take(&mut self.opts),
+            None,
+        )
+        .map_err(js_error)
+    }
+
+    fn resolve(&mut self, _env: Env, result: DiffResult) -> napi::Result<serde_json::Value> {
+        serde_json::to_value(&result).map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+}
+
+/// `diffFiles(fileA, fileB, keys, options?)`: resolves to the same result
+/// object as Python's `diff_files`. `options` takes the `diff_files`
+/// keyword arguments as an object (`{ abs_tol: 0.01, limit_a: 1000 }`).
+/// Invalid options throw right away; read and comparison errors reject.
+#[napi]
+pub fn diff_files(
+    file_a: String,
+    file_b: String,
+    keys: Vec<String>,
+    options: Option<serde_json::Value>,
+) -> napi::Result<AsyncTask<Diff>> {
+    let opts = match options {
+        Some(options) => DiffOptions::from_json(options).map_err(js_error)?,
+        None => DiffOptions::default(),
+    };
+    Ok(AsyncTask::new(Diff {
+        file_a,
+        file_b,
+        keys,
+        opts,
+    }))
+}
+
+/// `render(result, format)`: the report in one of the built-in formats
+/// (text, json, markdown, html: a string; xlsx: a Buffer), as Python renders it
+/// with the default options.
+#[napi]
+pub fn render(result: serde_json::Value, format: String) -> napi::Result<Either<String, Buffer>> {
+    let reason = |e: &dyn std::fmt::Display| napi::Error::from_reason(e.to_string());
+    let result: DiffResult = serde_json::from_value(result).map_err(|e| reason(&e))?;
+    let registry = Registry::builtin();
+    let renderer = registry.get(&format).map_err(|e| reason(&e))?;
+    let output = renderer
+        .render(&result, &RenderOptions::default())
+        .map_err(|e| reason(&e))?;
+    if renderer.is_binary() {
+        Ok(Either::B(output.into()))
+    } else {
+        Ok(Either::A(String::from_utf8_lossy(&output).into_owned()))
+    }
+}
+
+fn js_error(e: DiffError) -> napi::Error {
+    napi::Error::from_reason(e.to_string())
+}
@@ -0,0 +1,703 @@
+// koala-diff/src/options.rs
+// Comparison options passed as keyword arguments to `diff_files`
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::LazyLock;
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::aggregates;
+use crate::bands;
+use crate::csv;
+use crate::dictionary;
+use crate::dtypes;
+use crate::error::{DiffError, Result};
+use crate::partition;
+use crate::sensitive;
+use crate::subsets::{self, ComparisonGroup};
+
+/// Options controlling how values are compared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiffOptions {
+    /// Whether `+inf == +inf` (and `-inf == -inf`) counts as a match.
+    pub inf_equal: bool,
+    /// Finite values with a magnitude at or above this threshold match an
+    /// infinity of the same sign (e.g. `1e308` vs `inf`).
+    pub inf_match_threshold: Option<f64>,
+    /// Flush subnormal floats to zero on both sides before comparing.
+    pub flush_subnormals: bool,
+    /// Numeric values at most this far apart match.
+    pub abs_tol: Option<f64>,
+    /// Numeric values within this fraction of the larger magnitude match.
+    pub rel_tol: Option<f64>,
+    /// Per-column absolute tolerance, overriding `abs_tol` for that column.
+    pub tolerances: HashMap<String, f64>,
+    /// Profile each file independently (approximate distinct counts, ...);
+    /// `None` leaves it to the planner (small inputs only).
+    pub profile: Option<bool>,
+    /// Relative drop in uniqueness/entropy (0..1) from A to B that gets
+    /// flagged in profile mode.
+    pub drift_drop_threshold: f64,
+    /// Compare the row order of matched keys between A and B.
+    pub check_order: bool,
+    /// Monotonically increasing column of an append-only log; restricts the
+    /// diff to the window both files cover.
+    pub append_key: Option<String>,
+    /// Arbitrary per-column metadata (owner, domain, ...) echoed into the
+    /// column stats as `tags`.
+    pub column_tags: HashMap<String, BTreeMap<String, String>>,
+    /// Truncate sample values longer than this many characters.
+    pub max_sample_value_len: Option<usize>,
+    /// Report this many top "old -> new" change patterns per column.
+    pub change_patterns: Option<usize>,
+    /// How to read file A.
+    #[serde(skip_deserializing)]
+    pub read_a: ReadOptions,
+    /// How to read file B.
+    #[serde(skip_deserializing)]
+    pub read_b: ReadOptions,
+    /// Run the root-cause heuristics (swapped columns, ...) on mismatches.
+    pub diagnose: bool,
+    /// Break mismatch rates down by this column.
+    pub group_by: Option<String>,
+    /// Columns compared by salted hash; their values never reach samples.
+    pub sensitive_columns: Vec<String>,
+    /// Seeds of the salted hash (from `hash_salt`, random by default). Never
+    /// written to manifests.
+    #[serde(skip)]
+    pub hash_seeds: [u64; 4],
+    /// File listing the only keys to compare (row-level scoping).
+    pub key_allowlist: Option<String>,
+    /// Attach a run manifest (resolved options, input fingerprints,
+    /// versions, timings) to the result.
+    pub manifest: bool,
+    /// Compare the Parquet footer statistics of both files too.
+    pub parquet_stats: bool,
+    /// Secondary keys matching line items within each key (hierarchical keys).
+    pub line_keys: Vec<String>,
+    /// Column statistics passes run concurrently (1: a single pass).
+    pub column_parallelism: usize,
+    /// Columns compared first and listed first in the column stats.
+    pub critical_columns: Vec<String>,
+    /// Named column subsets compared with their own options in the same pass.
+    #[serde(deserialize_with = "subsets::deserialize")]
+    pub comparison_groups: Vec<ComparisonGroup>,
+    /// Column -> {aggregate: absolute tolerance}, asserted on both files.
+    pub aggregate_checks: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Column -> green / amber thresholds: matched rows are counted per
+    /// band of their absolute difference.
+    pub bands: BTreeMap<String, bands::Thresholds>,
+    /// Hash both sides' compared content even when they differ.
+    pub content_hash: bool,
+    /// Write the complete row-level diff here (CSV or Parquet).
+    pub output_path: Option<String>,
+    /// Largest row-level diff written to `output_path`; above it the changes
+    /// are summarized as change patterns.
+    pub max_detail_rows: Option<usize>,
+    /// Run every pass on the streaming (out-of-core) engine; `None` decides
+    /// from the estimated size of the inputs and the available memory.
+    pub streaming: Option<bool>,
+    /// `hash` or `merge` (both sides sorted by the keys); `None` leaves it
+    /// to the planner.
+    pub join_strategy: Option<String>,
+    /// Key-hash partitions of the statistics pass; `None` leaves it to the
+    /// planner.
+    pub partitions: Option<usize>,
+    /// FX rate table (`currency`, `rate`) converting monetary columns to a
+    /// common currency.
+    pub fx_rates: Option<String>,
+    /// Monetary column -> the column holding its currency code.
+    pub currency_columns: HashMap<String, String>,
+    /// Time budget of the run; past it, the result computed so far is
+    /// returned flagged as timed out.
+    pub timeout_seconds: Option<f64>,
+    /// Id of the run; random by default.
+    pub run_id: Option<String>,
+    /// Start time of the run (RFC 3339), for reproducible reports; the
+    /// current time by default.
+    pub run_at: Option<String>,
+    /// JSON store of each dataset's last seen schema: every run checks both
+    /// inputs against it for drift, then records their schemas.
+    pub schema_history: Option<String>,
+    /// Data dictionary file describing each column's timezone, unit, locale
+    /// and sensitivity, applied to both inputs.
+    pub data_dictionary: Option<String>,
+    /// The entries read from `data_dictionary`.
+    #[serde(skip_deserializing)]
+    pub dictionary: Vec<dictionary::Entry>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            inf_equal: true,
+            inf_match_threshold: None,
+            flush_subnormals: false,
+            abs_tol: None,
+            rel_tol: None,
+            tolerances: HashMap::new(),
+            profile: None,
+            drift_drop_threshold: 0.5,
+            check_order: false,
+            append_key: None,
+            column_tags: HashMap::new(),
+            max_sample_value_len: None,
+            change_patterns: None,
+            read_a: ReadOptions::default(),
+            read_b: ReadOptions::default(),
+            diagnose: true,
+            group_by: None,
+            sensitive_columns: Vec::new(),
+            hash_seeds: sensitive::seeds(None),
+            key_allowlist: None,
+            manifest: false,
+            parquet_stats: false,
+            line_keys: Vec::new(),
+            column_parallelism: 1,
+            critical_columns: Vec::new(),
+            comparison_groups: Vec::new(),
+            aggregate_checks: BTreeMap::new(),
+            bands: BTreeMap::new(),
+            content_hash: false,
+            output_path: None,
+            max_detail_rows: Some(10_000_000),
+            streaming: None,
+            join_strategy: None,
+            partitions: None,
+            fx_rates: None,
+            currency_columns: HashMap::new(),
+            timeout_seconds: None,
+            run_id: None,
+            run_at: None,
+            schema_history: None,
+            data_dictionary: None,
+            dictionary: Vec::new(),
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Absolute tolerance of `column`: its `tolerances` entry, else `abs_tol`.
+    pub fn abs_tol_for(&self, column: &str) -> Option<f64> {
+        self.tolerances.get(column).copied().or(self.abs_tol)
+    }
+
+    /// Builds the options from the `**options` kwargs of `diff_files`.
+    #[cfg(feature = "python")]
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut opts = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok(opts);
+        };
+
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            if let Some(name) = key.strip_suffix("_a") {
+                if opts.read_a.set(name, &value)? {
+                    continue;
+                }
+            }
+            if let Some(name) = key.strip_suffix("_b") {
+                if opts.read_b.set(name, &value)? {
+                    continue;
+                }
+            }
+            if opts.read_a.set(&key, &value)? {
+                opts.read_b.set(&key, &value)?;
+                continue;
+            }
+
+            match key.as_str() {
+                "inf_equal" => opts.inf_equal = value.extract()?,
+                "inf_match_threshold" => opts.inf_match_threshold = value.extract()?,
+                "flush_subnormals" => opts.flush_subnormals = value.extract()?,
+                "abs_tol" => opts.abs_tol = value.extract()?,
+                "rel_tol" => opts.rel_tol = value.extract()?,
+                "tolerances" => opts.tolerances = value.extract()?,
+                "profile" => opts.profile = value.extract()?,
+                "drift_drop_threshold" => opts.drift_drop_threshold = value.extract()?,
+                "check_order" => opts.check_order = value.extract()?,
+                "append_key" => opts.append_key = value.extract()?,
+                "column_tags" => opts.column_tags = value.extract()?,
+                "max_sample_value_len" => opts.max_sample_value_len = value.extract()?,
+                "change_patterns" => opts.change_patterns = value.extract()?,
+                "diagnose" => opts.diagnose = value.extract()?,
+                "group_by" => opts.group_by = value.extract()?,
+                "sensitive_columns" => opts.sensitive_columns = value.extract()?,
+                "hash_salt" => {
+                    opts.hash_seeds = sensitive::seeds(Some(&value.extract::<String>()?))
+                }
+                "key_allowlist" => opts.key_allowlist = value.extract()?,
+                "manifest" => opts.manifest = value.extract()?,
+                "parquet_stats" => opts.parquet_stats = value.extract()?,
+                "line_keys" => opts.line_keys = value.extract()?,
+                "column_parallelism" => opts.column_parallelism = value.extract()?,
+                "critical_columns" => opts.critical_columns = value.extract()?,
+                "comparison_groups" => opts.comparison_groups = subsets::parse(&value)?,
+                "aggregate_checks" => opts.aggregate_checks = value.extract()?,
+                "bands" => opts.bands = pythonize::depythonize(&value)?,
+                "content_hash" => opts.content_hash = value.extract()?,
+                "output_path" => opts.output_path = value.extract()?,
+                "max_detail_rows" => opts.max_detail_rows = value.extract()?,
+                "streaming" => opts.streaming = value.extract()?,
+                "join_strategy" => opts.join_strategy = value.extract()?,
+                "partitions" => opts.partitions = value.extract()?,
+                "fx_rates" => opts.fx_rates = value.extract()?,
+                "currency_columns" => opts.currency_columns = value.extract()?,
+                "timeout_seconds" => opts.timeout_seconds = value.extract()?,
+                "run_id" => opts.run_id = value.extract()?,
+                "run_at" => opts.run_at = value.extract()?,
+                "schema_history" => opts.schema_history = value.extract()?,
+                "data_dictionary" => opts.data_dictionary = value.extract()?,
+                other => {
+                    return Err(DiffError::unsupported(format!(
+                        "diff_files() got an unexpected option '{}'",
+                        other
+                    ))
+                    .into())
+                }
+            }
+        }
+
+        dictionary::load(&mut opts)?;
+        opts.validate()?;
+        Ok(opts)
+    }
+
+    /// Builds the options from a JSON object with the same keys as the
+    /// `diff_files` kwargs, read options suffixed the same way (the C ABI
+    /// and Node bindings).
+    pub fn from_json(value: serde_json::Value) -> Result<Self> {
+        let invalid = |e: serde_json::Error| DiffError::invalid(format!("Invalid options: {}", e));
+        let serde_json::Value::Object(mut object) = value else {
+            return Err(DiffError::invalid("options must be a JSON object"));
+        };
+        let salt = object
+            .remove("hash_salt")
+            .map(serde_json::from_value::<String>)
+            .transpose()
+            .map_err(invalid)?;
+
+        let (mut rest, mut both) = (serde_json::Map::new(), serde_json::Map::new());
+        let mut sides = [serde_json::Map::new(), serde_json::Map::new()];
+        for (key, value) in object {
+            let suffixed = [("_a", 0), ("_b", 1)]
+                .into_iter()
+                .find_map(|(suffix, side)| {
+                    key.strip_suffix(suffix)
+                        .filter(|name| ReadOptions::is_option(name))
+                        .map(|name| (side, name.to_string()))
+                });
+            match suffixed {
+                Some((side, name)) => {
+                    sides[side].insert(name, value);
+                }
+                None if ReadOptions::is_option(&key) => {
+                    both.insert(key, value);
+                }
+                None => {
+                    rest.insert(key, value);
+                }
+            }
+        }
+        // A suffixed read option wins over the un-suffixed one
+        let [read_a, read_b] = sides.map(|mut side| {
+            for (key, value) in &both {
+                side.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+            serde_json::from_value::<ReadOptions>(serde_json::Value::Object(side))
+        });
+
+        let mut opts: Self =
+            serde_json::from_value(serde_json::Value::Object(rest)).map_err(invalid)?;
+        opts.read_a = read_a.map_err(invalid)?;
+        opts.read_b = read_b.map_err(invalid)?;
+        if salt.is_some() {
+            opts.hash_seeds = sensitive::seeds(salt.as_deref());
+        }
+        dictionary::load(&mut opts)?;
+        opts.validate()?;
+        Ok(opts)
+    }
+
+    /// Checks the values no type can rule out.
+    fn validate(&self) -> Result<()> {
+        let negative = |tol: Option<f64>| tol.is_some_and(|t| t.is_nan() || t < 0.0);
+        for (name, tol) in [("abs_tol", self.abs_tol), ("rel_tol", self.rel_tol)] {
+            if negative(tol) {
+                return Err(DiffError::invalid(format!(
+                    "{} must be a non-negative number",
+                    name
+                )));
+            }
+        }
+        if let Some((column, _)) = self.tolerances.iter().find(|(_, t)| negative(Some(**t))) {
+            return Err(
+                DiffError::invalid("tolerance must be a non-negative number").in_column(column),
+            );
+        }
+        for group in &self.comparison_groups {
+            if group.columns.is_empty() {
+                return Err(DiffError::invalid(format!(
+                    "comparison group '{}' needs a non-empty 'columns' list",
+                    group.name
+                )));
+            }
+            for (name, tol) in [("abs_tol", group.abs_tol), ("rel_tol", group.rel_tol)] {
+                if negative(tol) {
+                    return Err(DiffError::invalid(format!(
+                        "{} must be a non-negative number",
+                        name
+                    )));
+                }
+            }
+        }
+        for (column, checks) in &self.aggregate_checks {
+            for (aggregate, &tol) in checks {
+                if !aggregates::AGGREGATES.contains(&aggregate.as_str()) {
+                    return Err(DiffError::invalid(format!(
+                        "unknown aggregate '{}' (expected one of {})",
+                        aggregate,
+                        aggregates::AGGREGATES.join(", ")
+                    ))
+                    .in_column(column));
+                }
+                if negative(Some(tol)) {
+                    return Err(DiffError::invalid(format!(
+                        "the tolerance of aggregate check '{}' must be a non-negative number",
+                        aggregate
+                    ))
+                    .in_column(column));
+                }
+                // Sensitive columns hold salted hashes by then
+                if aggregates::is_numeric(aggregate) && self.sensitive_columns.contains(column) {
+                    return Err(DiffError::invalid(format!(
+                        "aggregate check '{}' can't run on a sensitive column",
+                        aggregate
+                    ))
+                    .in_column(column));
+                }
+            }
+        }
+        for (column, thresholds) in &self.bands {
+            if negative(Some(thresholds.green))
+                || thresholds.amber.is_nan()
+                || thresholds.amber < thresholds.green
+            {
+                return Err(DiffError::invalid(
+                    "bands need 0 <= green <= amber (e.g. {\"green\": 0.01, \"amber\": 1.0})",
+                )
+                .in_column(column));
+            }
+            // Sensitive columns hold salted hashes by then
+            if self.sensitive_columns.contains(column) {
+                return Err(
+                    DiffError::invalid("bands can't apply to a sensitive column").in_column(column),
+                );
+            }
+        }
+        if self.column_parallelism == 0 {
+            return Err(DiffError::invalid("column_parallelism must be at least 1"));
+        }
+        if self
+            .partitions
+            .is_some_and(|n| n == 0 || n > partition::MAX_PARTITIONS)
+        {
+            return Err(DiffError::invalid(format!(
+                "partitions must be between 1 and {}",
+                partition::MAX_PARTITIONS
+            )));
+        }
+        if let Some(other) = self
+            .join_strategy
+            .as_deref()
+            .filter(|s| !matches!(*s, "hash" | "merge"))
+        {
+            return Err(DiffError::invalid(format!(
+                "join_strategy must be 'hash' or 'merge', got '{}'",
+                other
+            )));
+        }
+        if self
+            .timeout_seconds
+            .is_some_and(|s| !(s > 0.0 && s.is_finite()))
+        {
+            return Err(DiffError::invalid(
+                "timeout_seconds must be a positive number of seconds",
+            ));
+        }
+        if let Some(at) = self
+            .run_at
+            .as_deref()
+            .filter(|at| chrono::DateTime::parse_from_rfc3339(at).is_err())
+        {
+            return Err(DiffError::invalid(format!(
+                "run_at must be an RFC 3339 timestamp (e.g. 2024-01-31T00:00:00Z), got '{}'",
+                at
+            )));
+        }
+        for read in [&self.read_a, &self.read_b] {
+            if let Some(layout) = &read.fixed_width {
+                let names: BTreeSet<&String> = layout.iter().map(|(name, _, _)| name).collect();
+                if layout.is_empty()
+                    || names.len() < layout.len()
+                    || layout.iter().any(|&(_, _, width)| width == 0)
+                {
+                    return Err(DiffError::invalid(
+                        "fixed_width needs (name, start, width) columns with distinct names \
+                         and non-zero widths",
+                    ));
+                }
+            }
+            if let Some(csv) = &read.csv_options {
+                csv.validate()?;
+            }
+            if let Some((column, dtype)) = read
+                .dtypes
+                .iter()
+                .find(|(_, dtype)| dtypes::parse(dtype).is_none())
+            {
+                return Err(DiffError::invalid(format!(
+                    "Unknown dtype '{}' (e.g. 'str', 'int64', 'float64', 'bool', 'date', \
+                     'datetime', 'decimal(18, 2)')",
+                    dtype
+                ))
+                .in_column(column));
+            }
+            let aes_key = |key: &String| {
+                hex::decode(key).is_ok_and(|bytes| [16, 24, 32].contains(&bytes.len()))
+            };
+            let parquet_keys = read.parquet_footer_key.iter();
+            if !parquet_keys
+                .chain(read.parquet_column_keys.values())
+                .all(aes_key)
+            {
+                return Err(DiffError::invalid(
+                    "Parquet keys must be hex-encoded AES keys of 16, 24 or 32 bytes",
+                ));
+            }
+            if read.parquet_footer_key.is_none() && !read.parquet_column_keys.is_empty() {
+                return Err(DiffError::invalid(
+                    "parquet_column_keys needs the file's parquet_footer_key too",
+                ));
+            }
+            if read.sse_c_key.as_deref().is_some_and(|key| !sse_c_key(key)) {
+                return Err(DiffError::invalid(
+                    "sse_c_key must be a base64-encoded 256-bit key (44 characters)",
+                ));
+            }
+            if let Some(label) = read
+                .encoding
+                .as_deref()
+                .filter(|label| *label != "auto" && csv::encoding_for(label).is_none())
+            {
+                return Err(DiffError::invalid(format!(
+                    "Unknown encoding '{}' (e.g. 'utf-8', 'latin-1', 'windows-1252', 'auto')",
+                    label
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Per-file read options. Passed un-suffixed to apply to both files, or with
+/// an `_a` / `_b` suffix (e.g. `record_schema_a=...`) to target one file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadOptions {
+    /// Avro schema (`.avsc`) or protobuf descriptor set describing a binary
+    /// record file.
+    pub record_schema: Option<String>,
+    /// Fully-qualified protobuf message name of each record.
+    pub record_message: Option<String>,
+    /// Only read the first `limit` rows (after `offset`).
+    pub limit: Option<usize>,
+    /// Skip this many rows at the start of the file.
+    pub offset: usize,
+    /// The path is a warehouse unload manifest; its shards are read as one file.
+    pub unload_manifest: bool,
+    /// Kafka inputs: only messages at or after this time (epoch ms).
+    pub kafka_from: Option<i64>,
+    /// Kafka inputs: only messages before this time (epoch ms).
+    pub kafka_to: Option<i64>,
+    /// Kafka inputs: payload field keying the rows instead of the message key.
+    pub kafka_key_field: Option<String>,
+    /// The file holds Debezium change events, replayed to the latest state per key.
+    pub debezium: bool,
+    /// Excel inputs: sheet to read, by name or 0-based position (default the first).
+    pub sheet_name: Option<String>,
+    pub sheet_index: Option<usize>,
+    /// Excel inputs: 1-based sheet row holding the column names.
+    pub header_row: Option<usize>,
+    /// Excel inputs: A1-style range the table is read from, e.g. "B3:H200".
+    pub cell_range: Option<String>,
+    /// Names the file's dataset in the `schema_history` store (default its
+    /// path), e.g. to track a daily export whose file name changes.
+    pub dataset: Option<String>,
+    /// The file is fixed-width: one `(name, start, width)` per column,
+    /// `start` 0-based in characters.
+    pub fixed_width: Option<Vec<(String, usize, usize)>>,
+    /// Column -> type it is read as ("str", "int64", "float64", "date",
+    /// ...), overriding the type inferred; CSV columns are parsed as it.
+    pub dtypes: BTreeMap<String, String>,
+    /// CSV inputs: how the file is parsed (separator, quoting, header, ...).
+    pub csv_options: Option<CsvOptions>,
+    /// CSV inputs: character encoding ("latin-1", "windows-1252", ...), or
+    /// "auto" to detect it (default UTF-8).
+    pub encoding: Option<String>,
+    /// Parquet inputs: memory-map the file (`None` / `true`, Polars' default)
+    /// or read it into memory (`false`).
+    pub mmap: Option<bool>,
+    /// Encrypted Parquet inputs (modular encryption): the footer key,
+    /// hex-encoded. Never written to manifests.
+    #[serde(serialize_with = "redacted")]
+    pub parquet_footer_key: Option<String>,
+    /// Encrypted Parquet inputs: the keys of columns encrypted with their
+    /// own, by column path, hex-encoded. Only the columns are written to
+    /// manifests.
+    #[serde(serialize_with = "key_names")]
+    pub parquet_column_keys: HashMap<String, String>,
+    /// S3 inputs stored with SSE-C: the customer key, base64-encoded. Never
+    /// written to manifests.
+    #[serde(serialize_with = "redacted")]
+    pub sse_c_key: Option<String>,
+    /// Delta tables: read this version instead of the latest.
+    pub delta_version: Option<u64>,
+    /// Delta tables: read the last version committed at or before this time.
+    pub delta_timestamp: Option<String>,
+    /// Iceberg tables: read this snapshot instead of the current one.
+    pub iceberg_snapshot_id: Option<i64>,
+    /// Database inputs: SQL query whose result is compared.
+    pub query: Option<String>,
+    /// Database inputs: table compared whole (`schema.table` where needed).
+    pub table: Option<String>,
+    /// HTTP(S) inputs: request headers (`Authorization`, API keys). Only
+    /// their names are written to manifests.
+    #[serde(serialize_with = "key_names")]
+    pub http_headers: HashMap<String, String>,
+}
+
+impl ReadOptions {
+    /// Whether `name` is a read option (a field of the struct).
+    fn is_option(name: &str) -> bool {
+        static NAMES: LazyLock<Vec<String>> =
+            LazyLock::new(|| match serde_json::to_value(ReadOptions::default()) {
+                Ok(serde_json::Value::Object(fields)) => fields.keys().cloned().collect(),
+                _ => Vec::new(),
+            });
+        NAMES.iter().any(|n| n == name)
+    }
+
+    /// Sets `name` from `value`; returns `false` if `name` isn't a read option.
+    #[cfg(feature = "python")]
+    fn set(&mut self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<bool> {
+        match name {
+            "record_schema" => self.record_schema = value.extract()?,
+            "record_message" => self.record_message = value.extract()?,
+            "limit" => self.limit = value.extract()?,
+            "offset" => self.offset = value.extract()?,
+            "unload_manifest" => self.unload_manifest = value.extract()?,
+            "kafka_from" => self.kafka_from = value.extract()?,
+            "kafka_to" => self.kafka_to = value.extract()?,
+            "kafka_key_field" => self.kafka_key_field = value.extract()?,
+            "debezium" => self.debezium = value.extract()?,
+            "sheet_name" => self.sheet_name = value.extract()?,
+            "sheet_index" => self.sheet_index = value.extract()?,
+            "header_row" => self.header_row = value.extract()?,
+            "cell_range" => self.cell_range = value.extract()?,
+            "dataset" => self.dataset = value.extract()?,
+            "fixed_width" => self.fixed_width = value.extract()?,
+            "dtypes" => self.dtypes = value.extract()?,
+            "csv_options" => self.csv_options = pythonize::depythonize(value)?,
+            "encoding" => self.encoding = value.extract()?,
+            "mmap" => self.mmap = value.extract()?,
+            "parquet_footer_key" => self.parquet_footer_key = value.extract()?,
+            "parquet_column_keys" => self.parquet_column_keys = value.extract()?,
+            "sse_c_key" => self.sse_c_key = value.extract()?,
+            "delta_version" => self.delta_version = value.extract()?,
+            "delta_timestamp" => self.delta_timestamp = value.extract()?,
+            "iceberg_snapshot_id" => self.iceberg_snapshot_id = value.extract()?,
+            "query" => self.query = value.extract()?,
+            "table" => self.table = value.extract()?,
+            "http_headers" => self.http_headers = value.extract()?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// How a CSV file is parsed, Polars' defaults for what is left out. Single
+/// characters are given as one-character strings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CsvOptions {
+    /// Field separator, e.g. ";" (default ",").
+    pub separator: Option<String>,
+    /// Quote character (default `"`); "" reads quotes as plain characters.
+    pub quote_char: Option<String>,
+    /// Whether the first row (after `skip_rows`) holds the column names
+    /// (default true); without one, columns are named `column_1`, ...
+    pub has_header: Option<bool>,
+    /// Names of the columns, in file order: for headerless files (raw
+    /// extracts), or to replace the header's. Must match the column count.
+    pub column_names: Option<Vec<String>>,
+    /// Lines skipped before the header (preambles, export banners).
+    pub skip_rows: usize,
+    /// Lines starting with this character are skipped, e.g. "#".
+    pub comment_char: Option<String>,
+}
+
+impl CsvOptions {
+    fn validate(&self) -> Result<()> {
+        let fields = [
+            ("separator", &self.separator, false),
+            ("quote_char", &self.quote_char, true),
+            ("comment_char", &self.comment_char, false),
+        ];
+        for (name, value, may_be_empty) in fields {
+            let Some(value) = value else { continue };
+            let single = value.len() == 1 && value.is_ascii() && value != "\n";
+            if !(single || (may_be_empty && value.is_empty())) {
+                return Err(DiffError::invalid(format!(
+                    "csv_options '{}' must be a single ASCII character, got {:?}",
+                    name, value
+                )));
+            }
+        }
+        if let Some(names) = &self.column_names {
+            let unique: BTreeSet<&String> = names.iter().collect();
+            if names.is_empty() || unique.len() < names.len() {
+                return Err(DiffError::invalid(
+                    "csv_options 'column_names' must be a non-empty list of distinct names",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Request headers and column keys by name only: their values are
+/// credentials.
+fn key_names<S: Serializer>(
+    values: &HashMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    values.keys().collect::<BTreeSet<_>>().serialize(serializer)
+}
+
+/// Key material as whether it was given: "<redacted>", or null.
+fn redacted<S: Serializer>(key: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    key.as_ref().map(|_| "<redacted>").serialize(serializer)
+}
+
+/// Whether `key` is 32 bytes in standard base64: 43 characters and one "=".
+fn sse_c_key(key: &str) -> bool {
+    let base64 = |b: u8| b.is_ascii_alphanumeric() || b == b'+' || b == b'/';
+    key.len() == 44 && key.ends_with('=') && key.bytes().take(43).all(base64)
+}
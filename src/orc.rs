@@ -0,0 +1,65 @@
+// koala-diff/src/orc.rs
+// ORC files (Hive / Hadoop exports) as inputs
+
+#[cfg(feature = "orc")]
+use std::fs::File;
+#[cfg(feature = "orc")]
+use std::ptr;
+
+#[cfg(feature = "orc")]
+use arrow_array::ffi_stream::FFI_ArrowArrayStream;
+#[cfg(feature = "orc")]
+use arrow_array::RecordBatchIterator;
+#[cfg(feature = "orc")]
+use orc_rust::ArrowReaderBuilder;
+use polars::prelude::*;
+#[cfg(feature = "orc")]
+use polars_arrow::ffi::ArrowArrayStream;
+
+#[cfg(feature = "orc")]
+use crate::error::Context;
+use crate::error::{DiffError, Result};
+
+/// Reads an ORC file into a DataFrame.
+///
+/// The ORC reader yields arrow-rs record batches; they are handed to Polars
+/// through the Arrow C stream interface, as database results are, which
+/// keeps the column types (nested ones included).
+#[cfg(feature = "orc")]
+pub fn read(path: &str) -> Result<DataFrame> {
+    let file = File::open(path).stage("reading").file(path)?;
+    let reader = ArrowReaderBuilder::try_new(file)
+        .map_err(|e| orc_err(e, path))?
+        .build();
+    let schema = reader.schema();
+    let batches = reader
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| orc_err(e, path))?;
+
+    let mut exported = FFI_ArrowArrayStream::new(Box::new(RecordBatchIterator::new(
+        batches.into_iter().map(Ok),
+        schema,
+    )));
+    let stream = unsafe {
+        ptr::replace(
+            ptr::from_mut(&mut exported).cast::<ArrowArrayStream>(),
+            ArrowArrayStream::empty(),
+        )
+    };
+    unsafe { crate::ffi::read_stream(Box::new(stream), path) }
+}
+
+#[cfg(feature = "orc")]
+fn orc_err(e: impl std::fmt::Display, path: &str) -> DiffError {
+    DiffError::invalid(e.to_string())
+        .in_stage("reading")
+        .in_file(path)
+}
+
+#[cfg(not(feature = "orc"))]
+pub fn read(path: &str) -> Result<DataFrame> {
+    Err(DiffError::unsupported(
+        "ORC files can't be read: koala-diff was built without the 'orc' feature",
+    )
+    .in_file(path))
+}
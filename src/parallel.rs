@@ -0,0 +1,77 @@
+// koala-diff/src/parallel.rs
+// Column ordering and the (optionally parallel) statistics pass
+
+use std::thread;
+
+use polars::prelude::*;
+
+use crate::error::{Context, DiffError, Result};
+
+/// Schema columns with the critical ones first, each group in schema order.
+pub fn critical_first<'a>(
+    schema: &'a Schema,
+    critical: &[String],
+) -> Vec<(&'a PlSmallStr, &'a DataType)> {
+    let is_critical = |name: &PlSmallStr| critical.iter().any(|c| c == name.as_str());
+    let (mut columns, rest): (Vec<_>, Vec<_>) =
+        schema.iter().partition(|(name, _)| is_critical(name));
+    columns.extend(rest);
+    columns
+}
+
+/// Runs the one-row statistics aggregations over `joined`.
+///
+/// With `parallelism` 1 everything is a single pass. Above that, the
+/// per-column aggregations (already in priority order) are split into that
+/// many passes run on their own threads, and the one-row results are stacked
+/// side by side afterwards. `global` goes with the first pass.
+pub fn collect_stats(
+    joined: &LazyFrame,
+    global: Vec<Expr>,
+    per_column: Vec<Vec<Expr>>,
+    parallelism: usize,
+) -> Result<DataFrame> {
+    let pass = |aggs: Vec<Expr>| {
+        joined
+            .clone()
+            .select(aggs)
+            .with_new_streaming(true)
+            .collect()
+    };
+
+    if parallelism <= 1 || per_column.len() <= 1 {
+        let aggs = global.into_iter().chain(per_column.into_iter().flatten());
+        return pass(aggs.collect()).stage("computing column statistics");
+    }
+
+    let batch_size = per_column.len().div_ceil(parallelism);
+    let mut batches: Vec<Vec<Expr>> = per_column
+        .chunks(batch_size)
+        .map(|chunk| chunk.iter().flatten().cloned().collect())
+        .collect();
+    batches[0].splice(0..0, global);
+
+    let results: Vec<Result<DataFrame>> = thread::scope(|scope| {
+        let handles: Vec<_> = batches
+            .into_iter()
+            .map(|aggs| scope.spawn(move || pass(aggs)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| match handle.join() {
+                Ok(part) => part.stage("computing column statistics"),
+                Err(_) => Err(DiffError::internal("A column statistics pass panicked")
+                    .in_stage("computing column statistics")),
+            })
+            .collect()
+    });
+
+    let mut results = results.into_iter();
+    let mut stats = results.next().unwrap()?;
+    for part in results {
+        stats
+            .hstack_mut(part?.columns())
+            .stage("computing column statistics")?;
+    }
+    Ok(stats)
+}
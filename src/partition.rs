@@ -0,0 +1,143 @@
+// koala-diff/src/partition.rs
+// Key-hash partitioned execution of the statistics pass, for inputs larger than memory
+
+use polars::prelude::*;
+
+use crate::error::{Context, Result};
+use crate::parallel;
+
+/// Most partitions the planner splits a run into. Each one reads both
+/// inputs again (see `collect_stats`).
+pub const MAX_PARTITIONS: usize = 256;
+
+/// Inner join of the two sides on `keys`, B's columns suffixed `_right`.
+pub fn join(lf_a: LazyFrame, lf_b: LazyFrame, keys: &[String]) -> LazyFrame {
+    let on: Vec<Expr> = keys.iter().map(|key| col(key.as_str())).collect();
+    lf_a.join(
+        lf_b,
+        on.clone(),
+        on,
+        JoinArgs::new(JoinType::Inner).with_suffix(Some("_right".into())),
+    )
+}
+
+/// The partition of each row: a hash of its keys modulo `partitions`. Keys
+/// are hashed as strings, so equal keys land together even where the two
+/// sides store them with different integer widths.
+fn partition_of(keys: &[String], partitions: usize) -> Expr {
+    let keys: Vec<Expr> = keys
+        .iter()
+        .map(|key| col(key.as_str()).cast(DataType::String))
+        .collect();
+    as_struct(keys).hash(0, 0, 0, 0) % lit(partitions as u64)
+}
+
+/// Runs the statistics aggregations (see `parallel::collect_stats`) over the
+/// join of `lf_a` and `lf_b`.
+///
+/// With more than one partition, the key space is split by hash and each
+/// partition is joined and aggregated on its own, one after the other: only
+/// a partition's join state is held at a time, so memory stays bounded
+/// whatever the input size. Rows with equal keys always share a partition,
+/// so the partial results just add up (maxima for `_max_diff`).
+///
+/// Memory is bought with time: each partition filters both inputs from the
+/// start, so `partitions` partitions read each input `partitions` times (up
+/// to `MAX_PARTITIONS`). The planner only partitions when the join wouldn't
+/// fit otherwise, and with as few partitions as fit.
+pub fn collect_stats(
+    lf_a: LazyFrame,
+    lf_b: LazyFrame,
+    keys: &[String],
+    global: Vec<Expr>,
+    per_column: Vec<Vec<Expr>>,
+    parallelism: usize,
+    partitions: usize,
+) -> Result<DataFrame> {
+    if partitions <= 1 {
+        return parallel::collect_stats(&join(lf_a, lf_b, keys), global, per_column, parallelism);
+    }
+
+    let mut parts = Vec::with_capacity(partitions);
+    for partition in 0..partitions {
+        let in_partition = partition_of(keys, partitions).eq(lit(partition as u64));
+        let joined = join(
+            lf_a.clone().filter(in_partition.clone()),
+            lf_b.clone().filter(in_partition),
+            keys,
+        );
+        parts.push(
+            parallel::collect_stats(&joined, global.clone(), per_column.clone(), parallelism)?
+                .lazy(),
+        );
+    }
+    merge(parts)
+}
+
+/// Adds up the one-row partial results.
+fn merge(parts: Vec<LazyFrame>) -> Result<DataFrame> {
+    let mut stacked = concat(parts, UnionArgs::default()).stage("merging partition statistics")?;
+    let schema = stacked
+        .collect_schema()
+        .stage("merging partition statistics")?;
+    let totals: Vec<Expr> = schema
+        .iter_names()
+        .map(|name| match name.ends_with("_max_diff") {
+            true => col(name.clone()).max(),
+            false => col(name.clone()).sum(),
+        })
+        .collect();
+    stacked
+        .select(totals)
+        .collect()
+        .stage("merging partition statistics")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partitioned_stats_match_a_single_pass() {
+        let ids: Vec<i64> = (0..1000).collect();
+        let a = df!("id" => &ids, "v" => ids.iter().map(|i| (i % 97) as f64).collect::<Vec<_>>())
+            .unwrap();
+        let b = df!(
+            "id" => ids.iter().map(|i| *i as i32).collect::<Vec<_>>(),
+            "v" => ids.iter().map(|i| (i % 89) as f64).collect::<Vec<_>>()
+        )
+        .unwrap();
+        let keys = ["id".to_string()];
+        let diff = (col("v") - col("v_right")).abs();
+        let global = vec![len().alias("_joined")];
+        let per_column = vec![vec![
+            col("v").neq(col("v_right")).sum().alias("v_mismatches"),
+            diff.clone().sum().alias("v_sum_diff"),
+            diff.max().alias("v_max_diff"),
+        ]];
+        let stats = |partitions| {
+            collect_stats(
+                a.clone().lazy(),
+                b.clone().lazy(),
+                &keys,
+                global.clone(),
+                per_column.clone(),
+                1,
+                partitions,
+            )
+            .unwrap()
+        };
+        let single = stats(1);
+        assert_eq!(
+            single.column("_joined").unwrap().u32().unwrap().get(0),
+            Some(1000)
+        );
+        for partitions in [2, 7, MAX_PARTITIONS] {
+            assert!(
+                stats(partitions).equals(&single),
+                "{} partitions",
+                partitions
+            );
+        }
+    }
+}
@@ -0,0 +1,307 @@
+// koala-diff/src/planner.rs
+// Execution plan of a diff run: engine, join strategy and profiling, chosen from the input sizes
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::statistics::Statistics;
+use polars::prelude::*;
+use polars_plan::plans::{HintIR, Sorted};
+
+use crate::cloud;
+use crate::error::{Context, Result};
+use crate::estimate::{self, MEDIUM_BYTES};
+use crate::options::{DiffOptions, ReadOptions};
+use crate::parquet_stats;
+use crate::partition::MAX_PARTITIONS;
+use crate::result::ExecutionPlan;
+
+pub struct Plan {
+    pub report: ExecutionPlan,
+    /// Per side, whether its Parquet footer proves it sorted by the keys.
+    presorted: [bool; 2],
+}
+
+impl Plan {
+    /// Decides how to run the diff of `file_a` and `file_b`. Options set
+    /// explicitly (`streaming`, `join_strategy`, `profile`) always win; the
+    /// rest follows from the estimated peak memory of the diff (see
+    /// `estimate`) against the memory available to the process.
+    pub fn decide(file_a: &str, file_b: &str, keys: &[String], opts: &DiffOptions) -> Self {
+        let peak = estimate::estimate_pair(file_a, file_b)
+            .ok()
+            .and_then(|e| e.estimated_peak_memory_bytes);
+        let available = available_memory();
+        let mut reasons = Vec::new();
+
+        let streaming = match (opts.streaming, peak, available) {
+            (Some(streaming), _, _) => {
+                reasons.push("engine: set by the 'streaming' option".to_string());
+                streaming
+            }
+            (None, None, _) => {
+                reasons.push("engine: input sizes unknown, in memory".to_string());
+                false
+            }
+            // Half the memory left: the join's hash table and the passes'
+            // intermediate frames come on top of the inputs
+            (None, Some(peak), Some(available)) => {
+                let streaming = peak > available / 2;
+                reasons.push(format!(
+                    "engine: estimated peak {} {} half of the {} available",
+                    mib(peak),
+                    if streaming { "exceeds" } else { "fits in" },
+                    mib(available)
+                ));
+                streaming
+            }
+            (None, Some(peak), None) => {
+                let streaming = peak >= MEDIUM_BYTES;
+                reasons.push(format!(
+                    "engine: estimated peak {}, available memory unknown",
+                    mib(peak)
+                ));
+                streaming
+            }
+        };
+
+        let presorted = [(file_a, &opts.read_a), (file_b, &opts.read_b)]
+            .map(|(path, read)| sorted_by_keys(path, read, keys, opts));
+        let merge = match opts.join_strategy.as_deref() {
+            Some(strategy) => {
+                reasons.push("join: set by the 'join_strategy' option".to_string());
+                strategy == "merge"
+            }
+            // A merge join only pays off out of core, where it spares the hash
+            // table; sorting first would cost more than it saves
+            None if streaming && presorted == [true, true] => {
+                reasons.push(
+                    "join: both Parquet footers show the inputs sorted by the keys, merge join"
+                        .to_string(),
+                );
+                true
+            }
+            None if !streaming => {
+                reasons.push("join: in memory, hash join".to_string());
+                false
+            }
+            None => {
+                reasons
+                    .push("join: inputs not known to be sorted by the keys, hash join".to_string());
+                false
+            }
+        };
+
+        // Partitions only split a hash join's table; a merge join already
+        // runs in bounded memory
+        let partitions = match (opts.partitions, peak, available) {
+            (Some(partitions), _, _) => {
+                reasons.push("partitions: set by the 'partitions' option".to_string());
+                partitions
+            }
+            _ if merge => {
+                reasons.push("partitions: merge join, a single pass".to_string());
+                1
+            }
+            // Each partition's join gets a quarter of the memory left
+            (None, Some(peak), Some(available)) if peak > available / 2 => {
+                let partitions = peak
+                    .div_ceil((available / 4).max(1))
+                    .clamp(2, MAX_PARTITIONS as u64) as usize;
+                reasons.push(format!(
+                    "partitions: estimated peak {} exceeds half of the {} available, \
+                     {} key-hash partitions (each scans both inputs)",
+                    mib(peak),
+                    mib(available),
+                    partitions
+                ));
+                partitions
+            }
+            _ => {
+                reasons.push("partitions: no memory pressure known, a single pass".to_string());
+                1
+            }
+        };
+
+        let profile = match (opts.profile, peak) {
+            (Some(profile), _) => {
+                reasons.push("profile: set by the 'profile' option".to_string());
+                profile
+            }
+            (None, Some(peak)) if peak < MEDIUM_BYTES => {
+                reasons.push("profile: small inputs, profiled".to_string());
+                true
+            }
+            (None, _) => {
+                reasons.push("profile: inputs not known to be small, skipped".to_string());
+                false
+            }
+        };
+
+        Plan {
+            report: ExecutionPlan {
+                engine: if streaming { "streaming" } else { "in_memory" }.to_string(),
+                join: if merge { "merge" } else { "hash" }.to_string(),
+                partitions,
+                profile,
+                estimated_peak_memory_bytes: peak,
+                available_memory_bytes: available,
+                reasons,
+            },
+            presorted,
+        }
+    }
+
+    pub fn streaming(&self) -> bool {
+        self.report.engine == "streaming"
+    }
+
+    pub fn partitions(&self) -> usize {
+        self.report.partitions
+    }
+
+    pub fn profile(&self) -> bool {
+        self.report.profile
+    }
+
+    /// The two sides as the join should see them. For a merge join they're
+    /// sorted by the keys (ascending, nulls first), which Polars is told
+    /// without re-sorting when the footer already proves it.
+    pub fn join_inputs(
+        &self,
+        lf_a: LazyFrame,
+        lf_b: LazyFrame,
+        keys: &[String],
+    ) -> Result<(LazyFrame, LazyFrame)> {
+        if self.report.join != "merge" {
+            return Ok((lf_a, lf_b));
+        }
+        let sorted: Arc<[Sorted]> = keys
+            .iter()
+            .map(|key| Sorted {
+                column: key.as_str().into(),
+                descending: Some(false),
+                nulls_last: Some(false),
+            })
+            .collect();
+        let by: Vec<Expr> = keys.iter().map(|key| col(key.as_str())).collect();
+        let [a, b] =
+            [(lf_a, self.presorted[0]), (lf_b, self.presorted[1])].map(|(lf, presorted)| {
+                match presorted {
+                    true => lf.hint(HintIR::Sorted(sorted.clone())),
+                    false => Ok(lf.sort_by_exprs(by.clone(), SortMultipleOptions::default())),
+                }
+            });
+        Ok((
+            a.stage("sorting for the merge join")?,
+            b.stage("sorting for the merge join")?,
+        ))
+    }
+}
+
+fn mib(bytes: u64) -> String {
+    format!("{} MiB", bytes >> 20)
+}
+
+/// Memory the process can still take: the system's available memory, capped
+/// by the cgroup (container) limit. `None` where neither can be read.
+fn available_memory() -> Option<u64> {
+    let system = std::fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|meminfo| {
+            meminfo
+                .lines()
+                .find_map(|line| line.strip_prefix("MemAvailable:"))
+                .and_then(|kib| kib.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+        })
+        .map(|kib| kib << 10);
+    let cgroup_value = |name: &str| {
+        std::fs::read_to_string(Path::new("/sys/fs/cgroup").join(name))
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    // "max" (no limit) doesn't parse
+    let cgroup = cgroup_value("memory.max")
+        .map(|limit| limit.saturating_sub(cgroup_value("memory.current").unwrap_or(0)));
+    match (system, cgroup) {
+        (Some(system), Some(cgroup)) => Some(system.min(cgroup)),
+        (system, cgroup) => system.or(cgroup),
+    }
+}
+
+/// Whether the Parquet footer of `path` proves the file sorted by `keys`,
+/// ascending and without null keys: every row group declares that sort
+/// order, and the first key's statistics show the row groups in order.
+/// Anything that can't be proven from the footer counts as unsorted.
+fn sorted_by_keys(path: &str, read: &ReadOptions, keys: &[String], opts: &DiffOptions) -> bool {
+    let plain_parquet = (path.ends_with(".parquet") || path.ends_with(".pq"))
+        && read.record_schema.is_none()
+        && !read.unload_manifest
+        && !read.debezium
+        && !cloud::is_remote(path);
+    // Hashed sensitive keys no longer sort like the values
+    let hashed = keys
+        .iter()
+        .any(|key| opts.sensitive_columns.iter().any(|c| c == key));
+    if !plain_parquet || hashed || keys.is_empty() {
+        return false;
+    }
+    let Some(reader) = File::open(path)
+        .ok()
+        .and_then(|file| SerializedFileReader::new(file).ok())
+    else {
+        return false;
+    };
+
+    let mut previous_max = None;
+    for row_group in reader.metadata().row_groups() {
+        let Some(sorting) = row_group.sorting_columns() else {
+            return false;
+        };
+        let declared = sorting.len() >= keys.len()
+            && sorting.iter().zip(keys).all(|(column, key)| {
+                !column.descending
+                    && (column.column_idx as usize) < row_group.num_columns()
+                    && row_group
+                        .column(column.column_idx as usize)
+                        .column_path()
+                        .string()
+                        == *key
+            });
+        if !declared {
+            return false;
+        }
+        for column in &sorting[..keys.len()] {
+            let stats = row_group.column(column.column_idx as usize).statistics();
+            if stats.and_then(Statistics::null_count_opt) != Some(0) {
+                return false;
+            }
+        }
+
+        // Fixed-length byte arrays (decimals, ...) don't order as bytes
+        let first = row_group
+            .column(sorting[0].column_idx as usize)
+            .statistics();
+        let Some(stats) = first.filter(|s| !matches!(s, Statistics::FixedLenByteArray(_))) else {
+            return false;
+        };
+        let (min, max) = parquet_stats::bounds(stats);
+        let (Some(min), Some(max)) = (min, max) else {
+            return false;
+        };
+        // With more keys, equal first keys across a boundary could be out of
+        // order on the next ones
+        let in_order = match &previous_max {
+            None => true,
+            Some(previous) if keys.len() == 1 => *previous <= min,
+            Some(previous) => *previous < min,
+        };
+        if !in_order {
+            return false;
+        }
+        previous_max = Some(max);
+    }
+    previous_max.is_some()
+}
@@ -0,0 +1,204 @@
+// koala-diff/src/privacy.rs
+// Noised, small-cell-suppressed summary of a diff result for external publishing
+
+use indexmap::IndexMap;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::error::DiffError;
+use crate::result::DiffResult;
+
+/// How the published counts were protected.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivacyPolicy {
+    pub mechanism: &'static str,
+    pub epsilon: f64,
+    /// Counts below this get Laplace noise; larger counts are published as-is.
+    pub noise_threshold: u64,
+    /// Non-zero counts below this are suppressed (`None`).
+    pub min_cell_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishedColumn {
+    pub non_match_count: Option<u64>,
+    pub match_rate: Option<f64>,
+}
+
+/// Aggregate counts only: no samples, keys or values.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishedSummary {
+    pub total_rows_a: Option<u64>,
+    pub total_rows_b: Option<u64>,
+    pub joined_count: Option<u64>,
+    pub identical_rows_count: Option<u64>,
+    pub modified_rows_count: Option<u64>,
+    pub added: Option<u64>,
+    pub removed: Option<u64>,
+    pub column_stats: IndexMap<String, PublishedColumn>,
+    pub privacy: PrivacyPolicy,
+}
+
+struct Publisher {
+    policy: PrivacyPolicy,
+    rng: StdRng,
+}
+
+impl Publisher {
+    /// Publishes one count (sensitivity 1).
+    fn count(&mut self, n: usize) -> Option<u64> {
+        let n = n as u64;
+        if n > 0 && n < self.policy.min_cell_size {
+            return None;
+        }
+        if n >= self.policy.noise_threshold {
+            return Some(n);
+        }
+        let noisy = n as f64 + self.laplace(1.0 / self.policy.epsilon);
+        Some(noisy.round().max(0.0) as u64)
+    }
+
+    /// Laplace(0, scale) by inverse transform sampling.
+    fn laplace(&mut self, scale: f64) -> f64 {
+        let u: f64 = self.rng.random_range(-0.5..0.5);
+        -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+    }
+}
+
+pub fn publish(result: &DiffResult, policy: PrivacyPolicy, seed: Option<u64>) -> PublishedSummary {
+    let rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+    let mut publisher = Publisher { policy, rng };
+
+    let joined_count = publisher.count(result.joined_count);
+    let column_stats = result
+        .column_stats
+        .iter()
+        .map(|(name, stats)| {
+            let non_match_count = stats.non_match_count.and_then(|n| publisher.count(n));
+            // Recomputed from the published numbers so it leaks nothing extra
+            let match_rate = match (non_match_count, joined_count) {
+                (Some(bad), Some(total)) if total > 0 => {
+                    Some((1.0 - (bad.min(total) as f64 / total as f64)) * 100.0)
+                }
+                _ => None,
+            };
+            let column = PublishedColumn {
+                non_match_count,
+                match_rate,
+            };
+            (name.clone(), column)
+        })
+        .collect();
+
+    PublishedSummary {
+        total_rows_a: publisher.count(result.total_rows_a),
+        total_rows_b: publisher.count(result.total_rows_b),
+        joined_count,
+        identical_rows_count: publisher.count(result.identical_rows_count),
+        modified_rows_count: publisher.count(result.modified_rows_count),
+        added: publisher.count(result.added),
+        removed: publisher.count(result.removed),
+        column_stats,
+        privacy: publisher.policy,
+    }
+}
+
+/// Summary of a diff result that is safe to publish externally
+///
+/// Counts below `noise_threshold` get Laplace noise calibrated to `epsilon`
+/// (each count has sensitivity 1); non-zero counts below `min_cell_size` are
+/// suppressed (None). Samples, keys and values are dropped entirely.
+///
+/// Args:
+///     result (dict): Result returned by `diff_files`
+///     epsilon (float): Privacy budget per count; smaller is noisier (default 1.0)
+///     noise_threshold (int): Counts at or above this are published exactly
+///         (default 1000)
+///     min_cell_size (int): Suppress non-zero counts below this (default 10)
+///     seed (int, optional): RNG seed, for reproducible output in tests only
+///
+/// Returns:
+///     dict: Row counts, per-column non_match_count / match_rate and the
+///         "privacy" policy applied
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (result, epsilon = 1.0, noise_threshold = 1000, min_cell_size = 10, seed = None))]
+pub fn publishable_summary<'py>(
+    py: Python<'py>,
+    result: &Bound<'py, PyAny>,
+    epsilon: f64,
+    noise_threshold: u64,
+    min_cell_size: u64,
+    seed: Option<u64>,
+) -> PyResult<Bound<'py, PyAny>> {
+    if !(epsilon > 0.0 && epsilon.is_finite()) {
+        return Err(DiffError::invalid("epsilon must be a positive number").into());
+    }
+    let result: DiffResult = pythonize::depythonize(result)?;
+    let policy = PrivacyPolicy {
+        mechanism: "laplace",
+        epsilon,
+        noise_threshold,
+        min_cell_size,
+    };
+    Ok(pythonize::pythonize(py, &publish(&result, policy, seed))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(epsilon: f64) -> PrivacyPolicy {
+        PrivacyPolicy {
+            mechanism: "laplace",
+            epsilon,
+            noise_threshold: 1000,
+            min_cell_size: 10,
+        }
+    }
+
+    #[test]
+    fn laplace_noise_has_the_calibrated_scale() {
+        let mut publisher = Publisher {
+            policy: policy(0.5),
+            rng: StdRng::seed_from_u64(42),
+        };
+        let draws: Vec<f64> = (0..20_000).map(|_| publisher.laplace(2.0)).collect();
+        let mean = draws.iter().sum::<f64>() / draws.len() as f64;
+        // E|X| of Laplace(0, b) is b
+        let mean_abs = draws.iter().map(|x| x.abs()).sum::<f64>() / draws.len() as f64;
+        assert!(mean.abs() < 0.1, "mean {}", mean);
+        assert!((mean_abs - 2.0).abs() < 0.1, "mean |x| {}", mean_abs);
+    }
+
+    #[test]
+    fn seeded_summary_is_reproducible_and_protects_small_counts() {
+        let result = DiffResult {
+            total_rows_a: 5000,
+            total_rows_b: 5003,
+            joined_count: 4990,
+            modified_rows_count: 120,
+            added: 3,
+            removed: 0,
+            ..Default::default()
+        };
+        let first = publish(&result, policy(1.0), Some(7));
+        let second = publish(&result, policy(1.0), Some(7));
+        assert_eq!(
+            serde_json::to_value(&first).unwrap(),
+            serde_json::to_value(&second).unwrap()
+        );
+        // Large counts are exact, small non-zero ones suppressed, zero noised
+        assert_eq!(first.total_rows_a, Some(5000));
+        assert_eq!(first.joined_count, Some(4990));
+        assert_eq!(first.added, None);
+        assert!(first.removed.is_some());
+        assert!(first.modified_rows_count.is_some());
+    }
+}
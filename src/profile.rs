@@ -0,0 +1,635 @@
+// koala-diff/src/profile.rs
+// Per-side column profiles, computed on each file independently of the join
+
+use indexmap::IndexMap;
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::DiffOptions;
+use crate::reader;
+use crate::result::{
+    ColumnProfile, ColumnProfileComparison, ColumnStats, Histogram, ProfileComparison,
+    ProfileSampling,
+};
+
+/// Relative difference tolerated between two HyperLogLog estimates.
+const DISTINCT_TOLERANCE: f64 = 0.02;
+/// Relative difference tolerated between two means (summation order).
+const MEAN_TOLERANCE: f64 = 1e-9;
+/// Bins of the histograms compared (and the KS statistic is measured on).
+const HISTOGRAM_BINS: usize = 20;
+/// c(α) of the two-sample KS test at α = 0.05.
+const KS_CRITICAL: f64 = 1.358;
+
+/// Rows the value-frequency entropy is estimated from; larger inputs are
+/// sampled down to about this many so its group-by stays bounded.
+const ENTROPY_SAMPLE_ROWS: usize = 100_000;
+
+/// Profiles `lf`, producing one row with a `<column>_<metric>` entry per
+/// column and metric: a pass for row count and distinct-count sketches,
+/// then one estimating entropies on a seeded sample.
+pub fn profile_side(lf: LazyFrame, schema: &Schema, path: &str) -> Result<DataFrame> {
+    let mut aggs = vec![len().alias("_rows")];
+    let mut entropies = Vec::new();
+    for (col_name, _) in schema.iter() {
+        let c = col_name.as_str();
+        // HyperLogLog sketch: cheap enough to run over every column
+        aggs.push(
+            col(c)
+                .approx_n_unique()
+                .alias(format!("{}_approx_distinct", c)),
+        );
+        // Shannon entropy (bits) of the value frequency distribution
+        entropies.push(
+            col(c)
+                .unique_counts()
+                .entropy(2.0, true)
+                .alias(format!("{}_entropy", c)),
+        );
+    }
+
+    let mut profile = lf
+        .clone()
+        .select(aggs)
+        .with_new_streaming(true)
+        .collect()
+        .stage("profiling")
+        .file(path)?;
+    let rows = profile
+        .column("_rows")
+        .ok()
+        .and_then(|c| c.get(0).ok())
+        .and_then(|v| v.try_extract::<u64>().ok())
+        .unwrap_or(0) as usize;
+    let entropy = sample_rows(lf, rows, ENTROPY_SAMPLE_ROWS, 0)
+        .select(entropies)
+        .with_new_streaming(true)
+        .collect()
+        .stage("profiling")
+        .file(path)?;
+    profile
+        .hstack_mut(entropy.columns())
+        .stage("profiling")
+        .file(path)?;
+    Ok(profile)
+}
+
+/// Keeps each of the `rows` rows of `lf` with probability `size / rows`, by
+/// a hash of its position seeded with `seed`: about `size` rows, the same
+/// for the same seed. A filter, so no sort of the whole input.
+fn sample_rows(lf: LazyFrame, rows: usize, size: usize, seed: u64) -> LazyFrame {
+    if rows <= size {
+        return lf;
+    }
+    let threshold = (u64::MAX as f64 * (size as f64 / rows as f64)) as u64;
+    let position = int_range(lit(0), len(), 1, DataType::Int64).hash(seed, 0, 0, 0);
+    lf.filter(position.lt(lit(threshold)))
+}
+
+/// Copies the profile metrics for `column` from both sides into `stats`.
+pub fn fill_stats(
+    stats: &mut ColumnStats,
+    column: &str,
+    profile_a: &DataFrame,
+    profile_b: &DataFrame,
+    opts: &DiffOptions,
+) {
+    let distinct_a = metric(profile_a, column, "approx_distinct");
+    let distinct_b = metric(profile_b, column, "approx_distinct");
+    stats.approx_distinct_a = distinct_a.map(|a| a as u64);
+    stats.approx_distinct_b = distinct_b.map(|b| b as u64);
+    if let (Some(a), Some(b)) = (distinct_a, distinct_b) {
+        if a > 0.0 {
+            stats.approx_distinct_ratio = Some(b / a);
+        }
+    }
+
+    let uniqueness = |profile: &DataFrame| {
+        let rows = profile
+            .column("_rows")
+            .ok()?
+            .get(0)
+            .ok()?
+            .try_extract::<f64>()
+            .ok()?;
+        // From the sketch: an exact distinct count holds every value in memory
+        let unique = metric(profile, column, "approx_distinct")?;
+        Some(if rows > 0.0 {
+            (unique / rows).min(1.0)
+        } else {
+            0.0
+        })
+    };
+    stats.uniqueness_a = uniqueness(profile_a);
+    stats.uniqueness_b = uniqueness(profile_b);
+    stats.entropy_a = metric(profile_a, column, "entropy");
+    stats.entropy_b = metric(profile_b, column, "entropy");
+
+    // A large relative drop hints at key corruption or default-value flooding
+    let dropped = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => a > 0.0 && b < a * (1.0 - opts.drift_drop_threshold),
+        _ => false,
+    };
+    stats.uniqueness_dropped = Some(dropped(stats.uniqueness_a, stats.uniqueness_b));
+    stats.entropy_dropped = Some(dropped(stats.entropy_a, stats.entropy_b));
+}
+
+/// Reads a single profile metric for `column`, if it was computed.
+fn metric(profile: &DataFrame, column: &str, metric: &str) -> Option<f64> {
+    profile
+        .column(&format!("{}_{}", column, metric))
+        .ok()?
+        .get(0)
+        .ok()?
+        .try_extract::<f64>()
+        .ok()
+}
+
+/// One pass computing the quick-mode profile of every column of `lf`.
+fn summarize_side(
+    mut lf: LazyFrame,
+    path: &str,
+    opts: &DiffOptions,
+) -> Result<(usize, Schema, IndexMap<String, ColumnProfile>)> {
+    let schema = lf
+        .collect_schema()
+        .stage("profiling")
+        .file(path)?
+        .as_ref()
+        .clone();
+
+    let mut aggs = vec![len().alias("_rows")];
+    for (col_name, dtype) in schema.iter() {
+        let c = col_name.as_str();
+        aggs.push(col(c).null_count().alias(format!("{}_null_count", c)));
+        aggs.push(
+            col(c)
+                .approx_n_unique()
+                .alias(format!("{}_approx_distinct", c)),
+        );
+        // Sensitive columns only get counts: min/max/mean would leak values
+        if opts.sensitive_columns.iter().any(|s| s == c) {
+            continue;
+        }
+        if !dtype.is_nested() && !matches!(dtype, DataType::Binary | DataType::Null) {
+            aggs.push(
+                col(c)
+                    .min()
+                    .cast(DataType::String)
+                    .alias(format!("{}_min", c)),
+            );
+            aggs.push(
+                col(c)
+                    .max()
+                    .cast(DataType::String)
+                    .alias(format!("{}_max", c)),
+            );
+        }
+        if dtype.is_numeric() {
+            aggs.push(
+                col(c)
+                    .cast(DataType::Float64)
+                    .mean()
+                    .alias(format!("{}_mean", c)),
+            );
+        }
+    }
+
+    let df = lf
+        .select(aggs)
+        .with_new_streaming(true)
+        .collect()
+        .stage("profiling")
+        .file(path)?;
+
+    let get = |column: &str| df.column(column).ok().and_then(|c| c.get(0).ok());
+    let count = |column: &str| {
+        get(column)
+            .and_then(|v| v.try_extract::<u64>().ok())
+            .unwrap_or(0)
+    };
+    let text = |column: &str| match get(column)? {
+        AnyValue::Null => None,
+        v => Some(v.get_str().map_or_else(|| v.to_string(), str::to_string)),
+    };
+
+    let rows = count("_rows") as usize;
+    let mut profiles = IndexMap::new();
+    for col_name in schema.iter_names() {
+        let c = col_name.as_str();
+        let null_count = count(&format!("{}_null_count", c)) as usize;
+        profiles.insert(
+            c.to_string(),
+            ColumnProfile {
+                count: rows.saturating_sub(null_count),
+                null_count,
+                min: text(&format!("{}_min", c)),
+                max: text(&format!("{}_max", c)),
+                approx_distinct: count(&format!("{}_approx_distinct", c)),
+                mean: get(&format!("{}_mean", c)).and_then(|v| v.try_extract::<f64>().ok()),
+            },
+        );
+    }
+    Ok((rows, schema, profiles))
+}
+
+/// Down-sampling of both sides to a common size before profiling.
+#[derive(Debug, Clone, Default)]
+pub struct SamplingOptions {
+    /// Rows kept per side; files already this small are profiled whole.
+    pub sample_size: Option<usize>,
+    /// Sample both sides down to the smaller one's row count.
+    pub normalize_sizes: bool,
+    /// Keep each value's share of the rows (stratified sampling).
+    pub stratify_by: Option<String>,
+    pub sample_seed: Option<u64>,
+}
+
+impl SamplingOptions {
+    /// Splits `**options` into sampling options and the comparison options
+    /// used to read both files.
+    #[cfg(feature = "python")]
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<(Self, DiffOptions)> {
+        let mut opts = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok((opts, DiffOptions::default()));
+        };
+        let rest = kwargs.copy()?;
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "sample_size" => opts.sample_size = value.extract()?,
+                "normalize_sizes" => opts.normalize_sizes = value.extract()?,
+                "stratify_by" => opts.stratify_by = value.extract()?,
+                "sample_seed" => opts.sample_seed = value.extract()?,
+                _ => continue,
+            }
+            rest.del_item(&key)?;
+        }
+        if opts.sample_size == Some(0) {
+            return Err(DiffError::invalid("sample_size must be at least 1").into());
+        }
+        Ok((opts, DiffOptions::from_kwargs(Some(&rest))?))
+    }
+
+    /// The common size for sides of `rows_a` and `rows_b` rows, if sampling.
+    fn size(&self, rows_a: usize, rows_b: usize) -> Option<usize> {
+        let smaller = self.normalize_sizes.then_some(rows_a.min(rows_b));
+        match (self.sample_size, smaller) {
+            (Some(size), Some(smaller)) => Some(size.min(smaller)),
+            (size, smaller) => size.or(smaller),
+        }
+    }
+}
+
+fn count_rows(lf: &LazyFrame, path: &str) -> Result<usize> {
+    let df = lf
+        .clone()
+        .select([len().alias("_rows")])
+        .collect()
+        .stage("profiling")
+        .file(path)?;
+    Ok(df
+        .column("_rows")
+        .ok()
+        .and_then(|c| c.get(0).ok())
+        .and_then(|v| v.try_extract::<u64>().ok())
+        .unwrap_or(0) as usize)
+}
+
+/// Keeps about `size` of the `rows` rows of `lf`, each `stratify_by` value
+/// keeping its share (rounded per value).
+///
+/// Unstratified, rows are kept by a seeded hash threshold (`sample_rows`);
+/// stratified, they are ranked by that hash within each value. Either way
+/// the same seed keeps the same rows, and the sampling runs inside the
+/// profiling query.
+fn sample(
+    lf: LazyFrame,
+    rows: usize,
+    size: usize,
+    stratify_by: Option<&str>,
+    seed: u64,
+) -> LazyFrame {
+    let Some(column) = stratify_by else {
+        return sample_rows(lf, rows, size, seed);
+    };
+    if size >= rows {
+        return lf;
+    }
+    let fraction = size as f64 / rows as f64;
+    let position = int_range(lit(0), len(), 1, DataType::Int64).hash(seed, 0, 0, 0);
+    let rank = position
+        .rank(
+            RankOptions {
+                method: RankMethod::Ordinal,
+                descending: false,
+            },
+            None,
+        )
+        .over([col(column)]);
+    let quota = len().over([col(column)]).cast(DataType::Float64) * lit(fraction) + lit(0.5);
+    lf.filter(rank.cast(DataType::Float64).lt_eq(quota))
+}
+
+/// Value counts of each `(column, low, high)` of `lf` on `HISTOGRAM_BINS`
+/// equal-width bins spanning `low..=high`, in one pass.
+fn histograms(lf: LazyFrame, path: &str, ranges: &[(String, f64, f64)]) -> Result<Vec<Vec<u64>>> {
+    let mut aggs = Vec::new();
+    for (i, (name, low, high)) in ranges.iter().enumerate() {
+        let width = (high - low) / HISTOGRAM_BINS as f64;
+        let bin = if width > 0.0 {
+            ((col(name.as_str()).cast(DataType::Float64) - lit(*low)) / lit(width)).floor()
+        } else {
+            // A single value: everything lands in the first bin
+            col(name.as_str()).is_not_null().cast(DataType::Float64) - lit(1.0)
+        };
+        for k in 0..HISTOGRAM_BINS {
+            let in_bin = if k + 1 == HISTOGRAM_BINS {
+                bin.clone().gt_eq(lit(k as f64))
+            } else {
+                bin.clone().eq(lit(k as f64))
+            };
+            aggs.push(
+                in_bin
+                    .fill_null(lit(false))
+                    .cast(DataType::UInt64)
+                    .sum()
+                    .alias(format!("_bin_{}_{}", i, k)),
+            );
+        }
+    }
+    let df = lf
+        .select(aggs)
+        .with_new_streaming(true)
+        .collect()
+        .stage("profiling")
+        .file(path)?;
+    Ok((0..ranges.len())
+        .map(|i| {
+            (0..HISTOGRAM_BINS)
+                .map(|k| {
+                    df.column(&format!("_bin_{}_{}", i, k))
+                        .ok()
+                        .and_then(|c| c.get(0).ok())
+                        .and_then(|v| v.try_extract::<u64>().ok())
+                        .unwrap_or(0)
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// The largest gap between the cumulative shares of two histograms, and
+/// whether it rejects "same distribution" at 5%. `None` for an empty side.
+fn ks_statistic(counts_a: &[u64], counts_b: &[u64]) -> Option<(f64, bool)> {
+    let n_a = counts_a.iter().sum::<u64>() as f64;
+    let n_b = counts_b.iter().sum::<u64>() as f64;
+    if n_a == 0.0 || n_b == 0.0 {
+        return None;
+    }
+    let (mut cum_a, mut cum_b, mut statistic) = (0.0, 0.0, 0.0_f64);
+    for (a, b) in counts_a.iter().zip(counts_b) {
+        cum_a += *a as f64;
+        cum_b += *b as f64;
+        statistic = statistic.max((cum_a / n_a - cum_b / n_b).abs());
+    }
+    let critical = KS_CRITICAL * ((n_a + n_b) / (n_a * n_b)).sqrt();
+    Some((statistic, statistic > critical))
+}
+
+/// Names of the profile metrics that differ between `a` and `b`.
+fn profile_differences(a: &ColumnProfile, b: &ColumnProfile) -> Vec<String> {
+    let relative = |x: f64, y: f64| {
+        let scale = x.abs().max(y.abs());
+        if scale == 0.0 {
+            0.0
+        } else {
+            (x - y).abs() / scale
+        }
+    };
+
+    let mut differences = Vec::new();
+    if a.count != b.count {
+        differences.push("count");
+    }
+    if a.null_count != b.null_count {
+        differences.push("null_count");
+    }
+    if a.min != b.min {
+        differences.push("min");
+    }
+    if a.max != b.max {
+        differences.push("max");
+    }
+    if relative(a.approx_distinct as f64, b.approx_distinct as f64) > DISTINCT_TOLERANCE {
+        differences.push("approx_distinct");
+    }
+    match (a.mean, b.mean) {
+        (Some(x), Some(y)) if relative(x, y) <= MEAN_TOLERANCE => {}
+        (None, None) => {}
+        _ => differences.push("mean"),
+    }
+    differences.into_iter().map(str::to_string).collect()
+}
+
+/// Compares column profiles of two files without joining them
+///
+/// For files with no common key: each file is profiled on its own (row
+/// count, nulls, min/max, approximate distinct count, mean) and the
+/// profiles are compared column by column. Numeric columns also get
+/// histograms on shared bins and a Kolmogorov-Smirnov statistic.
+///
+/// Args:
+///     file_a (str): Path to the source (old) file
+///     file_b (str): Path to the target (new) file
+///     **options: Read options of `diff_files` (limit, offset, record_schema,
+///         ...) and sensitive_columns, whose values are not profiled, plus
+///         sample_size (int): Profile about this many rows per file
+///         normalize_sizes (bool): Sample both files down to the smaller
+///             one's row count, so counts and distincts are comparable
+///         stratify_by (str): Sample each value of this column in proportion
+///             to its share of the file
+///         sample_seed (int): Seed of the sampling (random by default)
+///
+/// Returns:
+///     dict: total_rows_a / total_rows_b, "sampling" when sampled, and
+///         per-column "profile_a", "profile_b", the "differences" between
+///         them and, for numeric columns, "histogram" and "ks_statistic"
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, **options))]
+pub fn compare_profiles<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let (sampling, opts) = SamplingOptions::from_kwargs(options)?;
+    let mut lf_a = reader::scan(&file_a, &opts.read_a)?;
+    let mut lf_b = reader::scan(&file_b, &opts.read_b)?;
+
+    let mut sampled = None;
+    let mut total_rows = None;
+    if sampling.sample_size.is_some() || sampling.normalize_sizes {
+        let rows_a = count_rows(&lf_a, &file_a)?;
+        let rows_b = count_rows(&lf_b, &file_b)?;
+        if let Some(size) = sampling.size(rows_a, rows_b) {
+            let seed = sampling.sample_seed.unwrap_or_else(rand::random);
+            let stratify_by = sampling.stratify_by.as_deref();
+            lf_a = sample(lf_a, rows_a, size, stratify_by, seed);
+            lf_b = sample(lf_b, rows_b, size, stratify_by, seed);
+            sampled = Some((size, seed));
+        }
+        total_rows = Some((rows_a, rows_b));
+    }
+
+    let (rows_a, schema_a, mut profiles_a) = summarize_side(lf_a.clone(), &file_a, &opts)?;
+    let (rows_b, schema_b, mut profiles_b) = summarize_side(lf_b.clone(), &file_b, &opts)?;
+
+    // Histograms of the numeric columns of both files, over their joint range
+    // (sensitive columns have no min/max, so none)
+    let ranges: Vec<(String, f64, f64)> = schema_a
+        .iter()
+        .filter(|(name, dtype)| {
+            dtype.is_numeric() && schema_b.get(name.as_str()).is_some_and(|d| d.is_numeric())
+        })
+        .filter_map(|(name, _)| {
+            let bound = |profile: &IndexMap<String, ColumnProfile>, max: bool| {
+                let profile = profile.get(name.as_str())?;
+                let value = if max { &profile.max } else { &profile.min };
+                value
+                    .as_deref()?
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+            };
+            let low = bound(&profiles_a, false)?.min(bound(&profiles_b, false)?);
+            let high = bound(&profiles_a, true)?.max(bound(&profiles_b, true)?);
+            Some((name.to_string(), low, high))
+        })
+        .collect();
+    let mut distributions = IndexMap::new();
+    if !ranges.is_empty() {
+        let counts_a = histograms(lf_a, &file_a, &ranges)?;
+        let counts_b = histograms(lf_b, &file_b, &ranges)?;
+        for (((name, low, high), counts_a), counts_b) in
+            ranges.into_iter().zip(counts_a).zip(counts_b)
+        {
+            let width = (high - low) / HISTOGRAM_BINS as f64;
+            let edges = (0..=HISTOGRAM_BINS)
+                .map(|k| low + width * k as f64)
+                .collect();
+            let ks = ks_statistic(&counts_a, &counts_b);
+            let histogram = Histogram {
+                edges,
+                counts_a,
+                counts_b,
+            };
+            distributions.insert(name, (histogram, ks));
+        }
+    }
+
+    let mut columns = IndexMap::new();
+    let names = schema_a.iter_names().chain(
+        schema_b
+            .iter_names()
+            .filter(|n| !schema_a.contains(n.as_str())),
+    );
+    for name in names {
+        let dtype = |schema: &Schema| {
+            schema
+                .get(name.as_str())
+                .map_or_else(|| "MISSING".to_string(), |d| format!("{:?}", d))
+        };
+        let profile_a = profiles_a.shift_remove(name.as_str());
+        let profile_b = profiles_b.shift_remove(name.as_str());
+        let mut differences = match (&profile_a, &profile_b) {
+            (Some(a), Some(b)) => profile_differences(a, b),
+            _ => vec!["presence".to_string()],
+        };
+        let (histogram, ks) = match distributions.shift_remove(name.as_str()) {
+            Some((histogram, ks)) => (Some(histogram), ks),
+            None => (None, None),
+        };
+        if ks.is_some_and(|(_, rejected)| rejected) {
+            differences.push("distribution".to_string());
+        }
+        columns.insert(
+            name.to_string(),
+            ColumnProfileComparison {
+                source_dtype: dtype(&schema_a),
+                target_dtype: dtype(&schema_b),
+                profile_a,
+                profile_b,
+                differences,
+                histogram,
+                ks_statistic: ks.map(|(statistic, _)| statistic),
+            },
+        );
+    }
+
+    let (total_rows_a, total_rows_b) = total_rows.unwrap_or((rows_a, rows_b));
+    let comparison = ProfileComparison {
+        total_rows_a,
+        total_rows_b,
+        sampling: sampled.map(|(size, seed)| ProfileSampling {
+            sample_size: size,
+            stratify_by: sampling.stratify_by.clone(),
+            seed,
+            sampled_rows_a: rows_a,
+            sampled_rows_b: rows_b,
+        }),
+        columns,
+    };
+    Ok(pythonize::pythonize(py, &comparison)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(a: Vec<f64>, b: Vec<f64>) -> (Vec<u64>, Vec<u64>) {
+        let ranges = [("x".to_string(), 0.0, 200.0)];
+        let a = histograms(df!("x" => a).unwrap().lazy(), "a", &ranges).unwrap();
+        let b = histograms(df!("x" => b).unwrap().lazy(), "b", &ranges).unwrap();
+        (a[0].clone(), b[0].clone())
+    }
+
+    #[test]
+    fn ks_flags_a_shifted_distribution() {
+        let a = (0..1000).map(|i| i as f64 / 10.0).collect();
+        let b = (0..1000).map(|i| 100.0 + i as f64 / 10.0).collect();
+        let (a, b) = counts(a, b);
+        let (statistic, different) = ks_statistic(&a, &b).unwrap();
+        assert!(statistic > 0.9);
+        assert!(different);
+    }
+
+    #[test]
+    fn ks_passes_an_identical_distribution() {
+        let values: Vec<f64> = (0..1000).map(|i| (i * 7 % 200) as f64).collect();
+        let (a, b) = counts(values.clone(), values);
+        assert_eq!(ks_statistic(&a, &b), Some((0.0, false)));
+    }
+
+    #[test]
+    fn sampling_keeps_about_the_target_and_repeats_with_the_seed() {
+        let lf = df!("x" => (0..100_000i64).collect::<Vec<_>>())
+            .unwrap()
+            .lazy();
+        let kept = |seed| {
+            sample(lf.clone(), 100_000, 10_000, None, seed)
+                .collect()
+                .unwrap()
+        };
+        let first = kept(7);
+        assert!((9_000..11_000).contains(&first.height()));
+        assert!(first.equals(&kept(7)));
+        assert!(!first.equals(&kept(8)));
+    }
+}
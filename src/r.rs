@@ -0,0 +1,161 @@
+// koala-diff/src/r.rs
+// R bindings (extendr): the same engine, results as data.frames
+
+use extendr_api::prelude::*;
+
+use crate::error::DiffError;
+use crate::options::DiffOptions;
+use crate::reader::Input;
+use crate::render::{Registry, RenderOptions};
+use crate::result::DiffResult;
+
+/// Diffs `file_a` against `file_b` on `keys`. `options` is a JSON object of
+/// `diff_files` keyword arguments (the R wrapper builds it from `...`).
+///
+/// Returns a list of data.frames (`summary`, one row; `columns`, one row per
+/// column; `schema_diff`) and `json`, the full result as Python returns it,
+/// which `render` reads.
+#[extendr]
+fn diff_files(file_a: &str, file_b: &str, keys: Vec<String>, options: &str) -> Result<List> {
+    let options = serde_json::from_str(options).map_err(|e| Error::Other(e.to_string()))?;
+    let opts = DiffOptions::from_json(options).map_err(r_error)?;
+    let inputs = [
+        Input::Path(file_a.to_string()),
+        Input::Path(file_b.to_string()),
+    ];
+    let result = crate::run_diff(inputs, keys, opts, None).map_err(r_error)?;
+    let json = serde_json::to_string(&result).map_err(|e| Error::Other(e.to_string()))?;
+
+    List::from_names_and_values(
+        ["summary", "columns", "schema_diff", "json"],
+        [
+            summary(&result)?,
+            columns(&result)?,
+            schema_diff(&result)?,
+            json.into(),
+        ],
+    )
+}
+
+/// The report of a `diff_files` result (its `json`) in one of the built-in
+/// formats: a string, or a raw vector for xlsx.
+#[extendr]
+fn render(json: &str, format: &str) -> Result<Robj> {
+    let reason = |e: &dyn std::fmt::Display| Error::Other(e.to_string());
+    let result: DiffResult = serde_json::from_str(json).map_err(|e| reason(&e))?;
+    let registry = Registry::builtin();
+    let renderer = registry.get(format).map_err(|e| reason(&e))?;
+    let output = renderer
+        .render(&result, &RenderOptions::default())
+        .map_err(|e| reason(&e))?;
+    if renderer.is_binary() {
+        Ok(Raw::from_bytes(&output).into())
+    } else {
+        Ok(String::from_utf8_lossy(&output).into_owned().into())
+    }
+}
+
+fn summary(result: &DiffResult) -> Result<Robj> {
+    let count = |n: usize| -> Robj { Doubles::from_values([n as f64]).into() };
+    let flag = |b: bool| -> Robj { Logicals::from_values([b]).into() };
+    data_frame(
+        1,
+        vec![
+            ("total_rows_a", count(result.total_rows_a)),
+            ("total_rows_b", count(result.total_rows_b)),
+            ("joined_count", count(result.joined_count)),
+            ("identical_rows_count", count(result.identical_rows_count)),
+            ("modified_rows_count", count(result.modified_rows_count)),
+            ("added", count(result.added)),
+            ("removed", count(result.removed)),
+            ("identical", flag(result.identical)),
+            ("timed_out", flag(result.timed_out)),
+        ],
+    )
+}
+
+fn columns(result: &DiffResult) -> Result<Robj> {
+    let stats: Vec<_> = result.column_stats.values().collect();
+    let text = |f: &dyn Fn(usize) -> Option<String>| -> Robj {
+        Strings::from_values((0..stats.len()).map(|i| f(i).map_or_else(Rstr::na, Rstr::from)))
+            .into()
+    };
+    let number = |f: &dyn Fn(usize) -> Option<f64>| -> Robj {
+        Doubles::from_values((0..stats.len()).map(|i| Rfloat::from(f(i)))).into()
+    };
+    let flag = |f: &dyn Fn(usize) -> bool| -> Robj {
+        Logicals::from_values((0..stats.len()).map(f)).into()
+    };
+    // Counts as doubles: R integers stop at 2^31
+    let count = |n: Option<usize>| n.map(|n| n as f64);
+    data_frame(
+        stats.len(),
+        vec![
+            ("column", text(&|i| Some(stats[i].column_name.clone()))),
+            ("is_key", flag(&|i| stats[i].is_key)),
+            (
+                "source_dtype",
+                text(&|i| Some(stats[i].source_dtype.clone())),
+            ),
+            (
+                "target_dtype",
+                text(&|i| Some(stats[i].target_dtype.clone())),
+            ),
+            ("compared", flag(&|i| stats[i].compared)),
+            ("all_match", flag(&|i| stats[i].all_match)),
+            ("total_count", number(&|i| count(stats[i].total_count))),
+            ("match_count", number(&|i| count(stats[i].match_count))),
+            (
+                "non_match_count",
+                number(&|i| count(stats[i].non_match_count)),
+            ),
+            ("match_rate", number(&|i| stats[i].match_rate)),
+            ("max_value_diff", number(&|i| stats[i].max_value_diff)),
+            (
+                "null_count_diff",
+                number(&|i| stats[i].null_count_diff.map(|n| n as f64)),
+            ),
+            ("skip_reason", text(&|i| stats[i].skip_reason.clone())),
+        ],
+    )
+}
+
+fn schema_diff(result: &DiffResult) -> Result<Robj> {
+    let entries = &result.schema_diff;
+    let text = |f: &dyn Fn(usize) -> Option<String>| -> Robj {
+        Strings::from_values((0..entries.len()).map(|i| f(i).map_or_else(Rstr::na, Rstr::from)))
+            .into()
+    };
+    data_frame(
+        entries.len(),
+        vec![
+            ("column", text(&|i| Some(entries[i].column.clone()))),
+            ("change", text(&|i| Some(entries[i].change.clone()))),
+            ("from", text(&|i| entries[i].from.clone())),
+            ("to", text(&|i| entries[i].to.clone())),
+            (
+                "lossy",
+                Logicals::from_values(entries.iter().map(|e| e.lossy)).into(),
+            ),
+        ],
+    )
+}
+
+/// A data.frame of `columns`, each `rows` long.
+fn data_frame(rows: usize, columns: Vec<(&str, Robj)>) -> Result<Robj> {
+    let (names, values): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+    let mut frame = List::from_names_and_values(names, values)?;
+    frame.set_attrib("row.names", Integers::from_values(1..=rows as i32))?;
+    frame.set_class(["data.frame"])?;
+    Ok(frame.into())
+}
+
+fn r_error(e: DiffError) -> Error {
+    Error::Other(e.to_string())
+}
+
+extendr_module! {
+    mod koaladiff;
+    fn diff_files;
+    fn render;
+}
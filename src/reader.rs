@@ -0,0 +1,236 @@
+// koala-diff/src/reader.rs
+// Input readers: maps a path (plus per-file read options) to a LazyFrame
+
+use std::borrow::Cow;
+
+use polars::prelude::*;
+
+use crate::allowlist;
+use crate::cdc;
+use crate::cloud;
+use crate::csv;
+use crate::database;
+use crate::decrypt;
+use crate::delta;
+use crate::dictionary;
+use crate::dtypes;
+use crate::error::{Context, DiffError, Result};
+use crate::excel;
+use crate::fixed_width;
+use crate::fx;
+use crate::http;
+use crate::iceberg;
+use crate::kafka;
+use crate::options::{DiffOptions, ReadOptions};
+use crate::orc;
+use crate::records;
+use crate::sensitive;
+use crate::shards;
+
+/// One side of a diff: a path (or URI) to read, or a frame the caller
+/// already holds (Arrow streams handed over the C ABI).
+pub enum Input {
+    Path(String),
+    /// The label names the frame in messages and the manifest.
+    Frame {
+        label: String,
+        lf: LazyFrame,
+    },
+}
+
+impl Input {
+    /// What messages and the manifest call this side; database passwords
+    /// are masked.
+    pub fn label(&self) -> Cow<'_, str> {
+        match self {
+            Input::Path(path) => database::redact(path),
+            Input::Frame { label, .. } => Cow::Borrowed(label),
+        }
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Input::Path(path) => Some(path),
+            Input::Frame { .. } => None,
+        }
+    }
+
+    /// The rows of this side, as `scan_keyed` reads them. Read options only
+    /// apply to paths.
+    pub fn scan(&self, read: &ReadOptions, keys: &[String]) -> Result<LazyFrame> {
+        match self {
+            Input::Path(path) => scan_keyed(path, read, keys),
+            Input::Frame { lf, .. } => Ok(lf.clone()),
+        }
+    }
+}
+
+/// Opens `path` lazily, picking the reader from the read options or the extension.
+pub fn scan(path: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    Ok(window(dtypes::cast(scan_format(path, read)?, read)?, read))
+}
+
+/// Like `scan`, for CSV text held in memory (uploads, in the browser build)
+/// that `label` names in messages.
+#[cfg(feature = "wasm")]
+pub fn scan_csv(label: &str, bytes: Vec<u8>, read: &ReadOptions) -> Result<LazyFrame> {
+    Ok(window(
+        dtypes::cast(csv::read_buffer(label, bytes, read)?, read)?,
+        read,
+    ))
+}
+
+/// Like `scan`, for a file whose rows are identified by `keys`: change-event
+/// files are replayed to their latest state per key first.
+pub fn scan_keyed(path: &str, read: &ReadOptions, keys: &[String]) -> Result<LazyFrame> {
+    if read.debezium && !cloud::is_remote(path) {
+        let lf = dtypes::cast(cdc::materialize(path, read, keys)?, read)?;
+        return Ok(window(lf, read));
+    }
+    scan(path, read)
+}
+
+fn window(lf: LazyFrame, read: &ReadOptions) -> LazyFrame {
+    // Row window for quick iterations; Polars pushes the slice into the scan
+    match (read.offset, read.limit) {
+        (0, None) => lf,
+        (offset, limit) => lf.slice(offset as i64, limit.map_or(IdxSize::MAX, |n| n as IdxSize)),
+    }
+}
+
+/// Opens one side of a keyed comparison: read options, data dictionary, key
+/// allowlist, FX conversion and sensitive-column hashing applied.
+pub fn open(
+    path: &str,
+    read: &ReadOptions,
+    keys: &[String],
+    opts: &DiffOptions,
+) -> Result<LazyFrame> {
+    let dataset = read.dataset.as_deref().unwrap_or(path);
+    let lf = dictionary::normalize(scan_keyed(path, read, keys)?, opts, dataset)?;
+    let lf = allowlist::apply(lf, keys, opts)?;
+    sensitive::mask(fx::convert(lf, opts)?, opts)
+}
+
+pub fn scan_format(path: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    if read.sse_c_key.is_some() && !cloud::is_s3(path) {
+        return Err(
+            DiffError::unsupported("'sse_c_key' only applies to s3:// objects").in_file(path),
+        );
+    }
+    if let Some(target) = path.strip_prefix("kafka://") {
+        return kafka::scan(target, read);
+    }
+    if database::is_uri(path) || database::is_sqlite_file(path) {
+        return database::scan(path, read);
+    }
+    if http::is_url(path) {
+        return http::read(path, read);
+    }
+    if cloud::is_remote(path) {
+        return cloud::scan(path, read);
+    }
+    if read.unload_manifest {
+        return shards::scan(path, read);
+    }
+    if read.fixed_width.is_some() {
+        return fixed_width::read(path, read).map(|df| df.lazy());
+    }
+    if let Some(schema_path) = &read.record_schema {
+        // Schema-described binary records (Avro datums / protobuf messages)
+        return records::read_records(path, schema_path, read.record_message.as_deref())
+            .map(|df| df.lazy());
+    }
+
+    if delta::is_table(path) {
+        return delta::scan(path, read);
+    }
+    if iceberg::is_table(path) {
+        return iceberg::scan(path, read);
+    }
+    if path.ends_with(".avro") {
+        // Avro object container files carry their own schema
+        return records::read_avro(path).map(|df| df.lazy());
+    }
+    if path.ends_with(".orc") {
+        return orc::read(path).map(|df| df.lazy());
+    }
+    if excel::is_workbook(path) {
+        return excel::read(path, read).map(|df| df.lazy());
+    }
+
+    let is_parquet = path.ends_with(".parquet") || path.ends_with(".pq");
+    let is_json = [".json", ".jsonl", ".ndjson"]
+        .iter()
+        .any(|ext| path.ends_with(ext));
+    let csv_only = [
+        ("csv_options", read.csv_options.is_some()),
+        ("encoding", read.encoding.is_some()),
+    ];
+    if let Some((option, _)) = csv_only
+        .iter()
+        .find(|(_, set)| *set && (is_parquet || is_json))
+    {
+        return Err(
+            DiffError::unsupported(format!("'{}' only applies to CSV files", option)).in_file(path),
+        );
+    }
+    if read.parquet_footer_key.is_some() {
+        if !is_parquet {
+            return Err(DiffError::unsupported(
+                "'parquet_footer_key' only applies to Parquet files",
+            )
+            .in_file(path));
+        }
+        return decrypt::read(path, read).map(|df| df.lazy());
+    }
+    if read.encoding.is_some() && !is_parquet && !is_json {
+        return csv::read_encoded(path, read);
+    }
+    if read.mmap.is_some() && !is_parquet {
+        return Err(
+            DiffError::unsupported("'mmap' only applies to local Parquet files").in_file(path),
+        );
+    }
+
+    if is_parquet {
+        return scan_parquet(path, read);
+    }
+    let lf = if path.ends_with(".jsonl") || path.ends_with(".ndjson") {
+        // One JSON object per line (event logs, exports), scanned lazily
+        LazyJsonLineReader::new(path.into()).finish()
+    } else if path.ends_with(".json") {
+        // Standard JSON doesn't have a native lazy scanner in Polars
+        let file = std::fs::File::open(path).stage("reading").file(path)?;
+        JsonReader::new(file).finish().map(DataFrame::lazy)
+    } else {
+        csv::finish(csv::reader(path, read), read.csv_options.as_ref())
+    };
+    lf.stage("reading").file(path)
+}
+
+/// Opens a local Parquet file, memory-mapped unless `mmap` is false.
+#[cfg(feature = "parquet")]
+fn scan_parquet(path: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    let lf = if read.mmap == Some(false) {
+        // Read into memory instead of mapping: a mapped file truncated or
+        // replaced underneath (network filesystems) would crash the process
+        let bytes = std::fs::read(path).stage("reading").file(path)?;
+        ParquetReader::new(std::io::Cursor::new(bytes))
+            .finish()
+            .map(DataFrame::lazy)
+    } else {
+        // Polars memory-maps local files: pages come from the OS page cache,
+        // so repeat diffs of the same files skip the disk
+        LazyFrame::scan_parquet(path.into(), Default::default())
+    };
+    lf.stage("reading").file(path)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn scan_parquet(path: &str, _read: &ReadOptions) -> Result<LazyFrame> {
+    Err(DiffError::unsupported(
+        "Parquet files can't be read: koala-diff was built without the 'parquet' feature",
+    )
+    .in_file(path))
+}
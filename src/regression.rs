@@ -0,0 +1,170 @@
+// koala-diff/src/regression.rs
+// Diff of two diff results (e.g. yesterday's run vs today's)
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::Serialize;
+
+use crate::result::DiffResult;
+
+/// How a column's match rate moved between two runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+    Improved,
+    Regressed,
+    Unchanged,
+    /// Only in the current run.
+    Added,
+    /// Only in the previous run.
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnTrend {
+    pub column: String,
+    pub trend: Trend,
+    pub previous_match_rate: Option<f64>,
+    pub current_match_rate: Option<f64>,
+    pub match_rate_delta: Option<f64>,
+    pub non_match_delta: Option<i64>,
+    /// Keys among the current run's mismatch samples (at most 5 per column)
+    /// that weren't among the previous run's. A sample, not the set of keys
+    /// that started mismatching: a key missing here may still have regressed.
+    pub sampled_newly_regressed_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultComparison {
+    pub modified_rows_delta: i64,
+    pub added_delta: i64,
+    pub removed_delta: i64,
+    pub columns: Vec<ColumnTrend>,
+    pub improved_columns: Vec<String>,
+    pub regressed_columns: Vec<String>,
+    /// Union of the per-column sampled newly regressed keys; as sampled, not
+    /// exhaustive.
+    pub sampled_newly_regressed_keys: Vec<String>,
+}
+
+/// Compares a previous diff result against the current one.
+pub fn compare(previous: &DiffResult, current: &DiffResult) -> ResultComparison {
+    let delta = |prev: usize, curr: usize| curr as i64 - prev as i64;
+    let mut columns = Vec::new();
+
+    for (name, curr) in &current.column_stats {
+        let Some(prev) = previous.column_stats.get(name) else {
+            columns.push(ColumnTrend {
+                column: name.clone(),
+                trend: Trend::Added,
+                previous_match_rate: None,
+                current_match_rate: curr.match_rate,
+                match_rate_delta: None,
+                non_match_delta: None,
+                sampled_newly_regressed_keys: Vec::new(),
+            });
+            continue;
+        };
+
+        let rate_delta = match (prev.match_rate, curr.match_rate) {
+            (Some(p), Some(c)) => Some(c - p),
+            _ => None,
+        };
+        let trend = match rate_delta {
+            Some(d) if d > 1e-9 => Trend::Improved,
+            Some(d) if d < -1e-9 => Trend::Regressed,
+            _ => Trend::Unchanged,
+        };
+
+        let prev_keys = prev.mismatched_sample_keys.as_deref().unwrap_or_default();
+        let sampled_newly_regressed_keys = curr
+            .mismatched_sample_keys
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .filter(|k| !prev_keys.contains(k))
+            .cloned()
+            .collect();
+
+        columns.push(ColumnTrend {
+            column: name.clone(),
+            trend,
+            previous_match_rate: prev.match_rate,
+            current_match_rate: curr.match_rate,
+            match_rate_delta: rate_delta,
+            non_match_delta: match (prev.non_match_count, curr.non_match_count) {
+                (Some(p), Some(c)) => Some(delta(p, c)),
+                _ => None,
+            },
+            sampled_newly_regressed_keys,
+        });
+    }
+    for (name, prev) in &previous.column_stats {
+        if !current.column_stats.contains_key(name) {
+            columns.push(ColumnTrend {
+                column: name.clone(),
+                trend: Trend::Removed,
+                previous_match_rate: prev.match_rate,
+                current_match_rate: None,
+                match_rate_delta: None,
+                non_match_delta: None,
+                sampled_newly_regressed_keys: Vec::new(),
+            });
+        }
+    }
+
+    let with_trend = |trend: Trend| {
+        columns
+            .iter()
+            .filter(|c| c.trend == trend)
+            .map(|c| c.column.clone())
+            .collect::<Vec<_>>()
+    };
+    let improved_columns = with_trend(Trend::Improved);
+    let regressed_columns = with_trend(Trend::Regressed);
+
+    let mut sampled_newly_regressed_keys: Vec<String> = Vec::new();
+    for key in columns.iter().flat_map(|c| &c.sampled_newly_regressed_keys) {
+        if !sampled_newly_regressed_keys.contains(key) {
+            sampled_newly_regressed_keys.push(key.clone());
+        }
+    }
+
+    ResultComparison {
+        modified_rows_delta: delta(previous.modified_rows_count, current.modified_rows_count),
+        added_delta: delta(previous.added, current.added),
+        removed_delta: delta(previous.removed, current.removed),
+        columns,
+        improved_columns,
+        regressed_columns,
+        sampled_newly_regressed_keys,
+    }
+}
+
+/// Compares two results returned by `diff_files` (previous run vs current run)
+///
+/// Args:
+///     previous (dict): The older diff result
+///     current (dict): The newer diff result
+///
+/// Returns:
+///     dict: {
+///         "modified_rows_delta": int,
+///         "added_delta": int,
+///         "removed_delta": int,
+///         "columns": list[dict],          // per-column trend and match rate delta
+///         "improved_columns": list[str],
+///         "regressed_columns": list[str],
+///         "sampled_newly_regressed_keys": list[str],  // from the mismatch samples only
+///     }
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn compare_results<'py>(
+    py: Python<'py>,
+    previous: &Bound<'py, PyAny>,
+    current: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let previous: DiffResult = pythonize::depythonize(previous)?;
+    let current: DiffResult = pythonize::depythonize(current)?;
+    Ok(pythonize::pythonize(py, &compare(&previous, &current))?)
+}
@@ -0,0 +1,90 @@
+// koala-diff/src/render/format.rs
+// Locale-aware number formatting shared by every renderer
+
+/// Separators and precision used when printing numbers in reports.
+#[derive(Debug, Clone)]
+pub struct NumberFormat {
+    pub thousands_separator: String,
+    pub decimal_separator: String,
+    /// Decimal places for plain floats (e.g. max value diff).
+    pub decimal_places: usize,
+    /// Decimal places for percentages.
+    pub percent_decimals: usize,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::for_locale("en")
+    }
+}
+
+impl NumberFormat {
+    /// Separator presets for common locales, by language and region where it
+    /// differs (Swiss "de_CH" groups with '); unknown locales fall back to `en`.
+    pub fn for_locale(locale: &str) -> Self {
+        let mut parts = locale.split(['_', '-']);
+        let language = parts.next().unwrap_or("en");
+        let region = parts.next().unwrap_or("").to_uppercase();
+        let (thousands, decimal) = match (language, region.as_str()) {
+            ("de" | "fr" | "it", "CH") => ("'", "."),
+            ("de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da", _) => (".", ","),
+            ("fr" | "pl" | "cs" | "sv" | "fi" | "nb" | "ru" | "uk", _) => ("\u{202f}", ","),
+            _ => (",", "."),
+        };
+        Self {
+            thousands_separator: thousands.to_string(),
+            decimal_separator: decimal.to_string(),
+            decimal_places: 4,
+            percent_decimals: 1,
+        }
+    }
+
+    pub fn integer(&self, value: i64) -> String {
+        let sign = if value < 0 { "-" } else { "" };
+        format!("{}{}", sign, self.group(&value.unsigned_abs().to_string()))
+    }
+
+    pub fn float(&self, value: f64) -> String {
+        self.fixed(value, self.decimal_places)
+    }
+
+    /// `value` is already scaled to 0..100.
+    pub fn percent(&self, value: f64) -> String {
+        format!("{}%", self.fixed(value, self.percent_decimals))
+    }
+
+    fn fixed(&self, value: f64, decimals: usize) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        let raw = format!("{:.*}", decimals, value.abs());
+        let (int_part, frac_part) = raw.split_once('.').unwrap_or((&raw, ""));
+        let sign = if value < 0.0 && raw.chars().any(|c| c != '0' && c != '.') {
+            "-"
+        } else {
+            ""
+        };
+        if frac_part.is_empty() {
+            format!("{}{}", sign, self.group(int_part))
+        } else {
+            format!(
+                "{}{}{}{}",
+                sign,
+                self.group(int_part),
+                self.decimal_separator,
+                frac_part
+            )
+        }
+    }
+
+    fn group(&self, digits: &str) -> String {
+        let mut out = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push_str(&self.thousands_separator);
+            }
+            out.push(c);
+        }
+        out
+    }
+}
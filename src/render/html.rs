@@ -0,0 +1,455 @@
+// koala-diff/src/render/html.rs
+// Self-contained HTML dashboard (inline CSS, no scripts)
+
+use std::fmt::Write;
+
+use super::{RenderError, RenderOptions, Renderer};
+use crate::result::{ColumnStats, DiffResult};
+
+pub struct HtmlRenderer;
+
+const STYLE: &str = r#"
+:root {
+    --bg-page: #f9fafb;
+    --bg-card: #ffffff;
+    --text-main: #111827;
+    --text-secondary: #4b5563;
+    --text-muted: #9ca3af;
+    --primary: #4f46e5;
+    --success: #10b981;
+    --success-bg: #ecfdf5;
+    --danger: #ef4444;
+    --danger-bg: #fef2f2;
+    --warning: #f59e0b;
+    --border: #e5e7eb;
+    --font-sans: 'Inter', system-ui, -apple-system, sans-serif;
+    --font-mono: 'JetBrains Mono', monospace;
+}
+* { box-sizing: border-box; }
+body {
+    margin: 0;
+    font-family: var(--font-sans);
+    background-color: var(--bg-page);
+    color: var(--text-main);
+    -webkit-font-smoothing: antialiased;
+    line-height: 1.5;
+}
+.container { max-width: 1280px; margin: 0 auto; padding: 40px 20px; }
+.header { display: flex; align-items: center; justify-content: space-between; margin-bottom: 40px; }
+.brand { display: flex; align-items: center; gap: 12px; }
+.logo {
+    width: 44px; height: 44px; border-radius: 12px;
+    display: flex; align-items: center; justify-content: center;
+    font-size: 24px; background: var(--primary); color: white;
+    box-shadow: 0 4px 10px rgba(79, 70, 229, 0.2);
+}
+.title-area h1 { margin: 0; font-size: 24px; font-weight: 800; letter-spacing: -0.025em; }
+.title-area h1 small {
+    font-size: 11px; vertical-align: middle; background: #f1f5f9;
+    padding: 2px 6px; border-radius: 4px; margin-left: 8px; color: #64748b;
+}
+.title-area p { margin: 4px 0 0; color: var(--text-secondary); font-size: 14px; }
+.timestamp { font-size: 11px; color: var(--text-muted); font-weight: 500; letter-spacing: 0.05em; }
+.stats-container { display: grid; grid-template-columns: repeat(3, 1fr); gap: 20px; margin-bottom: 32px; }
+.stat-card {
+    background: var(--bg-card); padding: 20px; border-radius: 16px;
+    border: 1px solid var(--border); box-shadow: 0 1px 2px rgba(0,0,0,0.05);
+}
+.stat-label { font-size: 12px; font-weight: 600; color: var(--text-secondary); margin-bottom: 8px; }
+.stat-value { font-size: 24px; font-weight: 700; letter-spacing: -0.02em; }
+.stat-note { font-size: 11px; color: var(--text-muted); margin-top: 4px; }
+.val-primary { color: var(--primary); }
+.val-success { color: var(--success); }
+.val-danger { color: var(--danger); }
+.val-warning { color: var(--warning); }
+.section {
+    background: var(--bg-card); border-radius: 16px; border: 1px solid var(--border);
+    box-shadow: 0 1px 3px rgba(0,0,0,0.1); margin-bottom: 32px; overflow: hidden;
+}
+.section-header {
+    padding: 20px 24px; border-bottom: 1px solid var(--border);
+    display: flex; align-items: center; justify-content: space-between;
+}
+.section-header h2 { margin: 0; font-size: 16px; font-weight: 700; }
+.volumes { padding: 24px; display: grid; grid-template-columns: 1fr 1fr; gap: 40px; }
+.volumes h3 { font-size: 13px; text-transform: uppercase; color: var(--text-muted); margin: 0 0 16px; letter-spacing: 0.05em; }
+.volume { display: flex; justify-content: space-between; align-items: center; font-size: 14px; margin-bottom: 12px; }
+.volume span:last-child { font-family: var(--font-mono); font-weight: 600; }
+.volume.total { color: var(--primary); font-weight: 700; border-top: 1px solid var(--border); padding-top: 12px; }
+.note { padding: 10px; background: #f8fafc; border-radius: 8px; font-size: 12px; color: var(--text-secondary); }
+.table-wrapper { overflow-x: auto; }
+table { width: 100%; border-collapse: collapse; text-align: left; }
+th {
+    background: #fdfdfd; padding: 12px 24px; font-size: 11px; font-weight: 600;
+    text-transform: uppercase; letter-spacing: 0.05em; color: var(--text-muted);
+    border-bottom: 1px solid var(--border);
+}
+td { padding: 14px 24px; font-size: 13px; border-bottom: 1px solid #f3f4f6; vertical-align: middle; }
+tr:last-child td { border-bottom: none; }
+.num { text-align: right; font-family: var(--font-mono); }
+.match-rate { display: flex; align-items: center; gap: 12px; }
+.progress-track { flex: 1; height: 8px; background: #f3f4f6; border-radius: 4px; overflow: hidden; min-width: 60px; }
+.progress-fill { height: 100%; border-radius: 4px; }
+.badge {
+    display: inline-flex; align-items: center; padding: 4px 10px; border-radius: 6px;
+    font-size: 11px; font-weight: 700; letter-spacing: 0.02em; text-transform: uppercase;
+}
+.badge-key { background: #eff6ff; color: #1e40af; border: 1px solid #dbeafe; padding: 2px 6px; font-size: 9px; }
+.badge-pass { background: var(--success-bg); color: var(--success); }
+.badge-fail { background: var(--danger-bg); color: var(--danger); }
+.badge-change { background: #f3f4f6; color: var(--text-secondary); border: 1px solid var(--border); }
+.tags { color: var(--text-muted); font-size: 10px; display: block; }
+code {
+    font-family: var(--font-mono); font-size: 13px; background: #f8fafc;
+    padding: 3px 6px; border-radius: 4px; border: 1px solid #f1f5f9;
+}
+.diff-arrow { color: var(--text-muted); margin: 0 8px; font-weight: 400; }
+.val-a { color: var(--danger); font-weight: 500; }
+.val-b { color: var(--success); font-weight: 600; }
+.samples-header { padding: 24px 32px; border-bottom: 1px solid #f1f5f9; background: #fafbfc; }
+.samples-header h3 { font-size: 14px; margin: 0; color: var(--text-secondary); }
+.empty-state { padding: 48px; text-align: center; color: var(--text-muted); font-size: 15px; }
+footer {
+    margin-top: 60px; text-align: center; color: var(--text-muted); font-size: 12px;
+    border-top: 1px solid var(--border); padding-top: 32px;
+}
+@media (max-width: 850px) {
+    .stats-container, .volumes { grid-template-columns: 1fr; }
+}
+"#;
+
+impl Renderer for HtmlRenderer {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn render(&self, result: &DiffResult, options: &RenderOptions) -> Result<Vec<u8>, RenderError> {
+        let labels = &options.labels;
+        let numbers = &options.numbers;
+        let int = |value: usize| numbers.integer(value as i64);
+        let mut out = String::new();
+
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"UTF-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">\n\
+             <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<div class=\"container\">\n",
+            escape(labels.language()),
+            escape(&options.title),
+            STYLE
+        );
+        let _ = write!(
+            out,
+            "<header class=\"header\">\n<div class=\"brand\">\n<div class=\"logo\">🐨</div>\n\
+             <div class=\"title-area\">\n<h1>{} <small>v{}</small></h1>\n<p>{}</p>\n</div>\n</div>\n\
+             <div class=\"timestamp\">{} {}</div>\n</header>\n",
+            escape(&options.title),
+            env!("CARGO_PKG_VERSION"),
+            escape(labels.get("report_subtitle")),
+            escape(labels.get("generated")),
+            escape(&options.generated_at(result))
+        );
+
+        let coverage = if result.total_rows_a > 0 {
+            result.joined_count as f64 / result.total_rows_a as f64 * 100.0
+        } else {
+            0.0
+        };
+        let modified_class = if result.modified_rows_count > 0 {
+            "val-danger"
+        } else {
+            "val-success"
+        };
+        out.push_str("<div class=\"stats-container\">\n");
+        for (label, class, value, note) in [
+            (
+                "identical_rows",
+                "val-success",
+                int(result.identical_rows_count),
+                "",
+            ),
+            (
+                "modified_rows",
+                modified_class,
+                int(result.modified_rows_count),
+                "value_drift_detected",
+            ),
+            (
+                "join_integrity",
+                "val-primary",
+                numbers.percent(coverage),
+                "source_coverage",
+            ),
+        ] {
+            let _ = writeln!(
+                out,
+                "<div class=\"stat-card\"><div class=\"stat-label\">{}</div>\
+                 <div class=\"stat-value {}\">{}</div><div class=\"stat-note\">{}</div></div>",
+                escape(labels.get(label)),
+                class,
+                escape(&value),
+                if note.is_empty() {
+                    String::new()
+                } else {
+                    escape(labels.get(note))
+                }
+            );
+        }
+        out.push_str("</div>\n");
+
+        let warn = |count: usize| {
+            if count > 0 {
+                " class=\"val-warning\""
+            } else {
+                ""
+            }
+        };
+        let _ = write!(
+            out,
+            "<section class=\"section\">\n<div class=\"section-header\"><h2>{}</h2></div>\n\
+             <div class=\"volumes\">\n<div>\n<h3>{}</h3>\n\
+             <div class=\"volume\"><span>{}</span><span>{}</span></div>\n\
+             <div class=\"volume\"><span>{}</span><span>{}</span></div>\n\
+             <div class=\"volume total\"><span>{}</span><span>{}</span></div>\n</div>\n\
+             <div>\n<h3>{}</h3>\n\
+             <div class=\"volume\"><span>{}</span><span{}>{}</span></div>\n\
+             <div class=\"volume\"><span>{}</span><span{}>{}</span></div>\n\
+             <div class=\"note\">{}</div>\n</div>\n</div>\n</section>\n",
+            escape(labels.get("key_matching_summary")),
+            escape(labels.get("input_volumes")),
+            escape(labels.get("total_rows_a")),
+            int(result.total_rows_a),
+            escape(labels.get("total_rows_b")),
+            int(result.total_rows_b),
+            escape(labels.get("key_matched_rows")),
+            int(result.joined_count),
+            escape(labels.get("exclusivity_breakdown")),
+            escape(labels.get("exclusive_removed")),
+            warn(result.removed),
+            int(result.removed),
+            escape(labels.get("exclusive_added")),
+            warn(result.added),
+            int(result.added),
+            escape(labels.get("exclusive_note"))
+        );
+
+        let _ = write!(
+            out,
+            "<section class=\"section\">\n<div class=\"section-header\"><h2>{}</h2>\
+             <span class=\"badge badge-change\">{} {}</span></div>\n\
+             <div class=\"table-wrapper\">\n<table>\n<thead><tr>\
+             <th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th>\
+             </tr></thead>\n<tbody>\n",
+            escape(labels.get("column_metrics")),
+            int(result.column_stats.len()),
+            escape(labels.get("columns")),
+            escape(labels.get("feature_name")),
+            escape(labels.get("type_shift")),
+            escape(labels.get("match_integrity")),
+            escape(labels.get("mismatches")),
+            escape(labels.get("null_diff")),
+            escape(labels.get("max_diff")),
+            escape(labels.get("validation"))
+        );
+        for (name, stats) in &result.column_stats {
+            column_row(&mut out, name, stats, options);
+        }
+        out.push_str("</tbody>\n</table>\n</div>\n</section>\n");
+
+        let _ = writeln!(
+            out,
+            "<section class=\"section\">\n<div class=\"section-header\"><h2>{}</h2></div>",
+            escape(labels.get("mismatch_samples"))
+        );
+        let mut sampled = false;
+        for (name, stats) in &result.column_stats {
+            let Some(keys) = stats.mismatched_sample_keys.as_deref() else {
+                continue;
+            };
+            if keys.is_empty() {
+                continue;
+            }
+            sampled = true;
+            samples(&mut out, name, stats, keys, options);
+        }
+        if !sampled {
+            let _ = writeln!(
+                out,
+                "<div class=\"empty-state\">✅ {}</div>",
+                escape(labels.get("no_drift"))
+            );
+        }
+        out.push_str("</section>\n");
+
+        let _ = write!(
+            out,
+            "<footer><p>Koala Diff v{}</p></footer>\n</div>\n</body>\n</html>\n",
+            env!("CARGO_PKG_VERSION")
+        );
+        Ok(out.into_bytes())
+    }
+}
+
+/// One row of the column metrics table.
+fn column_row(out: &mut String, name: &str, stats: &ColumnStats, options: &RenderOptions) {
+    let labels = &options.labels;
+    let numbers = &options.numbers;
+    let key = if stats.is_key {
+        format!(
+            " <span class=\"badge badge-key\">{}</span>",
+            escape(labels.get("key"))
+        )
+    } else {
+        String::new()
+    };
+    let tags = match &stats.tags {
+        Some(tags) if !tags.is_empty() => format!(
+            "<small class=\"tags\">{}</small>",
+            escape(
+                &tags
+                    .iter()
+                    .map(|(tag, value)| format!("{}: {}", tag, value))
+                    .collect::<Vec<_>>()
+                    .join(" · ")
+            )
+        ),
+        _ => String::new(),
+    };
+    let rate = stats.match_rate.unwrap_or(0.0);
+    let fill = if rate >= 100.0 {
+        "var(--success)"
+    } else if rate > 90.0 {
+        "var(--warning)"
+    } else {
+        "var(--danger)"
+    };
+    let mismatches = stats.non_match_count.unwrap_or(0);
+    let null_diff = stats.null_count_diff.unwrap_or(0);
+    let (null_class, null_text) = match null_diff {
+        d if d > 0 => (" class=\"val-danger\"", format!("+{}", numbers.integer(d))),
+        d if d < 0 => (" class=\"val-success\"", numbers.integer(d)),
+        _ => ("", "0".to_string()),
+    };
+    let max_diff = match stats.max_value_diff {
+        Some(d) if d != 0.0 => numbers.float(d),
+        _ => "—".to_string(),
+    };
+    let (badge, verdict) = if stats.all_match {
+        ("badge-pass", labels.get("passed"))
+    } else {
+        ("badge-fail", labels.get("alert"))
+    };
+    let _ = writeln!(
+        out,
+        "<tr><td><code>{}</code>{}{}</td>\
+         <td><small>{}</small><span class=\"diff-arrow\">➔</span><small><b>{}</b></small></td>\
+         <td><div class=\"match-rate\"><div class=\"progress-track\">\
+         <div class=\"progress-fill\" style=\"width: {:.1}%; background: {};\"></div></div>\
+         <b>{}</b></div></td>\
+         <td class=\"num{}\">{}</td><td class=\"num\"><span{}>{}</span></td>\
+         <td class=\"num\">{}</td><td><span class=\"badge {}\">{}</span></td></tr>",
+        escape(name),
+        key,
+        tags,
+        escape(&stats.source_dtype),
+        escape(&stats.target_dtype),
+        rate.clamp(0.0, 100.0),
+        fill,
+        escape(&numbers.percent(rate)),
+        if mismatches > 0 { " val-danger" } else { "" },
+        numbers.integer(mismatches as i64),
+        null_class,
+        null_text,
+        escape(&max_diff),
+        badge,
+        escape(verdict)
+    );
+}
+
+/// The change patterns and sampled mismatches of one column.
+fn samples(
+    out: &mut String,
+    name: &str,
+    stats: &ColumnStats,
+    keys: &[String],
+    options: &RenderOptions,
+) {
+    let labels = &options.labels;
+    let _ = writeln!(
+        out,
+        "<div class=\"samples-header\"><h3><span class=\"val-danger\">●</span> <code>{}</code></h3></div>",
+        escape(name)
+    );
+    if let Some(patterns) = stats.change_patterns.as_ref().filter(|p| !p.is_empty()) {
+        let _ = writeln!(
+            out,
+            "<div class=\"table-wrapper\"><table><thead><tr><th>{}</th><th class=\"num\">{}</th></tr></thead><tbody>",
+            escape(labels.get("change_patterns")),
+            escape(labels.get("rows"))
+        );
+        for (pattern, rows) in patterns {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td class=\"num\">{}</td></tr>",
+                change(pattern),
+                options.numbers.integer(*rows as i64)
+            );
+        }
+        out.push_str("</tbody></table></div>\n");
+    }
+    let _ = writeln!(
+        out,
+        "<div class=\"table-wrapper\"><table><thead><tr><th>{}</th><th>{}</th></tr></thead><tbody>",
+        escape(labels.get("key_identifier")),
+        escape(labels.get("value_variance"))
+    );
+    for (i, key) in keys.iter().enumerate() {
+        // Sensitive columns carry keys but no values
+        let value = match stats
+            .mismatched_value_samples
+            .as_ref()
+            .and_then(|values| values.get(i))
+        {
+            Some(value) => change(value),
+            None => format!(
+                "<span class=\"stat-note\">{}</span>",
+                escape(labels.get("redacted"))
+            ),
+        };
+        let _ = writeln!(
+            out,
+            "<tr><td><code>{}</code></td><td>{}</td></tr>",
+            escape(key),
+            value
+        );
+    }
+    out.push_str("</tbody></table></div>\n");
+}
+
+/// An "old -> new" pair as the old value, an arrow and the new value.
+fn change(pair: &str) -> String {
+    match pair.split_once(" -> ") {
+        Some((a, b)) => format!(
+            "<span class=\"val-a\">{}</span><span class=\"diff-arrow\">➔</span>\
+             <span class=\"val-b\">{}</span>",
+            escape(a),
+            escape(b)
+        ),
+        None => escape(pair),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,20 @@
+// koala-diff/src/render/json.rs
+
+use super::{RenderError, RenderOptions, Renderer};
+use crate::result::DiffResult;
+
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn render(
+        &self,
+        result: &DiffResult,
+        _options: &RenderOptions,
+    ) -> Result<Vec<u8>, RenderError> {
+        Ok(serde_json::to_vec_pretty(result)?)
+    }
+}
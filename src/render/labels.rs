@@ -0,0 +1,306 @@
+// koala-diff/src/render/labels.rs
+// Translatable report labels: built-in locales plus user overrides
+
+use std::collections::BTreeMap;
+
+/// Label key -> English text. Every key must appear here.
+const EN: &[(&str, &str)] = &[
+    ("report_subtitle", "Data Quality & Comparison Report"),
+    ("generated", "Generated"),
+    ("metric", "Metric"),
+    ("rows", "Rows"),
+    ("rows_a", "Rows A"),
+    ("rows_b", "Rows B"),
+    ("matched", "Matched"),
+    ("identical", "Identical"),
+    ("modified", "Modified"),
+    ("added", "Added"),
+    ("removed", "Removed"),
+    ("columns", "Columns"),
+    ("column", "Column"),
+    ("type", "Type"),
+    ("source", "Source"),
+    ("target", "Target"),
+    ("match", "Match"),
+    ("mismatches", "Mismatches"),
+    ("null_diff", "Null Δ"),
+    ("max_diff", "Max Var"),
+    ("validation", "Validation"),
+    ("key", "KEY"),
+    ("passed", "PASSED"),
+    ("alert", "ALERT"),
+    ("identical_rows", "Identical Rows"),
+    ("modified_rows", "Modified Rows"),
+    ("join_integrity", "Join Integrity"),
+    ("key_matching_summary", "Key Matching Summary"),
+    ("total_rows_a", "Total Rows in Source (A)"),
+    ("total_rows_b", "Total Rows in Target (B)"),
+    ("key_matched_rows", "Key Matched Rows (Intersection)"),
+    ("exclusive_removed", "Exclusive to Source (Removed)"),
+    ("exclusive_added", "Exclusive to Target (Added)"),
+    ("column_metrics", "Advanced Column Metrics"),
+    ("mismatch_samples", "Mismatch Sample Records"),
+    ("key_identifier", "Key Identifier"),
+    ("value_variance", "Value Variance (Source ➔ Target)"),
+    ("value_drift_detected", "Value drift detected"),
+    ("source_coverage", "Source coverage"),
+    ("analysis_time", "Analysis Time"),
+    ("input_volumes", "Input Volumes"),
+    ("exclusivity_breakdown", "Exclusivity Breakdown"),
+    (
+        "exclusive_note",
+        "Exclusive rows are not compared for value drift.",
+    ),
+    ("feature_name", "Feature Name"),
+    ("type_shift", "Type Shift"),
+    ("match_integrity", "Match Integrity"),
+    ("no_drift", "No value drift detected in matching row keys."),
+    ("redacted", "Value hidden (sensitive column)"),
+    ("change_patterns", "Most frequent changes"),
+    ("mismatch_by_group", "Mismatch rate by"),
+    ("findings", "Findings"),
+    ("warnings", "Warnings"),
+    ("tolerance_bands", "Tolerance bands"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("report_subtitle", "Datenqualitäts- und Vergleichsbericht"),
+    ("generated", "Erstellt"),
+    ("metric", "Kennzahl"),
+    ("rows", "Zeilen"),
+    ("rows_a", "Zeilen A"),
+    ("rows_b", "Zeilen B"),
+    ("matched", "Zugeordnet"),
+    ("identical", "Identisch"),
+    ("modified", "Geändert"),
+    ("added", "Hinzugefügt"),
+    ("removed", "Entfernt"),
+    ("columns", "Spalten"),
+    ("column", "Spalte"),
+    ("type", "Typ"),
+    ("source", "Quelle"),
+    ("target", "Ziel"),
+    ("match", "Übereinstimmung"),
+    ("mismatches", "Abweichungen"),
+    ("null_diff", "Null Δ"),
+    ("max_diff", "Max. Abw."),
+    ("validation", "Prüfung"),
+    ("key", "SCHLÜSSEL"),
+    ("passed", "OK"),
+    ("alert", "WARNUNG"),
+    ("identical_rows", "Identische Zeilen"),
+    ("modified_rows", "Geänderte Zeilen"),
+    ("join_integrity", "Join-Integrität"),
+    ("key_matching_summary", "Schlüsselabgleich"),
+    ("total_rows_a", "Zeilen in Quelle (A)"),
+    ("total_rows_b", "Zeilen in Ziel (B)"),
+    ("key_matched_rows", "Zugeordnete Zeilen (Schnittmenge)"),
+    ("exclusive_removed", "Nur in Quelle (Entfernt)"),
+    ("exclusive_added", "Nur in Ziel (Hinzugefügt)"),
+    ("column_metrics", "Spaltenkennzahlen"),
+    ("mismatch_samples", "Beispiele für Abweichungen"),
+    ("key_identifier", "Schlüssel"),
+    ("value_variance", "Wertänderung (Quelle ➔ Ziel)"),
+    ("value_drift_detected", "Wertabweichung erkannt"),
+    ("source_coverage", "Abdeckung der Quelle"),
+    ("analysis_time", "Analysezeit"),
+    ("input_volumes", "Eingangsvolumen"),
+    ("exclusivity_breakdown", "Exklusive Zeilen"),
+    (
+        "exclusive_note",
+        "Exklusive Zeilen werden nicht auf Wertabweichungen geprüft.",
+    ),
+    ("feature_name", "Spaltenname"),
+    ("type_shift", "Typänderung"),
+    ("match_integrity", "Übereinstimmung"),
+    (
+        "no_drift",
+        "Keine Wertabweichungen bei zugeordneten Schlüsseln.",
+    ),
+    ("redacted", "Wert verborgen (sensible Spalte)"),
+    ("change_patterns", "Häufigste Änderungen"),
+    ("mismatch_by_group", "Abweichungsquote nach"),
+    ("findings", "Befunde"),
+    ("warnings", "Warnungen"),
+    ("tolerance_bands", "Toleranzbänder"),
+];
+
+const FR: &[(&str, &str)] = &[
+    (
+        "report_subtitle",
+        "Rapport de qualité et de comparaison des données",
+    ),
+    ("generated", "Généré le"),
+    ("metric", "Indicateur"),
+    ("rows", "Lignes"),
+    ("rows_a", "Lignes A"),
+    ("rows_b", "Lignes B"),
+    ("matched", "Appariées"),
+    ("identical", "Identiques"),
+    ("modified", "Modifiées"),
+    ("added", "Ajoutées"),
+    ("removed", "Supprimées"),
+    ("columns", "Colonnes"),
+    ("column", "Colonne"),
+    ("type", "Type"),
+    ("source", "Source"),
+    ("target", "Cible"),
+    ("match", "Concordance"),
+    ("mismatches", "Écarts"),
+    ("null_diff", "Δ nuls"),
+    ("max_diff", "Écart max."),
+    ("validation", "Validation"),
+    ("key", "CLÉ"),
+    ("passed", "OK"),
+    ("alert", "ALERTE"),
+    ("identical_rows", "Lignes identiques"),
+    ("modified_rows", "Lignes modifiées"),
+    ("join_integrity", "Intégrité de jointure"),
+    ("key_matching_summary", "Rapprochement des clés"),
+    ("total_rows_a", "Lignes dans la source (A)"),
+    ("total_rows_b", "Lignes dans la cible (B)"),
+    ("key_matched_rows", "Lignes appariées (intersection)"),
+    (
+        "exclusive_removed",
+        "Uniquement dans la source (supprimées)",
+    ),
+    ("exclusive_added", "Uniquement dans la cible (ajoutées)"),
+    ("column_metrics", "Indicateurs par colonne"),
+    ("mismatch_samples", "Exemples d'écarts"),
+    ("key_identifier", "Clé"),
+    ("value_variance", "Écart de valeur (source ➔ cible)"),
+    ("value_drift_detected", "Écart de valeur détecté"),
+    ("source_coverage", "Couverture de la source"),
+    ("analysis_time", "Durée d'analyse"),
+    ("input_volumes", "Volumes en entrée"),
+    ("exclusivity_breakdown", "Lignes exclusives"),
+    (
+        "exclusive_note",
+        "Les lignes exclusives ne sont pas comparées.",
+    ),
+    ("feature_name", "Nom de colonne"),
+    ("type_shift", "Changement de type"),
+    ("match_integrity", "Concordance"),
+    ("no_drift", "Aucun écart de valeur sur les clés appariées."),
+    ("redacted", "Valeur masquée (colonne sensible)"),
+    ("change_patterns", "Changements les plus fréquents"),
+    ("mismatch_by_group", "Taux d'écart par"),
+    ("findings", "Constats"),
+    ("warnings", "Avertissements"),
+    ("tolerance_bands", "Bandes de tolérance"),
+];
+
+const ES: &[(&str, &str)] = &[
+    (
+        "report_subtitle",
+        "Informe de calidad y comparación de datos",
+    ),
+    ("generated", "Generado"),
+    ("metric", "Métrica"),
+    ("rows", "Filas"),
+    ("rows_a", "Filas A"),
+    ("rows_b", "Filas B"),
+    ("matched", "Emparejadas"),
+    ("identical", "Idénticas"),
+    ("modified", "Modificadas"),
+    ("added", "Añadidas"),
+    ("removed", "Eliminadas"),
+    ("columns", "Columnas"),
+    ("column", "Columna"),
+    ("type", "Tipo"),
+    ("source", "Origen"),
+    ("target", "Destino"),
+    ("match", "Coincidencia"),
+    ("mismatches", "Diferencias"),
+    ("null_diff", "Δ nulos"),
+    ("max_diff", "Dif. máx."),
+    ("validation", "Validación"),
+    ("key", "CLAVE"),
+    ("passed", "OK"),
+    ("alert", "ALERTA"),
+    ("identical_rows", "Filas idénticas"),
+    ("modified_rows", "Filas modificadas"),
+    ("join_integrity", "Integridad del cruce"),
+    ("key_matching_summary", "Conciliación de claves"),
+    ("total_rows_a", "Filas en origen (A)"),
+    ("total_rows_b", "Filas en destino (B)"),
+    ("key_matched_rows", "Filas emparejadas (intersección)"),
+    ("exclusive_removed", "Solo en origen (eliminadas)"),
+    ("exclusive_added", "Solo en destino (añadidas)"),
+    ("column_metrics", "Métricas por columna"),
+    ("mismatch_samples", "Muestras de diferencias"),
+    ("key_identifier", "Clave"),
+    ("value_variance", "Variación (origen ➔ destino)"),
+    ("value_drift_detected", "Diferencia de valores detectada"),
+    ("source_coverage", "Cobertura del origen"),
+    ("analysis_time", "Tiempo de análisis"),
+    ("input_volumes", "Volúmenes de entrada"),
+    ("exclusivity_breakdown", "Filas exclusivas"),
+    ("exclusive_note", "Las filas exclusivas no se comparan."),
+    ("feature_name", "Nombre de columna"),
+    ("type_shift", "Cambio de tipo"),
+    ("match_integrity", "Coincidencia"),
+    (
+        "no_drift",
+        "No hay diferencias de valores en las claves emparejadas.",
+    ),
+    ("redacted", "Valor oculto (columna sensible)"),
+    ("change_patterns", "Cambios más frecuentes"),
+    ("mismatch_by_group", "Tasa de diferencias por"),
+    ("findings", "Hallazgos"),
+    ("warnings", "Advertencias"),
+    ("tolerance_bands", "Bandas de tolerancia"),
+];
+
+/// Report labels for one locale, with user overrides applied.
+#[derive(Debug, Clone)]
+pub struct Labels {
+    map: BTreeMap<String, String>,
+    /// Language code of the locale, e.g. "de" for "de_CH".
+    language: String,
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        Self::for_locale("en")
+    }
+}
+
+impl Labels {
+    /// Built-in translations; unknown locales (or missing keys) fall back to English.
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale.split(['_', '-']).next().unwrap_or("en");
+        let translated: &[(&str, &str)] = match language {
+            "de" => DE,
+            "fr" => FR,
+            "es" => ES,
+            _ => &[],
+        };
+        let map = EN
+            .iter()
+            .chain(translated)
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Self {
+            map,
+            language: language.to_string(),
+        }
+    }
+
+    /// Replaces individual labels (e.g. `{"modified": "Changed"}`).
+    pub fn apply_overrides(&mut self, overrides: BTreeMap<String, String>) {
+        self.map.extend(overrides);
+    }
+
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.map.get(key).map(|v| v.as_str()).unwrap_or(key)
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn into_map(self) -> BTreeMap<String, String> {
+        self.map
+    }
+}
@@ -0,0 +1,155 @@
+// koala-diff/src/render/markdown.rs
+
+use std::fmt::Write;
+
+use super::{RenderError, RenderOptions, Renderer};
+use crate::result::DiffResult;
+
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn render(&self, result: &DiffResult, options: &RenderOptions) -> Result<Vec<u8>, RenderError> {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", options.title);
+        let labels = &options.labels;
+        let _ = writeln!(
+            out,
+            "_{} {}_\n",
+            labels.get("generated"),
+            options.generated_at(result)
+        );
+        let _ = writeln!(
+            out,
+            "| {} | {} |\n| :--- | ---: |",
+            labels.get("metric"),
+            labels.get("rows")
+        );
+        for (label, value) in [
+            ("rows_a", result.total_rows_a),
+            ("rows_b", result.total_rows_b),
+            ("matched", result.joined_count),
+            ("identical", result.identical_rows_count),
+            ("modified", result.modified_rows_count),
+            ("added", result.added),
+            ("removed", result.removed),
+        ] {
+            let _ = writeln!(
+                out,
+                "| {} | {} |",
+                labels.get(label),
+                options.numbers.integer(value as i64)
+            );
+        }
+
+        let _ = writeln!(out, "\n## {}\n", labels.get("columns"));
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {} |\n| :--- | :--- | :--- | ---: | ---: | ---: |",
+            labels.get("column"),
+            labels.get("source"),
+            labels.get("target"),
+            labels.get("match"),
+            labels.get("mismatches"),
+            labels.get("null_diff")
+        );
+        for (name, stats) in &result.column_stats {
+            let _ = writeln!(
+                out,
+                "| `{}`{} | {} | {} | {} | {} | {} |",
+                escape(name),
+                if stats.is_key { " (key)" } else { "" },
+                stats.source_dtype,
+                stats.target_dtype,
+                options.numbers.opt_percent(stats.match_rate),
+                options
+                    .numbers
+                    .opt_integer(stats.non_match_count.map(|v| v as i64)),
+                options.numbers.opt_integer(stats.null_count_diff),
+            );
+        }
+
+        if !result.findings.is_empty() {
+            let _ = writeln!(out, "\n## {}\n", labels.get("findings"));
+            for finding in &result.findings {
+                let _ = writeln!(out, "- {}", finding.message());
+            }
+        }
+
+        let banded: Vec<_> = result
+            .column_stats
+            .values()
+            .filter_map(|stats| Some((&stats.column_name, stats.band_counts.as_ref()?)))
+            .collect();
+        if !banded.is_empty() {
+            let _ = writeln!(out, "\n## {}\n", labels.get("tolerance_bands"));
+            let _ = writeln!(
+                out,
+                "| | green | amber | red |\n| :--- | ---: | ---: | ---: |"
+            );
+            for (column, bands) in banded {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} |",
+                    escape(column),
+                    options.numbers.integer(bands.green as i64),
+                    options.numbers.integer(bands.amber as i64),
+                    options.numbers.integer(bands.red as i64)
+                );
+            }
+        }
+
+        if !result.warnings.is_empty() {
+            let _ = writeln!(out, "\n## {}\n", labels.get("warnings"));
+            for warning in &result.warnings {
+                let _ = writeln!(out, "- {}", warning.message);
+            }
+        }
+
+        if let Some(grouped) = &result.group_mismatch {
+            let columns: Vec<&String> = grouped
+                .groups
+                .first()
+                .map(|g| g.mismatch_rates.keys().collect())
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "\n## {} `{}`\n",
+                labels.get("mismatch_by_group"),
+                escape(&grouped.group_column)
+            );
+            let mut header = format!(
+                "| {} | {} | {} |",
+                escape(&grouped.group_column),
+                labels.get("matched"),
+                labels.get("modified")
+            );
+            let mut align = "| :--- | ---: | ---: |".to_string();
+            for c in &columns {
+                header.push_str(&format!(" `{}` |", escape(c)));
+                align.push_str(" ---: |");
+            }
+            let _ = writeln!(out, "{}\n{}", header, align);
+            for group in &grouped.groups {
+                let mut row = format!(
+                    "| {} | {} | {} |",
+                    escape(&group.group),
+                    options.numbers.integer(group.matched_rows as i64),
+                    options.numbers.integer(group.modified_rows as i64)
+                );
+                for rate in group.mismatch_rates.values() {
+                    row.push_str(&format!(" {} |", options.numbers.percent(*rate)));
+                }
+                let _ = writeln!(out, "{}", row);
+            }
+        }
+        Ok(out.into_bytes())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|")
+}
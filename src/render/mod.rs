@@ -0,0 +1,274 @@
+// koala-diff/src/render/mod.rs
+// Output renderers: one uniform entry point, one registry of formats
+
+mod format;
+mod html;
+mod json;
+mod labels;
+mod markdown;
+mod text;
+mod xlsx;
+
+use std::collections::BTreeMap;
+
+use chrono::format::{Item, StrftimeItems};
+use indexmap::IndexMap;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyBytes, PyDict, PyString};
+
+use crate::error::{DiffError, Result};
+use crate::result::DiffResult;
+
+pub use self::format::NumberFormat;
+pub use self::labels::Labels;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error("Unknown output format '{0}' (available: {1})")]
+    UnknownFormat(String, String),
+    #[error("Unexpected render option '{0}'")]
+    UnknownOption(String),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+}
+
+/// Options shared by every renderer. Renderers ignore what doesn't apply.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub title: String,
+    pub numbers: NumberFormat,
+    /// Report labels in the requested locale.
+    pub labels: Labels,
+    /// strftime-style format for timestamps printed in reports.
+    pub date_format: String,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            title: "Koala Diff Report".to_string(),
+            numbers: NumberFormat::default(),
+            labels: Labels::default(),
+            date_format: "%Y-%m-%d %H:%M:%S".to_string(),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Builds the options from the `**options` kwargs of `render`.
+    ///
+    /// `locale` picks the separator preset and label language; explicit
+    /// separators and `labels` entries override it.
+    #[cfg(feature = "python")]
+    pub fn from_kwargs(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut opts = Self::default();
+        let Some(kwargs) = kwargs else {
+            return Ok(opts);
+        };
+        if let Some(locale) = kwargs.get_item("locale")? {
+            let locale: String = locale.extract()?;
+            opts.numbers = NumberFormat::for_locale(&locale);
+            opts.labels = Labels::for_locale(&locale);
+        }
+        for (key, value) in kwargs.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "locale" => {}
+                "title" => opts.title = value.extract()?,
+                "thousands_separator" => opts.numbers.thousands_separator = value.extract()?,
+                "decimal_separator" => opts.numbers.decimal_separator = value.extract()?,
+                "decimal_places" => opts.numbers.decimal_places = value.extract()?,
+                "percent_decimals" => opts.numbers.percent_decimals = value.extract()?,
+                "date_format" => opts.date_format = value.extract()?,
+                "labels" => opts
+                    .labels
+                    .apply_overrides(value.extract::<BTreeMap<String, String>>()?),
+                other => return Err(to_py_err(RenderError::UnknownOption(other.to_string()))),
+            }
+        }
+        opts.validate()?;
+        Ok(opts)
+    }
+
+    /// Rejects a `date_format` chrono can't print (it would panic on use).
+    pub fn validate(&self) -> Result<(), DiffError> {
+        if StrftimeItems::new(&self.date_format).any(|item| item == Item::Error) {
+            return Err(DiffError::invalid(format!(
+                "Invalid date_format '{}'",
+                self.date_format
+            )));
+        }
+        Ok(())
+    }
+
+    /// The time of the run behind the report (its `run_at`, which can be
+    /// set for reproducible reports), formatted with `date_format`; now for
+    /// results without one.
+    fn generated_at(&self, result: &DiffResult) -> String {
+        match chrono::DateTime::parse_from_rfc3339(&result.run_at) {
+            Ok(at) => at.format(&self.date_format).to_string(),
+            Err(_) => chrono::Local::now().format(&self.date_format).to_string(),
+        }
+    }
+}
+
+/// A single output format.
+pub trait Renderer: Send + Sync {
+    /// Format name used to select the renderer (e.g. `"markdown"`).
+    fn name(&self) -> &'static str;
+
+    /// Whether the output is binary rather than UTF-8 text.
+    fn is_binary(&self) -> bool {
+        false
+    }
+
+    fn render(&self, result: &DiffResult, options: &RenderOptions) -> Result<Vec<u8>, RenderError>;
+}
+
+/// Renderers by format name.
+pub struct Registry {
+    renderers: IndexMap<&'static str, Box<dyn Renderer>>,
+}
+
+impl Registry {
+    /// A registry holding every built-in format.
+    pub fn builtin() -> Self {
+        let mut registry = Self {
+            renderers: IndexMap::new(),
+        };
+        registry.register(Box::new(text::TextRenderer));
+        registry.register(Box::new(json::JsonRenderer));
+        registry.register(Box::new(markdown::MarkdownRenderer));
+        registry.register(Box::new(html::HtmlRenderer));
+        registry.register(Box::new(xlsx::XlsxRenderer));
+        registry
+    }
+
+    pub fn register(&mut self, renderer: Box<dyn Renderer>) {
+        self.renderers.insert(renderer.name(), renderer);
+    }
+
+    pub fn get(&self, format: &str) -> Result<&dyn Renderer, RenderError> {
+        self.renderers
+            .get(format)
+            .map(|r| r.as_ref())
+            .ok_or_else(|| {
+                RenderError::UnknownFormat(format.to_string(), self.formats().join(", "))
+            })
+    }
+
+    pub fn formats(&self) -> Vec<&'static str> {
+        self.renderers.keys().copied().collect()
+    }
+}
+
+#[cfg(feature = "python")]
+fn to_py_err(e: RenderError) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string())
+}
+
+/// Renders a diff result in the given format
+///
+/// Args:
+///     result (dict): Result returned by `diff_files`
+///     format (str): One of `render_formats()` (text, json, markdown, html, xlsx)
+///     **options: Render options
+///         title (str): Report title
+///         locale (str): Separator preset and label language, e.g. "en", "de", "fr", "es"
+///         labels (dict[str, str]): Overrides for individual report labels (see `report_labels`)
+///         thousands_separator (str), decimal_separator (str): Override the preset
+///         decimal_places (int): Decimals for float values (default 4)
+///         percent_decimals (int): Decimals for percentages (default 1)
+///         date_format (str): strftime format for timestamps
+///
+/// Returns:
+///     str | bytes: Rendered report (bytes for binary formats such as xlsx)
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (result, format, **options))]
+pub fn render<'py>(
+    py: Python<'py>,
+    result: &Bound<'py, PyAny>,
+    format: &str,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let result: DiffResult = pythonize::depythonize(result)?;
+    let options = RenderOptions::from_kwargs(options)?;
+    let registry = Registry::builtin();
+    let renderer = registry.get(format).map_err(to_py_err)?;
+    let output = renderer.render(&result, &options).map_err(to_py_err)?;
+
+    if renderer.is_binary() {
+        Ok(PyBytes::new(py, &output).into_any())
+    } else {
+        Ok(PyString::new(py, &String::from_utf8_lossy(&output)).into_any())
+    }
+}
+
+/// Lists the output formats known to the Rust renderer registry.
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn render_formats() -> Vec<&'static str> {
+    Registry::builtin().formats()
+}
+
+/// Formats a single value the way reports do
+///
+/// Args:
+///     value (int | float): The number
+///     kind (str): "integer", "float" or "percent" (0..100)
+///     **options: Same formatting options as `render`
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (value, kind = "float", **options))]
+pub fn format_number(
+    value: f64,
+    kind: &str,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<String> {
+    let numbers = RenderOptions::from_kwargs(options)?.numbers;
+    Ok(match kind {
+        "integer" => numbers.integer(value as i64),
+        "percent" => numbers.percent(value),
+        _ => numbers.float(value),
+    })
+}
+
+/// Report labels for a locale, as used by the text, markdown and HTML reports
+///
+/// Args:
+///     locale (str, optional): "en" (default), "de", "fr" or "es"; unknown
+///         locales fall back to English
+///     labels (dict[str, str], optional): Overrides applied on top
+///
+/// Returns:
+///     dict[str, str]: Label key -> text
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (locale = None, labels = None))]
+pub fn report_labels(
+    locale: Option<&str>,
+    labels: Option<BTreeMap<String, String>>,
+) -> BTreeMap<String, String> {
+    let mut table = Labels::for_locale(locale.unwrap_or("en"));
+    if let Some(labels) = labels {
+        table.apply_overrides(labels);
+    }
+    table.into_map()
+}
+
+impl NumberFormat {
+    /// Formats an optional percentage for tables.
+    fn opt_percent(&self, value: Option<f64>) -> String {
+        value.map_or("—".to_string(), |v| self.percent(v))
+    }
+
+    /// Formats an optional count for tables.
+    fn opt_integer(&self, value: Option<i64>) -> String {
+        value.map_or("—".to_string(), |v| self.integer(v))
+    }
+}
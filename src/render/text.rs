@@ -0,0 +1,103 @@
+// koala-diff/src/render/text.rs
+
+use std::fmt::Write;
+
+use super::{RenderError, RenderOptions, Renderer};
+use crate::result::DiffResult;
+
+pub struct TextRenderer;
+
+impl Renderer for TextRenderer {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn render(&self, result: &DiffResult, options: &RenderOptions) -> Result<Vec<u8>, RenderError> {
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", options.title);
+        let _ = writeln!(out, "{}", "=".repeat(options.title.chars().count()));
+        let labels = &options.labels;
+        let _ = writeln!(
+            out,
+            "{} {}\n",
+            labels.get("generated"),
+            options.generated_at(result)
+        );
+        for (label, value) in [
+            ("rows_a", result.total_rows_a),
+            ("rows_b", result.total_rows_b),
+            ("matched", result.joined_count),
+            ("identical", result.identical_rows_count),
+            ("modified", result.modified_rows_count),
+            ("added", result.added),
+            ("removed", result.removed),
+        ] {
+            let _ = writeln!(
+                out,
+                "{:<12}{:>14}",
+                labels.get(label),
+                options.numbers.integer(value as i64)
+            );
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "{:<24} {:<24} {:>8} {:>12} {:>8}",
+            labels.get("column"),
+            labels.get("type"),
+            labels.get("match"),
+            labels.get("mismatches"),
+            labels.get("null_diff")
+        );
+        for (name, stats) in &result.column_stats {
+            let dtype = if stats.source_dtype == stats.target_dtype {
+                stats.source_dtype.clone()
+            } else {
+                format!("{} -> {}", stats.source_dtype, stats.target_dtype)
+            };
+            let _ = writeln!(
+                out,
+                "{:<24} {:<24} {:>8} {:>12} {:>8}",
+                name,
+                dtype,
+                options.numbers.opt_percent(stats.match_rate),
+                options
+                    .numbers
+                    .opt_integer(stats.non_match_count.map(|v| v as i64)),
+                options.numbers.opt_integer(stats.null_count_diff),
+            );
+        }
+        if !result.findings.is_empty() {
+            let _ = writeln!(out, "\n{}", labels.get("findings"));
+            for finding in &result.findings {
+                let _ = writeln!(out, "  - {}", finding.message());
+            }
+        }
+        let banded: Vec<_> = result
+            .column_stats
+            .values()
+            .filter_map(|stats| Some((&stats.column_name, stats.band_counts.as_ref()?)))
+            .collect();
+        if !banded.is_empty() {
+            let _ = writeln!(out, "\n{}", labels.get("tolerance_bands"));
+            for (column, bands) in banded {
+                let _ = writeln!(
+                    out,
+                    "  - {}: {} green / {} amber / {} red",
+                    column,
+                    options.numbers.integer(bands.green as i64),
+                    options.numbers.integer(bands.amber as i64),
+                    options.numbers.integer(bands.red as i64)
+                );
+            }
+        }
+        if !result.warnings.is_empty() {
+            let _ = writeln!(out, "\n{}", labels.get("warnings"));
+            for warning in &result.warnings {
+                let _ = writeln!(out, "  - {}", warning.message);
+            }
+        }
+        Ok(out.into_bytes())
+    }
+}
@@ -0,0 +1,106 @@
+// koala-diff/src/render/xlsx.rs
+
+use rust_xlsxwriter::{DocProperties, ExcelDateTime, Format, Workbook};
+
+use super::{RenderError, RenderOptions, Renderer};
+use crate::result::DiffResult;
+
+pub struct XlsxRenderer;
+
+impl Renderer for XlsxRenderer {
+    fn name(&self) -> &'static str {
+        "xlsx"
+    }
+
+    fn is_binary(&self) -> bool {
+        true
+    }
+
+    fn render(&self, result: &DiffResult, options: &RenderOptions) -> Result<Vec<u8>, RenderError> {
+        let bold = Format::new().set_bold();
+        let mut workbook = Workbook::new();
+        // The run's time rather than now, so the same result renders to
+        // the same bytes
+        if let Ok(at) = chrono::DateTime::parse_from_rfc3339(&result.run_at) {
+            let created = ExcelDateTime::from_timestamp(at.timestamp())?;
+            workbook.set_properties(&DocProperties::new().set_creation_datetime(&created));
+        }
+
+        let summary = workbook.add_worksheet().set_name("Summary")?;
+        summary.write_string_with_format(0, 0, &options.title, &bold)?;
+        for (row, (label, value)) in [
+            ("Rows A", result.total_rows_a),
+            ("Rows B", result.total_rows_b),
+            ("Matched", result.joined_count),
+            ("Identical", result.identical_rows_count),
+            ("Modified", result.modified_rows_count),
+            ("Added", result.added),
+            ("Removed", result.removed),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            summary.write_string(row as u32 + 2, 0, label)?;
+            summary.write_number(row as u32 + 2, 1, value as f64)?;
+        }
+
+        let columns = workbook.add_worksheet().set_name("Columns")?;
+        for (col, header) in [
+            "Column",
+            "Key",
+            "Source Type",
+            "Target Type",
+            "Match Rate",
+            "Mismatches",
+            "Null Diff",
+            "Max Value Diff",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            columns.write_string_with_format(0, col as u16, header, &bold)?;
+        }
+        for (i, (name, stats)) in result.column_stats.iter().enumerate() {
+            let row = i as u32 + 1;
+            columns.write_string(row, 0, name)?;
+            columns.write_boolean(row, 1, stats.is_key)?;
+            columns.write_string(row, 2, &stats.source_dtype)?;
+            columns.write_string(row, 3, &stats.target_dtype)?;
+            if let Some(v) = stats.match_rate {
+                columns.write_number(row, 4, v)?;
+            }
+            if let Some(v) = stats.non_match_count {
+                columns.write_number(row, 5, v as f64)?;
+            }
+            if let Some(v) = stats.null_count_diff {
+                columns.write_number(row, 6, v as f64)?;
+            }
+            if let Some(v) = stats.max_value_diff {
+                columns.write_number(row, 7, v)?;
+            }
+        }
+
+        if let Some(grouped) = &result.group_mismatch {
+            let sheet = workbook.add_worksheet().set_name("Groups")?;
+            sheet.write_string_with_format(0, 0, &grouped.group_column, &bold)?;
+            sheet.write_string_with_format(0, 1, "Matched", &bold)?;
+            sheet.write_string_with_format(0, 2, "Modified", &bold)?;
+            if let Some(first) = grouped.groups.first() {
+                for (j, name) in first.mismatch_rates.keys().enumerate() {
+                    sheet.write_string_with_format(0, j as u16 + 3, name, &bold)?;
+                }
+            }
+            for (i, group) in grouped.groups.iter().enumerate() {
+                let row = i as u32 + 1;
+                sheet.write_string(row, 0, &group.group)?;
+                sheet.write_number(row, 1, group.matched_rows as f64)?;
+                sheet.write_number(row, 2, group.modified_rows as f64)?;
+                for (j, rate) in group.mismatch_rates.values().enumerate() {
+                    sheet.write_number(row, j as u16 + 3, *rate)?;
+                }
+            }
+        }
+
+        Ok(workbook.save_to_buffer()?)
+    }
+}
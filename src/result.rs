@@ -0,0 +1,862 @@
+// koala-diff/src/result.rs
+// The diff result model. Built by the engine, handed to Python as a plain dict.
+
+use std::collections::BTreeMap;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::RunManifest;
+use crate::schema::Compatibility;
+
+/// Summary of a keyed comparison between file A and file B.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiffResult {
+    pub total_rows_a: usize,
+    pub total_rows_b: usize,
+    pub joined_count: usize,
+    pub identical_rows_count: usize,
+    pub modified_rows_count: usize,
+    pub added: usize,
+    pub removed: usize,
+    /// No added, removed or modified rows and no schema change.
+    pub identical: bool,
+    /// SHA-256 of each side's compared projection (keys + compared columns),
+    /// independent of row and column order: a compact proof of equality.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash_a: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash_b: Option<String>,
+    /// SHA-256 of each side's column names and dtypes, in order: equal
+    /// exactly when the schemas are.
+    pub schema_fingerprint_a: String,
+    pub schema_fingerprint_b: String,
+    /// Per-column statistics, in file A's column order, then B-only columns.
+    pub column_stats: IndexMap<String, ColumnStats>,
+    /// Every column-level schema difference, in file A's column order.
+    pub schema_diff: Vec<SchemaDiffEntry>,
+    pub schema_compatibility: SchemaCompatibility,
+    /// Each side's schema against the last one the `schema_history` store
+    /// recorded for its dataset.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub schema_drift: Vec<SchemaDrift>,
+    /// How much of the data the match figures are actually based on.
+    pub coverage: Coverage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub append_log: Option<AppendLog>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_order: Option<RowOrder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_mismatch: Option<GroupedMismatch>,
+    /// Per `comparison_groups` entry, by name.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub comparison_groups: IndexMap<String, ComparisonGroupSummary>,
+    /// The `aggregate_checks` assertions, by column then aggregate.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aggregate_checks: Vec<AggregateCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hierarchy: Option<HierarchySummary>,
+    /// Likely root causes of the mismatches (swapped columns, ...).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<Finding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parquet_stats: Option<ParquetStatsComparison>,
+    /// The row-level diff written for the `output_path` option.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_level_output: Option<RowLevelOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<RunManifest>,
+    /// Identifies the run (random unless set with the `run_id` option).
+    pub run_id: String,
+    /// When the run started (RFC 3339), or the `run_at` option.
+    pub run_at: String,
+    /// How the run was executed, and why.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<ExecutionPlan>,
+    /// Non-fatal problems met on the way (lossy casts, duplicate keys, rows
+    /// dropped by filters, ...), in the order they came up.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+    /// `timeout_seconds` ran out: only what was computed before is filled in.
+    pub timed_out: bool,
+    /// The phase running (or next) when it did, e.g. "statistics".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timed_out_phase: Option<String>,
+}
+
+/// Statistics for one column of file A and its counterpart in file B.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnStats {
+    pub column_name: String,
+    pub is_key: bool,
+    pub source_dtype: String,
+    /// `"MISSING"` when the column doesn't exist in file B.
+    pub target_dtype: String,
+    /// Whether the values were compared; see `skip_reason` when not.
+    pub compared: bool,
+    /// `only_in_a`, `only_in_b` or `incompatible_dtype` (types that can't be
+    /// compared value by value, e.g. String vs Int64).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<String>,
+    pub all_match: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_match_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_rate: Option<f64>,
+    /// Rows with a value (either side) the Float64 comparison can't
+    /// represent exactly: 64-bit integers past 2^53, wide decimals.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coercion_lossy_count: Option<usize>,
+    /// Matches on those rows, which may hide a difference the cast rounded
+    /// away; counted as matches all the same.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coercion_lossy_matches: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_value_diff: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub null_count_diff: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inf_count_a: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inf_count_b: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mismatched_sample_keys: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mismatched_value_samples: Option<Vec<String>>,
+    /// Most frequent "old -> new" changes and their row counts (`change_patterns`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_patterns: Option<IndexMap<String, usize>>,
+    /// Matched rows per tolerance band (`bands`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub band_counts: Option<BandCounts>,
+
+    // Profile mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approx_distinct_a: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approx_distinct_b: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approx_distinct_ratio: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uniqueness_a: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uniqueness_b: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy_a: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy_b: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uniqueness_dropped: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entropy_dropped: Option<bool>,
+}
+
+/// Matched rows of a column by the band their difference falls in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BandCounts {
+    pub green: usize,
+    pub amber: usize,
+    pub red: usize,
+}
+
+/// Schema changes from A to B with an overall compatibility verdict.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaCompatibility {
+    pub verdict: Compatibility,
+    pub changes: Vec<SchemaChange>,
+}
+
+impl Default for SchemaCompatibility {
+    fn default() -> Self {
+        Self {
+            verdict: Compatibility::Full,
+            changes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaChange {
+    pub column: String,
+    /// `added`, `removed` or `retyped`.
+    pub change: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub compatibility: Compatibility,
+}
+
+/// Share of the cells of both files that were actually compared.
+///
+/// Cells are rows × non-key columns. Only matched rows of columns present in
+/// both files with a non-breaking type are compared; one-sided columns,
+/// unmatched rows and rows left out by the allowlist or a read/append window
+/// all count against the coverage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Coverage {
+    /// Percentage of cells compared (100.0 when there are no cells).
+    pub cell_coverage: f64,
+    pub compared_cells: usize,
+    pub total_cells: usize,
+    pub compared_columns: usize,
+    /// Non-key columns of either file.
+    pub value_columns: usize,
+    pub one_sided_columns: Vec<String>,
+    /// Shared columns retyped in a breaking way.
+    pub incompatible_columns: Vec<String>,
+    /// Added plus removed rows.
+    pub unmatched_rows: usize,
+    pub suppressed_rows_a: usize,
+    pub suppressed_rows_b: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDiffEntry {
+    pub column: String,
+    /// `added`, `removed`, `retyped` or `reordered`.
+    pub change: String,
+    /// The dtype in A, or for `reordered` the position among the shared columns.
+    pub from: Option<String>,
+    /// The dtype in B, or for `reordered` the position among the shared columns.
+    pub to: Option<String>,
+    /// Whether moving A's values to B's schema loses information: a removed
+    /// column, or a retype that isn't a widening (e.g. Int64 -> Int32).
+    pub lossy: bool,
+}
+
+/// One side's schema against the last one recorded for its dataset
+/// (`schema_history`), independently of the other side.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchemaDrift {
+    pub dataset: String,
+    pub fingerprint: String,
+    /// `None` the first time the dataset is seen.
+    pub previous_fingerprint: Option<String>,
+    /// The run that recorded the previous schema, and when.
+    pub previous_run_id: Option<String>,
+    pub previous_run_at: Option<String>,
+    /// The fingerprint changed since the previous run.
+    pub drifted: bool,
+    /// What changed, as in `schema_diff` (previous schema -> this one).
+    pub changes: Vec<SchemaDriftChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDriftChange {
+    pub column: String,
+    /// `added`, `removed`, `retyped` or `reordered`.
+    pub change: String,
+    /// The previous dtype, or for `reordered` the previous position among
+    /// the shared columns.
+    pub from: Option<String>,
+    /// The current dtype, or for `reordered` the current position.
+    pub to: Option<String>,
+}
+
+/// Append-only log mode: the compared window and what happened to history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppendLog {
+    pub append_key: String,
+    pub window_start: i64,
+    pub window_end: i64,
+    pub appended_rows: usize,
+    pub truncated_rows: usize,
+    pub missing_history_rows: usize,
+    pub rewritten_rows: usize,
+    pub is_superset: bool,
+}
+
+/// Row order comparison of matched keys.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RowOrder {
+    pub order_matches: bool,
+    pub rank_correlation: f64,
+    pub moved_rows: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_divergence_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_divergence_row_a: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_divergence_row_b: Option<u64>,
+}
+
+/// Per-group mismatch rates (`group_by` option), worst groups first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroupedMismatch {
+    pub group_column: String,
+    pub groups: Vec<GroupMismatch>,
+    /// Whether groups beyond the reporting limit were dropped.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroupMismatch {
+    pub group: String,
+    pub matched_rows: usize,
+    pub modified_rows: usize,
+    /// Column -> percentage of the group's matched rows that differ.
+    pub mismatch_rates: IndexMap<String, f64>,
+}
+
+/// What `generate_mutations` injected into the copy it wrote.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MutationReport {
+    pub path: String,
+    pub rows_in: usize,
+    pub rows_out: usize,
+    pub dropped_rows: usize,
+    /// Column -> non-null values shifted by epsilon.
+    pub perturbed: IndexMap<String, usize>,
+    /// Column -> non-null values set to null.
+    pub nulled: IndexMap<String, usize>,
+}
+
+/// Outcome of `self_check`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SelfCheck {
+    pub runs: usize,
+    pub passed: usize,
+    pub failed: usize,
+    /// Seed of the whole check, to reproduce it.
+    pub seed: u64,
+    pub failures: Vec<SelfCheckFailure>,
+}
+
+/// A reported count that differs from the injected ground truth.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SelfCheckFailure {
+    pub run: usize,
+    /// Seed of this run's dataset and mutations.
+    pub seed: u64,
+    pub check: String,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+/// Metadata-only estimate of one input (`estimate`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileEstimate {
+    pub path: String,
+    /// `parquet_footer`, `line_sample` or `unknown`.
+    pub method: String,
+    /// Whether `estimated_rows` is a count rather than an extrapolation.
+    pub exact: bool,
+    pub file_bytes: u64,
+    pub estimated_rows: Option<u64>,
+    pub columns: Option<usize>,
+    pub estimated_memory_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiffEstimate {
+    pub a: FileEstimate,
+    pub b: FileEstimate,
+    /// Estimated rows of B minus rows of A.
+    pub row_count_delta: Option<i64>,
+    pub estimated_peak_memory_bytes: Option<u64>,
+    /// `small` (< 1 GiB), `medium` (< 16 GiB), `large` or `unknown`.
+    pub cost_class: String,
+}
+
+/// Engine, join strategy and profiling chosen for a `diff_files` run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionPlan {
+    /// `streaming` or `in_memory`.
+    pub engine: String,
+    /// `hash`, or `merge` (both sides sorted by the keys).
+    pub join: String,
+    /// Key-hash partitions the statistics pass ran in (1: a single pass).
+    pub partitions: usize,
+    pub profile: bool,
+    pub estimated_peak_memory_bytes: Option<u64>,
+    pub available_memory_bytes: Option<u64>,
+    /// One line per decision, with what it was based on.
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RowLevelOutput {
+    pub path: String,
+    /// Rows written: one per added / removed row and per changed cell (when
+    /// `summarized`, the rows it would have had).
+    pub rows: usize,
+    /// Over `max_detail_rows`: nothing was written, the columns' change
+    /// patterns summarize the changes instead.
+    #[serde(default)]
+    pub summarized: bool,
+}
+
+/// Summary of one named comparison group over the matched rows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComparisonGroupSummary {
+    /// Group columns that were compared.
+    pub columns: Vec<String>,
+    /// Group columns that are keys or missing from either file.
+    pub skipped_columns: Vec<String>,
+    pub matched_rows: usize,
+    pub identical_rows: usize,
+    /// Matched rows with at least one differing group column.
+    pub modified_rows: usize,
+    /// Percentage of matched rows identical on the group's columns.
+    pub match_rate: f64,
+    /// Column -> rows differing under the group's options.
+    pub column_mismatches: IndexMap<String, usize>,
+}
+
+/// One `aggregate_checks` assertion, evaluated over each whole file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AggregateCheck {
+    pub column: String,
+    /// `sum`, `mean`, `min`, `max`, `count`, `null_count` or `count_distinct`.
+    pub aggregate: String,
+    /// The aggregate over file A; `None` when A lacks the column or the
+    /// aggregate is undefined (the mean of only nulls).
+    pub value_a: Option<f64>,
+    pub value_b: Option<f64>,
+    /// Largest accepted absolute difference (0: equal).
+    pub tolerance: f64,
+    pub passed: bool,
+}
+
+/// A non-fatal problem met during a run: the figures stand, but may not mean
+/// quite what they seem.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Warning {
+    /// What happened: "lossy_cast", "duplicate_keys", "rows_filtered", ...
+    pub kind: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    /// Rows or values affected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+}
+
+/// A likely root cause behind a column's mismatches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Finding {
+    /// A's values of one column show up in B's other column and vice versa.
+    ColumnSwap {
+        columns: [String; 2],
+        swapped_rows: usize,
+        /// Share of the columns' mismatched rows explained by the swap.
+        swap_rate: f64,
+    },
+    /// B = A + offset on the mismatched rows.
+    ConstantOffset {
+        column: String,
+        offset: f64,
+        /// Share of the column's non-null mismatches explained.
+        explained_rate: f64,
+    },
+    /// B = A × factor on the mismatched rows (e.g. cents vs dollars).
+    ConstantFactor {
+        column: String,
+        factor: f64,
+        explained_rate: f64,
+    },
+    /// B's values are A's cut at a fixed number of characters.
+    Truncation {
+        column: String,
+        /// Inferred column length limit in B.
+        limit: usize,
+        explained_rate: f64,
+    },
+    /// Every mismatched pair is equal once both sides are rounded to
+    /// `decimals` places.
+    Rounding {
+        column: String,
+        decimals: u32,
+        max_abs_diff: f64,
+        /// Absolute tolerance (`abs_tol`) that would make the column match.
+        suggested_tolerance: f64,
+    },
+    /// Mismatched strings look like UTF-8 decoded as Latin-1/Windows-1252.
+    EncodingCorruption {
+        column: String,
+        /// `a` or `b`: the side holding the garbled text.
+        corrupted_side: String,
+        sampled_rows: usize,
+        corrupted_rows: usize,
+        /// `[a, b]` value pairs.
+        examples: Vec<[String; 2]>,
+    },
+}
+
+impl Finding {
+    /// One-line description for reports.
+    pub fn message(&self) -> String {
+        match self {
+            Finding::ColumnSwap {
+                columns,
+                swapped_rows,
+                swap_rate,
+            } => format!(
+                "'{}' and '{}' look swapped ({} rows, {:.1}% of mismatches): likely a column mapping error",
+                columns[0],
+                columns[1],
+                swapped_rows,
+                swap_rate * 100.0
+            ),
+            Finding::ConstantOffset {
+                column,
+                offset,
+                explained_rate,
+            } => format!(
+                "'{}': B = A {:+} on {:.1}% of mismatches",
+                column,
+                offset,
+                explained_rate * 100.0
+            ),
+            Finding::ConstantFactor {
+                column,
+                factor,
+                explained_rate,
+            } => format!(
+                "'{}': B = A × {} on {:.1}% of mismatches (unit or scale change?)",
+                column,
+                factor,
+                explained_rate * 100.0
+            ),
+            Finding::Truncation {
+                column,
+                limit,
+                explained_rate,
+            } => format!(
+                "'{}': B's values are A's cut at {} characters on {:.1}% of mismatches (column length limit?)",
+                column,
+                limit,
+                explained_rate * 100.0
+            ),
+            Finding::Rounding {
+                column,
+                decimals,
+                max_abs_diff,
+                ..
+            } => format!(
+                "'{}': differences explained by rounding at {} dp (max |diff| {})",
+                column, decimals, max_abs_diff
+            ),
+            Finding::EncodingCorruption {
+                column,
+                corrupted_side,
+                sampled_rows,
+                corrupted_rows,
+                examples,
+            } => {
+                let example = examples
+                    .first()
+                    .map(|[a, b]| format!(", e.g. '{}' vs '{}'", a, b))
+                    .unwrap_or_default();
+                format!(
+                    "'{}': suspected encoding corruption in file {} ({} of {} sampled mismatches{})",
+                    column,
+                    corrupted_side.to_uppercase(),
+                    corrupted_rows,
+                    sampled_rows,
+                    example
+                )
+            }
+        }
+    }
+}
+
+/// Key-less comparison of two files' column profiles (quick mode).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileComparison {
+    pub total_rows_a: usize,
+    pub total_rows_b: usize,
+    /// How both files were sampled before profiling, if they were.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<ProfileSampling>,
+    /// Every column of either file: file A's order, then B-only columns.
+    pub columns: IndexMap<String, ColumnProfileComparison>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSampling {
+    /// Target rows per file.
+    pub sample_size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stratify_by: Option<String>,
+    /// Seed reproducing the same samples.
+    pub seed: u64,
+    /// Rows actually profiled (sampling by hash, and rounding per value,
+    /// move them off the target).
+    pub sampled_rows_a: usize,
+    pub sampled_rows_b: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnProfileComparison {
+    /// `"MISSING"` when the column doesn't exist in that file.
+    pub source_dtype: String,
+    pub target_dtype: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_a: Option<ColumnProfile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_b: Option<ColumnProfile>,
+    /// Profile metrics that differ between the files (`"null_count"`, `"max"`,
+    /// ..., `"distribution"` when the KS test rejects equality at 5%).
+    pub differences: Vec<String>,
+    /// Numeric columns in both files: value counts on shared bins.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<Histogram>,
+    /// Two-sample Kolmogorov-Smirnov statistic (largest gap between the
+    /// two cumulative distributions), measured at the histogram's bin edges,
+    /// so a lower bound of the exact one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ks_statistic: Option<f64>,
+}
+
+/// Equal-width histogram of a column over the range of both files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Histogram {
+    /// Bin boundaries: bin `i` holds `edges[i] <= x < edges[i + 1]` (the
+    /// last one includes its upper edge).
+    pub edges: Vec<f64>,
+    pub counts_a: Vec<u64>,
+    pub counts_b: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnProfile {
+    pub count: usize,
+    pub null_count: usize,
+    /// Min/max in their string form; absent for nested types.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<String>,
+    /// HyperLogLog estimate.
+    pub approx_distinct: u64,
+    /// Numeric columns only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean: Option<f64>,
+}
+
+/// Footer statistics of two Parquet files (`parquet_stats` option).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParquetStatsComparison {
+    pub row_count_a: u64,
+    pub row_count_b: u64,
+    /// Columns present in both files, in file A's order.
+    pub columns: IndexMap<String, ParquetColumnStats>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParquetColumnStats {
+    pub null_count_a: Option<u64>,
+    pub null_count_b: Option<u64>,
+    pub min_a: Option<String>,
+    pub min_b: Option<String>,
+    pub max_a: Option<String>,
+    pub max_b: Option<String>,
+    pub distinct_count_a: Option<u64>,
+    pub distinct_count_b: Option<u64>,
+    /// Row counts, null counts and min/max all agree.
+    pub identical: bool,
+}
+
+/// Header-level rollup of a line-level diff (`line_keys` option).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HierarchySummary {
+    pub header_keys: Vec<String>,
+    pub line_keys: Vec<String>,
+    pub headers_a: usize,
+    pub headers_b: usize,
+    pub matched_headers: usize,
+    /// Matched headers whose lines all match.
+    pub identical_headers: usize,
+    /// Matched headers with an added, removed or changed line.
+    pub modified_headers: usize,
+    pub added_headers: usize,
+    pub removed_headers: usize,
+    pub headers_with_added_lines: usize,
+    pub headers_with_removed_lines: usize,
+    pub headers_with_modified_lines: usize,
+    /// Most modified headers first.
+    pub sample_headers: Vec<HeaderDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HeaderDiff {
+    /// Header key values joined with `|`.
+    pub key: String,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    pub modified_lines: usize,
+}
+
+/// Multiset comparison of the rows under each key (`diff_row_sets`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RowSetDiff {
+    pub keys_a: usize,
+    pub keys_b: usize,
+    pub matched_keys: usize,
+    /// Matched keys whose row multisets are equal.
+    pub identical_keys: usize,
+    pub modified_keys: usize,
+    pub added_keys: usize,
+    pub removed_keys: usize,
+    pub rows_a: usize,
+    pub rows_b: usize,
+    /// Row changes under matched keys.
+    pub added_rows: usize,
+    pub removed_rows: usize,
+    pub modified_rows: usize,
+    /// Most changed keys first.
+    pub sample_keys: Vec<KeyGroupDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyGroupDiff {
+    /// Key values joined with `|`.
+    pub key: String,
+    pub rows_a: usize,
+    pub rows_b: usize,
+    pub added_rows: usize,
+    pub removed_rows: usize,
+    pub modified_rows: usize,
+}
+
+/// Ordered comparison of the rows under each key (`diff_sequences`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SequenceDiff {
+    pub keys_a: usize,
+    pub keys_b: usize,
+    pub matched_keys: usize,
+    pub identical_keys: usize,
+    pub modified_keys: usize,
+    pub added_keys: usize,
+    pub removed_keys: usize,
+    /// Edits summed over the aligned keys.
+    pub insertions: usize,
+    pub deletions: usize,
+    pub substitutions: usize,
+    /// Modified keys whose sequences differ over too long a stretch to
+    /// align.
+    pub skipped_keys: usize,
+    /// Most edited keys first.
+    pub sample_keys: Vec<KeySequenceDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeySequenceDiff {
+    /// Key values joined with `|`.
+    pub key: String,
+    pub length_a: usize,
+    pub length_b: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub substitutions: usize,
+    /// Position in A's sequence of the first edit.
+    pub first_divergence: usize,
+}
+
+/// Effective-dated comparison aligned on key and overlapping validity (`diff_intervals`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntervalDiff {
+    pub keys_a: usize,
+    pub keys_b: usize,
+    pub matched_keys: usize,
+    pub added_keys: usize,
+    pub removed_keys: usize,
+    pub intervals_a: usize,
+    pub intervals_b: usize,
+    /// Pairs of A / B intervals of a key that overlap, compared over the overlap.
+    pub overlapping_segments: usize,
+    pub identical_segments: usize,
+    pub modified_segments: usize,
+    /// Overlapping pairs whose validity boundaries differ.
+    pub shifted_segments: usize,
+    /// Intervals overlapping no interval of the other file.
+    pub unmatched_intervals_a: usize,
+    pub unmatched_intervals_b: usize,
+    /// Column -> overlapping segments where it differs.
+    pub column_mismatches: IndexMap<String, usize>,
+    pub integrity_a: IntervalIntegrity,
+    pub integrity_b: IntervalIntegrity,
+    pub sample_segments: Vec<IntervalSegmentDiff>,
+}
+
+/// Gaps and overlaps between consecutive intervals of a key, in one file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntervalIntegrity {
+    pub gaps: usize,
+    pub overlaps: usize,
+    /// Keys with a gap or overlap (key values joined with `|`).
+    pub sample_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntervalSegmentDiff {
+    /// Key values joined with `|`.
+    pub key: String,
+    pub valid_from: String,
+    /// `None` for an open-ended segment.
+    pub valid_to: Option<String>,
+    pub columns: Vec<String>,
+}
+
+/// Three-way comparison against a common base (`diff_three_way`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConflictReport {
+    /// The conflicts table written.
+    pub path: String,
+    pub keys_base: usize,
+    /// Base keys still present in A / B.
+    pub keys_in_a: usize,
+    pub keys_in_b: usize,
+    /// Base keys each side deleted or changed.
+    pub changed_keys_a: usize,
+    pub changed_keys_b: usize,
+    /// Base keys with at least one conflicting cell.
+    pub conflicting_keys: usize,
+    /// Cell counts summed over the columns.
+    pub conflicts: usize,
+    pub a_only_changes: usize,
+    pub b_only_changes: usize,
+    /// Cells both sides changed to the same value.
+    pub convergent_changes: usize,
+    pub columns: IndexMap<String, ColumnConflicts>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ColumnConflicts {
+    pub conflicts: usize,
+    pub a_only_changes: usize,
+    pub b_only_changes: usize,
+    pub convergent_changes: usize,
+}
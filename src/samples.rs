@@ -0,0 +1,80 @@
+// koala-diff/src/samples.rs
+// Formatting of mismatched value samples
+
+use indexmap::IndexMap;
+use polars::prelude::*;
+
+use crate::error::{Context, Result};
+
+/// Renders a sample value, truncating the middle of long values so multi-KB
+/// blobs don't flood the result. Truncated values end with their full length.
+pub fn display_value(value: &AnyValue, max_len: Option<usize>) -> String {
+    let text = value.to_string();
+    match max_len {
+        Some(max_len) => truncate_middle(&text, max_len),
+        None => text,
+    }
+}
+
+fn truncate_middle(text: &str, max_len: usize) -> String {
+    let total = text.chars().count();
+    if total <= max_len {
+        return text.to_string();
+    }
+    let head = max_len.div_ceil(2);
+    let tail = max_len - head;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(total - tail).collect();
+    format!("{}…{} [{} chars]", start, end, total)
+}
+
+/// Patterns per column reported in place of a row-level diff over
+/// `max_detail_rows`.
+pub const SUMMARY_PATTERNS: usize = 20;
+
+/// The `top` most frequent `old -> new` changes of each column, with their
+/// row counts, most frequent first.
+///
+/// `joined` must carry the `{column}_is_diff` flag of every name in `columns`.
+/// Values are rendered like the mismatch samples.
+pub fn change_patterns(
+    joined: &LazyFrame,
+    columns: &[String],
+    top: usize,
+    max_len: Option<usize>,
+) -> Result<IndexMap<String, IndexMap<String, usize>>> {
+    let mut patterns = IndexMap::new();
+    for name in columns {
+        let right_name = format!("{}_right", name);
+        let df = joined
+            .clone()
+            .filter(col(format!("{}_is_diff", name).as_str()))
+            .group_by([
+                col(name.as_str()).alias("_old"),
+                col(right_name.as_str()).alias("_new"),
+            ])
+            .agg([len().alias("_rows")])
+            .sort(
+                ["_rows"],
+                SortMultipleOptions::default().with_order_descending(true),
+            )
+            .limit(top as IdxSize)
+            .collect()
+            .stage("grouping change patterns")
+            .column(name)?;
+
+        let value = |column: &str, i: usize| df.column(column).unwrap().get(i).unwrap();
+        let mut counts = IndexMap::new();
+        for i in 0..df.height() {
+            let pattern = format!(
+                "{} -> {}",
+                display_value(&value("_old", i), max_len),
+                display_value(&value("_new", i), max_len)
+            );
+            let count = value("_rows", i).try_extract::<u64>().unwrap_or(0) as usize;
+            counts.insert(pattern, count);
+        }
+        patterns.insert(name.clone(), counts);
+    }
+    Ok(patterns)
+}
@@ -0,0 +1,226 @@
+// koala-diff/src/schema.rs
+// Schema evolution analysis: classifies column changes between A (old) and B (new)
+
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::result::{SchemaChange, SchemaCompatibility, SchemaDiffEntry};
+
+/// Compatibility of a single schema change, in schema-registry terms:
+/// - backward: readers on B's schema can read A's data
+/// - forward: readers on A's schema can read B's data
+/// - full: both directions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compatibility {
+    Full,
+    Backward,
+    Forward,
+    Breaking,
+}
+
+impl Compatibility {
+    /// Folds per-change classifications into an overall verdict.
+    fn combine(self, other: Compatibility) -> Compatibility {
+        use Compatibility::*;
+        match (self, other) {
+            (Full, c) | (c, Full) => c,
+            (Backward, Backward) => Backward,
+            (Forward, Forward) => Forward,
+            _ => Breaking,
+        }
+    }
+}
+
+/// Builds the `schema_compatibility` section: one entry per changed column
+/// plus an overall verdict.
+pub fn compatibility_report(schema_a: &Schema, schema_b: &Schema) -> SchemaCompatibility {
+    let mut changes = Vec::new();
+    let mut record = |column: &str,
+                      change: &str,
+                      from: Option<&DataType>,
+                      to: Option<&DataType>,
+                      compatibility: Compatibility| {
+        changes.push(SchemaChange {
+            column: column.to_string(),
+            change: change.to_string(),
+            from: from.map(|d| format!("{:?}", d)),
+            to: to.map(|d| format!("{:?}", d)),
+            compatibility,
+        });
+    };
+
+    for (name, dtype_a) in schema_a.iter() {
+        match schema_b.get(name.as_str()) {
+            // Columns are nullable, so old readers simply lose the field
+            None => record(
+                name,
+                "removed",
+                Some(dtype_a),
+                None,
+                Compatibility::Backward,
+            ),
+            Some(dtype_b) if dtype_b != dtype_a => record(
+                name,
+                "retyped",
+                Some(dtype_a),
+                Some(dtype_b),
+                retype(dtype_a, dtype_b),
+            ),
+            Some(_) => {}
+        }
+    }
+    for (name, dtype_b) in schema_b.iter() {
+        if !schema_a.contains(name.as_str()) {
+            // New nullable column: old data reads as null, old readers ignore it
+            record(name, "added", None, Some(dtype_b), Compatibility::Full);
+        }
+    }
+
+    let verdict = changes
+        .iter()
+        .fold(Compatibility::Full, |acc, c| acc.combine(c.compatibility));
+    SchemaCompatibility { verdict, changes }
+}
+
+/// Builds the `schema_diff` list: removed and retyped columns in A's order,
+/// then added columns, then shared columns whose relative order changed.
+pub fn schema_diff(schema_a: &Schema, schema_b: &Schema) -> Vec<SchemaDiffEntry> {
+    let entry = |column: &str, change: &str, from: Option<String>, to: Option<String>, lossy| {
+        SchemaDiffEntry {
+            column: column.to_string(),
+            change: change.to_string(),
+            from,
+            to,
+            lossy,
+        }
+    };
+    let dtype = |d: &DataType| Some(format!("{:?}", d));
+
+    let mut entries = Vec::new();
+    for (name, dtype_a) in schema_a.iter() {
+        match schema_b.get(name.as_str()) {
+            None => entries.push(entry(name, "removed", dtype(dtype_a), None, true)),
+            Some(dtype_b) if dtype_b != dtype_a => entries.push(entry(
+                name,
+                "retyped",
+                dtype(dtype_a),
+                dtype(dtype_b),
+                !promotes(dtype_a, dtype_b),
+            )),
+            Some(_) => {}
+        }
+    }
+    for (name, dtype_b) in schema_b.iter() {
+        if !schema_a.contains(name.as_str()) {
+            entries.push(entry(name, "added", None, dtype(dtype_b), false));
+        }
+    }
+
+    // Positions among the shared columns, so an added or removed column
+    // doesn't make every later column look moved
+    let shared = |schema: &Schema, other: &Schema| -> Vec<String> {
+        schema
+            .iter_names()
+            .filter(|n| other.contains(n.as_str()))
+            .map(|n| n.to_string())
+            .collect()
+    };
+    let shared_a = shared(schema_a, schema_b);
+    let shared_b = shared(schema_b, schema_a);
+    for (pos_a, name) in shared_a.iter().enumerate() {
+        let pos_b = shared_b.iter().position(|n| n == name).unwrap_or(pos_a);
+        if pos_a != pos_b {
+            entries.push(entry(
+                name,
+                "reordered",
+                Some(pos_a.to_string()),
+                Some(pos_b.to_string()),
+                false,
+            ));
+        }
+    }
+    entries
+}
+
+/// Column names and dtypes in order, as reports spell them.
+pub fn columns(schema: &Schema) -> Vec<(String, String)> {
+    schema
+        .iter()
+        .map(|(name, dtype)| (name.to_string(), format!("{:?}", dtype)))
+        .collect()
+}
+
+/// SHA-256 of the column names and dtypes, in order: equal exactly when the
+/// schemas are, whatever the data.
+pub fn fingerprint(schema: &Schema) -> String {
+    let mut digest = Sha256::new();
+    for (name, dtype) in columns(schema) {
+        digest.update(name.as_bytes());
+        digest.update([0]);
+        digest.update(dtype.as_bytes());
+        digest.update([b'\n']);
+    }
+    hex::encode(digest.finalize())
+}
+
+/// Classifies a dtype change from `from` (A) to `to` (B).
+pub fn retype(from: &DataType, to: &DataType) -> Compatibility {
+    if promotes(from, to) {
+        Compatibility::Backward
+    } else if promotes(to, from) {
+        Compatibility::Forward
+    } else {
+        Compatibility::Breaking
+    }
+}
+
+/// Whether values of the two dtypes can be compared one to one: same type,
+/// both numeric, or one promotes to the other.
+pub fn comparable(a: &DataType, b: &DataType) -> bool {
+    a == b || (a.is_numeric() && b.is_numeric()) || promotes(a, b) || promotes(b, a)
+}
+
+/// Whether every `from` value is representable as `to` (Arrow promotion rules).
+pub fn promotes(from: &DataType, to: &DataType) -> bool {
+    if from == to {
+        return true;
+    }
+    match (from, to) {
+        (DataType::Null, _) => true,
+        (DataType::Float32, DataType::Float64) => true,
+        (DataType::Date, DataType::Datetime(_, None)) => true,
+        (DataType::Categorical(..), DataType::String) => true,
+        _ => match (int_width(from), int_width(to), float_mantissa(to)) {
+            (Some((signed_f, bits_f)), Some((signed_t, bits_t)), _) => {
+                bits_t > bits_f && (signed_f == signed_t || (!signed_f && signed_t))
+            }
+            // Integers fit in a float when the mantissa covers every bit
+            (Some((_, bits_f)), None, Some(mantissa)) => bits_f < mantissa,
+            _ => false,
+        },
+    }
+}
+
+fn int_width(dtype: &DataType) -> Option<(bool, u32)> {
+    match dtype {
+        DataType::Int8 => Some((true, 8)),
+        DataType::Int16 => Some((true, 16)),
+        DataType::Int32 => Some((true, 32)),
+        DataType::Int64 => Some((true, 64)),
+        DataType::UInt8 => Some((false, 8)),
+        DataType::UInt16 => Some((false, 16)),
+        DataType::UInt32 => Some((false, 32)),
+        DataType::UInt64 => Some((false, 64)),
+        _ => None,
+    }
+}
+
+fn float_mantissa(dtype: &DataType) -> Option<u32> {
+    match dtype {
+        DataType::Float32 => Some(24),
+        DataType::Float64 => Some(53),
+        _ => None,
+    }
+}
@@ -0,0 +1,183 @@
+// koala-diff/src/selfcheck.rs
+// Self-check: random datasets and mutations, diffed and checked against the ground truth
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::error::{Context, DiffError, Result};
+use crate::export;
+use crate::mutate::{self, MutationSpec};
+use crate::options::DiffOptions;
+use crate::reader::Input;
+use crate::result::{DiffResult, MutationReport, SelfCheck, SelfCheckFailure};
+
+/// A random dataset: unique `id`, untouched `qty`, perturbable `amount`,
+/// non-null `label` (so every nulled value is a mismatch).
+fn generate(rng: &mut StdRng, rows: usize) -> PolarsResult<DataFrame> {
+    let ids: Vec<i64> = (0..rows as i64).collect();
+    let qty: Vec<i64> = (0..rows).map(|_| rng.random_range(0..100)).collect();
+    let amount: Vec<f64> = (0..rows).map(|_| rng.random_range(0.0..1000.0)).collect();
+    let label: Vec<String> = (0..rows)
+        .map(|_| format!("label-{}", rng.random_range(0..50)))
+        .collect();
+    df!(
+        "id" => ids,
+        "qty" => qty,
+        "amount" => amount,
+        "label" => label,
+    )
+}
+
+/// A random mix of mutations. Perturbations (epsilon 1.0 on values below
+/// 1000) and nulls hit different columns so each count is exact.
+fn mutations(rng: &mut StdRng, rows: usize) -> MutationSpec {
+    let mut spec = MutationSpec {
+        drop_rows: rng.random_range(0..=rows / 10),
+        seed: Some(rng.random()),
+        ..Default::default()
+    };
+    if rng.random_bool(0.5) {
+        spec.perturb.insert("amount".to_string(), 1.0);
+    }
+    if rng.random_bool(0.5) {
+        spec.null_out
+            .insert("label".to_string(), rng.random_range(0.0..50.0));
+    }
+    spec
+}
+
+/// Compares what the engine reported with what was injected.
+fn verify(
+    run: usize,
+    seed: u64,
+    result: &DiffResult,
+    injected: &MutationReport,
+) -> Vec<SelfCheckFailure> {
+    let mismatches = |name: &str| {
+        result
+            .column_stats
+            .get(name)
+            .and_then(|s| s.non_match_count)
+            .unwrap_or(0)
+    };
+    let perturbed = injected.perturbed.get("amount").copied().unwrap_or(0);
+    let nulled = injected.nulled.get("label").copied().unwrap_or(0);
+    let matched = injected.rows_out;
+    let expected_modified = if perturbed > 0 { matched } else { nulled };
+
+    let checks = [
+        ("total_rows_a", injected.rows_in, result.total_rows_a),
+        ("total_rows_b", injected.rows_out, result.total_rows_b),
+        ("joined_count", matched, result.joined_count),
+        ("removed", injected.dropped_rows, result.removed),
+        ("added", 0, result.added),
+        (
+            "modified_rows_count",
+            expected_modified,
+            result.modified_rows_count,
+        ),
+        ("qty mismatches", 0, mismatches("qty")),
+        ("amount mismatches", perturbed, mismatches("amount")),
+        ("label mismatches", nulled, mismatches("label")),
+    ];
+    checks
+        .into_iter()
+        .filter(|(_, expected, actual)| expected != actual)
+        .map(|(check, expected, actual)| SelfCheckFailure {
+            run,
+            seed,
+            check: check.to_string(),
+            expected,
+            actual,
+        })
+        .collect()
+}
+
+fn run_one(dir: &Path, run: usize, seed: u64, rows: usize) -> Result<Vec<SelfCheckFailure>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut df = generate(&mut rng, rows).stage("generating")?;
+    let spec = mutations(&mut rng, rows);
+    let (mut mutated, injected) = mutate::mutate(df.clone(), &spec).stage("mutating")?;
+
+    let path_a = dir.join(format!("run{}_a.parquet", run));
+    let path_b = dir.join(format!("run{}_b.parquet", run));
+    export::write_table(&mut df, &path_a.to_string_lossy(), "writing")?;
+    export::write_table(&mut mutated, &path_b.to_string_lossy(), "writing")?;
+
+    let opts = DiffOptions {
+        diagnose: false,
+        ..Default::default()
+    };
+    let result = crate::run_diff(
+        [
+            Input::Path(path_a.to_string_lossy().into_owned()),
+            Input::Path(path_b.to_string_lossy().into_owned()),
+        ],
+        vec!["id".to_string()],
+        opts,
+        None,
+    )?;
+    Ok(verify(run, seed, &result, &injected))
+}
+
+/// Diffs random datasets against randomly mutated copies and checks every
+/// reported count against the injected ground truth
+///
+/// Meant as a smoke test after upgrades (`koala-diff self-check`). Each run
+/// is reproducible from its seed, reported with any failure.
+///
+/// Args:
+///     runs (int): Number of dataset/mutation pairs (default 20)
+///     rows (int): Rows per generated dataset (default 1000)
+///     seed (int, optional): Seed of the whole check (random by default)
+///
+/// Returns:
+///     dict: {"runs", "passed", "failed", "seed",
+///            "failures": [{"run", "seed", "check", "expected", "actual"}]}
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (runs = 20, rows = 1000, seed = None))]
+pub fn self_check<'py>(
+    py: Python<'py>,
+    runs: usize,
+    rows: usize,
+    seed: Option<u64>,
+) -> PyResult<Bound<'py, PyAny>> {
+    if rows == 0 {
+        return Err(DiffError::invalid("self_check needs at least one row per dataset").into());
+    }
+    let seed = seed.unwrap_or_else(|| StdRng::from_os_rng().random());
+    let mut seeds = StdRng::seed_from_u64(seed);
+
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("koala-diff-self-check-{}", std::process::id()));
+    fs::create_dir_all(&dir)
+        .stage("creating the work directory")
+        .file(&dir.to_string_lossy())?;
+
+    let mut report = SelfCheck {
+        runs,
+        seed,
+        ..Default::default()
+    };
+    let outcome = (0..runs).try_for_each(|run| {
+        let failures = run_one(&dir, run, seeds.random(), rows)?;
+        if failures.is_empty() {
+            report.passed += 1;
+        } else {
+            report.failed += 1;
+            report.failures.extend(failures);
+        }
+        Ok::<_, DiffError>(())
+    });
+    let _ = fs::remove_dir_all(&dir);
+    outcome?;
+
+    Ok(pythonize::pythonize(py, &report)?)
+}
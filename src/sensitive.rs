@@ -0,0 +1,61 @@
+// koala-diff/src/sensitive.rs
+// Sensitive columns are replaced by salted hashes before any comparison
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use polars::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Context, Result};
+use crate::options::DiffOptions;
+
+/// The four hash seeds derived from `hash_salt`, or random ones for this run.
+///
+/// Both files must be hashed with the same seeds; a random per-run salt keeps
+/// the hashes from being compared across runs.
+pub fn seeds(salt: Option<&str>) -> [u64; 4] {
+    let mut seeds = [0u64; 4];
+    match salt {
+        Some(salt) => {
+            let digest = Sha256::digest(salt.as_bytes());
+            for (seed, chunk) in seeds.iter_mut().zip(digest.chunks_exact(8)) {
+                *seed = u64::from_le_bytes(chunk.try_into().unwrap());
+            }
+        }
+        None => {
+            for seed in seeds.iter_mut() {
+                *seed = RandomState::new().build_hasher().finish();
+            }
+        }
+    }
+    seeds
+}
+
+/// Replaces every sensitive column of `lf` with a salted hash of its value.
+///
+/// Values are hashed through their string form so `1` (Int32) and `1`
+/// (Int64) still match; nulls stay null.
+pub fn mask(mut lf: LazyFrame, opts: &DiffOptions) -> Result<LazyFrame> {
+    if opts.sensitive_columns.is_empty() {
+        return Ok(lf);
+    }
+    let schema = lf.collect_schema().stage("hashing sensitive columns")?;
+    let [k0, k1, k2, k3] = opts.hash_seeds;
+    let hashed: Vec<Expr> = opts
+        .sensitive_columns
+        .iter()
+        .filter(|name| schema.contains(name.as_str()))
+        .map(|name| {
+            when(col(name.as_str()).is_null())
+                .then(lit(NULL).cast(DataType::UInt64))
+                .otherwise(
+                    col(name.as_str())
+                        .cast(DataType::String)
+                        .hash(k0, k1, k2, k3),
+                )
+                .alias(name.as_str())
+        })
+        .collect();
+    Ok(lf.with_columns(hashed))
+}
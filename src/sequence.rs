@@ -0,0 +1,244 @@
+// koala-diff/src/sequence.rs
+// Ordered comparison of the rows under each key (event sequences per user)
+
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+
+use crate::error::{Context, Result};
+use crate::multiset::{exact_only, key_string, row_hashes};
+use crate::options::DiffOptions;
+use crate::reader;
+use crate::result::{KeySequenceDiff, SequenceDiff};
+
+/// Most changed keys listed in the result.
+const MAX_SAMPLE_KEYS: usize = 20;
+/// Largest alignment table of one key (u32 cells, 16 MiB); keys whose
+/// sequences differ over a longer stretch are skipped.
+const MAX_ALIGNMENT_CELLS: usize = 1 << 22;
+
+/// Insertions, deletions and substitutions turning `a` into `b`, or `None`
+/// when the part between their common prefix and suffix is too long to
+/// align.
+///
+/// Aligns on the longest common subsequence; within each gap between
+/// aligned elements, paired deletions and insertions count as
+/// substitutions. Also returns the position in `a` of the first edit.
+fn align(a: &[u64], b: &[u64]) -> Option<(usize, usize, usize, Option<usize>)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+    let (n, m) = (a.len(), b.len());
+    if (n + 1).saturating_mul(m + 1) > MAX_ALIGNMENT_CELLS {
+        return None;
+    }
+    // lcs[i][j]: LCS length of a[i..] and b[j..]
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if a[i] == b[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+
+    let (mut insertions, mut deletions, mut substitutions) = (0, 0, 0);
+    let mut first_edit = None;
+    let (mut gap_del, mut gap_ins) = (0usize, 0usize);
+    let mut close_gap = |del: &mut usize, ins: &mut usize| {
+        let subs = (*del).min(*ins);
+        substitutions += subs;
+        deletions += *del - subs;
+        insertions += *ins - subs;
+        *del = 0;
+        *ins = 0;
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            close_gap(&mut gap_del, &mut gap_ins);
+            i += 1;
+            j += 1;
+            continue;
+        }
+        first_edit.get_or_insert(prefix + i);
+        if j == m || (i < n && lcs[at(i + 1, j)] >= lcs[at(i, j + 1)]) {
+            gap_del += 1;
+            i += 1;
+        } else {
+            gap_ins += 1;
+            j += 1;
+        }
+    }
+    close_gap(&mut gap_del, &mut gap_ins);
+    Some((insertions, deletions, substitutions, first_edit))
+}
+
+/// Compares the sequence of rows under each key, ordered by `order_by`.
+///
+/// Rows are identified by a hash of their shared non-key columns other than
+/// `order_by`, so re-stamped sequences still align.
+pub fn compare_sequences(
+    mut lf_a: LazyFrame,
+    mut lf_b: LazyFrame,
+    key_cols: &[String],
+    order_by: &str,
+    opts: &DiffOptions,
+) -> Result<SequenceDiff> {
+    exact_only(opts, "sequences")?;
+    let schema_a = lf_a.collect_schema().stage("comparing sequences")?;
+    let schema_b = lf_b.collect_schema().stage("comparing sequences")?;
+    let mut skipped = key_cols.to_vec();
+    skipped.push(order_by.to_string());
+    let [hash_a, hash_b] = row_hashes(&schema_a, &schema_b, &skipped, opts);
+
+    let keys: Vec<Expr> = key_cols.iter().map(|k| col(k.as_str())).collect();
+    let sequences = |lf: LazyFrame, row_hash: Expr, alias: &str| {
+        lf.group_by(keys.clone()).agg([row_hash
+            .sort_by([col(order_by)], SortMultipleOptions::default())
+            .alias(alias)])
+    };
+
+    let joined = sequences(lf_a, hash_a, "_seq_a")
+        .join(
+            sequences(lf_b, hash_b, "_seq_b"),
+            keys.clone(),
+            keys.clone(),
+            JoinArgs::new(JoinType::Full).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .collect()
+        .stage("comparing sequences")?;
+
+    let list = |name: &str| -> Result<ListChunked> {
+        Ok(joined
+            .column(name)
+            .stage("comparing sequences")?
+            .as_materialized_series()
+            .list()
+            .stage("comparing sequences")?
+            .clone())
+    };
+    let (seq_a, seq_b) = (list("_seq_a")?, list("_seq_b")?);
+    let hashes = |series: Option<Series>| -> Option<Vec<u64>> {
+        let series = series?;
+        Some(series.u64().ok()?.into_iter().flatten().collect())
+    };
+
+    let mut diff = SequenceDiff::default();
+    for row in 0..joined.height() {
+        let (a, b) = match (
+            hashes(seq_a.get_as_series(row)),
+            hashes(seq_b.get_as_series(row)),
+        ) {
+            (Some(a), Some(b)) => (a, b),
+            (Some(_), None) => {
+                diff.keys_a += 1;
+                diff.removed_keys += 1;
+                continue;
+            }
+            (None, Some(_)) => {
+                diff.keys_b += 1;
+                diff.added_keys += 1;
+                continue;
+            }
+            (None, None) => continue,
+        };
+        diff.keys_a += 1;
+        diff.keys_b += 1;
+        diff.matched_keys += 1;
+        if a == b {
+            diff.identical_keys += 1;
+            continue;
+        }
+        diff.modified_keys += 1;
+        let Some((insertions, deletions, substitutions, first_edit)) = align(&a, &b) else {
+            diff.skipped_keys += 1;
+            continue;
+        };
+        diff.insertions += insertions;
+        diff.deletions += deletions;
+        diff.substitutions += substitutions;
+        diff.sample_keys.push(KeySequenceDiff {
+            key: key_string(&joined, key_cols, row),
+            length_a: a.len(),
+            length_b: b.len(),
+            insertions,
+            deletions,
+            substitutions,
+            first_divergence: first_edit.unwrap_or(a.len()),
+        });
+    }
+
+    diff.sample_keys
+        .sort_by_key(|s| std::cmp::Reverse(s.insertions + s.deletions + s.substitutions));
+    diff.sample_keys.truncate(MAX_SAMPLE_KEYS);
+    Ok(diff)
+}
+
+/// Compares the ordered sequence of rows under each key
+///
+/// For keyed groups where order matters (events per user): each side's rows
+/// are ordered by `order_by` and the two sequences aligned (longest common
+/// subsequence) to count insertions, deletions and substitutions.
+///
+/// Args:
+///     file_a (str): Path to the source (old) file
+///     file_b (str): Path to the target (new) file
+///     key_cols (list[str]): Group key columns
+///     order_by (str): Column ordering the rows within a key; not compared
+///     **options: Read and preprocessing options of `diff_files`; rows match
+///         exactly, so `abs_tol`, `rel_tol` and `tolerances` are refused
+///
+/// Returns:
+///     dict: Key counts, total insertions/deletions/substitutions and
+///         "sample_keys" with the per-key edits and first divergence
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (file_a, file_b, key_cols, order_by, **options))]
+pub fn diff_sequences<'py>(
+    py: Python<'py>,
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    order_by: String,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let opts = DiffOptions::from_kwargs(options)?;
+    let lf_a = reader::open(&file_a, &opts.read_a, &key_cols, &opts)?;
+    let lf_b = reader::open(&file_b, &opts.read_b, &key_cols, &opts)?;
+    let diff = compare_sequences(lf_a, lf_b, &key_cols, &order_by, &opts)?;
+    Ok(pythonize::pythonize(py, &diff)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_edits_between_aligned_rows() {
+        let (a, b) = ([1, 2, 3, 4], [1, 9, 3, 4, 5]);
+        assert_eq!(align(&a, &b), Some((1, 0, 1, Some(1))));
+    }
+
+    #[test]
+    fn long_sequences_with_a_short_edit_still_align() {
+        let a: Vec<u64> = (0..100_000).collect();
+        let mut b = a.clone();
+        b[50_000] = u64::MAX;
+        assert_eq!(align(&a, &b), Some((0, 0, 1, Some(50_000))));
+
+        let c: Vec<u64> = (0..100_000).rev().collect();
+        assert_eq!(align(&a, &c), None);
+    }
+}
@@ -0,0 +1,245 @@
+// koala-diff/src/shards.rs
+// Warehouse unloads split across shard files, stitched via their manifest
+
+use std::path::{Path, PathBuf};
+
+use polars::prelude::*;
+use serde_json::Value;
+
+use crate::error::{Context, DiffError, Result};
+use crate::options::ReadOptions;
+use crate::reader;
+
+/// One file listed by a manifest, with whatever the warehouse recorded about it.
+#[derive(Debug)]
+struct Shard {
+    path: PathBuf,
+    size_bytes: Option<u64>,
+    rows: Option<u64>,
+}
+
+/// Opens the shards listed by the unload manifest at `manifest_path` as one
+/// frame, after checking that every shard is present and complete.
+///
+/// Accepted manifests:
+/// - `{"entries": [{"url", "meta": {"content_length", "record_count"}}]}`
+///   (Snowflake/Redshift-style `MANIFEST` unloads)
+/// - `[{"FILE_NAME", "FILE_SIZE", "ROW_COUNT"}]` (Snowflake `COPY INTO`
+///   `DETAILED_OUTPUT = TRUE` rows, saved as JSON)
+/// - a BigQuery extract job (`bq show --format=json -j <job>`): the
+///   wildcard destination URI is expanded and the shard count checked
+///   against `destinationUriFileCounts`.
+///
+/// Shard URLs (`s3://`, `gs://`, `@stage/`) are looked up by file name next
+/// to the manifest, which is where a download of the unload puts them.
+pub fn scan(manifest_path: &str, read: &ReadOptions) -> Result<LazyFrame> {
+    let text = std::fs::read_to_string(manifest_path)
+        .stage("reading the unload manifest")
+        .file(manifest_path)?;
+    let manifest: Value = serde_json::from_str(&text).map_err(|e| {
+        DiffError::invalid(format!(
+            "Invalid unload manifest '{}': {}",
+            manifest_path, e
+        ))
+    })?;
+    let base_dir = Path::new(manifest_path)
+        .parent()
+        .unwrap_or_else(|| Path::new("."));
+
+    let shards = list_shards(&manifest, base_dir)?;
+    if shards.is_empty() {
+        return Err(DiffError::invalid(format!(
+            "Unload manifest '{}' lists no files",
+            manifest_path
+        )));
+    }
+
+    let shard_read = ReadOptions {
+        unload_manifest: false,
+        limit: None,
+        offset: 0,
+        ..read.clone()
+    };
+    let mut problems = Vec::new();
+    let mut frames = Vec::with_capacity(shards.len());
+    for shard in &shards {
+        let display = shard.path.display();
+        let Ok(metadata) = std::fs::metadata(&shard.path) else {
+            problems.push(format!("{} is missing", display));
+            continue;
+        };
+        if let Some(expected) = shard.size_bytes.filter(|size| *size != metadata.len()) {
+            problems.push(format!(
+                "{} has {} bytes, manifest says {}",
+                display,
+                metadata.len(),
+                expected
+            ));
+            continue;
+        }
+        let lf = reader::scan_format(&shard.path.to_string_lossy(), &shard_read)?;
+        if let Some(expected) = shard.rows {
+            let rows = count_rows(lf.clone())?;
+            if rows != expected {
+                problems.push(format!(
+                    "{} has {} rows, manifest says {}",
+                    display, rows, expected
+                ));
+                continue;
+            }
+        }
+        frames.push(lf);
+    }
+    if !problems.is_empty() {
+        return Err(DiffError::invalid(format!(
+            "Incomplete unload '{}': {}",
+            manifest_path,
+            problems.join("; ")
+        )));
+    }
+
+    let args = UnionArgs {
+        to_supertypes: true,
+        ..Default::default()
+    };
+    concat(frames, args)
+        .stage("stitching shards")
+        .file(manifest_path)
+}
+
+fn list_shards(manifest: &Value, base_dir: &Path) -> Result<Vec<Shard>> {
+    if let Some(entries) = manifest.get("entries").and_then(Value::as_array) {
+        return entries
+            .iter()
+            .map(|entry| {
+                let url = entry.get("url").and_then(Value::as_str).ok_or_else(|| {
+                    DiffError::invalid("Unload manifest entry without a 'url'".to_string())
+                })?;
+                let meta = entry.get("meta");
+                Ok(Shard {
+                    path: locate(url, base_dir),
+                    size_bytes: meta.and_then(|m| m.get("content_length")).and_then(as_u64),
+                    rows: meta.and_then(|m| m.get("record_count")).and_then(as_u64),
+                })
+            })
+            .collect();
+    }
+
+    if let Some(rows) = manifest.as_array() {
+        return rows
+            .iter()
+            .map(|row| {
+                let url = field(row, "file_name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| {
+                        DiffError::invalid("Unload manifest row without a FILE_NAME".to_string())
+                    })?;
+                Ok(Shard {
+                    path: locate(url, base_dir),
+                    size_bytes: field(row, "file_size").and_then(as_u64),
+                    rows: field(row, "row_count").and_then(as_u64),
+                })
+            })
+            .collect();
+    }
+
+    let extract = |section: &str| manifest.get(section).and_then(|s| s.get("extract"));
+    if let Some(uris) = extract("configuration")
+        .and_then(|e| e.get("destinationUris"))
+        .and_then(Value::as_array)
+    {
+        let counts = extract("statistics")
+            .and_then(|e| e.get("destinationUriFileCounts"))
+            .and_then(Value::as_array);
+        let mut shards = Vec::new();
+        for (i, uri) in uris.iter().enumerate() {
+            let uri = uri.as_str().unwrap_or_default();
+            let found = expand_wildcard(&locate(uri, base_dir))?;
+            if let Some(expected) = counts.and_then(|c| c.get(i)).and_then(as_u64) {
+                if found.len() as u64 != expected {
+                    return Err(DiffError::invalid(format!(
+                        "Incomplete unload: {} files match '{}', the extract job wrote {}",
+                        found.len(),
+                        uri,
+                        expected
+                    )));
+                }
+            }
+            shards.extend(found.into_iter().map(|path| Shard {
+                path,
+                size_bytes: None,
+                rows: None,
+            }));
+        }
+        return Ok(shards);
+    }
+
+    Err(DiffError::invalid(
+        "Unrecognized unload manifest: expected 'entries', a list of FILE_NAME rows or a BigQuery extract job"
+            .to_string(),
+    ))
+}
+
+/// Case-insensitive field lookup; Snowflake upper-cases result columns.
+fn field<'a>(row: &'a Value, name: &str) -> Option<&'a Value> {
+    row.as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Numbers may be recorded as JSON numbers or (BigQuery) as strings.
+fn as_u64(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+fn locate(url: &str, base_dir: &Path) -> PathBuf {
+    let local = Path::new(url);
+    if local.exists() {
+        return local.to_path_buf();
+    }
+    let name = url.rsplit('/').next().unwrap_or(url);
+    base_dir.join(name)
+}
+
+/// Files in the pattern's directory matching its single `*` wildcard, sorted.
+fn expand_wildcard(pattern: &Path) -> Result<Vec<PathBuf>> {
+    let name = pattern
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let Some((prefix, suffix)) = name.split_once('*') else {
+        return Ok(vec![pattern.to_path_buf()]);
+    };
+    let dir = pattern.parent().unwrap_or_else(|| Path::new("."));
+    let entries = std::fs::read_dir(dir)
+        .stage("listing shards")
+        .file(&dir.to_string_lossy())?;
+    let mut found: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let file = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            file.len() >= prefix.len() + suffix.len()
+                && file.starts_with(prefix)
+                && file.ends_with(suffix)
+        })
+        .collect();
+    found.sort();
+    Ok(found)
+}
+
+fn count_rows(lf: LazyFrame) -> Result<u64> {
+    let df = lf.select([len()]).collect().stage("counting shard rows")?;
+    Ok(df
+        .columns()
+        .first()
+        .and_then(|c| c.get(0).ok())
+        .and_then(|v| v.try_extract::<u64>().ok())
+        .unwrap_or(0))
+}
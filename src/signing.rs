@@ -0,0 +1,204 @@
+// koala-diff/src/signing.rs
+// Signed JSON reports: HMAC-SHA256 or ed25519 over a canonical encoding
+
+use ed25519_dalek::{Signer, Verifier};
+use hmac::{Hmac, Mac};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::error::{Context, DiffError, Result};
+
+const HMAC_SHA256: &str = "hmac-sha256";
+const ED25519: &str = "ed25519";
+
+/// The file written by `write_signed_report`.
+#[derive(Debug, Serialize, Deserialize)]
+struct SignedReport {
+    report: serde_json::Value,
+    signature: Signature,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Signature {
+    algorithm: String,
+    /// Hex-encoded MAC or signature.
+    value: String,
+}
+
+/// The signed bytes: compact JSON with object keys sorted, so the signature
+/// survives re-indentation of the file.
+fn canonical(report: &serde_json::Value) -> Vec<u8> {
+    // serde_json's Map is ordered by key unless `preserve_order` is enabled
+    serde_json::to_vec(report).expect("JSON values always serialize")
+}
+
+/// Key material as bytes. `str` keys are used as-is for HMAC and hex-decoded
+/// for ed25519.
+#[cfg(feature = "python")]
+fn key_bytes(key: &Bound<'_, PyAny>, algorithm: &str) -> PyResult<Vec<u8>> {
+    if let Ok(bytes) = key.cast::<PyBytes>() {
+        return Ok(bytes.as_bytes().to_vec());
+    }
+    let text: String = key.extract()?;
+    if algorithm == HMAC_SHA256 {
+        Ok(text.into_bytes())
+    } else {
+        Ok(hex::decode(text.trim())
+            .map_err(|e| DiffError::invalid(format!("Key is not valid hex: {}", e)))?)
+    }
+}
+
+fn ed25519_key(bytes: &[u8]) -> Result<[u8; 32]> {
+    bytes
+        .try_into()
+        .map_err(|_| DiffError::invalid(format!("ed25519 keys are 32 bytes, got {}", bytes.len())))
+}
+
+fn sign(message: &[u8], key: &[u8], algorithm: &str) -> Result<Vec<u8>> {
+    match algorithm {
+        HMAC_SHA256 => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key)
+                .map_err(|e| DiffError::invalid(e.to_string()))?;
+            mac.update(message);
+            Ok(mac.finalize().into_bytes().to_vec())
+        }
+        ED25519 => {
+            let signing_key = ed25519_dalek::SigningKey::from_bytes(&ed25519_key(key)?);
+            Ok(signing_key.sign(message).to_bytes().to_vec())
+        }
+        other => Err(DiffError::invalid(format!(
+            "Unknown signature algorithm '{}' (expected '{}' or '{}')",
+            other, HMAC_SHA256, ED25519
+        ))),
+    }
+}
+
+fn verify(message: &[u8], signature: &[u8], key: &[u8], algorithm: &str) -> Result<bool> {
+    match algorithm {
+        HMAC_SHA256 => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key)
+                .map_err(|e| DiffError::invalid(e.to_string()))?;
+            mac.update(message);
+            // Constant-time comparison
+            Ok(mac.verify_slice(signature).is_ok())
+        }
+        ED25519 => {
+            let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&ed25519_key(key)?)
+                .map_err(|e| DiffError::invalid(e.to_string()))?;
+            let Ok(signature) = ed25519_dalek::Signature::from_slice(signature) else {
+                return Ok(false);
+            };
+            Ok(verifying_key.verify(message, &signature).is_ok())
+        }
+        other => Err(DiffError::invalid(format!(
+            "Unknown signature algorithm '{}' in report",
+            other
+        ))),
+    }
+}
+
+/// Writes a diff result as a signed JSON report
+///
+/// Args:
+///     result (dict): Result returned by `diff_files`
+///     path (str): File to write
+///     key (str | bytes): HMAC secret, or the 32-byte ed25519 private key
+///         (raw bytes or hex)
+///     algorithm (str): "hmac-sha256" (default) or "ed25519"
+///
+/// The file holds {"report": ..., "signature": {"algorithm", "value"}}.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (result, path, key, algorithm = "hmac-sha256"))]
+pub fn write_signed_report(
+    result: &Bound<'_, PyAny>,
+    path: &str,
+    key: &Bound<'_, PyAny>,
+    algorithm: &str,
+) -> PyResult<()> {
+    let report: serde_json::Value = pythonize::depythonize(result)?;
+    let value = sign(&canonical(&report), &key_bytes(key, algorithm)?, algorithm)?;
+    let signed = SignedReport {
+        report,
+        signature: Signature {
+            algorithm: algorithm.to_string(),
+            value: hex::encode(value),
+        },
+    };
+    let json =
+        serde_json::to_string_pretty(&signed).map_err(|e| DiffError::invalid(e.to_string()))?;
+    Ok(std::fs::write(path, json)
+        .stage("writing the signed report")
+        .file(path)?)
+}
+
+/// Checks that a report written by `write_signed_report` is unmodified
+///
+/// Args:
+///     path (str): The signed report
+///     key (str | bytes): The HMAC secret, or the 32-byte ed25519 public key
+///         (raw bytes or hex)
+///
+/// Returns:
+///     bool: True if the signature matches the report content
+#[cfg(feature = "python")]
+#[pyfunction]
+pub fn verify_report(path: &str, key: &Bound<'_, PyAny>) -> PyResult<bool> {
+    let text = std::fs::read_to_string(path)
+        .stage("reading the signed report")
+        .file(path)?;
+    let signed: SignedReport = serde_json::from_str(&text)
+        .map_err(|e| DiffError::invalid(format!("{} is not a signed report: {}", path, e)))?;
+    let algorithm = signed.signature.algorithm.as_str();
+    let Ok(signature) = hex::decode(&signed.signature.value) else {
+        return Ok(false);
+    };
+    Ok(verify(
+        &canonical(&signed.report),
+        &signature,
+        &key_bytes(key, algorithm)?,
+        algorithm,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &[u8] = br#"{"added":0,"removed":1}"#;
+    const TAMPERED: &[u8] = br#"{"added":0,"removed":0}"#;
+
+    #[test]
+    fn hmac_signature_verifies_and_rejects_tampering() {
+        let signature = sign(MESSAGE, b"secret", HMAC_SHA256).unwrap();
+        assert!(verify(MESSAGE, &signature, b"secret", HMAC_SHA256).unwrap());
+        assert!(!verify(TAMPERED, &signature, b"secret", HMAC_SHA256).unwrap());
+        assert!(!verify(MESSAGE, &signature, b"other", HMAC_SHA256).unwrap());
+    }
+
+    #[test]
+    fn ed25519_signature_verifies_and_rejects_tampering() {
+        let private = [7u8; 32];
+        let public = ed25519_dalek::SigningKey::from_bytes(&private)
+            .verifying_key()
+            .to_bytes();
+        let signature = sign(MESSAGE, &private, ED25519).unwrap();
+        assert!(verify(MESSAGE, &signature, &public, ED25519).unwrap());
+        assert!(!verify(TAMPERED, &signature, &public, ED25519).unwrap());
+
+        let mut forged = signature.clone();
+        forged[0] ^= 1;
+        assert!(!verify(MESSAGE, &forged, &public, ED25519).unwrap());
+    }
+
+    #[test]
+    fn canonical_encoding_ignores_key_order() {
+        let a: serde_json::Value = serde_json::from_str(r#"{"b": 1, "a": [1, 2]}"#).unwrap();
+        let b: serde_json::Value = serde_json::from_str(r#"{"a":[1,2],"b":1}"#).unwrap();
+        assert_eq!(canonical(&a), canonical(&b));
+    }
+}
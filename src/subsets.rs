@@ -0,0 +1,230 @@
+// koala-diff/src/subsets.rs
+// Named column subsets compared with their own rules in the main statistics pass
+
+use indexmap::IndexMap;
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::compare;
+use crate::error::{DiffError, Result};
+use crate::options::DiffOptions;
+use crate::result::ComparisonGroupSummary;
+
+/// A named comparison over a subset of the columns (`comparison_groups`).
+///
+/// The comparison options left unset here are inherited from the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ComparisonGroup {
+    /// The key of the group in `comparison_groups`.
+    #[serde(skip_deserializing)]
+    pub name: String,
+    pub columns: Vec<String>,
+    pub inf_equal: Option<bool>,
+    pub inf_match_threshold: Option<f64>,
+    pub flush_subnormals: Option<bool>,
+    pub abs_tol: Option<f64>,
+    pub rel_tol: Option<f64>,
+}
+
+impl ComparisonGroup {
+    #[cfg(feature = "python")]
+    fn from_dict(name: String, spec: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let mut group = Self {
+            name,
+            columns: Vec::new(),
+            inf_equal: None,
+            inf_match_threshold: None,
+            flush_subnormals: None,
+            abs_tol: None,
+            rel_tol: None,
+        };
+        for (key, value) in spec.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "columns" => group.columns = value.extract()?,
+                "inf_equal" => group.inf_equal = value.extract()?,
+                "inf_match_threshold" => group.inf_match_threshold = value.extract()?,
+                "flush_subnormals" => group.flush_subnormals = value.extract()?,
+                "abs_tol" => group.abs_tol = value.extract()?,
+                "rel_tol" => group.rel_tol = value.extract()?,
+                other => {
+                    return Err(DiffError::unsupported(format!(
+                        "comparison group '{}' got an unexpected option '{}'",
+                        group.name, other
+                    ))
+                    .into())
+                }
+            }
+        }
+        Ok(group)
+    }
+
+    /// The run's options with this group's overrides applied.
+    fn options(&self, base: &DiffOptions) -> DiffOptions {
+        let mut opts = base.clone();
+        if let Some(inf_equal) = self.inf_equal {
+            opts.inf_equal = inf_equal;
+        }
+        if self.inf_match_threshold.is_some() {
+            opts.inf_match_threshold = self.inf_match_threshold;
+        }
+        if let Some(flush) = self.flush_subnormals {
+            opts.flush_subnormals = flush;
+        }
+        if self.abs_tol.is_some() {
+            opts.abs_tol = self.abs_tol;
+        }
+        if self.rel_tol.is_some() {
+            opts.rel_tol = self.rel_tol;
+        }
+        opts
+    }
+
+    /// Group columns that can be compared: non-key and present in both files.
+    fn compared(&self, schema_a: &Schema, schema_b: &Schema, keys: &[String]) -> Vec<&String> {
+        self.columns
+            .iter()
+            .filter(|c| {
+                !keys.contains(c) && schema_a.contains(c.as_str()) && schema_b.contains(c.as_str())
+            })
+            .collect()
+    }
+}
+
+/// Parses the `comparison_groups` option: `{name: {"columns": [...], ...}}`.
+#[cfg(feature = "python")]
+pub fn parse(value: &Bound<'_, PyAny>) -> PyResult<Vec<ComparisonGroup>> {
+    let groups = value.cast::<PyDict>().map_err(|_| {
+        DiffError::unsupported("comparison_groups must be a dict of name -> group options")
+    })?;
+    groups
+        .iter()
+        .map(|(name, spec)| {
+            let name: String = name.extract()?;
+            let spec = spec.cast::<PyDict>().map_err(|_| {
+                DiffError::unsupported(format!(
+                    "comparison group '{}' must be a dict of options",
+                    name
+                ))
+            })?;
+            ComparisonGroup::from_dict(name, spec)
+        })
+        .collect()
+}
+
+/// `comparison_groups` from JSON, in the same `{name: {...}}` shape.
+pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<ComparisonGroup>, D::Error> {
+    let groups = IndexMap::<String, ComparisonGroup>::deserialize(d)?;
+    Ok(groups
+        .into_iter()
+        .map(|(name, group)| ComparisonGroup { name, ..group })
+        .collect())
+}
+
+/// Aggregations of every group, run with the global aggregations of the
+/// statistics pass: one modified-row count per group and one diff count per
+/// group column, compared under the group's own options.
+pub fn aggs(
+    groups: &[ComparisonGroup],
+    schema_a: &Schema,
+    schema_b: &Schema,
+    keys: &[String],
+    opts: &DiffOptions,
+) -> Vec<Expr> {
+    let mut aggs = Vec::new();
+    for (i, group) in groups.iter().enumerate() {
+        let group_opts = group.options(opts);
+        let mut any_diff: Option<Expr> = None;
+        for name in group.compared(schema_a, schema_b, keys) {
+            let is_diff = compare::diff_expr(
+                name,
+                &format!("{}_right", name),
+                schema_a.get(name).unwrap(),
+                schema_b.get(name).unwrap(),
+                &group_opts,
+            );
+            aggs.push(
+                is_diff
+                    .clone()
+                    .cast(DataType::UInt64)
+                    .sum()
+                    .alias(format!("_cg{}_{}_diff_count", i, name)),
+            );
+            any_diff = Some(match any_diff {
+                Some(m) => m.or(is_diff),
+                None => is_diff,
+            });
+        }
+        if let Some(mask) = any_diff {
+            aggs.push(
+                mask.cast(DataType::UInt64)
+                    .sum()
+                    .alias(format!("_cg{}_modified", i)),
+            );
+        }
+    }
+    aggs
+}
+
+/// Reads the per-group summaries back from the statistics frame.
+pub fn summaries(
+    groups: &[ComparisonGroup],
+    stats: &DataFrame,
+    schema_a: &Schema,
+    schema_b: &Schema,
+    keys: &[String],
+    matched: usize,
+) -> IndexMap<String, ComparisonGroupSummary> {
+    let count = |name: &str| {
+        stats
+            .column(name)
+            .ok()
+            .and_then(|c| c.get(0).ok())
+            .and_then(|v| v.try_extract::<u64>().ok())
+            .unwrap_or(0) as usize
+    };
+
+    groups
+        .iter()
+        .enumerate()
+        .map(|(i, group)| {
+            let columns: Vec<String> = group
+                .compared(schema_a, schema_b, keys)
+                .into_iter()
+                .cloned()
+                .collect();
+            let skipped_columns = group
+                .columns
+                .iter()
+                .filter(|c| !columns.contains(c))
+                .cloned()
+                .collect();
+            let column_mismatches: IndexMap<String, usize> = columns
+                .iter()
+                .map(|c| (c.clone(), count(&format!("_cg{}_{}_diff_count", i, c))))
+                .collect();
+            let modified_rows = count(&format!("_cg{}_modified", i));
+            let identical_rows = matched.saturating_sub(modified_rows);
+            let match_rate = if matched > 0 {
+                identical_rows as f64 / matched as f64 * 100.0
+            } else {
+                100.0
+            };
+            let summary = ComparisonGroupSummary {
+                columns,
+                skipped_columns,
+                matched_rows: matched,
+                identical_rows,
+                modified_rows,
+                match_rate,
+                column_mismatches,
+            };
+            (group.name.clone(), summary)
+        })
+        .collect()
+}
@@ -0,0 +1,335 @@
+// koala-diff/src/threeway.rs
+// Three-way comparison against a common ancestor: changes made on both sides
+
+use std::fs::File;
+
+use polars::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+
+use crate::compare;
+use crate::error::Context;
+use crate::options::DiffOptions;
+use crate::reader;
+use crate::result::{ColumnConflicts, ConflictReport};
+
+/// Per-column change expressions of the base joined with A and B.
+struct ColumnChanges {
+    name: String,
+    changed_a: Expr,
+    changed_b: Expr,
+    /// A and B both changed the value, to different values, or one side
+    /// deleted the row the other changed.
+    conflict: Expr,
+}
+
+/// Builds the base rows joined with A (`_a` suffix) and B (`_b` suffix),
+/// plus the change expressions of every column present in all three.
+fn join_three(
+    mut base: LazyFrame,
+    mut lf_a: LazyFrame,
+    mut lf_b: LazyFrame,
+    key_cols: &[String],
+    opts: &DiffOptions,
+) -> PolarsResult<(LazyFrame, Vec<ColumnChanges>)> {
+    let schema_base = base.collect_schema()?;
+    let schema_a = lf_a.collect_schema()?;
+    let schema_b = lf_b.collect_schema()?;
+    let keys: Vec<Expr> = key_cols.iter().map(|k| col(k.as_str())).collect();
+
+    // Suffix every value column so names don't depend on which sides collide
+    let suffixed = |lf: LazyFrame, schema: &Schema, suffix: &str, flag: &str| {
+        let mut columns = keys.clone();
+        columns.extend(
+            schema
+                .iter_names()
+                .filter(|name| !key_cols.iter().any(|k| k == name.as_str()))
+                .map(|name| col(name.clone()).alias(format!("{}{}", name, suffix))),
+        );
+        columns.push(lit(true).alias(flag));
+        lf.select(columns)
+    };
+    let joined = base
+        .join(
+            suffixed(lf_a, &schema_a, "_a", "_in_a"),
+            keys.clone(),
+            keys.clone(),
+            JoinArgs::new(JoinType::Left),
+        )
+        .join(
+            suffixed(lf_b, &schema_b, "_b", "_in_b"),
+            keys.clone(),
+            keys,
+            JoinArgs::new(JoinType::Left),
+        );
+
+    let in_a = col("_in_a").is_not_null();
+    let in_b = col("_in_b").is_not_null();
+    let mut changes = Vec::new();
+    for (name, dtype) in schema_base.iter() {
+        if key_cols.iter().any(|k| k == name.as_str()) {
+            continue;
+        }
+        let (Some(dtype_a), Some(dtype_b)) =
+            (schema_a.get(name.as_str()), schema_b.get(name.as_str()))
+        else {
+            continue;
+        };
+        let (a, b) = (format!("{}_a", name), format!("{}_b", name));
+        let changed_a = in_a
+            .clone()
+            .and(compare::diff_expr(name, &a, dtype, dtype_a, opts));
+        let changed_b = in_b
+            .clone()
+            .and(compare::diff_expr(name, &b, dtype, dtype_b, opts));
+        let update = changed_a
+            .clone()
+            .and(changed_b.clone())
+            .and(compare::diff_expr(&a, &b, dtype_a, dtype_b, opts));
+        let delete_update = in_a
+            .clone()
+            .not()
+            .and(changed_b.clone())
+            .or(in_b.clone().not().and(changed_a.clone()));
+        changes.push(ColumnChanges {
+            name: name.to_string(),
+            changed_a,
+            changed_b,
+            conflict: update.or(delete_update),
+        });
+    }
+    Ok((joined, changes))
+}
+
+/// Long-format conflicts table: the key columns, `column`, `kind`
+/// ("update" or "delete_update") and the `base`, `a` and `b` values as strings.
+fn conflicts_table(
+    joined: &LazyFrame,
+    changes: &[ColumnChanges],
+    key_cols: &[String],
+) -> PolarsResult<LazyFrame> {
+    let keys: Vec<Expr> = key_cols.iter().map(|k| col(k.as_str())).collect();
+    let kind = when(col("_in_a").is_not_null().and(col("_in_b").is_not_null()))
+        .then(lit("update"))
+        .otherwise(lit("delete_update"));
+
+    let parts: Vec<LazyFrame> = changes
+        .iter()
+        .map(|change| {
+            let name = change.name.as_str();
+            let mut select = keys.clone();
+            select.extend([
+                lit(name).alias("column"),
+                kind.clone().alias("kind"),
+                col(name).cast(DataType::String).alias("base"),
+                col(format!("{}_a", name)).cast(DataType::String).alias("a"),
+                col(format!("{}_b", name)).cast(DataType::String).alias("b"),
+            ]);
+            joined
+                .clone()
+                .filter(change.conflict.clone())
+                .select(select)
+        })
+        .collect();
+
+    if parts.is_empty() {
+        let mut select = keys;
+        for name in ["column", "kind", "base", "a", "b"] {
+            select.push(lit(NULL).cast(DataType::String).alias(name));
+        }
+        return Ok(joined.clone().select(select).limit(0));
+    }
+    concat(parts, UnionArgs::default())
+}
+
+/// Counts the changes of each side against the base and their conflicts.
+fn count_changes(joined: &LazyFrame, changes: &[ColumnChanges]) -> PolarsResult<ConflictReport> {
+    let any = |exprs: Vec<Expr>| {
+        exprs
+            .into_iter()
+            .reduce(|acc, e| acc.or(e))
+            .unwrap_or(lit(false))
+    };
+    let changed_a = any(changes.iter().map(|c| c.changed_a.clone()).collect());
+    let changed_b = any(changes.iter().map(|c| c.changed_b.clone()).collect());
+    let conflict = any(changes.iter().map(|c| c.conflict.clone()).collect());
+    let in_a = col("_in_a").is_not_null();
+    let in_b = col("_in_b").is_not_null();
+
+    let mut totals = vec![
+        len().alias("keys_base"),
+        in_a.clone().sum().alias("keys_in_a"),
+        in_b.clone().sum().alias("keys_in_b"),
+        in_a.clone()
+            .not()
+            .or(changed_a)
+            .sum()
+            .alias("changed_keys_a"),
+        in_b.not().or(changed_b).sum().alias("changed_keys_b"),
+        conflict.sum().alias("conflicting_keys"),
+    ];
+    for (i, change) in changes.iter().enumerate() {
+        let (a, b) = (change.changed_a.clone(), change.changed_b.clone());
+        // A change the other side deleted the row under is a conflict only
+        let clean = change.conflict.clone().not();
+        totals.extend([
+            change
+                .conflict
+                .clone()
+                .sum()
+                .alias(format!("_conflicts_{}", i)),
+            a.clone()
+                .and(b.clone().not())
+                .and(clean.clone())
+                .sum()
+                .alias(format!("_a_only_{}", i)),
+            b.clone()
+                .and(a.clone().not())
+                .and(clean.clone())
+                .sum()
+                .alias(format!("_b_only_{}", i)),
+            a.and(b)
+                .and(clean)
+                .sum()
+                .alias(format!("_convergent_{}", i)),
+        ]);
+    }
+    let df = joined.clone().select(totals).collect()?;
+    let count = |name: &str| -> usize {
+        df.column(name)
+            .ok()
+            .and_then(|c| c.get(0).ok())
+            .and_then(|v| v.try_extract::<u64>().ok())
+            .unwrap_or(0) as usize
+    };
+
+    let mut report = ConflictReport {
+        keys_base: count("keys_base"),
+        keys_in_a: count("keys_in_a"),
+        keys_in_b: count("keys_in_b"),
+        changed_keys_a: count("changed_keys_a"),
+        changed_keys_b: count("changed_keys_b"),
+        conflicting_keys: count("conflicting_keys"),
+        ..Default::default()
+    };
+    for (i, change) in changes.iter().enumerate() {
+        let column = ColumnConflicts {
+            conflicts: count(&format!("_conflicts_{}", i)),
+            a_only_changes: count(&format!("_a_only_{}", i)),
+            b_only_changes: count(&format!("_b_only_{}", i)),
+            convergent_changes: count(&format!("_convergent_{}", i)),
+        };
+        report.conflicts += column.conflicts;
+        report.a_only_changes += column.a_only_changes;
+        report.b_only_changes += column.b_only_changes;
+        report.convergent_changes += column.convergent_changes;
+        report.columns.insert(change.name.clone(), column);
+    }
+    Ok(report)
+}
+
+/// Finds the conflicting changes of two files modified from a common base
+///
+/// For bidirectional sync: a cell conflicts when A and B both changed it
+/// from the base to different values ("update"), or when one side deleted a
+/// row the other side changed ("delete_update"). Changes made on one side
+/// only, or identically on both, are counted but not conflicts. Keys absent
+/// from the base (added on either side) are not compared.
+///
+/// Args:
+///     base (str): Path to the common ancestor
+///     file_a (str): Path to one side's modified copy
+///     file_b (str): Path to the other side's modified copy
+///     key_cols (list[str]): Columns to join on
+///     output_path (str): Conflicts table to write; CSV for a `.csv` path,
+///         Parquet otherwise. Columns: the keys, "column", "kind", "base",
+///         "a", "b" (values as strings)
+///     **options: Same options as `diff_files`; the base is read with the
+///         `_a` read options
+///
+/// Returns:
+///     dict: Key counts, "conflicts" (cells), "conflicting_keys",
+///         "a_only_changes", "b_only_changes", "convergent_changes" and the
+///         same counts per column under "columns"
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (base, file_a, file_b, key_cols, output_path, **options))]
+pub fn diff_three_way<'py>(
+    py: Python<'py>,
+    base: String,
+    file_a: String,
+    file_b: String,
+    key_cols: Vec<String>,
+    output_path: String,
+    options: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let opts = DiffOptions::from_kwargs(options)?;
+    let lf_base = reader::open(&base, &opts.read_a, &key_cols, &opts)?;
+    let lf_a = reader::open(&file_a, &opts.read_a, &key_cols, &opts)?;
+    let lf_b = reader::open(&file_b, &opts.read_b, &key_cols, &opts)?;
+
+    let (joined, changes) =
+        join_three(lf_base, lf_a, lf_b, &key_cols, &opts).stage("comparing three ways")?;
+    let mut report = count_changes(&joined, &changes).stage("comparing three ways")?;
+    let mut table = conflicts_table(&joined, &changes, &key_cols)
+        .stage("comparing three ways")?
+        .with_new_streaming(true)
+        .collect()
+        .stage("comparing three ways")?;
+
+    let file = File::create(&output_path)
+        .stage("writing conflicts")
+        .file(&output_path)?;
+    let written = if output_path.to_lowercase().ends_with(".csv") {
+        CsvWriter::new(file).finish(&mut table)
+    } else {
+        ParquetWriter::new(file)
+            .with_compression(ParquetCompression::Zstd(None))
+            .finish(&mut table)
+            .map(|_| ())
+    };
+    written.stage("writing conflicts").file(&output_path)?;
+
+    report.path = output_path;
+    Ok(pythonize::pythonize(py, &report)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_updates_and_deleted_rows_changed_on_the_other_side() {
+        let base = df!("id" => [1i64, 2, 3, 4], "x" => [1i64, 2, 3, 4]).unwrap();
+        // 1: both changed, differently; 2: deleted in A, changed in B;
+        // 3: changed in A only; 4: both changed, identically
+        let a = df!("id" => [1i64, 3, 4], "x" => [10i64, 30, 40]).unwrap();
+        let b = df!("id" => [1i64, 2, 3, 4], "x" => [20i64, 21, 3, 40]).unwrap();
+        let keys = ["id".to_string()];
+        let opts = DiffOptions::default();
+        let (joined, changes) = join_three(base.lazy(), a.lazy(), b.lazy(), &keys, &opts).unwrap();
+
+        let report = count_changes(&joined, &changes).unwrap();
+        assert_eq!(report.conflicts, 2);
+        assert_eq!(report.conflicting_keys, 2);
+        assert_eq!(report.a_only_changes, 1);
+        assert_eq!(report.b_only_changes, 0);
+        assert_eq!(report.convergent_changes, 1);
+
+        let table = conflicts_table(&joined, &changes, &keys)
+            .unwrap()
+            .sort(["id"], Default::default())
+            .collect()
+            .unwrap();
+        let kinds: Vec<_> = table
+            .column("kind")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(kinds, ["update", "delete_update"]);
+    }
+}
@@ -0,0 +1,85 @@
+// koala-diff/src/warnings.rs
+// Non-fatal warnings raised over a run, reported in the result's `warnings`
+
+use std::cell::RefCell;
+
+use polars::prelude::*;
+
+use crate::error::{Context, Result};
+use crate::result::Warning;
+
+thread_local! {
+    /// Warnings raised on this thread since the run started: readers deep in
+    /// the call tree raise them without a handle on the run.
+    static RAISED: RefCell<Vec<Warning>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Warning {
+    pub fn new(kind: &str, message: impl Into<String>) -> Self {
+        Warning {
+            kind: kind.to_string(),
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn in_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    pub fn in_column(mut self, column: &str) -> Self {
+        self.column = Some(column.to_string());
+        self
+    }
+
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+}
+
+/// Records `warning` for the run in progress on this thread.
+pub fn raise(warning: Warning) {
+    RAISED.with_borrow_mut(|raised| raised.push(warning));
+}
+
+/// Takes the warnings raised on this thread so far (at the start of a run,
+/// to drop those of an earlier one that failed).
+pub fn take() -> Vec<Warning> {
+    RAISED.take()
+}
+
+/// Warns about the rows `filter` drops from one side: `before` is the side
+/// as read, `after` as compared. Costs a count of each.
+pub fn dropped_rows(before: &LazyFrame, after: &LazyFrame, file: &str, filter: &str) -> Result<()> {
+    let counts = concat(
+        [before.clone(), after.clone()].map(|lf| lf.select([len().alias("rows")])),
+        UnionArgs::default(),
+    )
+    .and_then(LazyFrame::collect)
+    .stage("counting filtered rows")
+    .file(file)?;
+    let rows: Vec<usize> = counts
+        .column("rows")
+        .stage("counting filtered rows")?
+        .as_materialized_series()
+        .iter()
+        .map(|v| v.try_extract::<u64>().unwrap_or(0) as usize)
+        .collect();
+    let dropped = rows[0].saturating_sub(rows[1]);
+    if dropped > 0 {
+        raise(
+            Warning::new(
+                "rows_filtered",
+                format!(
+                    "{} of {} rows in {} dropped by {} before the comparison",
+                    dropped, rows[0], file, filter
+                ),
+            )
+            .in_file(file)
+            .count(dropped),
+        );
+    }
+    Ok(())
+}
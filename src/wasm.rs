@@ -0,0 +1,65 @@
+// koala-diff/src/wasm.rs
+// WebAssembly bindings (wasm-bindgen): diffs of uploaded CSV files in the browser
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::DiffError;
+use crate::options::DiffOptions;
+use crate::reader::{self, Input};
+use crate::render::{Registry, RenderOptions};
+use crate::result::DiffResult;
+
+/// `diffCsv(csvA, csvB, keys, options?)`: diffs two CSV files held as bytes
+/// (a `File`'s `arrayBuffer()`, as a `Uint8Array`) and returns the same
+/// result object as Python's `diff_files`. `options` takes the `diff_files`
+/// keyword arguments as an object, read options (`csv_options`, `dtypes`,
+/// `encoding`, ...; `_a` / `_b` for one side) applying to the files.
+/// The browser has no threads, so `timeout_seconds` and a
+/// `column_parallelism` above 1 are refused.
+#[wasm_bindgen(js_name = diffCsv)]
+pub fn diff_csv(
+    csv_a: &[u8],
+    csv_b: &[u8],
+    keys: Vec<String>,
+    options: JsValue,
+) -> Result<JsValue, JsError> {
+    let opts = match options.is_undefined() || options.is_null() {
+        true => DiffOptions::default(),
+        false => DiffOptions::from_json(serde_wasm_bindgen::from_value(options)?)?,
+    };
+    if opts.timeout_seconds.is_some() || opts.column_parallelism > 1 {
+        return Err(DiffError::unsupported(
+            "timeout_seconds and column_parallelism need threads, which the browser build doesn't have",
+        )
+        .into());
+    }
+    let inputs = [
+        Input::Frame {
+            label: "a".to_string(),
+            lf: reader::scan_csv("a", csv_a.to_vec(), &opts.read_a)?,
+        },
+        Input::Frame {
+            label: "b".to_string(),
+            lf: reader::scan_csv("b", csv_b.to_vec(), &opts.read_b)?,
+        },
+    ];
+    let result = crate::run_diff(inputs, keys, opts, None)?;
+    Ok(result.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+/// `render(result, format)`: the report in one of the built-in formats
+/// (text, json, markdown, html: a string; xlsx: a `Uint8Array`), as Python
+/// renders it with the default options.
+#[wasm_bindgen]
+pub fn render(result: JsValue, format: &str) -> Result<JsValue, JsError> {
+    let result: DiffResult = serde_wasm_bindgen::from_value(result)?;
+    let registry = Registry::builtin();
+    let renderer = registry.get(format)?;
+    let output = renderer.render(&result, &RenderOptions::default())?;
+    if renderer.is_binary() {
+        Ok(output.into())
+    } else {
+        Ok(JsValue::from_str(&String::from_utf8_lossy(&output)))
+    }
+}